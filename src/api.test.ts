@@ -0,0 +1,400 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import http from 'node:http';
+import type { AddressInfo } from 'node:net';
+import os from 'node:os';
+import path from 'node:path';
+
+import {
+  ApiResponseShapeError,
+  compareByPromulgation,
+  defaultCliOptions,
+  fetchJson,
+  fetchLawContents,
+  getApiProfile,
+  lawTitleFallbackQueries,
+  loadLawDataArchive,
+  loadLawDocument,
+  parseLawDataResponse,
+  parseLawsPage,
+  parseLawSuccessor,
+  searchLawCandidates,
+  searchLawCandidatesByLawNum,
+  searchLawCandidatesWithFallback,
+  selectBestCandidate,
+  saveLawPageArchive,
+  selectCandidateNonInteractive,
+} from './index.js';
+
+test('getApiProfile: v2のパスとレスポンス解釈を返す', async () => {
+  const profile = getApiProfile('2');
+  assert.equal(profile.lawsPath(), '/api/2/laws');
+  assert.equal(profile.lawDataPath('334AC0000000121'), '/api/2/law_data/334AC0000000121');
+
+  const payload = JSON.parse(await fs.readFile('tests/fixtures/laws_tokkyoho.json', 'utf8')) as unknown;
+  const candidates = profile.parseLawCandidates(payload);
+  assert.ok(candidates.some((candidate) => candidate.law_id === '334AC0000000121' && candidate.law_title === '特許法'));
+});
+
+test('getApiProfile: 候補に略称を取り込む', () => {
+  const candidates = getApiProfile('2').parseLawCandidates({
+    laws: [
+      {
+        law_info: { law_id: '322AC0000000054', law_type: 'Act' },
+        revision_info: {
+          law_title: '私的独占の禁止及び公正取引の確保に関する法律',
+          abbrev: '独占禁止法,独禁法',
+        },
+      },
+      { law_info: { law_id: '334AC0000000121', law_type: 'Act' }, revision_info: { law_title: '特許法', abbrev: '' } },
+    ],
+  });
+  assert.deepEqual(candidates.map((candidate) => candidate.abbrev), ['独占禁止法,独禁法', undefined]);
+});
+
+test('parseLawCandidates / parseLawDataResponse: 形式が違えば失敗したJSONパスと受信JSONの概要を示す', () => {
+  const parse = getApiProfile('2').parseLawCandidates;
+  assert.throws(
+    () => parse({ items: [], count: 0 }),
+    (error: unknown) =>
+      error instanceof ApiResponseShapeError &&
+      error.jsonPath === '$.laws' &&
+      /\$\.laws がありません/.test(error.message) &&
+      /トップレベルキー \[items, count\]/.test(error.message),
+  );
+  assert.throws(
+    () => parse({ laws: [{ law_info: { law_id: '334AC0000000121' }, revision_info: '特許法' }] }),
+    /\$\.laws\[0\]\.revision_info がオブジェクトではありません.*law_info あり \/ revision_info あり/,
+  );
+  assert.deepEqual(parse({ laws: [] }), []);
+
+  assert.throws(
+    () => parseLawDataResponse({ law_full_text: {} }),
+    /\$ に law_info と revision_info がありません（受信JSON: トップレベルキー \[law_full_text\] \/ law_info なし/,
+  );
+  assert.throws(() => parseLawDataResponse([]), /受信JSON: 配列/);
+  const response = { law_info: {}, revision_info: { law_title: '特許法' } };
+  assert.equal(parseLawDataResponse(response), response);
+});
+
+test('parseLawsPage: 返された件数と total_count を読み、総件数が無ければ undefined にする', async () => {
+  const payload = JSON.parse(await fs.readFile('tests/fixtures/laws_tokkyoho.json', 'utf8')) as unknown;
+  assert.deepEqual(getApiProfile('2').parseLawsPage(payload), { received: 12, totalCount: 12 });
+  // 法令名の無い要素も返された件数に数え、次のページの offset がずれないようにする。
+  assert.deepEqual(parseLawsPage({ laws: [{ law_info: { law_id: '334AC0000000121' } }], total_count: 9000 }), {
+    received: 1,
+    totalCount: 9000,
+  });
+  assert.deepEqual(parseLawsPage({ laws: [], total_count: '9000' }), { received: 0, totalCount: undefined });
+});
+
+test('getApiProfile: 未対応バージョンはエラー', () => {
+  assert.throws(() => getApiProfile('99'), /未対応のAPIバージョン/);
+});
+
+test('fetchJson: 応答ヘッダが来ない場合は接続タイムアウトで打ち切り、再試行する', async () => {
+  // 応答を返さないサーバーで接続タイムアウトを発生させる。
+  const server = http.createServer(() => undefined);
+  let connections = 0;
+  server.on('connection', () => {
+    connections += 1;
+  });
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+  const { port } = server.address() as AddressInfo;
+  try {
+    const options = { ...defaultCliOptions(), retry: 2, connectTimeoutMs: 50, requestIntervalMs: 0 };
+    await assert.rejects(fetchJson(`http://127.0.0.1:${port}/api/2/laws`, options), /API接続タイムアウト \(50ms\)/);
+    assert.equal(connections, 2);
+  } finally {
+    server.closeAllConnections();
+    await new Promise((resolve) => server.close(resolve));
+  }
+});
+
+test('fetchLawContents: 取得した law_data をアーカイブし、--from-archive ではAPIを呼ばずに読み込む', async () => {
+  const response = {
+    law_info: { law_id: '334AC0000000121' },
+    revision_info: { law_revision_id: '334AC0000000121_20250601_507AC0000000001', law_title: '特許法' },
+    law_full_text: { tag: 'Law', children: [] },
+  };
+  let requests = 0;
+  const server = http.createServer((_, res) => {
+    requests += 1;
+    res.writeHead(200, { 'content-type': 'application/json' });
+    res.end(JSON.stringify(response));
+  });
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+  const { port } = server.address() as AddressInfo;
+  const archiveDir = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-archive-'));
+  try {
+    const options = { ...defaultCliOptions(), apiBaseUrl: `http://127.0.0.1:${port}`, archiveDir };
+    await fetchLawContents(options, '334AC0000000121');
+    const archived = await loadLawDataArchive(archiveDir, '334AC0000000121');
+    assert.equal(archived?.revision_id, '334AC0000000121_20250601_507AC0000000001');
+    assert.equal(archived?.full_text_format, 'json');
+    assert.ok(archived?.fetched_at);
+    assert.deepEqual(archived?.response, response);
+
+    const fromArchive = await fetchLawContents({ ...options, fromArchive: true }, '334AC0000000121');
+    assert.deepEqual(fromArchive.data, response);
+    assert.equal(requests, 1);
+    // アーカイブに無い法令は通常どおり取得し、アーカイブへ追加する。
+    await fetchLawContents({ ...options, fromArchive: true }, '129AC0000000089');
+    assert.equal(requests, 2);
+    assert.ok(await loadLawDataArchive(archiveDir, '129AC0000000089'));
+    assert.equal(await loadLawDataArchive(archiveDir, '140AC0000000045'), undefined);
+  } finally {
+    server.closeAllConnections();
+    await new Promise((resolve) => server.close(resolve));
+  }
+});
+
+test('fetchLawContents: --full-text-format の本文フォーマットを law_full_text_format で要求する', async () => {
+  const requests: URLSearchParams[] = [];
+  const server = http.createServer((req, res) => {
+    requests.push(new URL(req.url ?? '', 'http://localhost').searchParams);
+    res.writeHead(200, { 'content-type': 'application/json' });
+    res.end(JSON.stringify({ law_info: { law_id: '334AC0000000121' }, law_full_text: '<Law />' }));
+  });
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+  const { port } = server.address() as AddressInfo;
+  try {
+    const options = { ...defaultCliOptions(), apiBaseUrl: `http://127.0.0.1:${port}`, requestIntervalMs: 0 };
+    // 既定は json。本文フォーマットによらず、レスポンス全体は常にJSONで受け取る。
+    assert.equal((await fetchLawContents(options, '334AC0000000121')).fullTextFormat, 'json');
+    const xml = await fetchLawContents({ ...options, fullTextFormat: 'xml' }, '334AC0000000121');
+    assert.equal(xml.fullTextFormat, 'xml');
+    assert.deepEqual(
+      requests.map((params) => [params.get('law_full_text_format'), params.get('response_format')]),
+      [
+        ['json', 'json'],
+        ['xml', 'json'],
+      ],
+    );
+  } finally {
+    server.closeAllConnections();
+    await new Promise((resolve) => server.close(resolve));
+  }
+});
+
+test('loadLawDocument: --from-archive では法令ページを開かずにアーカイブの本文を使い、無ければエラーにする', async () => {
+  const archiveDir = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-page-archive-'));
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [{ id: 'Mp-At_1', heading: '第一条', paragraphs: [{ anchor: 'Mp-At_1-Pr_1', segments: [] }] }],
+  };
+  await saveLawPageArchive(archiveDir, doc);
+  const options = { ...defaultCliOptions(), archiveDir, fromArchive: true };
+  assert.deepEqual(await loadLawDocument('334AC0000000121', options), doc);
+  await assert.rejects(
+    loadLawDocument('129AC0000000089', options),
+    /--from-archive: 法令ページのアーカイブがありません: 129AC0000000089/,
+  );
+});
+
+test('selectCandidateNonInteractive: 候補が1件か完全一致が1件のときだけ確定する', () => {
+  const candidate = (lawId: string, lawTitle: string) => ({ law_id: lawId, law_title: lawTitle });
+  assert.equal(selectCandidateNonInteractive('特許法', [candidate('334AC0000000121', '特許法')])?.law_id, '334AC0000000121');
+  assert.equal(
+    selectCandidateNonInteractive('特許法', [
+      candidate('334AC0000000121', '特許法'),
+      candidate('335M50000400010', '特許法施行規則'),
+    ])?.law_id,
+    '334AC0000000121',
+  );
+  assert.equal(
+    selectCandidateNonInteractive('特許', [
+      candidate('334AC0000000121', '特許法'),
+      candidate('335M50000400010', '特許法施行規則'),
+    ]),
+    undefined,
+  );
+  assert.equal(selectCandidateNonInteractive('特許法', []), undefined);
+});
+
+test('selectBestCandidate: 同名の候補が複数なら現行の法令、公布日の新しい法令の順に優先する', () => {
+  const candidate = (lawId: string, promulgationDate?: string, repealStatus?: string) => ({
+    law_id: lawId,
+    law_title: '行政書士法',
+    promulgation_date: promulgationDate,
+    repeal_status: repealStatus,
+  });
+  const preferCurrent = { candidatePolicy: 'prefer-current' } as const;
+  const repealed = candidate('226AC0000000004', '1951-02-22', 'Repeal');
+  const current = candidate('326AC0000000004', '1951-02-22', 'None');
+  assert.equal(selectBestCandidate('行政書士法', [repealed, current], preferCurrent)?.law_id, '326AC0000000004');
+  assert.equal(
+    selectBestCandidate('行政書士法', [candidate('A1', '1950-01-01'), candidate('A2', '1960-01-01')], preferCurrent)?.law_id,
+    'A2',
+  );
+  // 公布日でも優劣が付かなければ確定しない。
+  assert.equal(
+    selectBestCandidate('行政書士法', [candidate('A1', '1950-01-01'), candidate('A2', '1950-01-01')], preferCurrent),
+    undefined,
+  );
+  assert.equal(selectBestCandidate('行政書士法', [repealed, current], { candidatePolicy: 'strict' }), undefined);
+});
+
+test('compareByPromulgation: 区切りの違う公布日も日付として比べ、新しい順に並べて公布日の無い候補は末尾に回す', () => {
+  const candidates = [
+    { law_id: 'A', law_title: '行政書士法', promulgation_date: '1951/2/22' },
+    { law_id: 'B', law_title: '行政書士法' },
+    { law_id: 'C', law_title: '行政書士法', promulgation_date: '1960-01-01' },
+    { law_id: 'D', law_title: '行政書士法', promulgation_date: '1951-12-01' },
+    { law_id: 'E', law_title: '行政書士法', promulgation_date: '不明' },
+  ];
+  assert.deepEqual(
+    [...candidates].sort(compareByPromulgation).map((candidate) => candidate.law_id),
+    ['C', 'D', 'A', 'B', 'E'],
+  );
+  assert.equal(
+    compareByPromulgation(
+      { law_id: 'A', law_title: '行政書士法', promulgation_date: '1951/02/22' },
+      { law_id: 'B', law_title: '行政書士法', promulgation_date: '1951-02-22' },
+    ),
+    0,
+  );
+});
+
+test('selectBestCandidate: 完全一致が無ければ、partialMatchRatio 以上の部分一致が1件のときだけ確定する', () => {
+  const candidates = [
+    { law_id: '334AC0000000121', law_title: '特許法' },
+    { law_id: '335M50000400010', law_title: '実用新案法施行規則' },
+  ];
+  assert.equal(selectBestCandidate('特許', candidates, { candidatePolicy: 'prefer-current' }), undefined);
+  assert.equal(
+    selectBestCandidate('特許', candidates, { candidatePolicy: 'prefer-current', partialMatchRatio: 0.6 })?.law_id,
+    '334AC0000000121',
+  );
+  assert.equal(
+    selectBestCandidate('特許', candidates, { candidatePolicy: 'prefer-current', partialMatchRatio: 0.8 }),
+    undefined,
+  );
+});
+
+test('parseLawSuccessor: 廃止された法令だけ、廃止した法令を新法として返す', async () => {
+  const payload = JSON.parse(await fs.readFile('tests/fixtures/law_data_tokkyoho.json', 'utf8')) as {
+    revision_info: Record<string, unknown>;
+  };
+  assert.equal(parseLawSuccessor(payload.revision_info), undefined);
+  assert.deepEqual(
+    parseLawSuccessor({
+      repeal_status: 'Repeal',
+      amendment_law_id: '417AC0000000087',
+      amendment_law_title: '会社法の施行に伴う関係法律の整備等に関する法律',
+    }),
+    { lawId: '417AC0000000087', title: '会社法の施行に伴う関係法律の整備等に関する法律' },
+  );
+  assert.deepEqual(parseLawSuccessor({ repeal_status: 'Repeal', amendment_law_id: '417AC0000000087' }), {
+    lawId: '417AC0000000087',
+    title: 'law_417AC0000000087',
+  });
+  assert.equal(parseLawSuccessor(undefined), undefined);
+});
+
+test('searchLawCandidates: --exclude-repealed では廃止・失効した候補を除き、廃止状態の分からない候補は残す', async () => {
+  const law = (lawId: string, repealStatus?: string) => ({
+    law_info: { law_id: lawId },
+    revision_info: { law_title: '行政書士法', repeal_status: repealStatus },
+  });
+  const server = http.createServer((_, res) => {
+    res.writeHead(200, { 'content-type': 'application/json' });
+    res.end(
+      JSON.stringify({
+        total_count: 4,
+        laws: [
+          law('226AC0000000004', 'Repeal'),
+          law('236AC0000000004', 'Expire'),
+          law('326AC0000000004', 'None'),
+          law('X'),
+        ],
+      }),
+    );
+  });
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+  const { port } = server.address() as AddressInfo;
+  try {
+    const options = { ...defaultCliOptions(), apiBaseUrl: `http://127.0.0.1:${port}`, requestIntervalMs: 0 };
+    const lawIds = async (excludeRepealed: boolean) =>
+      (await searchLawCandidates({ ...options, excludeRepealed }, '行政書士法')).map((candidate) => candidate.law_id);
+    assert.deepEqual(await lawIds(false), ['226AC0000000004', '236AC0000000004', '326AC0000000004', 'X']);
+    assert.deepEqual(await lawIds(true), ['326AC0000000004', 'X']);
+  } finally {
+    server.closeAllConnections();
+    await new Promise((resolve) => server.close(resolve));
+  }
+});
+
+test('lawTitleFallbackQueries: 種別語を外した名前・主要な語・前方部分の順に、段数の上限まで検索語を短くする', () => {
+  assert.deepEqual(lawTitleFallbackQueries('個人情報保護法', 3), ['個人情報保護', '個人情報']);
+  assert.deepEqual(lawTitleFallbackQueries('電子署名及び認証業務法', 3), [
+    '電子署名及び認証業務',
+    '電子署名',
+    '電子署名及び認証',
+  ]);
+  assert.deepEqual(lawTitleFallbackQueries('個人情報保護法', 1), ['個人情報保護']);
+  assert.deepEqual(lawTitleFallbackQueries('個人情報保護法', 0), []);
+  // 短すぎる検索語は無関係な法令ばかり当たるため作らない。
+  assert.deepEqual(lawTitleFallbackQueries('特許法', 3), []);
+});
+
+test('searchLawCandidatesWithFallback: 0件なら短くした法令名で検索し直し、見つかった検索語を返す', async () => {
+  const queries: string[] = [];
+  const server = http.createServer((req, res) => {
+    const lawTitle = new URL(req.url ?? '', 'http://localhost').searchParams.get('law_title') ?? '';
+    queries.push(lawTitle);
+    const laws =
+      lawTitle === '個人情報'
+        ? [{ law_info: { law_id: '415AC0000000057' }, revision_info: { law_title: '個人情報の保護に関する法律' } }]
+        : [];
+    res.writeHead(200, { 'content-type': 'application/json' });
+    res.end(JSON.stringify({ total_count: laws.length, laws }));
+  });
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+  const { port } = server.address() as AddressInfo;
+  try {
+    const options = { ...defaultCliOptions(), apiBaseUrl: `http://127.0.0.1:${port}`, requestIntervalMs: 0 };
+    const found = await searchLawCandidatesWithFallback(options, '個人情報保護法');
+    assert.equal(found.query, '個人情報');
+    assert.deepEqual(found.candidates.map((candidate) => candidate.law_id), ['415AC0000000057']);
+    assert.deepEqual(queries, ['個人情報保護法', '個人情報保護', '個人情報']);
+
+    queries.length = 0;
+    const limited = await searchLawCandidatesWithFallback({ ...options, titleFallbackSteps: 1 }, '個人情報保護法');
+    assert.deepEqual(limited, { query: '個人情報保護法', candidates: [] });
+    assert.deepEqual(queries, ['個人情報保護法', '個人情報保護']);
+  } finally {
+    server.closeAllConnections();
+    await new Promise((resolve) => server.close(resolve));
+  }
+});
+
+test('searchLawCandidatesByLawNum: 正式な表記の法令番号で照会し、法令番号の一致する候補だけを返す', async () => {
+  const queries: string[] = [];
+  const server = http.createServer((req, res) => {
+    queries.push(new URL(req.url ?? '', 'http://localhost').searchParams.get('law_num') ?? '');
+    const laws = [
+      { law_info: { law_id: '334AC0000000121', law_num: '昭和三十四年法律第百二十一号' }, revision_info: { law_title: '特許法' } },
+      { law_info: { law_id: '334AC0000000123', law_num: '昭和三十四年法律第百二十三号' }, revision_info: { law_title: '意匠法' } },
+    ];
+    res.writeHead(200, { 'content-type': 'application/json' });
+    res.end(JSON.stringify({ total_count: laws.length, laws }));
+  });
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+  const { port } = server.address() as AddressInfo;
+  try {
+    const options = { ...defaultCliOptions(), apiBaseUrl: `http://127.0.0.1:${port}`, requestIntervalMs: 0 };
+    const candidates = await searchLawCandidatesByLawNum(options, '昭34法121');
+    assert.deepEqual(candidates.map((candidate) => candidate.law_id), ['334AC0000000121']);
+    assert.deepEqual(queries, ['昭和三十四年法律第百二十一号']);
+    // 法令番号と読めない入力ではAPIを呼ばない。
+    assert.deepEqual(await searchLawCandidatesByLawNum(options, '特許法'), []);
+    assert.equal(queries.length, 1);
+  } finally {
+    server.closeAllConnections();
+    await new Promise((resolve) => server.close(resolve));
+  }
+});
@@ -1,40 +1,204 @@
 import process from 'node:process';
-import { toSafeTitle } from './notes.js';
-import { writeJson } from './storage.js';
-import { wait } from './utils.js';
-import type { CliOptions, LawCandidate, LawDataResponse, LawDictionary } from './types.js';
+import { canonicalizeKanji } from './kanji.js';
+import { normalizeLawNum } from './lawnum.js';
+import { classifyLawType, getFileName, toSafeTitle } from './notes.js';
+import { countRunStat, recordApiRequest, recordCacheLookup } from './stats.js';
+import {
+  dictionaryProvenance,
+  findDictionaryKeyConflicts,
+  isSearchCacheFresh,
+  loadLawDataArchive,
+  loadSearchCache,
+  reportDictionaryKeyConflicts,
+  saveDictionary,
+  saveLawDataArchive,
+  writeJson,
+} from './storage.js';
+import { throttleRequest, wait } from './utils.js';
+import type { CliOptions, LawCandidate, LawContents, LawDataResponse, LawDictionary, LawSuccessor } from './types.js';
+
+// 検索し直す法令名がこれより短いと、無関係な法令ばかりが当たる。
+const TITLE_FALLBACK_MIN_LENGTH = 3;
+// うろ覚えの法令名でも付けがちな末尾の種別語。付けたままだと正式名称と一致しにくい。
+const TITLE_KIND_SUFFIX_PATTERN = /(?:に関する法律|施行規則|施行令|法律|規則|法|令)$/;
+const TITLE_TOKEN_SEPARATOR_PATTERN = /(?:に関する|及び|並びに|の|等|[、・\s])+/;
+
+/**
+ * 指定ミリ秒後に中断するAbortControllerを作る。
+ * `AbortSignal.timeout` と違い、途中で解除できることと中断理由を日本語で示せることが必要なため自前で用意する。
+ */
+function abortAfter(ms: number, message: string): { controller: AbortController; cancel: () => void } {
+  const controller = new AbortController();
+  const timer = setTimeout(() => controller.abort(new Error(message)), ms);
+  return { controller, cancel: () => clearTimeout(timer) };
+}
 
 /**
  * 指定URLのJSONを取得する。
+ * 応答ヘッダの受信までを `--connect-timeout-ms`、本文の受信完了までを `--timeout-ms` で打ち切る。
+ * タイムアウトは一時的な混雑で起きることが多いため、他の失敗と同じく `--retry` の回数まで再試行する。
+ * e-Govへの負荷を把握できるよう、再試行を含む呼び出し回数と受信サイズをAPI統計に記録する。
  */
-export async function fetchJson(url: string, retry: number): Promise<unknown> {
+export async function fetchJson(url: string, options: CliOptions): Promise<unknown> {
+  const retry = options.retry;
   let lastError: unknown;
   for (let i = 0; i < retry; i += 1) {
+    let overall: ReturnType<typeof abortAfter> | undefined;
+    let connect: ReturnType<typeof abortAfter> | undefined;
+    let bytes = 0;
     try {
-      const response = await fetch(url);
+      // 間隔制御の待機時間はタイムアウトに含めない。
+      const waitedMs = await throttleRequest(options.requestIntervalMs);
+      if (options.verbose && waitedMs > 0) {
+        process.stderr.write(`リクエスト間隔待機: ${waitedMs}ms (${url})\n`);
+      }
+      overall = abortAfter(options.timeoutMs, `APIタイムアウト (${options.timeoutMs}ms) ${url}`);
+      connect = abortAfter(options.connectTimeoutMs, `API接続タイムアウト (${options.connectTimeoutMs}ms) ${url}`);
+      const response = await fetch(url, {
+        signal: AbortSignal.any([overall.controller.signal, connect.controller.signal]),
+      });
+      connect.cancel();
+      const body = await response.text();
+      // 転送量を把握したいので、圧縮転送時も実際に受信した大きさを示す content-length を優先する。
+      bytes = Number(response.headers.get('content-length')) || Buffer.byteLength(body);
       if (!response.ok) {
-        const body = await response.text();
         throw new Error(`APIエラー ${response.status} ${url}: ${body}`);
       }
-      return await response.json();
+      return JSON.parse(body);
     } catch (error) {
       lastError = error;
       if (i + 1 < retry) {
         await wait(2 ** i * 1000);
       }
+    } finally {
+      overall?.cancel();
+      connect?.cancel();
+      recordApiRequest(url, i > 0, bytes);
     }
   }
   throw lastError;
 }
 
+/**
+ * APIバージョンごとに異なるエンドポイントパスとレスポンス解釈をまとめたもの。
+ * ミラーや将来のAPIバージョンへ向ける際は、ここに定義を追加して差し替える。
+ */
+export interface ApiVersionProfile {
+  version: string;
+  lawsPath: () => string;
+  lawDataPath: (lawId: string) => string;
+  parseLawCandidates: (payload: unknown) => LawCandidate[];
+  /** 一覧APIの1ページ分の件数と、APIが示す総件数（示さなければ undefined）。 */
+  parseLawsPage: (payload: unknown) => LawsPageInfo;
+  /** 一覧APIの結果から、`law_id` が一致する法令の改正情報（無ければ undefined）。 */
+  parseRevisionInfo: (payload: unknown, lawId: string) => Record<string, unknown> | undefined;
+}
+
+/**
+ * 一覧APIの1ページ分の件数。`received` は法令名の無い要素も含めて実際に返された件数。
+ */
+export interface LawsPageInfo {
+  received: number;
+  totalCount?: number;
+}
+
+function isJsonObject(value: unknown): value is Record<string, unknown> {
+  return typeof value === 'object' && value !== null && !Array.isArray(value);
+}
+
+/**
+ * 受信JSONの概要（トップレベルのキー一覧と `law_info` / `revision_info` の有無）を返す。
+ * 一覧APIでは `laws` の先頭要素を見る。
+ */
+export function describeJsonShape(payload: unknown): string {
+  if (!isJsonObject(payload)) {
+    return `受信JSON: ${Array.isArray(payload) ? '配列' : payload === null ? 'null' : typeof payload}`;
+  }
+  const first = Array.isArray(payload.laws) ? payload.laws[0] : payload;
+  const has = (key: string) => (isJsonObject(first) && key in first ? 'あり' : 'なし');
+  return (
+    `受信JSON: トップレベルキー [${Object.keys(payload).join(', ')}]` +
+    ` / law_info ${has('law_info')} / revision_info ${has('revision_info')}`
+  );
+}
+
+/**
+ * APIレスポンスが想定した形をしていないことを表すエラー。e-Gov APIの仕様変更にすぐ気づけるよう、
+ * 失敗したJSONパスと受信JSONの概要をメッセージに含める。
+ */
+export class ApiResponseShapeError extends Error {
+  constructor(
+    readonly jsonPath: string,
+    readonly problem: string,
+    readonly payload: unknown,
+  ) {
+    super(`APIレスポンスの形式が想定と異なります: ${jsonPath} ${problem}（${describeJsonShape(payload)}）`);
+    this.name = 'ApiResponseShapeError';
+  }
+}
+
+/**
+ * 法令名の候補が複数ある（または検索し直して得た不確かな候補しかない）ため、1件に確定できなかったことを表すエラー。候補一覧は標準出力へ出力済みで、終了コードは `2` にする。
+ * 即座に終了せず例外にするのは、終了時のAPI統計や実行サマリを書き出してから終わるため。
+ */
+export class AmbiguousLawTitleError extends Error {
+  readonly exitCode = 2;
+
+  constructor(
+    readonly lawTitle: string,
+    readonly candidateCount: number,
+  ) {
+    super(`法令名を1件に確定できません: ${lawTitle}（候補 ${candidateCount}件）`);
+    this.name = 'AmbiguousLawTitleError';
+  }
+}
+
+function expectOptionalObject(payload: unknown, value: unknown, jsonPath: string): void {
+  if (value !== undefined && !isJsonObject(value)) {
+    throw new ApiResponseShapeError(jsonPath, 'がオブジェクトではありません', payload);
+  }
+}
+
+/**
+ * 受信JSONを解釈し、形式が想定と異なれば `--dump-response` 指定時に生JSONを保存してからエラーにする。
+ */
+async function parseResponse<T>(
+  options: Pick<CliOptions, 'dumpResponsePath'>,
+  url: string,
+  payload: unknown,
+  parse: (payload: unknown) => T,
+): Promise<T> {
+  try {
+    return parse(payload);
+  } catch (error) {
+    if (!(error instanceof ApiResponseShapeError) || !options.dumpResponsePath) {
+      throw error;
+    }
+    await writeJson(options.dumpResponsePath, { url, received_at: new Date().toISOString(), response: payload });
+    error.message += ` 受信したJSONを ${options.dumpResponsePath} に保存しました`;
+    throw error;
+  }
+}
+
 /**
  * 法令候補検索API `/api/2/laws` の結果を候補配列へ変換する。
+ * `laws` が配列でない、または要素の `law_info` / `revision_info` がオブジェクトでない場合は `ApiResponseShapeError` にする。
  */
 export function parseLawCandidates(payload: unknown): LawCandidate[] {
-  const root = payload as {
-    laws?: Array<{ law_info?: Record<string, unknown>; revision_info?: Record<string, unknown> }>;
-  };
-  const list = root.laws ?? [];
+  if (!isJsonObject(payload)) {
+    throw new ApiResponseShapeError('$', 'がオブジェクトではありません', payload);
+  }
+  if (!Array.isArray(payload.laws)) {
+    throw new ApiResponseShapeError('$.laws', payload.laws === undefined ? 'がありません' : 'が配列ではありません', payload);
+  }
+  for (const [index, item] of payload.laws.entries()) {
+    if (!isJsonObject(item)) {
+      throw new ApiResponseShapeError(`$.laws[${index}]`, 'がオブジェクトではありません', payload);
+    }
+    expectOptionalObject(payload, item.law_info, `$.laws[${index}].law_info`);
+    expectOptionalObject(payload, item.revision_info, `$.laws[${index}].revision_info`);
+  }
+  const list = payload.laws as Array<{ law_info?: Record<string, unknown>; revision_info?: Record<string, unknown> }>;
   const candidates: LawCandidate[] = [];
   for (const item of list) {
     const lawInfo = item.law_info ?? {};
@@ -48,56 +212,379 @@ export function parseLawCandidates(payload: unknown): LawCandidate[] {
       law_num: typeof lawInfo.law_num === 'string' ? lawInfo.law_num : undefined,
       law_title: lawTitle,
       promulgation_date: typeof lawInfo.promulgation_date === 'string' ? lawInfo.promulgation_date : undefined,
+      law_type: typeof lawInfo.law_type === 'string' ? lawInfo.law_type : undefined,
+      abbrev: typeof revisionInfo.abbrev === 'string' && revisionInfo.abbrev ? revisionInfo.abbrev : undefined,
+      repeal_status:
+        typeof revisionInfo.repeal_status === 'string' && revisionInfo.repeal_status
+          ? revisionInfo.repeal_status
+          : undefined,
     });
   }
   return candidates;
 }
 
 /**
- * 法令名を law_id に解決する。
+ * 一覧API `/api/2/laws` の結果から、返された件数と総件数（`total_count`）を読む。形式の検証は `parseLawCandidates` で済ませておく。
  */
-export async function resolveLawIdByTitle(options: CliOptions, lawTitle: string): Promise<LawCandidate> {
-  const url = new URL('/api/2/laws', options.apiBaseUrl);
+export function parseLawsPage(payload: unknown): LawsPageInfo {
+  const laws = isJsonObject(payload) && Array.isArray(payload.laws) ? payload.laws : [];
+  const totalCount = isJsonObject(payload) ? payload.total_count : undefined;
+  return {
+    received: laws.length,
+    totalCount: Number.isInteger(totalCount) && (totalCount as number) >= 0 ? (totalCount as number) : undefined,
+  };
+}
+
+/**
+ * 一覧API `/api/2/laws` の結果から、`law_id` が一致する法令の `revision_info` を取り出す。
+ * 形式の検証は `parseLawCandidates` で済ませておく。
+ */
+export function parseRevisionInfo(payload: unknown, lawId: string): Record<string, unknown> | undefined {
+  const laws = isJsonObject(payload) && Array.isArray(payload.laws) ? payload.laws : [];
+  const item = laws.find((law) => isJsonObject(law) && isJsonObject(law.law_info) && law.law_info.law_id === lawId);
+  return isJsonObject(item) && isJsonObject(item.revision_info) ? item.revision_info : undefined;
+}
+
+/**
+ * 法令本文API `/api/2/law_data/{law_id}` の結果を検証する。`law_info` と `revision_info` がどちらも無い、
+ * またはオブジェクトでない場合は `ApiResponseShapeError` にする。
+ */
+export function parseLawDataResponse(payload: unknown): LawDataResponse {
+  if (!isJsonObject(payload)) {
+    throw new ApiResponseShapeError('$', 'がオブジェクトではありません', payload);
+  }
+  if (payload.law_info === undefined && payload.revision_info === undefined) {
+    throw new ApiResponseShapeError('$', 'に law_info と revision_info がありません', payload);
+  }
+  expectOptionalObject(payload, payload.law_info, '$.law_info');
+  expectOptionalObject(payload, payload.revision_info, '$.revision_info');
+  return payload as LawDataResponse;
+}
+
+/**
+ * `law_data` の `revision_info` から、廃止された法令を廃止した法令を取り出す。
+ * 廃止された法令の最新の改正情報は廃止を定めた法令を指すため、それを新法とみなす。廃止されていなければ undefined を返す。
+ */
+export function parseLawSuccessor(revisionInfo: Record<string, unknown> | undefined): LawSuccessor | undefined {
+  const lawId = revisionInfo?.amendment_law_id;
+  if (revisionInfo?.repeal_status !== 'Repeal' || typeof lawId !== 'string' || !lawId) {
+    return undefined;
+  }
+  const title = revisionInfo.amendment_law_title;
+  return { lawId, title: typeof title === 'string' && title ? title : `law_${lawId}` };
+}
+
+const API_PROFILES: Record<string, ApiVersionProfile> = {
+  '2': {
+    version: '2',
+    lawsPath: () => '/api/2/laws',
+    lawDataPath: (lawId) => `/api/2/law_data/${encodeURIComponent(lawId)}`,
+    parseLawCandidates,
+    parseLawsPage,
+    parseRevisionInfo,
+  },
+};
+
+/**
+ * `--api-version` に対応するAPI定義を返す。
+ */
+export function getApiProfile(version: string): ApiVersionProfile {
+  const profile = API_PROFILES[version];
+  if (!profile) {
+    throw new Error(`未対応のAPIバージョンです: ${version}`);
+  }
+  return profile;
+}
+
+/**
+ * `law_title` で法令候補を検索する。
+ * `--use-search-cache` 指定時は、TTL内のキャッシュがあればAPIを呼ばずに候補を返す。
+ * `--exclude-repealed` 指定時は廃止・失効した候補を除く。キャッシュには除く前の候補一式を保存する。
+ * 候補を見比べやすいよう、APIの返却順ではなく公布日の新しい順に並べて返す。
+ */
+export async function searchLawCandidates(options: CliOptions, lawTitle: string): Promise<LawCandidate[]> {
+  const candidates = await searchAllLawCandidates(options, lawTitle);
+  return (options.excludeRepealed ? candidates.filter(isCurrentCandidate) : [...candidates]).sort(
+    compareByPromulgation,
+  );
+}
+
+/**
+ * 法令名検索が0件だったときに順に試す、短くした検索語を返す。末尾の種別語（法・施行令など）を外した名前、
+ * それを助詞などで区切った語のうち最も長いもの、種別語を外した名前の前方を2文字ずつ削ったものの順に、`maxSteps` 個まで並べる。
+ */
+export function lawTitleFallbackQueries(lawTitle: string, maxSteps: number): string[] {
+  const title = lawTitle.trim();
+  const queries: string[] = [];
+  const add = (query: string) => {
+    if (query.length >= TITLE_FALLBACK_MIN_LENGTH && query !== title && !queries.includes(query)) {
+      queries.push(query);
+    }
+  };
+  const base = title.replace(TITLE_KIND_SUFFIX_PATTERN, '');
+  add(base);
+  const tokens = base.split(TITLE_TOKEN_SEPARATOR_PATTERN);
+  add(tokens.reduce((longest, token) => (token.length > longest.length ? token : longest), ''));
+  for (let length = base.length - 2; length >= TITLE_FALLBACK_MIN_LENGTH; length -= 2) {
+    add(base.slice(0, length));
+  }
+  return queries.slice(0, maxSteps);
+}
+
+/**
+ * `law_title` で法令候補を検索し、0件なら `lawTitleFallbackQueries` の検索語で `--title-fallback-steps` 回まで検索し直す。
+ * `query` は候補が見つかった検索語で、検索し直した場合は入力と異なる。検索し直して得た候補は入力との一致が不確かなため、
+ * 呼び出し側は自動では確定しないこと。
+ */
+export async function searchLawCandidatesWithFallback(
+  options: CliOptions,
+  lawTitle: string,
+): Promise<{ query: string; candidates: LawCandidate[] }> {
+  const candidates = await searchLawCandidates(options, lawTitle);
+  if (candidates.length > 0) {
+    return { query: lawTitle, candidates };
+  }
+  for (const query of lawTitleFallbackQueries(lawTitle, options.titleFallbackSteps)) {
+    const fallback = await searchLawCandidates(options, query);
+    if (fallback.length > 0) {
+      process.stderr.write(
+        `法令名「${lawTitle}」では見つからないため「${query}」で検索し直しました（${fallback.length}件）。` +
+          '入力と一致しない可能性があるため自動では確定しません\n',
+      );
+      return { query, candidates: fallback };
+    }
+  }
+  return { query: lawTitle, candidates: [] };
+}
+
+async function searchAllLawCandidates(options: CliOptions, lawTitle: string): Promise<LawCandidate[]> {
+  const cache = options.useSearchCache ? await loadSearchCache(options.searchCachePath) : undefined;
+  const cached = cache?.[lawTitle];
+  const hit = cached !== undefined && isSearchCacheFresh(cached, options.searchCacheTtlHours);
+  if (cache) {
+    recordCacheLookup('search', hit);
+  }
+  if (hit) {
+    return cached.candidates;
+  }
+
+  const profile = getApiProfile(options.apiVersion);
+  const url = new URL(profile.lawsPath(), options.apiBaseUrl);
   url.searchParams.set('law_title', lawTitle);
-  const payload = await fetchJson(url.toString(), options.retry);
-  const candidates = parseLawCandidates(payload);
+  const payload = await fetchJson(url.toString(), options);
+  const candidates = await parseResponse(options, url.toString(), payload, profile.parseLawCandidates);
+
+  if (cache) {
+    // 確定前の候補一式は辞書と性質が異なるため、別ファイルへ保存する。
+    cache[lawTitle] = { cached_at: new Date().toISOString(), candidates };
+    await writeJson(options.searchCachePath, cache);
+  }
+  return candidates;
+}
+
+/**
+ * 法令番号で法令一覧APIを照会し、法令番号が表記ゆれを除いて一致する候補を返す。
+ * 法令名検索では法令番号の入力に一致しないため、法令番号で指定されて辞書に無い法令の解決に使う。
+ */
+export async function searchLawCandidatesByLawNum(options: CliOptions, lawNum: string): Promise<LawCandidate[]> {
+  const target = normalizeLawNum(lawNum);
+  if (!target) {
+    return [];
+  }
+  const profile = getApiProfile(options.apiVersion);
+  const url = new URL(profile.lawsPath(), options.apiBaseUrl);
+  // APIは正規の表記で照合するため、略記や算用数字の入力も漢数字の正式な表記にして渡す。
+  url.searchParams.set('law_num', target.key);
+  const payload = await fetchJson(url.toString(), options);
+  const candidates = await parseResponse(options, url.toString(), payload, profile.parseLawCandidates);
+  return candidates.filter((candidate) => candidate.law_num && normalizeLawNum(candidate.law_num)?.key === target.key);
+}
+
+/**
+ * `law_id` で法令一覧APIを照会し、現行の法令名・法令番号を返す。APIに存在しなければ undefined を返す。
+ * 本文を含む `law_data` より応答が小さいため、辞書の全件検査に使う。
+ */
+export async function fetchLawInfoById(options: CliOptions, lawId: string): Promise<LawCandidate | undefined> {
+  const profile = getApiProfile(options.apiVersion);
+  const url = new URL(profile.lawsPath(), options.apiBaseUrl);
+  url.searchParams.set('law_id', lawId);
+  const payload = await fetchJson(url.toString(), options);
+  const candidates = await parseResponse(options, url.toString(), payload, profile.parseLawCandidates);
+  return candidates.find((candidate) => candidate.law_id === lawId);
+}
+
+/**
+ * `law_id` で法令一覧APIを照会し、その法令の `revision_info`（廃止状態・最新の改正法令・リビジョンID）を返す。
+ * 本文を取得しない実行でも、`law_data` を呼ばずに廃止や前後関係を読めるようにする。APIに存在しなければ undefined を返す。
+ */
+export async function fetchRevisionInfoById(
+  options: CliOptions,
+  lawId: string,
+): Promise<Record<string, unknown> | undefined> {
+  const profile = getApiProfile(options.apiVersion);
+  const url = new URL(profile.lawsPath(), options.apiBaseUrl);
+  url.searchParams.set('law_id', lawId);
+  const payload = await fetchJson(url.toString(), options);
+  return parseResponse(options, url.toString(), payload, (body) => {
+    // 壊れた応答は候補の変換と同じ基準で ApiResponseShapeError にする。
+    profile.parseLawCandidates(body);
+    return profile.parseRevisionInfo(body, lawId);
+  });
+}
+
+/**
+ * `revision_info.repeal_status` が廃止・失効などを示すかを返す。現行（`None`）と、値が無く分からない場合は false にする。
+ */
+export function isRepealedStatus(repealStatus: unknown): boolean {
+  return typeof repealStatus === 'string' && repealStatus !== '' && repealStatus !== 'None';
+}
+
+function promulgationDateKey(date: string | undefined): number | undefined {
+  // APIは `YYYY-MM-DD` で返すが、古いキャッシュや辞書には `YYYY/MM/DD` も混じるため、区切りを問わず数値にして比べる。
+  const match = date?.trim().match(/^(\d{4})[-/](\d{1,2})[-/](\d{1,2})$/);
+  return match ? Number(match[1]) * 10000 + Number(match[2]) * 100 + Number(match[3]) : undefined;
+}
+
+/**
+ * 候補を公布日の新しい順に並べる比較関数。公布日が無い・読めない候補は末尾に回し、それらどうしは元の順を保つ。
+ */
+export function compareByPromulgation(a: LawCandidate, b: LawCandidate): number {
+  const aKey = promulgationDateKey(a.promulgation_date);
+  const bKey = promulgationDateKey(b.promulgation_date);
+  if (aKey === undefined || bKey === undefined) {
+    return (aKey === undefined ? 1 : 0) - (bKey === undefined ? 1 : 0);
+  }
+  return bKey - aKey;
+}
+
+function isCurrentCandidate(candidate: LawCandidate): boolean {
+  // 廃止状態の分からない候補（古い検索キャッシュなど）は現行とみなす。
+  return !isRepealedStatus(candidate.repeal_status);
+}
+
+function preferCurrentCandidate(candidates: LawCandidate[]): LawCandidate | undefined {
+  const current = candidates.filter(isCurrentCandidate);
+  const pool = current.length > 0 ? current : candidates;
+  if (pool.length === 1) {
+    return pool[0];
+  }
+  const [latest, next] = [...pool].sort(compareByPromulgation);
+  // 公布日が分からない・同じ日の候補は優劣を付けられないため確定しない。
+  return promulgationDateKey(latest.promulgation_date) !== undefined && compareByPromulgation(latest, next) !== 0
+    ? latest
+    : undefined;
+}
+
+/**
+ * 対話なしで候補を1件に確定する。候補が1件ならそれを、複数なら法令名が完全一致する唯一の候補を選ぶ。
+ * 完全一致が複数（同名の別法令）のときは `candidatePolicy` に従い、`prefer-current` なら現行の法令、公布日の新しい法令の順に
+ * 優先する。完全一致が無いときは、`partialMatchRatio` の指定があれば、法令名に検索語を含む唯一の候補を、
+ * 検索語が法令名に占める割合がその値以上の場合に限って選ぶ。
+ * 法令名は旧字体・踊り字などの表記ゆれを正規化して比べる。確定できなければ undefined を返す。
+ */
+export function selectBestCandidate(
+  lawTitle: string,
+  candidates: LawCandidate[],
+  policy: Pick<CliOptions, 'candidatePolicy' | 'partialMatchRatio'>,
+): LawCandidate | undefined {
+  const withId = candidates.filter((candidate) => candidate.law_id);
+  if (withId.length === 1) {
+    return withId[0];
+  }
+  const key = canonicalizeKanji(lawTitle);
+  const exact = withId.filter((candidate) => canonicalizeKanji(candidate.law_title) === key);
+  if (exact.length === 1) {
+    return exact[0];
+  }
+  if (exact.length > 1) {
+    return policy.candidatePolicy === 'prefer-current' ? preferCurrentCandidate(exact) : undefined;
+  }
+  if (policy.partialMatchRatio === undefined || !key) {
+    return undefined;
+  }
+  const partial = withId.filter((candidate) => canonicalizeKanji(candidate.law_title).includes(key));
+  if (partial.length !== 1) {
+    return undefined;
+  }
+  const ratio = key.length / canonicalizeKanji(partial[0].law_title).length;
+  return ratio >= policy.partialMatchRatio ? partial[0] : undefined;
+}
+
+/**
+ * 既定の方針（`--candidate-policy prefer-current`、部分一致では確定しない）で候補を1件に確定する。
+ */
+export function selectCandidateNonInteractive(lawTitle: string, candidates: LawCandidate[]): LawCandidate | undefined {
+  return selectBestCandidate(lawTitle, candidates, { candidatePolicy: 'prefer-current' });
+}
+
+/**
+ * 法令名を law_id に解決する。0件なら短くした法令名で検索し直し、そこで見つかった候補は1件でも一覧として示して止める。
+ */
+export async function resolveLawIdByTitle(options: CliOptions, lawTitle: string): Promise<LawCandidate> {
+  const { query, candidates } = await searchLawCandidatesWithFallback(options, lawTitle);
 
   if (candidates.length === 0) {
     throw new Error(`法令候補を抽出できませんでした: ${lawTitle}`);
   }
-  if (candidates.length > 1) {
+  // 検索し直して得た候補は、1件でも入力の法令とは限らないため候補一覧として示す。
+  if (candidates.length > 1 || query !== lawTitle) {
     process.stdout.write(
       `${JSON.stringify(
         {
           error: 'ambiguous_law_title',
           input: lawTitle,
-          candidates,
+          fallback_query: query !== lawTitle ? query : undefined,
+          // 同名に近い候補を見分けやすいよう、略称に加えてlaw_id等から推定した法令種別も示す。
+          candidates: candidates.map((candidate) => ({
+            ...candidate,
+            law_kind: candidate.law_id ? classifyLawType(candidate.law_id, candidate) : undefined,
+            // 廃止状態の分からない候補には付けない。
+            repealed: candidate.repeal_status ? isRepealedStatus(candidate.repeal_status) : undefined,
+          })),
         },
         null,
         2,
       )}\n`,
     );
-    process.exit(2);
+    throw new AmbiguousLawTitleError(lawTitle, candidates.length);
   }
   return candidates[0];
 }
 
 /**
  * `/api/2/laws` を全件走査し、参照解決用の辞書ファイルを再生成する。
+ * 1回の取得件数は `--dict-page-size` で、APIが総件数を返せばそれで進捗を示し、読み終えたところで止める。
  */
 export async function buildDictionary(options: CliOptions): Promise<void> {
+  const profile = getApiProfile(options.apiVersion);
   const dictionary: LawDictionary = {};
   let offset = 0;
-  const limit = 100;
+  const limit = options.dictPageSize;
+  let cappedWarned = false;
 
   while (true) {
-    const url = new URL('/api/2/laws', options.apiBaseUrl);
+    const url = new URL(profile.lawsPath(), options.apiBaseUrl);
     url.searchParams.set('limit', String(limit));
     url.searchParams.set('offset', String(offset));
 
-    const payload = await fetchJson(url.toString(), options.retry);
-    const candidates = parseLawCandidates(payload);
-    if (candidates.length === 0) {
+    let payload: unknown;
+    try {
+      payload = await fetchJson(url.toString(), options);
+    } catch (error) {
+      // 最初のページから断られるのは、多くは limit がAPIの上限を超えているため。
+      const message = error instanceof Error ? error.message : String(error);
+      if (offset === 0 && /^APIエラー 4\d\d /.test(message)) {
+        throw new Error(
+          `法令一覧を取得できませんでした。--dict-page-size ${limit} がAPIの上限を超えている可能性があります。` +
+            `小さい値を指定してください: ${message}`,
+          { cause: error },
+        );
+      }
+      throw error;
+    }
+    const candidates = await parseResponse(options, url.toString(), payload, profile.parseLawCandidates);
+    const page = profile.parseLawsPage(payload);
+    if (page.received === 0) {
       break;
     }
 
@@ -109,26 +596,77 @@ export async function buildDictionary(options: CliOptions): Promise<void> {
       dictionary[item.law_id] = {
         title: item.law_title,
         safe_title: safeTitle,
-        file_name: `${safeTitle}_${item.law_id}.md`,
+        file_name: getFileName(item.law_id, item.law_title),
         updated_at: new Date().toISOString(),
+        law_num: item.law_num,
+        law_type: item.law_type,
+        promulgation_date: item.promulgation_date,
+        abbrev: item.abbrev,
+        law_num_key: item.law_num ? normalizeLawNum(item.law_num)?.key : undefined,
+        ...dictionaryProvenance('api_scan'),
       };
     }
 
-    offset += limit;
+    // APIが上限に切り詰めて返した場合に取りこぼさないよう、要求した件数ではなく返された件数だけ進める。
+    if (page.received < limit && !cappedWarned && (page.totalCount ?? 0) > offset + page.received) {
+      process.stderr.write(`警告: APIが1回に返す件数が --dict-page-size ${limit} より少ないため、${page.received}件ずつ取得します\n`);
+      cappedWarned = true;
+    }
+    offset += page.received;
+    if (page.totalCount !== undefined) {
+      process.stderr.write(`辞書を取得中: ${Math.min(offset, page.totalCount)}/${page.totalCount}件\n`);
+      if (offset >= page.totalCount) {
+        break;
+      }
+    } else {
+      process.stderr.write(`辞書を取得中: ${offset}件\n`);
+    }
   }
 
-  await writeJson(options.dictionaryPath, dictionary);
+  await reportDictionaryKeyConflicts(options, findDictionaryKeyConflicts(dictionary, 'api_scan'));
+  await saveDictionary(options, dictionary);
+  countRunStat('dictionaryAdded', Object.keys(dictionary).length);
   process.stdout.write(`辞書を生成しました: ${options.dictionaryPath} (${Object.keys(dictionary).length}件)\n`);
 }
 
+/**
+ * `/api/2/law_data/{law_id}` を取得する。
+ * 本文フォーマットは `--full-text-format` に従い、メタ情報を読むためレスポンス全体は常にJSONで受け取る。
+ * `--archive-dir` 指定時は生レスポンスをアーカイブへ保存し、`--from-archive` 指定時はアーカイブにあればAPIを呼ばない。
+ */
+export async function fetchLawContents(options: CliOptions, lawId: string): Promise<LawContents> {
+  if (options.fromArchive && options.archiveDir) {
+    const archived = await loadLawDataArchive(options.archiveDir, lawId);
+    // 本文フォーマットが違うアーカイブは要求どおりに変換できないため、取得し直してアーカイブを置き換える。
+    if (archived && archived.full_text_format === options.fullTextFormat) {
+      return { lawId, fullTextFormat: archived.full_text_format, data: archived.response };
+    }
+  }
+  const url = new URL(getApiProfile(options.apiVersion).lawDataPath(lawId), options.apiBaseUrl);
+  url.searchParams.set('response_format', 'json');
+  url.searchParams.set('law_full_text_format', options.fullTextFormat);
+  const payload = await parseResponse(
+    options,
+    url.toString(),
+    await fetchJson(url.toString(), options),
+    parseLawDataResponse,
+  );
+  if (options.archiveDir) {
+    await saveLawDataArchive(options.archiveDir, lawId, options.fullTextFormat, payload);
+  }
+  return {
+    lawId,
+    fullTextFormat: options.fullTextFormat,
+    data: payload,
+  };
+}
+
 /**
  * `law_id` から法令名を取得し、辞書未登録エントリを補完する。
  */
 export async function fetchLawTitleById(options: CliOptions, lawId: string): Promise<string | undefined> {
-  const url = new URL(`/api/2/law_data/${lawId}`, options.apiBaseUrl);
-  url.searchParams.set('response_format', 'json');
-  const payload = (await fetchJson(url.toString(), options.retry)) as LawDataResponse;
-  const revisionInfo = payload.revision_info ?? {};
+  const contents = await fetchLawContents(options, lawId);
+  const revisionInfo = contents.data.revision_info ?? {};
   const title = revisionInfo.law_title;
   if (typeof title === 'string' && title.trim().length > 0) {
     return title.trim();
@@ -0,0 +1,36 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { parseArgs } from './index.js';
+
+const target = ['--law-id', '334AC0000000121'];
+
+test('parseArgs: --skip-existing は --if-exists skip と同じで、既定では既存ノートの先も辿る', () => {
+  const options = parseArgs([...target, '--skip-existing']);
+  assert.equal(options.ifExists, 'skip');
+  assert.equal(options.followExisting, true);
+  assert.equal(parseArgs(target).ifExists, 'overwrite');
+});
+
+test('parseArgs: --no-follow-existing は既存ノートをスキップするときだけ指定できる', () => {
+  assert.equal(parseArgs([...target, '--skip-existing', '--no-follow-existing']).followExisting, false);
+  assert.equal(parseArgs([...target, '--no-follow-existing', '--if-exists', 'skip']).followExisting, false);
+  assert.throws(() => parseArgs([...target, '--no-follow-existing']), /--no-follow-existing は --skip-existing/);
+  // 後から --if-exists overwrite を指定するとスキップしなくなるため、組み合わせられない。
+  assert.throws(
+    () => parseArgs([...target, '--skip-existing', '--no-follow-existing', '--if-exists', 'overwrite']),
+    /--no-follow-existing/,
+  );
+  // txt・html のノートは読み戻せないため、既存ノートをスキップする指定とは組み合わせられない。
+  assert.throws(() => parseArgs([...target, '--skip-existing', '--format', 'txt']), /--if-exists skip/);
+});
+
+test('parseArgs: --changelog は条の本文を持たない出力とは同時に指定できない', () => {
+  assert.equal(parseArgs([...target, '--changelog']).changelog, true);
+  // 分割出力では法令ノートに条の本文が無く、前回のノートと条単位で比べられない。
+  assert.throws(
+    () => parseArgs([...target, '--changelog', '--split-by', 'chapter']),
+    /--changelog は --split-by chapter と同時に指定できません/,
+  );
+  assert.throws(() => parseArgs([...target, '--changelog', '--format', 'html']), /--changelog/);
+});
@@ -1,29 +1,123 @@
+import process from 'node:process';
 import {
   DEFAULT_API_BASE,
+  DEFAULT_API_VERSION,
+  DEFAULT_DICT_PAGE_SIZE,
+  DEFAULT_ARCHIVE_DIR,
   DEFAULT_DICTIONARY_PATH,
+  DEFAULT_FAILED_PATH,
+  DEFAULT_HEADING_BASE_LEVEL,
+  DEFAULT_MISSING_LINKS_PATH,
+  DEFAULT_MOC_NAME,
   DEFAULT_OUTPUT_DIR,
+  DEFAULT_SEARCH_CACHE_PATH,
+  DEFAULT_TITLE_FALLBACK_STEPS,
   DEFAULT_UNRESOLVED_PATH,
+  SUPPORTED_API_VERSIONS,
 } from './config.js';
+import { LINKIFY_KINDS, parseLinkifyRules } from './linkify.js';
+import { parsePartialRange } from './partial.js';
+import { defaultRefPolicies, parseRefPolicy } from './refpolicy.js';
 import type { CliOptions } from './types.js';
 
 /**
- * CLI引数を解釈し、処理に必要なオプションを構築する。
+ * 引数未指定時の既定オプションを生成する。
  */
-export function parseArgs(argv: string[]): CliOptions {
-  const options: CliOptions = {
+export function defaultCliOptions(): CliOptions {
+  return {
     buildDictionary: false,
     maxDepth: 1,
     ifExists: 'overwrite',
+    followExisting: true,
+    exportLinks: 'keep',
+    fromArchive: false,
     retry: 3,
     timeoutMs: 30_000,
+    connectTimeoutMs: 10_000,
     dictionaryPath: DEFAULT_DICTIONARY_PATH,
+    dictionaryPaths: [DEFAULT_DICTIONARY_PATH],
     dictionaryAutoupdate: false,
     unresolvedPath: DEFAULT_UNRESOLVED_PATH,
     outputDir: DEFAULT_OUTPUT_DIR,
     apiBaseUrl: DEFAULT_API_BASE,
+    requestIntervalMs: 0,
+    fullTextFormat: 'json',
+    onConflict: 'keep',
+    dryRun: false,
+    backlinksSection: true,
+    useSearchCache: false,
+    searchCachePath: DEFAULT_SEARCH_CACHE_PATH,
+    searchCacheTtlHours: 168,
+    linkStyle: 'absolute',
+    selfLinks: 'path',
+    headingBaseLevel: DEFAULT_HEADING_BASE_LEVEL,
+    filenameScheme: 'title',
+    apiVersion: DEFAULT_API_VERSION,
+    dictPageSize: DEFAULT_DICT_PAGE_SIZE,
+    organizeByType: false,
+    dictCompact: false,
+    verbose: false,
+    stats: true,
+    apiStats: false,
+    summaryCallout: true,
+    failedPath: DEFAULT_FAILED_PATH,
+    missingLinksPath: DEFAULT_MISSING_LINKS_PATH,
+    stubMissing: false,
+    linkOnlyExisting: false,
+    timeline: false,
+    changelog: false,
+    retryFailed: false,
+    resolveUnresolved: false,
+    fetchResolved: false,
+    paragraphBlockIds: false,
+    diff: false,
+    fetchAllCandidates: false,
+    excludeRepealed: false,
+    nonInteractive: false,
+    refStatus: 'resolved',
+    titlesFromStdin: false,
+    repl: false,
+    splitBy: 'none',
+    lockTimeoutMs: 10_000,
+    refPolicies: defaultRefPolicies(),
+    refScope: 'all',
+    linkifyRules: [...LINKIFY_KINDS],
+    linkTextRefs: false,
+    shortRefLabels: false,
+    retryFailedPasses: 1,
+    dumpTags: false,
+    anchorStyle: 'id',
+    itemStyle: 'plain',
+    verifyDictionary: false,
+    fix: false,
+    yes: false,
+    traversal: 'bfs',
+    outputFormat: 'obsidian',
+    noteFormat: 'md',
+    lineEnding: 'lf',
+    citationStyle: 'none',
+    bom: false,
+    inlineTables: false,
+    standardLinks: false,
+    slowestLaws: 5,
+    titleFallbackSteps: DEFAULT_TITLE_FALLBACK_STEPS,
+    followSuccession: false,
+    minConfidence: 0,
+    candidatePolicy: 'prefer-current',
+    flushEach: false,
+    flushDictionaryEvery: 10,
   };
+}
+
+/**
+ * CLI引数を解釈し、処理に必要なオプションを構築する。
+ */
+export function parseArgs(argv: string[]): CliOptions {
+  const options = defaultCliOptions();
 
   const positional: string[] = [];
+  const dictionaryPaths: string[] = [];
+  let dictionaryWritePath: string | undefined;
   for (let i = 0; i < argv.length; i += 1) {
     const arg = argv[i];
     if (arg === '--law-id') {
@@ -46,6 +140,46 @@ export function parseArgs(argv: string[]): CliOptions {
       options.ifExists = v;
       continue;
     }
+    if (arg === '--export') {
+      const v = argv[++i];
+      if (v !== 'zip' && v !== 'single-md') {
+        throw new Error(`--export は zip または single-md を指定してください: ${v}`);
+      }
+      options.exportFormat = v;
+      continue;
+    }
+    if (arg === '--archive-dir') {
+      options.archiveDir = argv[++i];
+      continue;
+    }
+    if (arg === '--dump-response') {
+      options.dumpResponsePath = argv[++i];
+      continue;
+    }
+    if (arg === '--from-archive') {
+      options.fromArchive = true;
+      continue;
+    }
+    if (arg === '--export-out') {
+      options.exportOut = argv[++i];
+      continue;
+    }
+    if (arg === '--export-links') {
+      const v = argv[++i];
+      if (v !== 'keep' && v !== 'anchor') {
+        throw new Error(`--export-links は keep または anchor を指定してください: ${v}`);
+      }
+      options.exportLinks = v;
+      continue;
+    }
+    if (arg === '--skip-existing') {
+      options.ifExists = 'skip';
+      continue;
+    }
+    if (arg === '--no-follow-existing') {
+      options.followExisting = false;
+      continue;
+    }
     if (arg === '--retry') {
       options.retry = Number(argv[++i]);
       continue;
@@ -54,8 +188,28 @@ export function parseArgs(argv: string[]): CliOptions {
       options.timeoutMs = Number(argv[++i]);
       continue;
     }
+    if (arg === '--connect-timeout-ms') {
+      options.connectTimeoutMs = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--lock-timeout-ms') {
+      options.lockTimeoutMs = Number(argv[++i]);
+      continue;
+    }
     if (arg === '--dictionary') {
-      options.dictionaryPath = argv[++i];
+      dictionaryPaths.push(argv[++i]);
+      continue;
+    }
+    if (arg === '--dictionary-write-path') {
+      dictionaryWritePath = argv[++i];
+      continue;
+    }
+    if (arg === '--dict-conflict-log') {
+      options.dictConflictLogPath = argv[++i];
+      continue;
+    }
+    if (arg === '--dict-page-size') {
+      options.dictPageSize = Number(argv[++i]);
       continue;
     }
     if (arg === '--dictionary-autoupdate') {
@@ -74,19 +228,448 @@ export function parseArgs(argv: string[]): CliOptions {
       options.apiBaseUrl = argv[++i];
       continue;
     }
+    if (arg === '--web-base-url') {
+      options.webBaseUrl = argv[++i];
+      continue;
+    }
+    if (arg === '--request-interval-ms') {
+      options.requestIntervalMs = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--full-text-format') {
+      const v = argv[++i];
+      if (v !== 'json' && v !== 'xml') {
+        throw new Error(`--full-text-format は json または xml を指定してください: ${v}`);
+      }
+      options.fullTextFormat = v;
+      continue;
+    }
+    if (arg === '--import-dictionary') {
+      options.importDictionaryPath = argv[++i];
+      continue;
+    }
+    if (arg === '--on-conflict') {
+      const v = argv[++i];
+      if (v !== 'keep' && v !== 'overwrite' && v !== 'prefer-with-id') {
+        throw new Error(`--on-conflict は keep / overwrite / prefer-with-id のいずれかを指定してください: ${v}`);
+      }
+      options.onConflict = v;
+      continue;
+    }
+    if (arg === '--dry-run') {
+      options.dryRun = true;
+      continue;
+    }
+    if (arg === '--diff') {
+      options.diff = true;
+      continue;
+    }
+    if (arg === '--no-backlinks-section') {
+      options.backlinksSection = false;
+      continue;
+    }
+    if (arg === '--use-search-cache') {
+      options.useSearchCache = true;
+      continue;
+    }
+    if (arg === '--search-cache-path') {
+      options.searchCachePath = argv[++i];
+      continue;
+    }
+    if (arg === '--choices-path') {
+      options.choicesPath = argv[++i];
+      continue;
+    }
+    if (arg === '--search-cache-ttl-hours') {
+      options.searchCacheTtlHours = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--link-style') {
+      const v = argv[++i];
+      if (v !== 'shortest' && v !== 'relative' && v !== 'absolute') {
+        throw new Error(`--link-style は shortest / relative / absolute のいずれかを指定してください: ${v}`);
+      }
+      options.linkStyle = v;
+      continue;
+    }
+    if (arg === '--self-links') {
+      const v = argv[++i];
+      if (v !== 'path' && v !== 'internal') {
+        throw new Error(`--self-links は path または internal を指定してください: ${v}`);
+      }
+      options.selfLinks = v;
+      continue;
+    }
+    if (arg === '--heading-base-level') {
+      options.headingBaseLevel = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--filename-scheme') {
+      const v = argv[++i];
+      if (v !== 'title' && v !== 'law-id' && v !== 'law-num') {
+        throw new Error(`--filename-scheme は title / law-id / law-num のいずれかを指定してください: ${v}`);
+      }
+      options.filenameScheme = v;
+      continue;
+    }
+    if (arg === '--api-version') {
+      const v = argv[++i];
+      if (!SUPPORTED_API_VERSIONS.includes(v)) {
+        throw new Error(`--api-version は ${SUPPORTED_API_VERSIONS.join(' / ')} のいずれかを指定してください: ${v}`);
+      }
+      options.apiVersion = v;
+      continue;
+    }
+    if (arg === '--organize-by-type') {
+      options.organizeByType = true;
+      continue;
+    }
+    if (arg === '--dict-compact') {
+      options.dictCompact = true;
+      continue;
+    }
+    if (arg === '--dict-format') {
+      const v = argv[++i];
+      if (v !== 'json' && v !== 'messagepack') {
+        throw new Error(`--dict-format は json または messagepack を指定してください: ${v}`);
+      }
+      options.dictFormat = v;
+      continue;
+    }
+    if (arg === '--no-summary-callout') {
+      options.summaryCallout = false;
+      continue;
+    }
+    if (arg === '--no-stats') {
+      options.stats = false;
+      continue;
+    }
+    if (arg === '--api-stats') {
+      options.apiStats = true;
+      continue;
+    }
+    if (arg === '--fetch-all-candidates') {
+      options.fetchAllCandidates = true;
+      continue;
+    }
+    if (arg === '--exclude-repealed') {
+      options.excludeRepealed = true;
+      continue;
+    }
+    if (arg === '--paragraph-block-ids') {
+      options.paragraphBlockIds = true;
+      continue;
+    }
+    if (arg === '--missing-links-path') {
+      options.missingLinksPath = argv[++i];
+      continue;
+    }
+    if (arg === '--stub-missing') {
+      options.stubMissing = true;
+      continue;
+    }
+    if (arg === '--link-only-existing') {
+      options.linkOnlyExisting = true;
+      continue;
+    }
+    if (arg === '--timeline') {
+      options.timeline = true;
+      continue;
+    }
+    if (arg === '--changelog') {
+      options.changelog = true;
+      continue;
+    }
+    if (arg === '--moc') {
+      options.mocName ??= DEFAULT_MOC_NAME;
+      continue;
+    }
+    if (arg === '--moc-name') {
+      const v = argv[++i] ?? '';
+      if (!v || /[\\/]/.test(v)) {
+        throw new Error(`--moc-name には出力ディレクトリ直下のファイル名を指定してください: ${v}`);
+      }
+      options.mocName = v.endsWith('.md') ? v : `${v}.md`;
+      continue;
+    }
+    if (arg === '--failed-path') {
+      options.failedPath = argv[++i];
+      continue;
+    }
+    if (arg === '--flush-each') {
+      options.flushEach = true;
+      continue;
+    }
+    if (arg === '--flush-dictionary-every') {
+      options.flushDictionaryEvery = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--summary-json') {
+      options.summaryJsonPath = argv[++i];
+      continue;
+    }
+    if (arg === '--index-db') {
+      options.indexDbPath = argv[++i];
+      continue;
+    }
+    if (arg === '--retry-failed') {
+      options.retryFailed = true;
+      continue;
+    }
+    if (arg === '--title-filter') {
+      options.titleFilter = argv[++i];
+      continue;
+    }
+    if (arg === '--max-laws') {
+      options.maxLaws = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--non-interactive') {
+      options.nonInteractive = true;
+      continue;
+    }
+    if (arg === '--articles') {
+      options.articles = argv[++i];
+      continue;
+    }
+    if (arg === '--chapters') {
+      options.chapters = argv[++i];
+      continue;
+    }
+    if (arg === '--resolve-ref') {
+      options.resolveRef = argv[++i];
+      continue;
+    }
+    if (arg === '--resolve-unresolved') {
+      options.resolveUnresolved = true;
+      continue;
+    }
+    if (arg === '--fetch-resolved') {
+      options.fetchResolved = true;
+      continue;
+    }
+    if (arg === '--ref-source') {
+      options.refSource = argv[++i];
+      continue;
+    }
+    if (arg === '--ref-status') {
+      const v = argv[++i];
+      if (v !== 'resolved' && v !== 'ignored') {
+        throw new Error(`--ref-status は resolved または ignored を指定してください: ${v}`);
+      }
+      options.refStatus = v;
+      continue;
+    }
+    if (arg === '--ref-law-id') {
+      options.refLawId = argv[++i];
+      continue;
+    }
+    if (arg === '--ref-title') {
+      options.refTitle = argv[++i];
+      continue;
+    }
+    if (arg === '--verify-dictionary') {
+      options.verifyDictionary = true;
+      continue;
+    }
+    if (arg === '--fix') {
+      options.fix = true;
+      continue;
+    }
+    if (arg === '--yes') {
+      options.yes = true;
+      continue;
+    }
+    if (arg === '--anchor-style') {
+      const v = argv[++i];
+      if (v !== 'id' && v !== 'kanji' && v !== 'arabic') {
+        throw new Error(`--anchor-style は id / kanji / arabic のいずれかを指定してください: ${v}`);
+      }
+      options.anchorStyle = v;
+      continue;
+    }
+    if (arg === '--item-style') {
+      const v = argv[++i];
+      if (v !== 'plain' && v !== 'indent' && v !== 'list') {
+        throw new Error(`--item-style は plain / indent / list のいずれかを指定してください: ${v}`);
+      }
+      options.itemStyle = v;
+      continue;
+    }
+    if (arg === '--output-format') {
+      const v = argv[++i];
+      if (v !== 'obsidian' && v !== 'plain') {
+        throw new Error(`--output-format は obsidian / plain のいずれかを指定してください: ${v}`);
+      }
+      options.outputFormat = v;
+      continue;
+    }
+    if (arg === '--format') {
+      const v = argv[++i];
+      if (v !== 'md' && v !== 'txt' && v !== 'html') {
+        throw new Error(`--format は md / txt / html のいずれかを指定してください: ${v}`);
+      }
+      options.noteFormat = v;
+      continue;
+    }
+    if (arg === '--line-ending') {
+      const v = argv[++i];
+      if (v !== 'lf' && v !== 'crlf') {
+        throw new Error(`--line-ending は lf または crlf を指定してください: ${v}`);
+      }
+      options.lineEnding = v;
+      continue;
+    }
+    if (arg === '--citation-style') {
+      const v = argv[++i];
+      if (v !== 'none' && v !== 'section' && v !== 'frontmatter') {
+        throw new Error(`--citation-style は none / section / frontmatter のいずれかを指定してください: ${v}`);
+      }
+      options.citationStyle = v;
+      continue;
+    }
+    if (arg === '--bom') {
+      options.bom = true;
+      continue;
+    }
+    if (arg === '--inline-tables') {
+      options.inlineTables = true;
+      continue;
+    }
+    if (arg === '--standard-links') {
+      options.standardLinks = true;
+      continue;
+    }
+    if (arg === '--traversal') {
+      const v = argv[++i];
+      if (v !== 'bfs' && v !== 'dfs') {
+        throw new Error(`--traversal は bfs / dfs のいずれかを指定してください: ${v}`);
+      }
+      options.traversal = v;
+      continue;
+    }
+    if (arg === '--dump-tags') {
+      options.dumpTags = true;
+      continue;
+    }
+    if (arg === '--min-confidence') {
+      options.minConfidence = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--candidate-policy') {
+      const v = argv[++i];
+      if (v !== 'strict' && v !== 'prefer-current') {
+        throw new Error(`--candidate-policy は strict / prefer-current のいずれかを指定してください: ${v}`);
+      }
+      options.candidatePolicy = v;
+      continue;
+    }
+    if (arg === '--partial-match-ratio') {
+      options.partialMatchRatio = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--follow-succession') {
+      options.followSuccession = true;
+      continue;
+    }
+    if (arg === '--slowest-laws') {
+      options.slowestLaws = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--title-fallback-steps') {
+      options.titleFallbackSteps = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--retry-failed-passes') {
+      options.retryFailedPasses = Number(argv[++i]);
+      continue;
+    }
+    if (arg === '--ref-policy') {
+      options.refPolicies = parseRefPolicy(argv[++i] ?? '', options.refPolicies);
+      continue;
+    }
+    if (arg === '--ref-scope') {
+      const v = argv[++i];
+      if (v !== 'main' && v !== 'all') {
+        throw new Error(`--ref-scope は main または all を指定してください: ${v}`);
+      }
+      options.refScope = v;
+      continue;
+    }
+    if (arg === '--linkify-rules') {
+      options.linkifyRules = parseLinkifyRules(argv[++i] ?? '');
+      continue;
+    }
+    if (arg === '--link-text-refs') {
+      options.linkTextRefs = true;
+      continue;
+    }
+    if (arg === '--short-ref-labels') {
+      options.shortRefLabels = true;
+      continue;
+    }
+    if (arg === '--split-by') {
+      const v = argv[++i];
+      if (v !== 'none' && v !== 'chapter') {
+        throw new Error(`--split-by は none または chapter を指定してください: ${v}`);
+      }
+      options.splitBy = v;
+      continue;
+    }
+    if (arg === '--verbose') {
+      options.verbose = true;
+      continue;
+    }
+    if (arg === '--repl') {
+      options.repl = true;
+      continue;
+    }
     if (arg.startsWith('--')) {
       throw new Error(`未対応オプションです: ${arg}`);
     }
     positional.push(arg);
   }
 
-  if (positional.length > 0) {
+  if (positional.length === 1 && positional[0] === '-') {
+    options.titlesFromStdin = true;
+  } else if (positional.length > 0) {
     options.lawTitle = positional.join(' ');
   }
+  // `--dictionary` は複数指定でき、先に指定したものを優先して読み込む。
+  // 新規登録は最後に指定した辞書（`--dictionary-write-path` 指定時はそちら）にだけ書き戻す。
+  if (dictionaryPaths.length > 0) {
+    options.dictionaryPaths = dictionaryPaths;
+  }
+  options.dictionaryPath = dictionaryWritePath ?? options.dictionaryPaths[options.dictionaryPaths.length - 1];
+  if (!options.dictionaryPaths.includes(options.dictionaryPath)) {
+    options.dictionaryPaths = [...options.dictionaryPaths, options.dictionaryPath];
+  }
 
-  if (!options.buildDictionary && !options.lawId && !options.lawTitle) {
+  const hasTarget =
+    options.buildDictionary ||
+    Boolean(options.importDictionaryPath) ||
+    options.retryFailed ||
+    Boolean(options.titleFilter) ||
+    Boolean(options.resolveRef) ||
+    options.resolveUnresolved ||
+    options.verifyDictionary ||
+    Boolean(options.exportFormat) ||
+    options.repl ||
+    Boolean(options.lawId) ||
+    Boolean(options.lawTitle);
+  // 他のスクリプトからパイプで法令名リストを渡された場合は、引数なしでも標準入力から読む。
+  if (!hasTarget && !options.titlesFromStdin && !process.stdin.isTTY) {
+    options.titlesFromStdin = true;
+  }
+  if (!hasTarget && !options.titlesFromStdin) {
     throw new Error('法令名または --law-id を指定してください');
   }
+  if (options.repl && (options.lawId || options.lawTitle || options.titlesFromStdin)) {
+    throw new Error('--repl では法令名を対話で入力するため、法令名・--law-id と併用できません');
+  }
+  if (options.fetchResolved && !options.resolveUnresolved) {
+    throw new Error('--fetch-resolved は --resolve-unresolved と組み合わせて指定してください');
+  }
   if (options.maxDepth < 0 || Number.isNaN(options.maxDepth)) {
     throw new Error('--max-depth は0以上の整数にしてください');
   }
@@ -96,6 +679,100 @@ export function parseArgs(argv: string[]): CliOptions {
   if (options.timeoutMs <= 0 || Number.isNaN(options.timeoutMs)) {
     throw new Error('--timeout-ms は1以上の整数にしてください');
   }
+  if (options.titleFilter !== undefined) {
+    try {
+      new RegExp(options.titleFilter);
+    } catch (error) {
+      throw new Error(`--title-filter の正規表現が不正です: ${options.titleFilter} (${(error as Error).message})`);
+    }
+  }
+  if (options.maxLaws !== undefined && (!Number.isInteger(options.maxLaws) || options.maxLaws < 1)) {
+    throw new Error('--max-laws は1以上の整数にしてください');
+  }
+  // 範囲指定の書式誤りは取得を始める前に知らせる。
+  parsePartialRange(options.articles, options.chapters);
+  if (options.connectTimeoutMs <= 0 || Number.isNaN(options.connectTimeoutMs)) {
+    throw new Error('--connect-timeout-ms は1以上の整数にしてください');
+  }
+  if (!Number.isInteger(options.retryFailedPasses) || options.retryFailedPasses < 0) {
+    throw new Error('--retry-failed-passes は0以上の整数にしてください');
+  }
+  if (!Number.isInteger(options.slowestLaws) || options.slowestLaws < 0) {
+    throw new Error('--slowest-laws は0以上の整数にしてください');
+  }
+  if (!Number.isInteger(options.titleFallbackSteps) || options.titleFallbackSteps < 0) {
+    throw new Error('--title-fallback-steps は0以上の整数にしてください');
+  }
+  if (Number.isNaN(options.minConfidence) || options.minConfidence < 0 || options.minConfidence > 1) {
+    throw new Error('--min-confidence は0以上1以下の数値にしてください');
+  }
+  if (!Number.isInteger(options.dictPageSize) || options.dictPageSize < 1) {
+    throw new Error('--dict-page-size は1以上の整数にしてください');
+  }
+  if (!Number.isInteger(options.flushDictionaryEvery) || options.flushDictionaryEvery < 1) {
+    throw new Error('--flush-dictionary-every は1以上の整数にしてください');
+  }
+  if (
+    options.partialMatchRatio !== undefined &&
+    (Number.isNaN(options.partialMatchRatio) || options.partialMatchRatio <= 0 || options.partialMatchRatio > 1)
+  ) {
+    throw new Error('--partial-match-ratio は0より大きく1以下の数値にしてください');
+  }
+  if (options.fromArchive) {
+    options.archiveDir ??= DEFAULT_ARCHIVE_DIR;
+  }
+  if (options.exportFormat && !options.exportOut) {
+    throw new Error('--export では書き出し先を --export-out で指定してください');
+  }
+  if (!options.followExisting && options.ifExists !== 'skip') {
+    throw new Error('--no-follow-existing は --skip-existing（--if-exists skip）と組み合わせて指定してください');
+  }
+  if (options.standardLinks && options.outputFormat !== 'plain') {
+    throw new Error('--standard-links は --output-format plain と組み合わせて指定してください');
+  }
+  if (options.noteFormat !== 'md') {
+    // txt・html は書き出した後にノートとして読み戻せないため、既存ノートを前提にする機能とは組み合わせられない。
+    const conflicting = [
+      options.splitBy === 'chapter' && '--split-by chapter',
+      options.linkOnlyExisting && '--link-only-existing',
+      options.stubMissing && '--stub-missing',
+      options.timeline && '--timeline',
+      options.changelog && '--changelog',
+      options.mocName && '--moc',
+      options.citationStyle !== 'none' && '--citation-style',
+      options.ifExists === 'skip' && '--if-exists skip',
+    ].filter(Boolean);
+    if (conflicting.length > 0) {
+      throw new Error(`--format ${options.noteFormat} は ${conflicting.join(' / ')} と同時に指定できません`);
+    }
+  }
+  if (options.linkOnlyExisting && options.stubMissing) {
+    throw new Error('--link-only-existing と --stub-missing は同時に指定できません');
+  }
+  if (options.changelog && options.splitBy === 'chapter') {
+    // 分割出力では法令ノートに条の本文が無く、条単位で比べられない。
+    throw new Error('--changelog は --split-by chapter と同時に指定できません');
+  }
+  if (options.apiStats && !options.stats) {
+    throw new Error('--api-stats は --no-stats と同時に指定できません');
+  }
+  if (options.apiStats && options.fullTextFormat !== 'json') {
+    // 構造要素はJSON形式のタグ木からしか数えられない。
+    throw new Error('--api-stats は --full-text-format json のときだけ指定できます');
+  }
+  if (options.lockTimeoutMs < 0 || Number.isNaN(options.lockTimeoutMs)) {
+    throw new Error('--lock-timeout-ms は0以上の整数にしてください');
+  }
+
+  if (options.searchCacheTtlHours < 0 || Number.isNaN(options.searchCacheTtlHours)) {
+    throw new Error('--search-cache-ttl-hours は0以上の数値にしてください');
+  }
+  if (!Number.isInteger(options.headingBaseLevel) || options.headingBaseLevel < 2 || options.headingBaseLevel > 6) {
+    throw new Error('--heading-base-level は2以上6以下の整数にしてください');
+  }
+  if (options.requestIntervalMs < 0 || Number.isNaN(options.requestIntervalMs)) {
+    throw new Error('--request-interval-ms は0以上の整数にしてください');
+  }
 
   return options;
 }
@@ -0,0 +1,97 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { diffArticles, prependChangelogEntry } from './index.js';
+
+const note = (articles: string[]) =>
+  ['---', 'law_id: 129AC0000000089', '---', '', '# 民法', '', ...articles, '', '## 被参照', '', '- [[商法]]'].join('\n');
+
+test('diffArticles: 条見出しごとに追加・削除・変更を判定し、リンク先だけの違いは変更にしない', () => {
+  const before = note([
+    '## 第一条（基本原則）',
+    '<a id="Mp-At_1"></a>',
+    '私権は、[[民法_129AC0000000089#第二条|公共の福祉]]に適合しなければならない。',
+    '',
+    '## 第二条',
+    '<a id="Mp-At_2"></a>',
+    '削除',
+    '',
+    '## 第三条',
+    '<a id="Mp-At_3"></a>',
+    '私権の享有は、出生に始まる。',
+  ]);
+  const after = note([
+    '## 第一条（基本原則）',
+    '<a id="Mp-At_1"></a>',
+    '私権は、[[129AC0000000089#第二条|公共の福祉]]に適合しなければならない。',
+    '',
+    '## 第二条',
+    '<a id="Mp-At_2"></a>',
+    'この法律は、個人の尊厳と両性の本質的平等を旨として、解釈しなければならない。',
+    '',
+    '## 第三条の二',
+    '<a id="Mp-At_3_2"></a>',
+    '法律行為の当事者が意思表示をした時に意思能力を有しなかったときは、その法律行為は、無効とする。',
+  ]);
+
+  const changes = diffArticles(before, after, 2);
+  assert.deepEqual(changes.added, ['第三条の二']);
+  assert.deepEqual(changes.removed, ['第三条']);
+  assert.deepEqual(
+    changes.changed.map((change) => change.heading),
+    ['第二条'],
+  );
+  assert.match(changes.changed[0].diff, /^-削除$/m);
+  assert.deepEqual(diffArticles(after, after, 2), { added: [], removed: [], changed: [] });
+  // 条の無いノート（スタブノートなど）からの取得は差分にしない。
+  assert.deepEqual(diffArticles('# 民法\n', after, 2), { added: [], removed: [], changed: [] });
+});
+
+test('diffArticles: --heading-base-level の条見出しで分け、同じレベルの被参照セクションは条に含めない', () => {
+  const deeper = (text: string, backlink: string) =>
+    ['## 民法', '', '### 第一条', text, '', '### 被参照', '', backlink, '', '### 参照した法令とリビジョン'].join('\n');
+  const before = deeper('私権は、公共の福祉に適合しなければならない。', '- [[商法]]');
+  // 被参照・参照した法令のセクションだけが変わっても条の変更にはしない。
+  assert.deepEqual(diffArticles(before, deeper('私権は、公共の福祉に適合しなければならない。', '- [[会社法]]'), 3), {
+    added: [],
+    removed: [],
+    changed: [],
+  });
+  const changes = diffArticles(before, deeper('私権は、公共の福祉に適合する。', '- [[商法]]'), 3);
+  assert.deepEqual(changes.added, []);
+  assert.deepEqual(changes.removed, []);
+  assert.deepEqual(
+    changes.changed.map((change) => change.heading),
+    ['第一条'],
+  );
+});
+
+test('prependChangelogEntry: 今回の記録を法令名の見出しの直後に差し込む', () => {
+  const first = prependChangelogEntry(
+    undefined,
+    '民法',
+    { added: ['第三条の二'], removed: [], changed: [] },
+    '2026-10-16',
+    2,
+  );
+  const second = prependChangelogEntry(first, '民法', { added: [], removed: ['第三条'], changed: [] }, '2026-11-01', 2);
+  assert.equal(
+    second,
+    [
+      '# 民法 変更履歴',
+      '',
+      '## 2026-11-01',
+      '',
+      '### 削除された条',
+      '',
+      '- 第三条',
+      '',
+      '## 2026-10-16',
+      '',
+      '### 追加された条',
+      '',
+      '- 第三条の二',
+      '',
+    ].join('\n'),
+  );
+});
@@ -0,0 +1,147 @@
+import path from 'node:path';
+import process from 'node:process';
+import { BACKLINKS_TITLE, CHANGELOG_SUFFIX, CITATIONS_TITLE } from './config.js';
+import { unifiedDiff } from './diff.js';
+import { splitFrontmatter } from './frontmatter.js';
+import { heading } from './render.js';
+import { encodeNoteText, readNoteText, writeFileAtomic } from './storage.js';
+import type { CliOptions } from './types.js';
+
+const ANCHOR_LINE_PATTERN = /^<a id="[^"]+"><\/a>$/;
+// リンク先は辞書やファイル名の付け方で変わり、条文の改正とは関係しないため、比較では表示名だけにする。
+const WIKI_LINK_PATTERN = /\[\[(?:[^\]|]*\|)?([^\]]*)\]\]/g;
+const MARKDOWN_LINK_PATTERN = /\[([^\]]*)\]\([^)]*\)/g;
+
+/**
+ * 前回の本文と今回の本文の条単位の差分。`changed` は条見出しと、その条の本文の unified diff。
+ */
+export interface ArticleChanges {
+  added: string[];
+  removed: string[];
+  changed: Array<{ heading: string; diff: string }>;
+}
+
+/**
+ * ノートの本文（frontmatterを除く）を条見出し（`headingLevel` の見出し）ごとに分け、見出し → 本文の対応を返す。
+ * 本文はリンクを表示名に戻し、アンカーの行を除いたもの。被参照・参照した法令のセクションは条ではないため含めない。
+ */
+export function splitArticleSections(markdown: string, headingLevel: number): Map<string, string> {
+  const articlePrefix = `${'#'.repeat(headingLevel)} `;
+  const sections = new Map<string, string[]>();
+  let current: string[] | undefined;
+  for (const line of splitFrontmatter(markdown).body.split('\n')) {
+    // 被参照・参照した法令のセクションは条見出しと同じレベルの見出しで、ノートの末尾に置かれる。
+    if (line === `${articlePrefix}${BACKLINKS_TITLE}` || line === `${articlePrefix}${CITATIONS_TITLE}`) {
+      break;
+    }
+    if (line.startsWith(articlePrefix)) {
+      current = [];
+      sections.set(line.slice(articlePrefix.length).trim(), current);
+      continue;
+    }
+    if (/^#+ /.test(line)) {
+      // 法令名など条より上の階層の見出しは条の本文に含めない。
+      current = undefined;
+      continue;
+    }
+    if (current && !ANCHOR_LINE_PATTERN.test(line)) {
+      current.push(line.replace(WIKI_LINK_PATTERN, '$1').replace(MARKDOWN_LINK_PATTERN, '$1'));
+    }
+  }
+  return new Map([...sections].map(([title, lines]) => [title, lines.join('\n').trim()]));
+}
+
+/**
+ * 既存ノートと新しい本文を条単位で比べ、追加・削除・変更された条を返す。並びはそれぞれの本文での出現順。
+ * 既存ノートに条が1つも無ければ差分なしとする。
+ */
+export function diffArticles(oldMarkdown: string, newMarkdown: string, headingLevel: number): ArticleChanges {
+  const before = splitArticleSections(oldMarkdown, headingLevel);
+  const after = splitArticleSections(newMarkdown, headingLevel);
+  const changes: ArticleChanges = { added: [], removed: [], changed: [] };
+  if (before.size === 0) {
+    // スタブノートのように条を持たないノートからの取得は、改正ではないため差分にしない。
+    return changes;
+  }
+  for (const [title, text] of after) {
+    const previous = before.get(title);
+    if (previous === undefined) {
+      changes.added.push(title);
+    } else if (previous !== text) {
+      changes.changed.push({ heading: title, diff: unifiedDiff(`${previous}\n`, `${text}\n`, '前回', '今回') });
+    }
+  }
+  changes.removed = [...before.keys()].filter((title) => !after.has(title));
+  return changes;
+}
+
+/**
+ * 変更履歴ノートに、今回の差分を先頭の記録として加える。`existing` は既存の変更履歴ノートで、新規なら undefined。
+ * 記録は新しい順に並べ、法令名の見出しの直後に今回の記録を差し込む。
+ */
+export function prependChangelogEntry(
+  existing: string | undefined,
+  title: string,
+  changes: ArticleChanges,
+  date: string,
+  headingBaseLevel: number,
+): string {
+  const entry = [heading(headingBaseLevel, date)];
+  const listSection = (label: string, headings: string[]) => {
+    if (headings.length > 0) {
+      entry.push('', heading(headingBaseLevel + 1, label), '', ...headings.map((item) => `- ${item}`));
+    }
+  };
+  listSection('追加された条', changes.added);
+  listSection('削除された条', changes.removed);
+  if (changes.changed.length > 0) {
+    entry.push('', heading(headingBaseLevel + 1, '変更された条'));
+    for (const change of changes.changed) {
+      entry.push('', heading(headingBaseLevel + 2, change.heading), '', '```diff', change.diff.trimEnd(), '```');
+    }
+  }
+  // 先頭行は自分で書いた法令名の見出しのため、それより後ろを以前の記録として残す。
+  const previous = existing === undefined ? '' : existing.split('\n').slice(1).join('\n').trim();
+  const lines = [heading(headingBaseLevel - 1, `${title} 変更履歴`), '', ...entry];
+  if (previous) {
+    lines.push('', previous);
+  }
+  return `${lines.join('\n')}\n`;
+}
+
+/**
+ * `--changelog` で、既存ノート `previousNotePath` と書き出す前の新しい本文を条単位で比べ、差分があれば
+ * ノートと同じディレクトリの `法令名_changelog.md` に記録を加える。既存ノートが無い・差分が無いときは何もしない。
+ */
+export async function writeLawChangelog(
+  options: CliOptions,
+  previousNotePath: string | undefined,
+  notePath: string,
+  title: string,
+  safeTitle: string,
+  markdown: string,
+): Promise<void> {
+  if (!previousNotePath) {
+    return;
+  }
+  const changes = diffArticles(await readNoteText(previousNotePath), markdown, options.headingBaseLevel);
+  if (changes.added.length + changes.removed.length + changes.changed.length === 0) {
+    return;
+  }
+  const changelogPath = path.join(path.dirname(notePath), `${safeTitle}${CHANGELOG_SUFFIX}`);
+  let existing: string | undefined;
+  try {
+    existing = await readNoteText(changelogPath);
+  } catch (error) {
+    if ((error as NodeJS.ErrnoException).code !== 'ENOENT') {
+      throw error;
+    }
+  }
+  const date = new Date().toISOString().slice(0, 10);
+  const updated = prependChangelogEntry(existing, title, changes, date, options.headingBaseLevel);
+  await writeFileAtomic(changelogPath, encodeNoteText(updated, options));
+  process.stdout.write(
+    `変更履歴を書き出しました: ${changelogPath} ` +
+      `(追加 ${changes.added.length}件 / 削除 ${changes.removed.length}件 / 変更 ${changes.changed.length}件)\n`,
+  );
+}
@@ -0,0 +1,75 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+
+import {
+  candidateChoiceKey,
+  defaultCliOptions,
+  findCandidateChoice,
+  recordCandidateChoice,
+  resolveRefTexts,
+  selectCandidateNonInteractive,
+} from './index.js';
+import type { LawDictionary } from './types.js';
+
+const notCalled = async (): Promise<never> => {
+  throw new Error('呼ばれないはず');
+};
+
+test('candidateChoiceKey: 表記ゆれと空白だけが違う法令名は同じキーにする', () => {
+  assert.equal(candidateChoiceKey('國家公務員法'), candidateChoiceKey('国家公務員法'));
+  assert.equal(candidateChoiceKey('個人情報 保護法'), candidateChoiceKey('個人情報保護法'));
+});
+
+test('recordCandidateChoice: 選んだ法令を同一実行内で使い回し、--choices-path に保存する', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-choices-'));
+  const options = { ...defaultCliOptions(), choicesPath: path.join(tmp, 'choices.json') };
+  await recordCandidateChoice(options, '国家公務員法', { law_id: '322AC0000000120', law_title: '国家公務員法' });
+
+  // この実行で選んだ法令はAPIで確かめ直さない。
+  assert.equal((await findCandidateChoice(options, '國家公務員法', notCalled))?.law_id, '322AC0000000120');
+  const saved = JSON.parse(await fs.readFile(options.choicesPath, 'utf8'));
+  assert.equal(saved[candidateChoiceKey('国家公務員法')].law_id, '322AC0000000120');
+
+  // 辞書に同じ法令名があっても、ユーザーが選んだ法令を優先する。
+  const dictionary: LawDictionary = {
+    '999AC0000000001': { title: '国家公務員法', safe_title: '国家公務員法', file_name: 'x.md', updated_at: '' },
+  };
+  const resolved = await resolveRefTexts(
+    options,
+    ['国家公務員法第二条'],
+    dictionary,
+    notCalled,
+    selectCandidateNonInteractive,
+    new Map(),
+  );
+  assert.equal(resolved.get('国家公務員法第二条'), '322AC0000000120');
+});
+
+test('findCandidateChoice: 保存済みの選択履歴は初回にだけ確かめ、無効な law_id は履歴から外す', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-choices-stale-'));
+  const choicesPath = path.join(tmp, 'choices.json');
+  const choice = (lawId: string, query: string) => ({ query, law_id: lawId, law_title: query, chosen_at: '' });
+  await fs.writeFile(
+    choicesPath,
+    JSON.stringify({
+      [candidateChoiceKey('民法')]: choice('129AC0000000089', '民法'),
+      [candidateChoiceKey('旧法')]: choice('100AC0000000001', '旧法'),
+    }),
+  );
+  const options = { ...defaultCliOptions(), choicesPath };
+  const checked: string[] = [];
+  const exists = async (lawId: string) => {
+    checked.push(lawId);
+    return lawId === '129AC0000000089';
+  };
+
+  assert.equal((await findCandidateChoice(options, '民法', exists))?.law_id, '129AC0000000089');
+  assert.equal((await findCandidateChoice(options, '民法', exists))?.law_id, '129AC0000000089');
+  assert.equal(await findCandidateChoice(options, '旧法', exists), undefined);
+  assert.deepEqual(checked, ['129AC0000000089', '100AC0000000001']);
+  const saved = JSON.parse(await fs.readFile(choicesPath, 'utf8'));
+  assert.deepEqual(Object.keys(saved), [candidateChoiceKey('民法')]);
+});
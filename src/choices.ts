@@ -0,0 +1,95 @@
+import process from 'node:process';
+import { fetchLawInfoById } from './api.js';
+import { canonicalizeKanji } from './kanji.js';
+import { loadCandidateChoices, writeJson } from './storage.js';
+import type { CandidateChoice, CliOptions } from './types.js';
+
+interface ChoiceSession {
+  choices: Map<string, CandidateChoice>;
+  // 同じ実行で一度確かめた law_id は、出現のたびにAPIで確かめ直さない。
+  verified: Set<string>;
+}
+
+// 選択履歴は保存先ごとに持つ。`--choices-path` が無い実行では同じ実行の中だけで使い回す。
+const sessions = new Map<string, ChoiceSession>();
+
+/**
+ * 選択履歴の照合キーを返す。表記ゆれや空白の違いだけの法令名で選び直させないよう、正規化して空白を除く。
+ */
+export function candidateChoiceKey(query: string): string {
+  return canonicalizeKanji(query).replace(/\s+/g, '');
+}
+
+async function choiceSession(options: Pick<CliOptions, 'choicesPath'>): Promise<ChoiceSession> {
+  const key = options.choicesPath ?? '';
+  let session = sessions.get(key);
+  if (!session) {
+    session = { choices: new Map(), verified: new Set() };
+    sessions.set(key, session);
+    if (options.choicesPath) {
+      for (const [choiceKey, choice] of Object.entries(await loadCandidateChoices(options.choicesPath))) {
+        if (choice?.law_id) {
+          session.choices.set(choiceKey, choice);
+        }
+      }
+    }
+  }
+  return session;
+}
+
+async function saveChoices(options: Pick<CliOptions, 'choicesPath'>, session: ChoiceSession): Promise<void> {
+  if (options.choicesPath) {
+    await writeJson(options.choicesPath, Object.fromEntries(session.choices));
+  }
+}
+
+/**
+ * 曖昧な法令名に対してユーザーが選んだ法令を選択履歴に記録する。`--choices-path` があればファイルにも保存する。
+ */
+export async function recordCandidateChoice(
+  options: Pick<CliOptions, 'choicesPath'>,
+  query: string,
+  selected: { law_id: string; law_title: string },
+): Promise<void> {
+  const session = await choiceSession(options);
+  session.choices.set(candidateChoiceKey(query), {
+    query,
+    law_id: selected.law_id,
+    law_title: selected.law_title,
+    chosen_at: new Date().toISOString(),
+  });
+  session.verified.add(selected.law_id);
+  await saveChoices(options, session);
+}
+
+/**
+ * 法令名に対する選択履歴を返す。辞書や検索より優先して使う。
+ * 前回以前の実行で選んだ law_id は廃止・改題で無効になっている場合があるため、この実行で初めて使うときにAPIで確かめ、
+ * 無効なら履歴から外して undefined を返す（呼び出し側は法令名検索へ戻る）。APIに届かないときは確かめずにそのまま使う。
+ */
+export async function findCandidateChoice(
+  options: CliOptions,
+  query: string,
+  exists: (lawId: string) => Promise<boolean> = async (lawId) => Boolean(await fetchLawInfoById(options, lawId)),
+): Promise<CandidateChoice | undefined> {
+  const session = await choiceSession(options);
+  const key = candidateChoiceKey(query);
+  const choice = session.choices.get(key);
+  if (!choice || session.verified.has(choice.law_id)) {
+    return choice;
+  }
+  let valid: boolean;
+  try {
+    valid = await exists(choice.law_id);
+  } catch {
+    return choice;
+  }
+  if (!valid) {
+    process.stderr.write(`警告: 選択履歴の法令が見つからないため、法令名から検索し直します: ${query} (${choice.law_id})\n`);
+    session.choices.delete(key);
+    await saveChoices(options, session);
+    return undefined;
+  }
+  session.verified.add(choice.law_id);
+  return choice;
+}
@@ -1,10 +1,10 @@
 import process from 'node:process';
 import { pathToFileURL } from 'node:url';
-import { runCli } from './index.js';
+import { exitCodeForError, runCli } from './index.js';
 
 if (process.argv[1] && import.meta.url === pathToFileURL(process.argv[1]).href) {
   runCli(process.argv.slice(2)).catch((error) => {
     process.stderr.write(`Error: ${error instanceof Error ? error.message : String(error)}\n`);
-    process.exit(1);
+    process.exit(exitCodeForError(error));
   });
 }
@@ -0,0 +1,125 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+
+import type { CollectorElement, CollectorNode, CollectorState } from './collectors.js';
+import { collectNode, ELEMENT_COLLECTORS, serializeCollectors } from './index.js';
+import type { ParagraphSegment } from './types.js';
+
+// ブラウザを使わずに取り込み方を確かめるため、DOMのうち取り込みで読むプロパティだけを持つ偽ノードを作る。
+function text(value: string): CollectorNode {
+  return { nodeType: 3, textContent: value, childNodes: [] };
+}
+
+function element(
+  tagName: string,
+  attributes: Record<string, string> = {},
+  children: CollectorNode[] = [],
+): CollectorElement {
+  return {
+    nodeType: 1,
+    tagName: tagName.toUpperCase(),
+    classList: attributes.class ? attributes.class.split(' ') : [],
+    textContent: children.map((child) => child.textContent ?? '').join(''),
+    childNodes: children,
+    getAttribute: (name) => attributes[name] ?? null,
+    hasAttribute: (name) => Object.hasOwn(attributes, name),
+  };
+}
+
+function collectAll(
+  nodes: CollectorNode[],
+  collect: (node: CollectorNode, state: CollectorState) => void,
+  unsupportedElements: Record<string, number> = {},
+): ParagraphSegment[] {
+  const segments: ParagraphSegment[] = [];
+  for (const node of nodes) {
+    collect(node, { segments, unsupportedElements });
+  }
+  return segments;
+}
+
+const collectDirect = (node: CollectorNode, state: CollectorState): void =>
+  collectNode(node, state, ELEMENT_COLLECTORS);
+// page.evaluate へ送るのと同じ文字列を評価し、モジュールの値を参照せずに動くことを確かめる。
+const collectSerialized = new Function(`return ${serializeCollectors()}`)() as (
+  node: CollectorNode,
+  state: CollectorState,
+) => void;
+
+// tests/fixtures/collectors/page.html の2つの文と同じ構造。
+const fixtureParagraphs = [
+  [
+    text('この法律は、'),
+    element('a', { href: '/law/334AC0000000121#Mp-Ch_1-At_2' }, [text('特許法第二条')]),
+    text('及び'),
+    element('a', {}, [text('附則第二条')]),
+    text('の規定による。'),
+  ],
+  [
+    text('様式は、'),
+    element('img', { alt: '別記様式', src: 'https://laws.e-gov.go.jp/data/fig1.png' }),
+    text('のとおりとし、'),
+    element('span', { class: 'ruby' }, [text('算式'), element('svg', { class: 'formula' })]),
+    text('及び'),
+    element('video'),
+    text('を含む。'),
+  ],
+];
+
+test('collectNode: 図・リンク・埋め込み要素を取り込み、それ以外の要素は子要素の文言を集める', () => {
+  const unsupportedElements: Record<string, number> = {};
+  const segments = collectAll(
+    [
+      element('img', { alt: ' 別記様式 ', src: 'fig1.png' }),
+      element('a', { href: '#Mp-At_2' }, [text(' 第二条 ')]),
+      element('a', {}, [text('前条')]),
+      element('ruby', {}, [text('算'), element('rt', {}, [text('さん')])]),
+      element('svg', { class: 'formula math' }),
+      element('SVG', { class: 'formula' }),
+      element('iframe'),
+      text(''),
+    ],
+    collectDirect,
+    unsupportedElements,
+  );
+  assert.deepEqual(segments, [
+    { type: 'figure', text: '別記様式', src: 'fig1.png' },
+    { type: 'link', text: '第二条', href: '#Mp-At_2' },
+    { type: 'text', text: '前条' },
+    { type: 'text', text: '算' },
+    { type: 'text', text: 'さん' },
+  ]);
+  assert.deepEqual(unsupportedElements, { 'svg.formula': 2, iframe: 1 });
+});
+
+test('collectNode: img は解決済みの src があれば属性値より優先する', () => {
+  const image = { ...element('img', { alt: '図', src: 'fig1.png' }), src: 'https://laws.e-gov.go.jp/data/fig1.png' };
+  assert.deepEqual(collectAll([image], collectDirect), [
+    { type: 'figure', text: '図', src: 'https://laws.e-gov.go.jp/data/fig1.png' },
+  ]);
+});
+
+test('serializeCollectors: 文字列から評価した取り込み関数が collectNode と同じ結果を返す', () => {
+  for (const nodes of fixtureParagraphs) {
+    const directUnsupported: Record<string, number> = {};
+    const serializedUnsupported: Record<string, number> = {};
+    assert.deepEqual(
+      collectAll(nodes, collectSerialized, serializedUnsupported),
+      collectAll(nodes, collectDirect, directUnsupported),
+    );
+    assert.deepEqual(serializedUnsupported, directUnsupported);
+  }
+});
+
+test('serializeCollectors: 要素の取り込み方ごとの抽出結果がゴールデンファイルと一致する', async () => {
+  // 取り込み方の表（ELEMENT_COLLECTORS）を変えたときは、抽出結果を確かめてから expected.json を更新する。
+  const expected = JSON.parse(await fs.readFile('tests/fixtures/collectors/expected.json', 'utf8')) as {
+    blocks: Array<{ paragraphs: Array<{ segments: ParagraphSegment[] }> }>;
+    unsupportedElements: Record<string, number>;
+  };
+  const unsupportedElements: Record<string, number> = {};
+  const segments = fixtureParagraphs.map((nodes) => collectAll(nodes, collectSerialized, unsupportedElements));
+  assert.deepEqual(segments, expected.blocks[0].paragraphs.map((paragraph) => paragraph.segments));
+  assert.deepEqual(unsupportedElements, expected.unsupportedElements);
+});
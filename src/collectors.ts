@@ -0,0 +1,123 @@
+import type { ParagraphSegment } from './types.js';
+
+/**
+ * 取り込みで読むDOMノードの形。ブラウザのDOMでもテスト用の偽ノードでも動くよう、使うプロパティだけを持つ。
+ * `nodeType` はDOMと同じく、要素が `1`、テキストが `3`。
+ */
+export interface CollectorNode {
+  readonly nodeType: number;
+  readonly textContent: string | null;
+  readonly childNodes: ArrayLike<CollectorNode>;
+}
+
+/**
+ * 取り込みで読む要素の形。`src` は `img` の解決済みURL（DOMの `HTMLImageElement.src`）で、無ければ属性値を使う。
+ */
+export interface CollectorElement extends CollectorNode {
+  readonly tagName: string;
+  readonly classList: ArrayLike<string>;
+  readonly src?: string;
+  getAttribute(name: string): string | null;
+  hasAttribute(name: string): boolean;
+}
+
+/**
+ * 1つの文を取り込む間の状態。`unsupportedElements` は法令全体で共有し、要素名・classごとの件数を数える。
+ */
+export interface CollectorState {
+  segments: ParagraphSegment[];
+  unsupportedElements: Record<string, number>;
+}
+
+/**
+ * 要素を段落の断片へ取り込む。取り込んだら true、子要素をたどって文言を集めさせるなら false を返す。
+ * `page.evaluate` で本文DOMに対して動かすため、ソースを文字列にしてブラウザへ送る。
+ * モジュールの他の値を参照できないので、関数の中で完結させる。
+ */
+export type ElementCollector = (element: CollectorElement, state: CollectorState) => boolean;
+
+/**
+ * タグ名（小文字）ごとの取り込み方。表に無いタグは、子要素をたどって文言を集める。
+ * - `img`: 図。画像の実体は取得できないため、元の参照先を残してノート側で埋め込みプレースホルダにする
+ * - `a`: `href` を持つリンク。`href` の無い `a` は文言として扱う
+ * - `svg` など: 文字を持たず本文から欠落する埋め込み要素。取りこぼしの発見用に要素名・classごとに数える
+ *
+ * ルビ・表などへの対応を足すときは、ここにタグと取り込み方を加える。
+ */
+export const ELEMENT_COLLECTORS: Readonly<Record<string, ElementCollector>> = {
+  img: (element, state) => {
+    state.segments.push({
+      type: 'figure',
+      text: element.getAttribute('alt')?.trim() ?? '',
+      src: element.src ?? element.getAttribute('src') ?? '',
+    });
+    return true;
+  },
+  a: (element, state) => {
+    // a[href]以外の参照文言はテキストのまま保持する。推測でのリンク化は描画時に `--link-text-refs` の指定があるときだけ行う。
+    if (!element.hasAttribute('href')) {
+      return false;
+    }
+    state.segments.push({
+      type: 'link',
+      text: element.textContent?.trim() ?? '',
+      href: element.getAttribute('href') ?? '',
+    });
+    return true;
+  },
+  ...Object.fromEntries(
+    ['svg', 'object', 'embed', 'iframe', 'canvas', 'video', 'audio'].map((tagName): [string, ElementCollector] => [
+      tagName,
+      (element, state) => {
+        const tag = element.tagName.toLowerCase();
+        const key = element.classList.length > 0 ? `${tag}.${element.classList[0]}` : tag;
+        state.unsupportedElements[key] = (state.unsupportedElements[key] ?? 0) + 1;
+        return true;
+      },
+    ]),
+  ),
+};
+
+/**
+ * ノードを `collectors` に従って段落の断片へ取り込む。テキストはそのまま文言にし、
+ * 表に無い要素と、取り込み方が false を返した要素は子要素をたどる。
+ * `ElementCollector` と同じくブラウザへ文字列で送るため、DOMの定数（`Node.TEXT_NODE`）も使わない。
+ */
+export function collectNode(
+  node: CollectorNode,
+  state: CollectorState,
+  collectors: Readonly<Record<string, ElementCollector>>,
+): void {
+  if (node.nodeType === 3) {
+    const text = node.textContent ?? '';
+    if (text) {
+      state.segments.push({ type: 'text', text });
+    }
+    return;
+  }
+  if (node.nodeType !== 1) {
+    return;
+  }
+  const element = node as CollectorElement;
+  if (collectors[element.tagName.toLowerCase()]?.(element, state)) {
+    return;
+  }
+  for (const child of Array.from(node.childNodes)) {
+    collectNode(child, state, collectors);
+  }
+}
+
+/**
+ * `collectNode` と取り込み方の表を、`page.evaluate` へ送るスクリプトに埋め込める式にする。
+ * 式を評価すると `(node, state) => void` の取り込み関数になる。関数は `page.evaluate` の引数として渡せないため、
+ * Playwrightが関数を送るときと同じくソースを文字列にする。
+ */
+export function serializeCollectors(
+  collectors: Readonly<Record<string, ElementCollector>> = ELEMENT_COLLECTORS,
+): string {
+  const table = Object.entries(collectors)
+    .map(([tagName, collector]) => `${JSON.stringify(tagName)}: ${collector.toString()}`)
+    .join(', ');
+  const bind = '(collect, collectors) => (node, state) => collect(node, state, collectors)';
+  return `(${bind})(${collectNode.toString()}, {${table}})`;
+}
@@ -1,4 +1,27 @@
 export const DEFAULT_API_BASE = 'https://laws.e-gov.go.jp';
+export const DEFAULT_API_VERSION = '2';
+export const DEFAULT_DICT_PAGE_SIZE = 100;
+export const SUPPORTED_API_VERSIONS = ['2'];
 export const DEFAULT_DICTIONARY_PATH = 'data/law_dictionary.json';
+/** 辞書ファイルの形式のバージョン。エントリの持ち方を変えたら上げ、読み込み時に古い形式から移し替える。 */
+export const DICTIONARY_SCHEMA_VERSION = 1;
 export const DEFAULT_UNRESOLVED_PATH = 'data/unresolved_refs.json';
 export const DEFAULT_OUTPUT_DIR = 'laws';
+export const DEFAULT_FAILED_PATH = 'data/failed_laws.json';
+export const DEFAULT_MISSING_LINKS_PATH = 'data/missing_links.json';
+export const DEFAULT_SEARCH_CACHE_PATH = 'data/search_cache.json';
+export const DEFAULT_ARCHIVE_DIR = 'data/raw';
+/** 条見出しの見出しレベル（`--heading-base-level`）の既定値。法令名はその1つ上のレベルにする。 */
+export const DEFAULT_HEADING_BASE_LEVEL = 2;
+/** ノート末尾のセクション名。見出しは条見出しと同じレベル（`--heading-base-level`）にする。 */
+export const BACKLINKS_TITLE = '被参照';
+export const CITATIONS_TITLE = '参照した法令とリビジョン';
+/** `--changelog` で法令ノートの隣に作る変更履歴ノートのファイル名の末尾（`法令名_changelog.md`）。 */
+export const CHANGELOG_SUFFIX = '_changelog.md';
+export const VAULT_LAWS_DIR = 'laws';
+/** `--moc` で出力ディレクトリ直下に作る一覧ノートの既定のファイル名。法令ノートと並べても先頭に来るよう `_` で始める。 */
+export const DEFAULT_MOC_NAME = '_MOC.md';
+/** これ未満の確信度の辞書エントリを辞書引きで使うときは警告する。 */
+export const LOW_DICTIONARY_CONFIDENCE = 0.8;
+/** 法令名検索が0件のとき、短くした法令名で検索し直す回数の既定値。 */
+export const DEFAULT_TITLE_FALLBACK_STEPS = 3;
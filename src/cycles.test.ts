@@ -0,0 +1,28 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { findReferenceCycles } from './index.js';
+
+const edge = (source: string, target: string) => ({ source_law_id: source, target_law_id: target, text: '' });
+
+test('findReferenceCycles: 互いに参照し合う法令のまとまりだけを返し、自己参照と一方向の参照は除く', () => {
+  const cycles = findReferenceCycles([
+    edge('129AC0000000089', '132AC0000000048'),
+    edge('132AC0000000048', '129AC0000000089'),
+    edge('132AC0000000048', '417AC0000000086'),
+    edge('417AC0000000086', '417AC0000000086'),
+    edge('334AC0000000121', '334AC0000000122'),
+    edge('334AC0000000122', '334AC0000000123'),
+    edge('334AC0000000123', '334AC0000000121'),
+  ]);
+  assert.deepEqual(cycles, [
+    ['129AC0000000089', '132AC0000000048'],
+    ['334AC0000000121', '334AC0000000122', '334AC0000000123'],
+  ]);
+});
+
+test('findReferenceCycles: 長い参照の連鎖でも再帰の深さに依らず求める', () => {
+  const lawIds = Array.from({ length: 20000 }, (_, index) => `law_${String(index).padStart(5, '0')}`);
+  const edges = lawIds.map((lawId, index) => edge(lawId, lawIds[(index + 1) % lawIds.length]));
+  assert.equal(findReferenceCycles(edges)[0].length, 20000);
+});
@@ -0,0 +1,72 @@
+import type { LawReferenceEdge } from './types.js';
+
+/**
+ * 法令間の参照関係から、互いに参照し合う法令のまとまり（強連結成分のうち2法令以上のもの）を求める。
+ * 取得を終えた後の参照関係に対して1回だけ使う。自分自身への参照は循環とみなさない。
+ * 結果は各まとまりの law_id を昇順に並べ、まとまりどうしも先頭の law_id 順にする。
+ */
+export function findReferenceCycles(edges: LawReferenceEdge[]): string[][] {
+  const graph = new Map<string, Set<string>>();
+  for (const edge of edges) {
+    if (edge.source_law_id === edge.target_law_id) {
+      continue;
+    }
+    graph.set(edge.source_law_id, (graph.get(edge.source_law_id) ?? new Set()).add(edge.target_law_id));
+    if (!graph.has(edge.target_law_id)) {
+      graph.set(edge.target_law_id, new Set());
+    }
+  }
+
+  // Tarjanの強連結成分分解。参照の連鎖は長くなり得るため、再帰せずに明示的なスタックでたどる。
+  const indexes = new Map<string, number>();
+  const lowLinks = new Map<string, number>();
+  const stack: string[] = [];
+  const onStack = new Set<string>();
+  const cycles: string[][] = [];
+  let nextIndex = 0;
+  for (const start of [...graph.keys()].sort()) {
+    if (indexes.has(start)) {
+      continue;
+    }
+    const frames: Array<{ lawId: string; targets: Iterator<string> }> = [];
+    const visit = (lawId: string): void => {
+      indexes.set(lawId, nextIndex);
+      lowLinks.set(lawId, nextIndex);
+      nextIndex += 1;
+      stack.push(lawId);
+      onStack.add(lawId);
+      frames.push({ lawId, targets: (graph.get(lawId) as Set<string>).values() });
+    };
+    visit(start);
+    while (frames.length > 0) {
+      const frame = frames[frames.length - 1];
+      const next = frame.targets.next();
+      if (!next.done) {
+        if (!indexes.has(next.value)) {
+          visit(next.value);
+        } else if (onStack.has(next.value)) {
+          lowLinks.set(frame.lawId, Math.min(lowLinks.get(frame.lawId) as number, indexes.get(next.value) as number));
+        }
+        continue;
+      }
+      frames.pop();
+      const parent = frames[frames.length - 1];
+      if (parent) {
+        lowLinks.set(parent.lawId, Math.min(lowLinks.get(parent.lawId) as number, lowLinks.get(frame.lawId) as number));
+      }
+      if (lowLinks.get(frame.lawId) === indexes.get(frame.lawId)) {
+        const component: string[] = [];
+        let member: string;
+        do {
+          member = stack.pop() as string;
+          onStack.delete(member);
+          component.push(member);
+        } while (member !== frame.lawId);
+        if (component.length > 1) {
+          cycles.push(component.sort());
+        }
+      }
+    }
+  }
+  return cycles.sort((a, b) => a[0].localeCompare(b[0]));
+}
@@ -0,0 +1,224 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  acceptDictionaryEntry,
+  addDictionaryAlias,
+  applyDictionaryFixes,
+  checkDictionaryEntry,
+  defaultCliOptions,
+  dictionaryEntryConfidence,
+  dictionaryProvenance,
+  findDictionaryNameIssues,
+  findLawIdByAlias,
+  findLawsByTitle,
+  mergeDictionaries,
+} from './index.js';
+import type { LawDictionary } from './types.js';
+
+const base = {
+  '334AC0000000121': {
+    title: '特許法',
+    safe_title: '特許法',
+    file_name: '特許法_334AC0000000121.md',
+    updated_at: '2026-02-20T00:00:00Z',
+  },
+  '129AC0000000089': {
+    title: 'law_129AC0000000089',
+    safe_title: 'law_129AC0000000089',
+    file_name: 'law_129AC0000000089.md',
+    updated_at: '2026-02-20T00:00:00Z',
+  },
+};
+
+const incoming = {
+  '334AC0000000121': {
+    title: '特許法（別名）',
+    safe_title: '特許法（別名）',
+    file_name: '特許法（別名）_334AC0000000121.md',
+    updated_at: '2026-03-01T00:00:00Z',
+  },
+  '129AC0000000089': {
+    title: '民法',
+    safe_title: '民法',
+    file_name: '民法_129AC0000000089.md',
+    updated_at: '2026-03-01T00:00:00Z',
+  },
+  '140AC0000000045': {
+    title: '刑法',
+    safe_title: '刑法',
+    file_name: '刑法_140AC0000000045.md',
+    updated_at: '2026-03-01T00:00:00Z',
+  },
+};
+
+test('mergeDictionaries: keep は衝突時に既存を維持する', () => {
+  const result = mergeDictionaries(base, incoming, 'keep');
+  assert.deepEqual(result.added, ['140AC0000000045']);
+  assert.deepEqual(result.overwritten, []);
+  assert.deepEqual(result.skipped.sort(), ['129AC0000000089', '334AC0000000121']);
+  assert.equal(result.merged['334AC0000000121'].title, '特許法');
+});
+
+test('mergeDictionaries: overwrite は衝突時に取り込み側で上書きする', () => {
+  const result = mergeDictionaries(base, incoming, 'overwrite');
+  assert.deepEqual(result.overwritten.sort(), ['129AC0000000089', '334AC0000000121']);
+  assert.equal(result.merged['334AC0000000121'].title, '特許法（別名）');
+});
+
+test('mergeDictionaries: prefer-with-id は仮登録エントリのみ上書きする', () => {
+  const result = mergeDictionaries(base, incoming, 'prefer-with-id');
+  assert.deepEqual(result.overwritten, ['129AC0000000089']);
+  assert.equal(result.merged['129AC0000000089'].title, '民法');
+  assert.equal(result.merged['334AC0000000121'].title, '特許法');
+  assert.equal(base['129AC0000000089'].title, 'law_129AC0000000089');
+});
+
+test('findLawsByTitle: 法令名が正規表現にマッチする確定済みの法令だけを返す', () => {
+  const dictionary = {
+    ...base,
+    '327AC0000000180': {
+      title: '道路法',
+      safe_title: '道路法',
+      file_name: '道路法_327AC0000000180.md',
+      updated_at: '2026-02-20T00:00:00Z',
+    },
+    '335AC0000000105': {
+      title: '道路交通法',
+      safe_title: '道路交通法',
+      file_name: '道路交通法_335AC0000000105.md',
+      updated_at: '2026-02-20T00:00:00Z',
+    },
+  };
+  assert.deepEqual(
+    findLawsByTitle(dictionary, /道路/).map(([lawId]) => lawId),
+    ['327AC0000000180', '335AC0000000105'],
+  );
+  assert.deepEqual(findLawsByTitle(dictionary, /^特許/).map(([lawId]) => lawId), ['334AC0000000121']);
+  // 仮登録（law_<law_id>）は法令名で絞り込めないため対象外。
+  assert.deepEqual(findLawsByTitle(dictionary, /^law_/), []);
+});
+
+test('findLawsByTitle: 略称も別名として絞り込みに使う', () => {
+  const dictionary = {
+    '322AC0000000054': {
+      title: '私的独占の禁止及び公正取引の確保に関する法律',
+      safe_title: '私的独占の禁止及び公正取引の確保に関する法律',
+      file_name: '私的独占の禁止及び公正取引の確保に関する法律_322AC0000000054.md',
+      updated_at: '2026-02-20T00:00:00Z',
+      abbrev: '独占禁止法,独禁法',
+    },
+  };
+  assert.deepEqual(findLawsByTitle(dictionary, /^独禁法$/).map(([lawId]) => lawId), ['322AC0000000054']);
+  assert.deepEqual(findLawsByTitle(dictionary, /^特許/), []);
+});
+
+test('addDictionaryAlias: 候補から確定した入力を別名に登録し、次からは別名で法令を引ける', () => {
+  const dictionary: LawDictionary = structuredClone(base);
+  assert.equal(addDictionaryAlias(dictionary, '334AC0000000121', ' 特許の法律 '), true);
+  assert.deepEqual(dictionary['334AC0000000121'].aliases, ['特許の法律']);
+  // 法令名・登録済みの別名と一致する入力と、辞書に無い法令は登録しない。
+  assert.equal(addDictionaryAlias(dictionary, '334AC0000000121', '特許法'), false);
+  assert.equal(addDictionaryAlias(dictionary, '334AC0000000121', '特許の法律'), false);
+  assert.equal(addDictionaryAlias(dictionary, '999AC0000000999', '特許の法律'), false);
+  assert.deepEqual(dictionary['334AC0000000121'].aliases, ['特許の法律']);
+
+  assert.equal(findLawIdByAlias(dictionary, '特許の法律'), '334AC0000000121');
+  assert.equal(findLawIdByAlias(dictionary, '特許法'), undefined);
+  assert.equal(findLawIdByAlias(dictionary, '特許の法律', () => false), undefined);
+  assert.deepEqual(findLawsByTitle(dictionary, /^特許の法律$/).map(([lawId]) => lawId), ['334AC0000000121']);
+});
+
+test('dictionaryEntryConfidence: 経路・確信度の無い旧形式のエントリは仮登録だけを0とみなす', () => {
+  assert.equal(dictionaryEntryConfidence('334AC0000000121', base['334AC0000000121']), 1);
+  assert.equal(dictionaryEntryConfidence('129AC0000000089', base['129AC0000000089']), 0);
+  const fuzzy = { ...base['334AC0000000121'], ...dictionaryProvenance('fuzzy') };
+  assert.equal(dictionaryEntryConfidence('334AC0000000121', fuzzy), 0.6);
+  assert.equal(dictionaryEntryConfidence('334AC0000000121', { ...fuzzy, confidence: 0.95 }), 0.95);
+});
+
+test('acceptDictionaryEntry: --min-confidence 未満のエントリは辞書引きで使わない', () => {
+  const dictionary = {
+    ...base,
+    '327AC0000000180': {
+      title: '道路法',
+      safe_title: '道路法',
+      file_name: '道路法_327AC0000000180.md',
+      updated_at: '2026-02-20T00:00:00Z',
+      ...dictionaryProvenance('fuzzy'),
+    },
+    '335AC0000000105': {
+      title: '道路交通法',
+      safe_title: '道路交通法',
+      file_name: '道路交通法_335AC0000000105.md',
+      updated_at: '2026-02-20T00:00:00Z',
+      ...dictionaryProvenance('api_scan'),
+    },
+  };
+  const options = { ...defaultCliOptions(), minConfidence: 0.7 };
+  assert.deepEqual(
+    findLawsByTitle(dictionary, /道路/, (lawId, entry) => acceptDictionaryEntry(options, lawId, entry)).map(
+      ([lawId]) => lawId,
+    ),
+    ['335AC0000000105'],
+  );
+  // 既定（0）では確信度の低いエントリも警告付きで使う。
+  assert.equal(acceptDictionaryEntry(defaultCliOptions(), '327AC0000000180', dictionary['327AC0000000180']), true);
+});
+
+test('checkDictionaryEntry: APIの現行の法令名・法令番号と突き合わせる', () => {
+  const entry = { ...base['334AC0000000121'], law_num: '昭和三十四年法律第百二十一号' };
+  assert.deepEqual(checkDictionaryEntry('334AC0000000121', entry, { title: '特許法', lawNum: '昭和34年法律第121号' }), []);
+  assert.deepEqual(
+    checkDictionaryEntry('334AC0000000121', entry, { title: '特許法（改題）', lawNum: '昭和三十四年法律第百二十二号' }).map(
+      (issue) => [issue.kind, issue.expected],
+    ),
+    [
+      ['title_mismatch', '特許法（改題）'],
+      ['law_num_mismatch', '昭和三十四年法律第百二十二号'],
+    ],
+  );
+  assert.deepEqual(checkDictionaryEntry('334AC0000000121', entry, undefined), [
+    { lawId: '334AC0000000121', kind: 'missing', current: '特許法' },
+  ]);
+  // 仮登録は法令名が未確定なので、不一致として扱わない。
+  assert.deepEqual(checkDictionaryEntry('129AC0000000089', base['129AC0000000089'], { title: '民法' }), []);
+});
+
+test('findDictionaryNameIssues: 短すぎる・曖昧な法令名と重複する法令名を検出する', () => {
+  const entry = (title: string) => ({
+    title,
+    safe_title: title,
+    file_name: `${title}.md`,
+    updated_at: '2026-02-20T00:00:00Z',
+  });
+  const issues = findDictionaryNameIssues({
+    ...base,
+    '999AC0000000001': entry('同法'),
+    '999AC0000000002': entry('法'),
+    '999AC0000000003': entry('特許法'),
+  });
+  assert.deepEqual(
+    issues.map((issue) => [issue.lawId, issue.kind]),
+    [
+      ['999AC0000000001', 'ambiguous_title'],
+      ['999AC0000000002', 'ambiguous_title'],
+      ['334AC0000000121', 'duplicate_title'],
+      ['999AC0000000003', 'duplicate_title'],
+    ],
+  );
+});
+
+test('applyDictionaryFixes: 法令名の不一致は現行名へ更新し、存在しないエントリは削除する', () => {
+  const result = applyDictionaryFixes(base, [
+    { lawId: '334AC0000000121', kind: 'title_mismatch', current: '特許法', expected: '特許法（改題）' },
+    { lawId: '129AC0000000089', kind: 'missing', current: 'law_129AC0000000089' },
+    { lawId: '129AC0000000089', kind: 'ambiguous_title', current: 'law_129AC0000000089' },
+  ]);
+  assert.deepEqual(result.updated, ['334AC0000000121']);
+  assert.deepEqual(result.removed, ['129AC0000000089']);
+  assert.equal(result.dictionary['334AC0000000121'].title, '特許法（改題）');
+  assert.equal(result.dictionary['334AC0000000121'].file_name, '特許法(改題)_334AC0000000121.md');
+  assert.equal(result.dictionary['129AC0000000089'], undefined);
+  assert.equal(base['129AC0000000089'].title, 'law_129AC0000000089');
+});
@@ -0,0 +1,417 @@
+import fs from 'node:fs/promises';
+import process from 'node:process';
+import { fetchLawInfoById } from './api.js';
+import { LOW_DICTIONARY_CONFIDENCE } from './config.js';
+import { canonicalizeKanji } from './kanji.js';
+import { normalizeLawNum } from './lawnum.js';
+import { dictionaryEntryNames, getFileName, toSafeTitle } from './notes.js';
+import {
+  decodeDictionaryContent,
+  dictionaryEntryConfidence,
+  dictionaryProvenance,
+  isFallbackDictionaryEntry,
+  loadDictionary,
+  parseDictionaryFile,
+  saveDictionary,
+} from './storage.js';
+import { confirm } from './utils.js';
+import type {
+  CliOptions,
+  DictEntrySource,
+  DictionaryConflictPolicy,
+  DictionaryIssue,
+  DictionaryIssueKind,
+  DictionaryMergeResult,
+  LawDictionary,
+  LawDictionaryEntry,
+} from './types.js';
+
+const DICT_ENTRY_SOURCES: DictEntrySource[] = [
+  'api_scan',
+  'api_lookup',
+  'fetched',
+  'candidate',
+  'fuzzy',
+  'import',
+  'manual',
+  'fallback',
+];
+
+function isDictEntrySource(value: unknown): value is DictEntrySource {
+  return DICT_ENTRY_SOURCES.includes(value as DictEntrySource);
+}
+
+/**
+ * 辞書引きで見つかったエントリを使うかを判定する。`--min-confidence` 未満のエントリは使わず、
+ * 確信度の低いエントリを使うときは、誤った法令へリンクしていないか確かめられるよう警告する。
+ */
+export function acceptDictionaryEntry(
+  options: Pick<CliOptions, 'minConfidence'>,
+  lawId: string,
+  entry: LawDictionaryEntry,
+): boolean {
+  const confidence = dictionaryEntryConfidence(lawId, entry);
+  if (confidence < options.minConfidence) {
+    return false;
+  }
+  if (confidence < LOW_DICTIONARY_CONFIDENCE) {
+    process.stderr.write(
+      `警告: 確信度の低い辞書エントリを使います: ${entry.title} (${lawId}) ` +
+        `source=${entry.source ?? '不明'} confidence=${confidence}\n`,
+    );
+  }
+  return true;
+}
+
+function isDictionaryEntry(value: unknown): value is LawDictionaryEntry {
+  const entry = value as Partial<LawDictionaryEntry> | null;
+  return (
+    typeof entry === 'object' &&
+    entry !== null &&
+    typeof entry.title === 'string' &&
+    typeof entry.safe_title === 'string' &&
+    typeof entry.file_name === 'string'
+  );
+}
+
+/**
+ * 外部辞書（JSONまたはMessagePack）を読み込む。
+ * 手作業で整備された辞書は欠損キーを含み得るため、必須キーが揃わないエントリは取り込まない。
+ */
+export async function loadExternalDictionary(filePath: string): Promise<{ dictionary: LawDictionary; invalid: string[] }> {
+  const parsed = parseDictionaryFile(decodeDictionaryContent(await fs.readFile(filePath)), filePath);
+  const dictionary: LawDictionary = {};
+  const invalid: string[] = [];
+  for (const [lawId, value] of Object.entries(parsed)) {
+    if (!isDictionaryEntry(value)) {
+      invalid.push(lawId);
+      continue;
+    }
+    dictionary[lawId] = {
+      title: value.title,
+      safe_title: value.safe_title,
+      file_name: value.file_name,
+      updated_at: typeof value.updated_at === 'string' ? value.updated_at : new Date().toISOString(),
+      law_num: typeof value.law_num === 'string' ? value.law_num : undefined,
+      law_type: typeof value.law_type === 'string' ? value.law_type : undefined,
+      promulgation_date: typeof value.promulgation_date === 'string' ? value.promulgation_date : undefined,
+      abbrev: typeof value.abbrev === 'string' ? value.abbrev : undefined,
+      aliases: Array.isArray(value.aliases) ? value.aliases.filter((alias) => typeof alias === 'string') : undefined,
+      law_num_key: typeof value.law_num === 'string' ? normalizeLawNum(value.law_num)?.key : undefined,
+      // 外部辞書が自前で経路・確信度を持っていればそれを尊重し、無ければ取り込み由来として扱う。
+      ...dictionaryProvenance('import'),
+      ...(isDictEntrySource(value.source) ? dictionaryProvenance(value.source) : {}),
+      ...(typeof value.confidence === 'number' ? { confidence: value.confidence } : {}),
+    };
+  }
+  return { dictionary, invalid };
+}
+
+function shouldOverwrite(
+  lawId: string,
+  current: LawDictionaryEntry,
+  incoming: LawDictionaryEntry,
+  policy: DictionaryConflictPolicy,
+): boolean {
+  if (policy === 'overwrite') {
+    return true;
+  }
+  if (policy === 'keep') {
+    return false;
+  }
+  // prefer-with-id: 法令名が確定していない仮登録（law_<law_id>）より、
+  // 法令名を伴うエントリを優先する。双方確定済みなら既存を維持する。
+  return isFallbackDictionaryEntry(lawId, current) && !isFallbackDictionaryEntry(lawId, incoming);
+}
+
+/**
+ * 既存辞書へ外部辞書をマージし、追加・上書き・スキップの内訳を返す。
+ */
+export function mergeDictionaries(
+  base: LawDictionary,
+  incoming: LawDictionary,
+  policy: DictionaryConflictPolicy,
+): DictionaryMergeResult {
+  const merged: LawDictionary = { ...base };
+  const added: string[] = [];
+  const overwritten: string[] = [];
+  const skipped: string[] = [];
+
+  for (const [lawId, entry] of Object.entries(incoming)) {
+    const current = merged[lawId];
+    if (!current) {
+      merged[lawId] = entry;
+      added.push(lawId);
+      continue;
+    }
+    if (current.title === entry.title && current.file_name === entry.file_name) {
+      skipped.push(lawId);
+      continue;
+    }
+    if (shouldOverwrite(lawId, current, entry, policy)) {
+      merged[lawId] = entry;
+      overwritten.push(lawId);
+    } else {
+      skipped.push(lawId);
+    }
+  }
+
+  return { merged, added, overwritten, skipped };
+}
+
+/**
+ * 辞書から法令名・略称・別名のいずれかが正規表現にマッチする法令を law_id 順に返す。
+ * 法令名が確定していない仮登録（law_<law_id>）は法令名で絞り込めないため対象外とする。
+ */
+export function findLawsByTitle(
+  dictionary: LawDictionary,
+  pattern: RegExp,
+  accept: (lawId: string, entry: LawDictionaryEntry) => boolean = () => true,
+): Array<[string, LawDictionaryEntry]> {
+  return Object.entries(dictionary)
+    .filter(
+      ([lawId, entry]) =>
+        !isFallbackDictionaryEntry(lawId, entry) &&
+        dictionaryEntryNames(entry).some((name) => pattern.test(name)) &&
+        accept(lawId, entry),
+    )
+    .sort(([a], [b]) => a.localeCompare(b));
+}
+
+/**
+ * 候補から確定した法令に、確定のもとになった入力を別名（`aliases`）として登録する。
+ * 次からは同じ入力を辞書で引けるため、検索し直しや候補の確認を繰り返さずに済む。
+ * 入力が既に法令名・略称・別名として表記ゆれを除いて一致する場合と、辞書に無い法令では何もせず false を返す。
+ */
+export function addDictionaryAlias(dictionary: LawDictionary, lawId: string, alias: string): boolean {
+  const entry = dictionary[lawId];
+  const name = alias.trim();
+  if (!entry || !name) {
+    return false;
+  }
+  const key = canonicalizeKanji(name);
+  if (dictionaryEntryNames(entry).some((other) => canonicalizeKanji(other) === key)) {
+    return false;
+  }
+  dictionary[lawId] = { ...entry, aliases: [...(entry.aliases ?? []), name], updated_at: new Date().toISOString() };
+  return true;
+}
+
+/**
+ * 入力が別名（`aliases`）として表記ゆれを除いて一致する法令が辞書に1件だけあれば、その law_id を返す。
+ * `accept` で使わないエントリを除ける。
+ */
+export function findLawIdByAlias(
+  dictionary: LawDictionary,
+  name: string,
+  accept: (lawId: string, entry: LawDictionaryEntry) => boolean = () => true,
+): string | undefined {
+  const key = canonicalizeKanji(name.trim());
+  const matched = Object.entries(dictionary).filter(([, entry]) =>
+    (entry.aliases ?? []).some((alias) => canonicalizeKanji(alias) === key),
+  );
+  return matched.length === 1 && accept(...matched[0]) ? matched[0][0] : undefined;
+}
+
+/**
+ * `--import-dictionary` で指定された外部辞書を既存辞書へ取り込む。
+ */
+export async function importDictionary(options: CliOptions): Promise<void> {
+  if (!options.importDictionaryPath) {
+    throw new Error('--import-dictionary に取り込む辞書のパスを指定してください');
+  }
+  const base = await loadDictionary(options.dictionaryPath, options);
+  const { dictionary: incoming, invalid } = await loadExternalDictionary(options.importDictionaryPath);
+  const result = mergeDictionaries(base, incoming, options.onConflict);
+
+  for (const lawId of invalid) {
+    process.stderr.write(`警告: 必須キーが欠けているため取り込みません: ${lawId}\n`);
+  }
+  for (const lawId of result.overwritten) {
+    process.stdout.write(`上書き: ${lawId} ${base[lawId]?.title ?? ''} -> ${result.merged[lawId].title}\n`);
+  }
+  process.stdout.write(
+    `辞書インポート: 追加 ${result.added.length}件 / 上書き ${result.overwritten.length}件 / スキップ ${result.skipped.length}件` +
+      ` (on-conflict=${options.onConflict})\n`,
+  );
+
+  if (options.dryRun) {
+    process.stdout.write('--dry-run のため辞書は保存しません\n');
+    return;
+  }
+  await saveDictionary(options, result.merged);
+  process.stdout.write(`辞書を保存しました: ${options.dictionaryPath} (${Object.keys(result.merged).length}件)\n`);
+}
+
+// 本文中で前出の法令を指す語や一般名詞は、法令名として引くと別の法令に当たってしまう。
+const AMBIGUOUS_TITLES = new Set(['法', '同法', '本法', '旧法', '新法', 'この法律', '同令', '本令', '規則', '同規則']);
+
+/**
+ * 1件の辞書エントリを、APIから取得した現行の法令名・法令番号と突き合わせる。
+ * `current` が undefined の場合は、APIに存在しない law_id として扱う。
+ */
+export function checkDictionaryEntry(
+  lawId: string,
+  entry: LawDictionaryEntry,
+  current: { title?: string; lawNum?: string } | undefined,
+): DictionaryIssue[] {
+  if (!current) {
+    return [{ lawId, kind: 'missing', current: entry.title }];
+  }
+  const issues: DictionaryIssue[] = [];
+  // 仮登録（law_<law_id>）は法令名が未確定なので、法令名の不一致としては扱わない。
+  if (current.title && current.title !== entry.title && !isFallbackDictionaryEntry(lawId, entry)) {
+    issues.push({ lawId, kind: 'title_mismatch', current: entry.title, expected: current.title });
+  }
+  const entryKey = entry.law_num ? normalizeLawNum(entry.law_num)?.key : undefined;
+  const currentKey = current.lawNum ? normalizeLawNum(current.lawNum)?.key : undefined;
+  if (entryKey && currentKey && entryKey !== currentKey) {
+    issues.push({ lawId, kind: 'law_num_mismatch', current: entry.law_num ?? '', expected: current.lawNum });
+  }
+  return issues;
+}
+
+/**
+ * APIに照会せずに分かる問題（短すぎる・曖昧な法令名、複数の法令で重複する法令名）を検出する。
+ */
+export function findDictionaryNameIssues(dictionary: LawDictionary): DictionaryIssue[] {
+  const issues: DictionaryIssue[] = [];
+  const byTitle = new Map<string, string[]>();
+  for (const [lawId, entry] of Object.entries(dictionary).sort(([a], [b]) => a.localeCompare(b))) {
+    if (isFallbackDictionaryEntry(lawId, entry)) {
+      continue;
+    }
+    if ([...entry.title].length < 2 || AMBIGUOUS_TITLES.has(entry.title)) {
+      issues.push({ lawId, kind: 'ambiguous_title', current: entry.title });
+    }
+    // 表記ゆれだけが違う法令名も、法令名で引くと同じ法令に当たり得るため重複とみなす。
+    const key = canonicalizeKanji(entry.title);
+    byTitle.set(key, [...(byTitle.get(key) ?? []), lawId]);
+  }
+  for (const lawIds of byTitle.values()) {
+    if (lawIds.length > 1) {
+      issues.push(
+        ...lawIds.map((lawId) => ({
+          lawId,
+          kind: 'duplicate_title' as const,
+          current: dictionary[lawId].title,
+          expected: lawIds.filter((other) => other !== lawId).join(', '),
+        })),
+      );
+    }
+  }
+  return issues;
+}
+
+/**
+ * 検査結果のうち自動で直せるものを辞書へ反映する。
+ * 法令名の不一致は現行の法令名へ更新し、APIに存在しないエントリは削除する。
+ */
+export function applyDictionaryFixes(
+  dictionary: LawDictionary,
+  issues: DictionaryIssue[],
+): { dictionary: LawDictionary; updated: string[]; removed: string[] } {
+  const fixed: LawDictionary = { ...dictionary };
+  const updated: string[] = [];
+  const removed: string[] = [];
+  for (const issue of issues) {
+    if (issue.kind === 'title_mismatch' && issue.expected && fixed[issue.lawId]) {
+      fixed[issue.lawId] = {
+        ...fixed[issue.lawId],
+        title: issue.expected,
+        safe_title: toSafeTitle(issue.expected),
+        file_name: getFileName(issue.lawId, issue.expected),
+        updated_at: new Date().toISOString(),
+        ...dictionaryProvenance('api_lookup'),
+      };
+      updated.push(issue.lawId);
+    } else if (issue.kind === 'missing' && fixed[issue.lawId]) {
+      delete fixed[issue.lawId];
+      removed.push(issue.lawId);
+    }
+  }
+  return { dictionary: fixed, updated, removed };
+}
+
+const ISSUE_LABELS: Record<DictionaryIssueKind, string> = {
+  missing: 'APIに存在しない law_id',
+  title_mismatch: '現行の法令名と不一致',
+  law_num_mismatch: '法令番号が不一致',
+  ambiguous_title: '短すぎる・曖昧な法令名',
+  duplicate_title: '複数の法令で重複する法令名',
+  lookup_failed: 'API照会に失敗（再実行してください）',
+};
+
+function printDictionaryIssues(issues: DictionaryIssue[]): void {
+  for (const [kind, label] of Object.entries(ISSUE_LABELS)) {
+    const matched = issues.filter((issue) => issue.kind === kind);
+    if (matched.length === 0) {
+      continue;
+    }
+    process.stdout.write(`${label}: ${matched.length}件\n`);
+    for (const issue of matched) {
+      process.stdout.write(`  - ${issue.lawId} ${issue.current}${issue.expected ? ` -> ${issue.expected}` : ''}\n`);
+    }
+  }
+}
+
+/**
+ * `--verify-dictionary` で辞書の全エントリをAPIと突き合わせ、問題をカテゴリ別に一覧する。
+ * `--fix` 指定時は、法令名の不一致を現行名へ更新し、APIに存在しないエントリを確認のうえ削除する。
+ * API照会は通常の取得と同じく `--request-interval-ms` の間隔制御に従う。
+ */
+export async function verifyDictionary(options: CliOptions): Promise<void> {
+  const dictionary = await loadDictionary(options.dictionaryPath, options);
+  const lawIds = Object.keys(dictionary).sort((a, b) => a.localeCompare(b));
+  const issues: DictionaryIssue[] = findDictionaryNameIssues(dictionary);
+  for (const [index, lawId] of lawIds.entries()) {
+    if (options.verbose) {
+      process.stderr.write(`照会中 (${index + 1}/${lawIds.length}): ${lawId}\n`);
+    }
+    try {
+      const info = await fetchLawInfoById(options, lawId);
+      issues.push(
+        ...checkDictionaryEntry(
+          lawId,
+          dictionary[lawId],
+          info && { title: info.law_title.trim(), lawNum: info.law_num },
+        ),
+      );
+    } catch (error) {
+      // 一時的な障害で実在する法令を削除しないよう、照会失敗は存在しないものと区別する。
+      const message = error instanceof Error ? error.message : String(error);
+      issues.push({ lawId, kind: 'lookup_failed', current: dictionary[lawId].title, expected: message });
+    }
+  }
+
+  process.stdout.write(`辞書検査: ${lawIds.length}件中 問題 ${issues.length}件\n`);
+  printDictionaryIssues(issues);
+  if (!options.fix || issues.length === 0) {
+    return;
+  }
+
+  const removable = issues.filter((issue) => issue.kind === 'missing');
+  let fixable = issues;
+  if (removable.length > 0) {
+    if (options.nonInteractive && !options.yes) {
+      throw new Error('--non-interactive でエントリを削除するには --yes を指定してください');
+    }
+    if (!options.nonInteractive) {
+      if (!process.stdin.isTTY) {
+        throw new Error('確認プロンプトを表示できません。端末以外から実行する場合は --non-interactive --yes を指定してください');
+      }
+      if (!(await confirm(`APIに存在しない ${removable.length}件のエントリを削除します。続行しますか？ [y/N] `))) {
+        process.stdout.write('削除は行いません\n');
+        fixable = issues.filter((issue) => issue.kind !== 'missing');
+      }
+    }
+  }
+
+  const result = applyDictionaryFixes(dictionary, fixable);
+  process.stdout.write(`修正: 法令名を更新 ${result.updated.length}件 / 削除 ${result.removed.length}件\n`);
+  if (options.dryRun) {
+    process.stdout.write('--dry-run のため辞書は保存しません\n');
+    return;
+  }
+  await saveDictionary(options, result.dictionary, result.removed);
+}
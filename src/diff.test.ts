@@ -0,0 +1,40 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { stripVolatileFrontmatter, unifiedDiff } from './index.js';
+
+test('unifiedDiff: fetched_at だけが異なるノートは差分なしとする', () => {
+  const before = '---\nlaw_id: 334AC0000000121\nfetched_at: 2026-02-20T00:00:00Z\n---\n\n# 特許法\n';
+  const after = '---\nlaw_id: 334AC0000000121\nfetched_at: 2026-10-16T00:00:00Z\n---\n\n# 特許法\n';
+  assert.equal(
+    unifiedDiff(stripVolatileFrontmatter(before), stripVolatileFrontmatter(after), 'old.md', 'new.md'),
+    '',
+  );
+});
+
+test('unifiedDiff: 変更行の前後3行を文脈として hunk を出力する', () => {
+  const before = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'].join('\n');
+  const after = ['a', 'b', 'c', 'd', 'E', 'f', 'g', 'h', 'i'].join('\n');
+  assert.equal(
+    unifiedDiff(`${before}\n`, `${after}\n`, 'old.md', 'new.md'),
+    [
+      '--- old.md',
+      '+++ new.md',
+      '@@ -2,7 +2,8 @@',
+      ' b',
+      ' c',
+      ' d',
+      '-e',
+      '+E',
+      ' f',
+      ' g',
+      ' h',
+      '+i',
+      '',
+    ].join('\n'),
+  );
+});
+
+test('unifiedDiff: 新規ノートは全行を追加として扱う', () => {
+  assert.equal(unifiedDiff('', '# 民法\n', '/dev/null', 'new.md'), '--- /dev/null\n+++ new.md\n@@ -0,0 +1,1 @@\n+# 民法\n');
+});
@@ -0,0 +1,123 @@
+import { joinFrontmatter, splitFrontmatter } from './frontmatter.js';
+
+/**
+ * 実行のたびに値が変わり、内容の差分として意味を持たないfrontmatterキー。
+ */
+const VOLATILE_FRONTMATTER_KEYS = ['fetched_at', 'content_hash'];
+
+interface DiffOp {
+  type: ' ' | '-' | '+';
+  line: string;
+}
+
+function splitLines(text: string): string[] {
+  if (text === '') {
+    return [];
+  }
+  const lines = text.split('\n');
+  if (lines[lines.length - 1] === '') {
+    lines.pop();
+  }
+  return lines;
+}
+
+/**
+ * frontmatterから毎回変わるキーの行を取り除く。
+ * 再取得しただけで差分ありと判定されないよう、比較前に両側へ適用する。
+ */
+export function stripVolatileFrontmatter(markdown: string): string {
+  const parts = splitFrontmatter(markdown);
+  if (!parts.frontmatter) {
+    return markdown;
+  }
+  const lines = parts.lines.filter((line) => !VOLATILE_FRONTMATTER_KEYS.some((key) => line.startsWith(`${key}:`)));
+  return joinFrontmatter({ ...parts, lines });
+}
+
+function diffLines(a: string[], b: string[]): DiffOp[] {
+  // 再取得の差分は局所的なことが多いため、共通の先頭・末尾を除いてからLCSを計算し表を小さく保つ。
+  let start = 0;
+  while (start < a.length && start < b.length && a[start] === b[start]) {
+    start += 1;
+  }
+  let endA = a.length;
+  let endB = b.length;
+  while (endA > start && endB > start && a[endA - 1] === b[endB - 1]) {
+    endA -= 1;
+    endB -= 1;
+  }
+  const midA = a.slice(start, endA);
+  const midB = b.slice(start, endB);
+  const table = Array.from({ length: midA.length + 1 }, () => new Uint32Array(midB.length + 1));
+  for (let i = midA.length - 1; i >= 0; i -= 1) {
+    for (let j = midB.length - 1; j >= 0; j -= 1) {
+      table[i][j] = midA[i] === midB[j] ? table[i + 1][j + 1] + 1 : Math.max(table[i + 1][j], table[i][j + 1]);
+    }
+  }
+
+  const ops: DiffOp[] = a.slice(0, start).map((line): DiffOp => ({ type: ' ', line }));
+  let i = 0;
+  let j = 0;
+  while (i < midA.length && j < midB.length) {
+    if (midA[i] === midB[j]) {
+      ops.push({ type: ' ', line: midA[i] });
+      i += 1;
+      j += 1;
+    } else if (table[i + 1][j] >= table[i][j + 1]) {
+      ops.push({ type: '-', line: midA[i] });
+      i += 1;
+    } else {
+      ops.push({ type: '+', line: midB[j] });
+      j += 1;
+    }
+  }
+  for (; i < midA.length; i += 1) {
+    ops.push({ type: '-', line: midA[i] });
+  }
+  for (; j < midB.length; j += 1) {
+    ops.push({ type: '+', line: midB[j] });
+  }
+  ops.push(...a.slice(endA).map((line): DiffOp => ({ type: ' ', line })));
+  return ops;
+}
+
+function hunkRange(start: number, length: number): string {
+  // unified diff では空範囲の開始行を直前の行番号で表す。
+  return `${length === 0 ? start : start + 1},${length}`;
+}
+
+/**
+ * 2つのテキストの unified diff を生成する。差分が無ければ空文字を返す。
+ */
+export function unifiedDiff(oldText: string, newText: string, oldLabel: string, newLabel: string, context = 3): string {
+  const ops = diffLines(splitLines(oldText), splitLines(newText));
+  const changed = ops.flatMap((op, index) => (op.type === ' ' ? [] : [index]));
+  if (changed.length === 0) {
+    return '';
+  }
+
+  const groups: Array<[number, number]> = [];
+  for (const index of changed) {
+    const last = groups[groups.length - 1];
+    if (last && index - last[1] <= context * 2) {
+      last[1] = index;
+    } else {
+      groups.push([index, index]);
+    }
+  }
+
+  const lines = [`--- ${oldLabel}`, `+++ ${newLabel}`];
+  for (const [first, last] of groups) {
+    const from = Math.max(0, first - context);
+    const to = Math.min(ops.length, last + context + 1);
+    const before = ops.slice(0, from);
+    const hunk = ops.slice(from, to);
+    const oldStart = before.filter((op) => op.type !== '+').length;
+    const newStart = before.filter((op) => op.type !== '-').length;
+    const oldLength = hunk.filter((op) => op.type !== '+').length;
+    const newLength = hunk.filter((op) => op.type !== '-').length;
+    lines.push(`@@ -${hunkRange(oldStart, oldLength)} +${hunkRange(newStart, newLength)} @@`);
+    lines.push(...hunk.map((op) => `${op.type}${op.line}`));
+  }
+  return `${lines.join('\n')}\n`;
+}
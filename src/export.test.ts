@@ -0,0 +1,65 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import zlib from 'node:zlib';
+
+import { buildZip, concatNotes } from './index.js';
+
+const notes = [
+  {
+    path: 'laws/特許法_334AC0000000121.md',
+    markdown: [
+      '---',
+      'law_id: 334AC0000000121',
+      'title: "特許法"',
+      '---',
+      '',
+      '## 第一条',
+      '<a id="Mp-At_1"></a>',
+      '',
+      '[[laws/民法_129AC0000000089.md#Mp-At_3|民法第三条]]、[[#Mp-At_2|次条]]、[[laws/商法_132AC0000000048.md|商法]]',
+      '',
+    ].join('\n'),
+  },
+  {
+    path: 'laws/民法_129AC0000000089.md',
+    markdown: ['---', 'law_id: 129AC0000000089', 'title: "民法"', '---', '', '## 第三条', '<a id="Mp-At_3"></a>'].join(
+      '\n',
+    ),
+  },
+];
+
+test('buildZip: ファイル名と内容を読み戻せるZIPを作る', () => {
+  const data = Buffer.from('# 特許法\n本文');
+  const zip = buildZip([{ name: 'laws/特許法.md', data }], new Date(2026, 1, 20, 12, 0, 0));
+  assert.equal(zip.readUInt32LE(0), 0x04034b50);
+  // UTF-8のファイル名であることを示すフラグ。
+  assert.equal(zip.readUInt16LE(6) & 0x0800, 0x0800);
+  const nameLength = zip.readUInt16LE(26);
+  const compressedSize = zip.readUInt32LE(18);
+  assert.equal(zip.subarray(30, 30 + nameLength).toString('utf8'), 'laws/特許法.md');
+  const start = 30 + nameLength;
+  assert.deepEqual(zlib.inflateRawSync(zip.subarray(start, start + compressedSize)), data);
+  // 終端レコードのエントリ数と中央ディレクトリの位置。
+  const end = zip.subarray(zip.length - 22);
+  assert.equal(end.readUInt32LE(0), 0x06054b50);
+  assert.equal(end.readUInt16LE(10), 1);
+  assert.equal(zip.readUInt32LE(end.readUInt32LE(16)), 0x02014b50);
+});
+
+test('concatNotes: 法令名の見出しと区切り・元ファイル名を付けて連結し、keep ではリンクをそのまま残す', () => {
+  const markdown = concatNotes(notes, 'keep');
+  assert.match(markdown, /^<!-- file: laws\/特許法_334AC0000000121\.md -->\n# 特許法\n\n## 第一条/);
+  assert.match(markdown, /\n\n---\n\n<!-- file: laws\/民法_129AC0000000089\.md -->\n# 民法\n/);
+  assert.match(markdown, /\[\[laws\/民法_129AC0000000089\.md#Mp-At_3\|民法第三条\]\]/);
+  assert.doesNotMatch(markdown, /^law_id:/m);
+});
+
+test('concatNotes: anchor では連結したファイル内のアンカーへリンクを書き換える', () => {
+  const markdown = concatNotes(notes, 'anchor');
+  assert.match(markdown, /\[民法第三条\]\(#129AC0000000089-Mp-At_3\)/);
+  assert.match(markdown, /\[次条\]\(#334AC0000000121-Mp-At_2\)/);
+  assert.match(markdown, /<a id="129AC0000000089-Mp-At_3"><\/a>/);
+  assert.match(markdown, /<a id="334AC0000000121"><\/a>\n# 特許法/);
+  // 連結に含まれない法令へのリンクは書き換えない。
+  assert.match(markdown, /\[\[laws\/商法_132AC0000000048\.md\|商法\]\]/);
+});
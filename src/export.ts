@@ -0,0 +1,186 @@
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import process from 'node:process';
+import zlib from 'node:zlib';
+import { splitFrontmatter } from './frontmatter.js';
+import { listNotes } from './links.js';
+import { parseLawIdFromNoteFileName } from './notes.js';
+import { encodeNoteText, readNoteText, writeFileAtomic } from './storage.js';
+import type { CliOptions, ExportLinkMode, FrontmatterValue } from './types.js';
+
+// 図の埋め込み（`![[...]]`）はリンクではないため書き換えない。
+const WIKI_LINK_PATTERN = /(?<!!)\[\[([^\]|#]*)(?:#([^\]|]*))?(?:\|([^\]]*))?\]\]/g;
+const ANCHOR_TAG_PATTERN = /<a id="([^"]+)"><\/a>/g;
+const CRC32_TABLE = Array.from({ length: 256 }, (_, index) => {
+  let value = index;
+  for (let bit = 0; bit < 8; bit += 1) {
+    value = value & 1 ? 0xedb88320 ^ (value >>> 1) : value >>> 1;
+  }
+  return value >>> 0;
+});
+
+/**
+ * エクスポートする1ノート分の内容。`path` は出力ディレクトリからの相対パス。
+ */
+export interface ExportNote {
+  path: string;
+  markdown: string;
+}
+
+function crc32(data: Buffer): number {
+  let crc = 0xffffffff;
+  for (const byte of data) {
+    crc = CRC32_TABLE[(crc ^ byte) & 0xff] ^ (crc >>> 8);
+  }
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
+function dosDateTime(date: Date): { time: number; date: number } {
+  return {
+    time: (date.getHours() << 11) | (date.getMinutes() << 5) | Math.floor(date.getSeconds() / 2),
+    date: ((date.getFullYear() - 1980) << 9) | ((date.getMonth() + 1) << 5) | date.getDate(),
+  };
+}
+
+/**
+ * ファイル群を1つのZIPアーカイブにまとめる。依存ライブラリを増やさないよう、Deflate圧縮の最小限のZIP形式を自前で組み立てる。
+ * ファイル名は日本語を含むため、UTF-8であることを示すフラグを立てる。
+ */
+export function buildZip(files: Array<{ name: string; data: Buffer }>, modifiedAt = new Date()): Buffer {
+  const { time, date } = dosDateTime(modifiedAt);
+  const localParts: Buffer[] = [];
+  const centralParts: Buffer[] = [];
+  let offset = 0;
+  for (const file of files) {
+    const name = Buffer.from(file.name, 'utf8');
+    const compressed = zlib.deflateRawSync(file.data);
+    const crc = crc32(file.data);
+    const local = Buffer.alloc(30);
+    local.writeUInt32LE(0x04034b50, 0);
+    local.writeUInt16LE(20, 4);
+    local.writeUInt16LE(0x0800, 6);
+    local.writeUInt16LE(8, 8);
+    local.writeUInt16LE(time, 10);
+    local.writeUInt16LE(date, 12);
+    local.writeUInt32LE(crc, 14);
+    local.writeUInt32LE(compressed.length, 18);
+    local.writeUInt32LE(file.data.length, 22);
+    local.writeUInt16LE(name.length, 26);
+    local.writeUInt16LE(0, 28);
+    localParts.push(local, name, compressed);
+
+    const central = Buffer.alloc(46);
+    central.writeUInt32LE(0x02014b50, 0);
+    central.writeUInt16LE(20, 4);
+    central.writeUInt16LE(20, 6);
+    central.writeUInt16LE(0x0800, 8);
+    central.writeUInt16LE(8, 10);
+    central.writeUInt16LE(time, 12);
+    central.writeUInt16LE(date, 14);
+    central.writeUInt32LE(crc, 16);
+    central.writeUInt32LE(compressed.length, 20);
+    central.writeUInt32LE(file.data.length, 24);
+    central.writeUInt16LE(name.length, 28);
+    central.writeUInt32LE(offset, 42);
+    centralParts.push(central, name);
+    offset += local.length + name.length + compressed.length;
+  }
+  const centralDirectory = Buffer.concat(centralParts);
+  const end = Buffer.alloc(22);
+  end.writeUInt32LE(0x06054b50, 0);
+  end.writeUInt16LE(files.length, 8);
+  end.writeUInt16LE(files.length, 10);
+  end.writeUInt32LE(centralDirectory.length, 12);
+  end.writeUInt32LE(offset, 16);
+  return Buffer.concat([...localParts, centralDirectory, end]);
+}
+
+function frontmatterValue(frontmatter: Record<string, FrontmatterValue>, key: string): string | undefined {
+  const value = frontmatter[key];
+  return typeof value === 'string' || typeof value === 'number' ? String(value) : undefined;
+}
+
+function noteLawId(note: ExportNote, frontmatter: Record<string, FrontmatterValue>): string | undefined {
+  return frontmatterValue(frontmatter, 'law_id') ?? parseLawIdFromNoteFileName(path.posix.basename(note.path));
+}
+
+/**
+ * 全ノートを、法令名の見出しを付けて1つのMarkdownに連結する。各ノートの間は `---` で区切り、元のファイル名をコメントで残す。
+ * `links` が `anchor` のときは、連結したファイルの中にある法令へのWikiリンクを、法令ごとに接頭辞を付けたアンカーへの
+ * 標準Markdownリンクに書き換える。法令が違うとアンカー（`Mp-At_1` など）が重なるため、アンカーにも law_id を前置する。
+ */
+export function concatNotes(notes: ExportNote[], links: ExportLinkMode): string {
+  const lawIdsByFileName = new Map<string, string>();
+  const parsed = notes.map((note) => {
+    const { frontmatter = {}, body } = splitFrontmatter(note.markdown);
+    const lawId = noteLawId(note, frontmatter);
+    if (lawId) {
+      lawIdsByFileName.set(path.posix.basename(note.path), lawId);
+    }
+    return {
+      note,
+      lawId,
+      title: frontmatterValue(frontmatter, 'title') ?? path.posix.basename(note.path, '.md'),
+      body: body.trim(),
+    };
+  });
+
+  const sections = parsed.map(({ note, lawId, title, body }) => {
+    let text = body;
+    if (links === 'anchor' && lawId) {
+      text = text
+        .replace(ANCHOR_TAG_PATTERN, (_, anchor: string) => `<a id="${lawId}-${anchor}"></a>`)
+        .replace(WIKI_LINK_PATTERN, (whole, target: string, anchor: string | undefined, label: string | undefined) => {
+          const fileName = path.posix.basename(target.trim());
+          const targetLawId = target.trim()
+            ? lawIdsByFileName.get(fileName.endsWith('.md') ? fileName : `${fileName}.md`)
+            : lawId;
+          if (!targetLawId) {
+            // 連結に含まれない法令へのリンクは書き換え先が無いため、そのまま残す。
+            return whole;
+          }
+          const fragment = anchor ? `${targetLawId}-${anchor}` : targetLawId;
+          return `[${label ?? (target.trim() || anchor)}](#${fragment})`;
+        });
+    }
+    const lines = [`<!-- file: ${note.path} -->`];
+    if (links === 'anchor' && lawId) {
+      lines.push(`<a id="${lawId}"></a>`);
+    }
+    lines.push(`# ${title}`, '', text);
+    return lines.join('\n');
+  });
+  return `${sections.join('\n\n---\n\n')}\n`;
+}
+
+/**
+ * 出力ディレクトリのノートを `--export` の形式（ZIP・単一Markdown）で `--export-out` へ書き出す。
+ */
+export async function exportNotes(
+  options: Pick<CliOptions, 'outputDir' | 'exportFormat' | 'exportOut' | 'exportLinks' | 'lineEnding' | 'bom'>,
+): Promise<void> {
+  const relativePaths = (await listNotes(options.outputDir)).sort((a, b) => a.localeCompare(b));
+  if (relativePaths.length === 0) {
+    throw new Error(`エクスポートするノートがありません: ${options.outputDir}`);
+  }
+  const outPath = options.exportOut as string;
+  await fs.mkdir(path.dirname(outPath), { recursive: true });
+  if (options.exportFormat === 'zip') {
+    const files = await Promise.all(
+      relativePaths.map(async (relativePath) => ({
+        name: relativePath,
+        data: await fs.readFile(path.join(options.outputDir, relativePath)),
+      })),
+    );
+    await writeFileAtomic(outPath, buildZip(files));
+  } else {
+    const notes = await Promise.all(
+      relativePaths.map(async (relativePath) => ({
+        path: relativePath,
+        markdown: await readNoteText(path.join(options.outputDir, relativePath)),
+      })),
+    );
+    await writeFileAtomic(outPath, encodeNoteText(concatNotes(notes, options.exportLinks), options));
+  }
+  process.stdout.write(`エクスポートしました: ${outPath} (${relativePaths.length}件)\n`);
+}
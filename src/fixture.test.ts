@@ -31,6 +31,53 @@ test('fixture: 保存済みHTMLからMarkdown生成できる', async (t) => {
     assert.match(markdown, /fetched_at:/);
     assert.match(markdown, /第一条/);
     assert.match(markdown, /\[\[#/);
+    // 第2項以降の項番号が本文から落ちていないこと。
+    assert.match(markdown, /^\*\*２\*\* /m);
+    // 条見出しは要素IDの条番号と見出し（括弧書き）から作る。
+    assert.match(markdown, /^## 第二条（定義）$/m);
+    assert.match(markdown, /^## 第五十五条から第六十三条まで$/m);
+    // 本文中の「前号」などのリンク先（項・号の要素ID）がノート内に存在すること。
+    assert.match(markdown, /\[\[#Mp-Ch_1-At_2-Pr_3-It_2\|前号\]\]/);
+    assert.match(markdown, /<a id="Mp-Ch_1-At_2-Pr_3-It_2"><\/a>/);
+
+    // --inline-tables では条文中の表だけをMarkdownの表にし、別表は従来どおり文ごとに出す。
+    const tableDoc = await extractLawDocumentFromPage(
+      page,
+      '334AC0000000121',
+      'https://laws.e-gov.go.jp/law/334AC0000000121',
+      true,
+    );
+    const tableMarkdown = renderMarkdownForTest(tableDoc);
+    assert.match(tableMarkdown, /^\| 日本国民又はパリ条約の同盟国の国民.* \| 世界貿易機関の加盟国 \|$/m);
+    assert.doesNotMatch(markdown, /^\| /m);
+    assert.doesNotMatch(tableMarkdown, /^\| .*納付しなければならない者/m);
+  } finally {
+    await browser.close();
+  }
+});
+
+test('fixture: 要素の取り込み方ごとの抽出結果がゴールデンファイルと一致する', async (t) => {
+  // 取り込み方の表（ELEMENT_COLLECTORS）を変えたときは、抽出結果を確かめてから expected.json を更新する。
+  const html = await fs.readFile('tests/fixtures/collectors/page.html', 'utf8');
+  const expected = JSON.parse(await fs.readFile('tests/fixtures/collectors/expected.json', 'utf8')) as unknown;
+  let browser;
+  try {
+    browser = await chromium.launch({ headless: true });
+  } catch (error) {
+    t.skip(`Playwright起動不可のためスキップ: ${String(error)}`);
+    return;
+  }
+  const page = await browser.newPage();
+
+  try {
+    await page.setContent(html, { waitUntil: 'domcontentloaded' });
+    const doc = await extractLawDocumentFromPage(
+      page,
+      '501AC0000000001',
+      'https://laws.e-gov.go.jp/law/501AC0000000001',
+    );
+    // 値が undefined の項目はファイルに書けないため、JSONと同じ形にしてから比べる。
+    assert.deepEqual(JSON.parse(JSON.stringify(doc)), expected);
   } finally {
     await browser.close();
   }
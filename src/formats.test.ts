@@ -0,0 +1,71 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { buildProvisionTree, noteFileForFormat, renderHtml, renderPlainText } from './index.js';
+
+const text = (value: string) => ({ type: 'text' as const, text: value });
+
+const doc = {
+  lawId: '334AC0000000121',
+  title: '特許法',
+  lawNum: '昭和三十四年法律第百二十一号',
+  sourceUrl: '',
+  blocks: [
+    {
+      id: 'Mp-At_1',
+      heading: '第一条（目的）',
+      paragraphs: [
+        {
+          anchor: 'Mp-At_1-Pr_1',
+          segments: [text('第一条　この法律は、'), { type: 'link' as const, text: '民法', href: '/law/129AC0000000089' }],
+        },
+        { anchor: 'Mp-At_1-Pr_1-It_1', number: '一', level: 1, segments: [text('発明 <定義>')] },
+        { anchor: 'Mp-At_1-Pr_1-It_1-Sb1_1', number: 'イ', level: 2, segments: [text('物')] },
+        { anchor: 'Mp-At_1-Pr_2', number: '２', segments: [text('前項の規定')] },
+      ],
+    },
+  ],
+};
+
+test('buildProvisionTree: 号・細分を直前の項・号の下に入れ子にし、リンクは文言だけにする', () => {
+  const [article] = buildProvisionTree(doc);
+  assert.deepEqual(
+    article.paragraphs.map((node) => [node.text, node.children.map((child) => [child.number, child.children.length])]),
+    [
+      ['この法律は、民法', [['一', 1]]],
+      ['前項の規定', []],
+    ],
+  );
+});
+
+test('renderPlainText: 法令名・法令番号と条文だけを書き、号は全角空白で字下げする', () => {
+  assert.equal(
+    renderPlainText(doc),
+    [
+      '特許法',
+      '昭和三十四年法律第百二十一号',
+      '',
+      '第一条（目的）',
+      'この法律は、民法',
+      '　一　発明 <定義>',
+      '　　イ　物',
+      '２　前項の規定',
+      '',
+    ].join('\n'),
+  );
+});
+
+test('renderHtml: frontmatterを meta に、条を h2、項・号を p にし、リンクは表示名だけにする', () => {
+  const html = renderHtml(doc, {
+    law_id: '334AC0000000121',
+    depth: 0,
+    superseded_by: '[[新特許法_999AC0000000001.md|新特許法]]',
+    referenced_articles: { '129AC0000000089': ['第三条'] },
+  });
+  assert.match(html, /<meta name="law_id" content="334AC0000000121">/);
+  assert.match(html, /<meta name="superseded_by" content="新特許法">/);
+  assert.doesNotMatch(html, /referenced_articles/);
+  assert.match(html, /<h2 id="Mp-At_1">第一条（目的）<\/h2>/);
+  assert.match(html, /<p id="Mp-At_1-Pr_1-It_1" class="item-1">一　発明 &lt;定義&gt;<\/p>/);
+  assert.equal(noteFileForFormat('laws/特許法_334AC0000000121.md', 'html'), 'laws/特許法_334AC0000000121.html');
+});
@@ -0,0 +1,171 @@
+import { stripLeadingArticleTitle } from './provision.js';
+import type { ArticleParagraph, FrontmatterValue, NoteFormat, ScrapedLawDocument } from './types.js';
+
+const ARABIC_NUMBER_PATTERN = /^[0-9０-９]+$/;
+const WHITESPACE_RUN_PATTERN = /\s+/g;
+// Obsidian向け（`[[ノート|表示名]]`）と標準Markdown（`[表示名](パス)`）のリンクを、表示名だけに戻す。
+const WIKI_LINK_PATTERN = /\[\[(?:[^\]|]*\|)?([^\]]*)\]\]/g;
+const MARKDOWN_LINK_PATTERN = /\[([^\]]*)\]\([^)]*\)/g;
+
+/**
+ * 項・号（細分）の中間表現。リンクを外した本文を持ち、号は項の、細分は号の `children` に入れ子にする。
+ * 条文中の表は `rows`（行ごとのセルの文言）に持ち、`text` は空にする。
+ */
+export interface ProvisionNode {
+  anchor: string;
+  number?: string;
+  text: string;
+  rows?: string[][];
+  children: ProvisionNode[];
+}
+
+/**
+ * 条の中間表現。Markdown以外の形式へは、この木から書き出す。
+ */
+export interface ArticleNode {
+  id: string;
+  heading: string;
+  paragraphs: ProvisionNode[];
+}
+
+function paragraphNode(paragraph: ArticleParagraph, stripHeading?: string): ProvisionNode {
+  const rows: string[][] = [];
+  const cells: string[] = [];
+  let text = '';
+  for (const segment of paragraph.segments) {
+    if (segment.type === 'table_break') {
+      if (segment.boundary === 'cell') {
+        cells.push(text.replace(WHITESPACE_RUN_PATTERN, ' ').trim());
+      } else {
+        rows.push(cells.splice(0));
+      }
+      text = '';
+      continue;
+    }
+    // 参照はリンク先を持たず文言だけにする。図は代替テキストが無ければ「図」とだけ残す。
+    text += segment.type === 'figure' ? `［${segment.text || '図'}］` : segment.text;
+  }
+  if (rows.length > 0) {
+    return { anchor: paragraph.anchor, number: paragraph.number, text: '', rows, children: [] };
+  }
+  const body = stripHeading === undefined ? text : stripLeadingArticleTitle(text, stripHeading);
+  return {
+    anchor: paragraph.anchor,
+    number: paragraph.number,
+    text: body.replace(WHITESPACE_RUN_PATTERN, ' ').trim(),
+    children: [],
+  };
+}
+
+/**
+ * 取得した法令の本文を、条・項・号の木にする。号・細分は `level` をたどって直前の浅い項・号の下に入れる。
+ */
+export function buildProvisionTree(doc: ScrapedLawDocument): ArticleNode[] {
+  return doc.blocks.map((block) => {
+    const paragraphs: ProvisionNode[] = [];
+    // 深さごとの直近の項・号。深さ0が項。
+    const parents: ProvisionNode[] = [];
+    for (const [index, paragraph] of block.paragraphs.entries()) {
+      const node = paragraphNode(paragraph, index === 0 ? block.heading : undefined);
+      if (!node.text && !node.rows) {
+        continue;
+      }
+      // 第1項は番号を持たないため、条の先頭の文と算用数字の番号を持つ文を項の始まりとみなす（Markdownと同じ判定）。
+      const startsParagraph = index === 0 || ARABIC_NUMBER_PATTERN.test(paragraph.number ?? '');
+      const level = startsParagraph ? 0 : (paragraph.level ?? 0);
+      const parent = level > 0 ? parents.slice(0, level).reverse().find(Boolean) : undefined;
+      (parent ? parent.children : paragraphs).push(node);
+      parents.length = level;
+      parents[level] = node;
+    }
+    return { id: block.id, heading: block.heading, paragraphs };
+  });
+}
+
+function provisionLine(node: ProvisionNode): string {
+  return node.number ? `${node.number}　${node.text}` : node.text;
+}
+
+function plainTextLines(nodes: ProvisionNode[], depth: number): string[] {
+  return nodes.flatMap((node) => {
+    const indent = '　'.repeat(depth);
+    const own = node.rows
+      ? node.rows.map((row) => `${indent}${row.join('\t')}`)
+      : [`${indent}${provisionLine(node)}`];
+    return [...own, ...plainTextLines(node.children, depth + 1)];
+  });
+}
+
+/**
+ * `--format txt` の出力。リンクやfrontmatterを含まない素の条文で、法令名と法令番号の後に条ごとの見出しと本文を並べる。
+ * 号・細分は深さに応じて全角空白で字下げし、表はセルをタブで区切る。
+ */
+export function renderPlainText(doc: ScrapedLawDocument): string {
+  const lines = [doc.title];
+  if (doc.lawNum) {
+    lines.push(doc.lawNum);
+  }
+  for (const article of buildProvisionTree(doc)) {
+    lines.push('', article.heading, ...plainTextLines(article.paragraphs, 0));
+  }
+  return `${lines.join('\n')}\n`;
+}
+
+function escapeHtml(text: string): string {
+  return text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;').replace(/"/g, '&quot;');
+}
+
+function metaContent(value: FrontmatterValue): string | undefined {
+  if (typeof value === 'string' || typeof value === 'number' || typeof value === 'boolean') {
+    return String(value).replace(WIKI_LINK_PATTERN, '$1').replace(MARKDOWN_LINK_PATTERN, '$1');
+  }
+  if (Array.isArray(value)) {
+    return value.map((item) => item.replace(WIKI_LINK_PATTERN, '$1').replace(MARKDOWN_LINK_PATTERN, '$1')).join(', ');
+  }
+  // 条ごとの参照一覧のような入れ子の値は1つの `<meta>` に収まらないため出さない。
+  return undefined;
+}
+
+function htmlLines(nodes: ProvisionNode[], depth: number): string[] {
+  return nodes.flatMap((node) => {
+    const attributes = `id="${escapeHtml(node.anchor)}"${depth > 0 ? ` class="item-${depth}"` : ''}`;
+    const own = node.rows
+      ? [
+          `<table ${attributes}>`,
+          ...node.rows.map((row) => `<tr>${row.map((cell) => `<td>${escapeHtml(cell)}</td>`).join('')}</tr>`),
+          '</table>',
+        ]
+      : [`<p ${attributes}>${escapeHtml(provisionLine(node))}</p>`];
+    return [...own, ...htmlLines(node.children, depth + 1)];
+  });
+}
+
+/**
+ * `--format html` の出力。法令名を `<h1>`、条の見出しを `<h2>`、項・号を `<p>`（号・細分は深さを `class="item-N"`）にした
+ * 最小限のHTMLで、要素IDはMarkdownのアンカーと同じものを付ける。frontmatterは `<meta name="キー">` にし、
+ * リンクを含む値は表示名だけにする。
+ */
+export function renderHtml(doc: ScrapedLawDocument, frontmatter: Record<string, FrontmatterValue>): string {
+  const lines = ['<!DOCTYPE html>', '<html lang="ja">', '<head>', '<meta charset="utf-8">'];
+  lines.push(`<title>${escapeHtml(doc.title)}</title>`);
+  for (const [key, value] of Object.entries(frontmatter)) {
+    const content = metaContent(value);
+    if (content !== undefined) {
+      lines.push(`<meta name="${escapeHtml(key)}" content="${escapeHtml(content)}">`);
+    }
+  }
+  lines.push('</head>', '<body>', `<h1>${escapeHtml(doc.title)}</h1>`);
+  for (const article of buildProvisionTree(doc)) {
+    lines.push(`<h2 id="${escapeHtml(article.id)}">${escapeHtml(article.heading)}</h2>`);
+    lines.push(...htmlLines(article.paragraphs, 0));
+  }
+  lines.push('</body>', '</html>');
+  return `${lines.join('\n')}\n`;
+}
+
+/**
+ * ノートの相対パス（`.md`）を、`--format` の形式の拡張子に替える。
+ */
+export function noteFileForFormat(relativePath: string, format: NoteFormat): string {
+  return format === 'md' ? relativePath : relativePath.replace(/\.md$/, `.${format}`);
+}
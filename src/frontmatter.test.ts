@@ -0,0 +1,143 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  joinFrontmatter,
+  renderFrontmatter,
+  renderFrontmatterField,
+  renderMarkdownForTest,
+  setFrontmatterField,
+  splitFrontmatter,
+  yamlScalar,
+} from './index.js';
+
+test('yamlScalar: 改行・コロン・先頭記号を含む値をクォートする', () => {
+  assert.equal(yamlScalar('334AC0000000121'), '334AC0000000121');
+  assert.equal(yamlScalar('https://laws.e-gov.go.jp/law/334AC0000000121'), 'https://laws.e-gov.go.jp/law/334AC0000000121');
+  assert.equal(yamlScalar('法令: 改正\n附則'), '"法令: 改正\\n附則"');
+  assert.equal(yamlScalar('- 箇条'), '"- 箇条"');
+  assert.equal(yamlScalar('a "b" \\ c'), '"a \\"b\\" \\\\ c"');
+  assert.equal(yamlScalar('123'), '"123"');
+  assert.equal(yamlScalar('true'), '"true"');
+  // 指数・16進・8進・無限大・非数の表記も、YAMLでは数値として読まれる。
+  for (const numeric of ['1e5', '1E+5', '0x1F', '0o17', '0b101', '1_000', '.inf', '-.Inf', '.NaN']) {
+    assert.equal(yamlScalar(numeric), `"${numeric}"`);
+  }
+  assert.equal(yamlScalar('1e5a'), '1e5a');
+  assert.equal(yamlScalar(3), '3');
+});
+
+test('renderFrontmatter: 定義順に出力し、未設定キーと空配列を省略する', () => {
+  assert.deepEqual(
+    renderFrontmatter({
+      law_id: '334AC0000000121',
+      title: '特許法',
+      law_num: undefined,
+      aliases: [],
+      source_url: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+      fetched_at: '2026-10-16T00:00:00.000Z',
+      depth: 0,
+    }),
+    [
+      '---',
+      'law_id: 334AC0000000121',
+      'title: "特許法"',
+      'source_url: https://laws.e-gov.go.jp/law/334AC0000000121',
+      'fetched_at: 2026-10-16T00:00:00.000Z',
+      'depth: 0',
+      '---',
+    ],
+  );
+});
+
+test('renderMarkdownForTest: frontmatterの出力が従来と同じ', () => {
+  const markdown = renderMarkdownForTest({
+    lawId: '334AC0000000121',
+    title: '特許法',
+    lawNum: '昭和三十四年法律第百二十一号',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_1-At_1',
+        heading: '第一条',
+        paragraphs: [{ anchor: 'Mp-Ch_1-At_1-p1', segments: [{ type: 'text', text: '目的' }] }],
+      },
+    ],
+  });
+  const frontmatter = markdown.slice(0, markdown.indexOf('\n---\n') + 5).replace(/^fetched_at: .+$/m, 'fetched_at: <now>');
+  assert.equal(
+    frontmatter,
+    [
+      '---',
+      'law_id: 334AC0000000121',
+      'title: "特許法"',
+      'law_num: "昭和三十四年法律第百二十一号"',
+      'source_url: https://laws.e-gov.go.jp/law/334AC0000000121',
+      'fetched_at: <now>',
+      'depth: 0',
+      'article_count: 1',
+      'paragraph_count: 1',
+      'item_count: 0',
+      'char_count: 2',
+      'external_ref_count: 0',
+      'appendix_count: 0',
+      '---',
+      '',
+    ].join('\n'),
+  );
+});
+
+test('splitFrontmatter: 自前で出力したfrontmatterを値の型ごと読み戻す', () => {
+  const markdown = [
+    '---',
+    'law_id: 334AC0000000121',
+    ...renderFrontmatterField('title', '特許法: 改正 "附則"'),
+    'depth: 2',
+    'stub: true',
+    ...renderFrontmatterField('aliases', ['特許', '123']),
+    ...renderFrontmatterField('referenced_by', { '民法': ['第一条'], 'a: b': ['第二条', '第三条'] }),
+    "note: 'it''s' # コメント",
+    'empty:',
+    '---',
+    '',
+    '本文',
+  ].join('\n');
+  const { frontmatter, body } = splitFrontmatter(markdown);
+  assert.deepEqual(frontmatter, {
+    law_id: '334AC0000000121',
+    title: '特許法: 改正 "附則"',
+    depth: 2,
+    stub: true,
+    aliases: ['特許', '123'],
+    referenced_by: { 民法: ['第一条'], 'a: b': ['第二条', '第三条'] },
+    note: "it's",
+  });
+  assert.equal(body, '\n本文');
+});
+
+test('splitFrontmatter: 区切りの無いノートや閉じ区切りの無いノートは全体を本文として返す', () => {
+  assert.deepEqual(splitFrontmatter('# 見出し\n本文\n'), { lines: [], body: '# 見出し\n本文\n', eol: '\n', bom: false });
+  const unclosed = '---\n\n本文の水平線の下\n';
+  assert.equal(splitFrontmatter(unclosed).frontmatter, undefined);
+  assert.equal(splitFrontmatter(unclosed).body, unclosed);
+  // 本文中の `---` は閉じ区切りより後にあるので区切りとみなさない。
+  const parts = splitFrontmatter('---\nlaw_id: X1\n---\n本文\n\n---\n\n後半\n');
+  assert.deepEqual(parts.frontmatter, { law_id: 'X1' });
+  assert.equal(parts.body, '本文\n\n---\n\n後半\n');
+  // `---a` のような行は区切りではない。
+  assert.equal(splitFrontmatter('---\nlaw_id: X1\n----\n').frontmatter, undefined);
+});
+
+test('splitFrontmatter: BOMとCRLF改行を許し、joinFrontmatter で元のとおり組み立て直す', () => {
+  const markdown = '\uFEFF---\r\nlaw_id: X1\r\ndepth: 1\r\n---\r\n本文\r\n';
+  const parts = splitFrontmatter(markdown);
+  assert.deepEqual(parts.frontmatter, { law_id: 'X1', depth: 1 });
+  assert.deepEqual(parts.lines, ['law_id: X1', 'depth: 1']);
+  assert.equal(parts.eol, '\r\n');
+  assert.equal(parts.bom, true);
+  assert.equal(joinFrontmatter(parts), markdown);
+  assert.equal(
+    setFrontmatterField(markdown, 'depth', ['depth: 0']),
+    '\uFEFF---\r\nlaw_id: X1\r\ndepth: 0\r\n---\r\n本文\r\n',
+  );
+});
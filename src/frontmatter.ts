@@ -0,0 +1,210 @@
+import type { FrontmatterValue, NoteFrontmatter } from './types.js';
+
+// 英数字で始まり空白や `#` を含まない値は、クォートなしでも文字列として読まれる。
+// ただし数値・真偽値・nullと解釈される値は型が変わるためクォートする。
+// 数値は `1e5`・`0x1F`・`0o17` などの表記もあるため、`Number()` が数値として読める値もクォートする。
+const PLAIN_SCALAR_PATTERN = /^[A-Za-z0-9][A-Za-z0-9_.:\/+-]*$/;
+const NON_STRING_SCALAR_PATTERN = /^(?:true|false|yes|no|on|off|null|[0-9._+-]+|[-+]?\.(?:inf|nan))$/i;
+
+function isNonStringScalar(value: string): boolean {
+  return NON_STRING_SCALAR_PATTERN.test(value) || !Number.isNaN(Number(value));
+}
+
+/**
+ * YAMLのスカラー値を出力する。
+ * 法令名などの自由文字列は改行・コロン・先頭の記号を含み得るため、ダブルクォートでエスケープする。
+ */
+export function yamlScalar(value: string | number | boolean): string {
+  if (typeof value !== 'string') {
+    return String(value);
+  }
+  // JSON文字列はYAMLのダブルクォート文字列としてそのまま解釈できる。
+  return PLAIN_SCALAR_PATTERN.test(value) && !isNonStringScalar(value) ? value : JSON.stringify(value);
+}
+
+/**
+ * frontmatterの1キー分の行（キー行＋インデントされた値行）を生成する。
+ * 空配列・空マップはキーごと出力しない。
+ */
+export function renderFrontmatterField(key: string, value: FrontmatterValue | undefined): string[] {
+  if (value === undefined) {
+    return [];
+  }
+  if (Array.isArray(value)) {
+    return value.length > 0 ? [`${key}:`, ...value.map((item) => `  - ${yamlScalar(item)}`)] : [];
+  }
+  if (typeof value === 'object') {
+    const entries = Object.entries(value);
+    if (entries.length === 0) {
+      return [];
+    }
+    const lines = [`${key}:`];
+    for (const [childKey, items] of entries) {
+      lines.push(`  ${yamlScalar(childKey)}:`);
+      lines.push(...items.map((item) => `    - ${yamlScalar(item)}`));
+    }
+    return lines;
+  }
+  return [`${key}: ${yamlScalar(value)}`];
+}
+
+/**
+ * ノートのfrontmatterを `---` 区切りで出力する。
+ * キーはオブジェクトの定義順に出し、未設定（undefined）のキーは省略する。
+ */
+export function renderFrontmatter(frontmatter: NoteFrontmatter): string[] {
+  const lines = ['---'];
+  for (const [key, value] of Object.entries(frontmatter)) {
+    lines.push(...renderFrontmatterField(key, value as FrontmatterValue | undefined));
+  }
+  lines.push('---');
+  return lines;
+}
+
+const FRONTMATTER_OPEN_PATTERN = /^---[ \t]*$/;
+// YAMLの文書終端 `...` でも閉じられる。本文中の `---`（水平線）は閉じ区切りより後にあるので区切りとみなさない。
+const FRONTMATTER_CLOSE_PATTERN = /^(?:---|\.\.\.)[ \t]*$/;
+const FRONTMATTER_KEY_PATTERN = /^("(?:[^"\\]|\\.)*"|'(?:[^']|'')*'|[^\s:#'"][^:]*?):(?:[ \t]+(.*))?$/;
+
+/**
+ * ノートをfrontmatterと本文に分けた結果。
+ * `frontmatter` は区切りが揃っているときだけ設定し、`lines` には区切りの間の行を改行コードを除いてそのまま持つ。
+ */
+export interface FrontmatterParts {
+  frontmatter?: Record<string, FrontmatterValue>;
+  lines: string[];
+  body: string;
+  eol: '\n' | '\r\n';
+  bom: boolean;
+}
+
+function parseYamlScalar(text: string): string | number | boolean {
+  const trimmed = text.trim();
+  const doubleQuoted = trimmed.match(/^"(?:[^"\\]|\\.)*"/)?.[0];
+  if (doubleQuoted) {
+    // 自前の出力はJSON文字列なので、まずJSONとして読む。
+    try {
+      return JSON.parse(doubleQuoted) as string;
+    } catch {
+      return doubleQuoted.slice(1, -1);
+    }
+  }
+  const singleQuoted = trimmed.match(/^'((?:[^']|'')*)'/)?.[1];
+  if (singleQuoted !== undefined) {
+    return singleQuoted.replace(/''/g, "'");
+  }
+  const plain = trimmed.replace(/\s+#.*$/, '');
+  if (/^(?:true|false)$/i.test(plain)) {
+    return plain.toLowerCase() === 'true';
+  }
+  if (/^[-+]?\d+(?:\.\d+)?$/.test(plain)) {
+    return Number(plain);
+  }
+  return plain;
+}
+
+function parseYamlItems(text: string): string[] {
+  const trimmed = text.trim();
+  // `[a, b]` のフロー形式はObsidianで手編集されたノートにあり得るため、単純な形だけ読む。
+  const inner = trimmed.startsWith('[') && trimmed.endsWith(']') ? trimmed.slice(1, -1) : undefined;
+  if (inner === undefined) {
+    return [String(parseYamlScalar(trimmed))];
+  }
+  return inner.trim() ? inner.split(',').map((item) => String(parseYamlScalar(item))) : [];
+}
+
+/**
+ * frontmatterの行をキーと値に読む。このツールが出力する形（スカラー・`- item` のリスト・リストのマップ）だけを対象にし、
+ * 読めない行は飛ばす。値の無いキー（YAMLのnull）は結果に含めない。
+ */
+export function parseFrontmatterLines(lines: string[]): Record<string, FrontmatterValue> {
+  const result: Record<string, FrontmatterValue> = {};
+  let currentKey: string | undefined;
+  let childKey: string | undefined;
+  for (const line of lines) {
+    if (!line.trim() || line.trimStart().startsWith('#')) {
+      continue;
+    }
+    const indented = /^\s/.test(line);
+    if (!indented) {
+      const matched = line.match(FRONTMATTER_KEY_PATTERN);
+      currentKey = matched ? String(parseYamlScalar(matched[1])) : undefined;
+      childKey = undefined;
+      if (currentKey !== undefined && matched?.[2]?.trim()) {
+        const value = matched[2].trim();
+        result[currentKey] = value.startsWith('[') ? parseYamlItems(value) : parseYamlScalar(value);
+      }
+      continue;
+    }
+    if (currentKey === undefined) {
+      continue;
+    }
+    const item = line.match(/^\s+-(?:[ \t]+(.*))?$/);
+    const current = result[currentKey];
+    if (item) {
+      const value = String(parseYamlScalar(item[1] ?? ''));
+      if (childKey !== undefined && current && typeof current === 'object' && !Array.isArray(current)) {
+        current[childKey].push(value);
+      } else if (childKey === undefined && (current === undefined || Array.isArray(current))) {
+        result[currentKey] = [...(current ?? []), value];
+      }
+      continue;
+    }
+    const child = line.trim().match(FRONTMATTER_KEY_PATTERN);
+    if (child && (current === undefined || (typeof current === 'object' && !Array.isArray(current)))) {
+      const map = current ?? {};
+      childKey = String(parseYamlScalar(child[1]));
+      map[childKey] = child[2]?.trim() ? parseYamlItems(child[2]) : [];
+      result[currentKey] = map;
+    }
+  }
+  return result;
+}
+
+/**
+ * ノートの内容をfrontmatterと本文に分ける。先頭のBOMとCRLF改行を許し、閉じ区切りの無いノートはfrontmatter無しとして
+ * 全体を本文に返す。再処理系の機能はこれを使い、`joinFrontmatter` で元の改行コードのまま組み立て直す。
+ */
+export function splitFrontmatter(content: string): FrontmatterParts {
+  const bom = content.startsWith('\uFEFF');
+  const text = bom ? content.slice(1) : content;
+  const firstBreak = text.indexOf('\n');
+  const eol = firstBreak > 0 && text[firstBreak - 1] === '\r' ? '\r\n' : '\n';
+  const withoutFrontmatter: FrontmatterParts = { lines: [], body: text, eol, bom };
+  if (firstBreak < 0 || !FRONTMATTER_OPEN_PATTERN.test(text.slice(0, firstBreak).replace(/\r$/, ''))) {
+    return withoutFrontmatter;
+  }
+  const lines: string[] = [];
+  let position = firstBreak + 1;
+  while (position <= text.length) {
+    const next = text.indexOf('\n', position);
+    const line = text.slice(position, next >= 0 ? next : text.length).replace(/\r$/, '');
+    if (FRONTMATTER_CLOSE_PATTERN.test(line)) {
+      return {
+        frontmatter: parseFrontmatterLines(lines),
+        lines,
+        body: next >= 0 ? text.slice(next + 1) : '',
+        eol,
+        bom,
+      };
+    }
+    if (next < 0) {
+      break;
+    }
+    lines.push(line);
+    position = next + 1;
+  }
+  // 閉じ区切りが無ければ、先頭の `---` は本文の水平線とみなす。
+  return withoutFrontmatter;
+}
+
+/**
+ * `splitFrontmatter` の結果をノートの内容に戻す。`lines` を書き換えていればその内容で区切りの間を出力する。
+ */
+export function joinFrontmatter(parts: FrontmatterParts): string {
+  const bom = parts.bom ? '\uFEFF' : '';
+  if (!parts.frontmatter) {
+    return `${bom}${parts.body}`;
+  }
+  return `${bom}${['---', ...parts.lines, '---'].join(parts.eol)}${parts.eol}${parts.body}`;
+}
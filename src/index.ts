@@ -1,38 +1,406 @@
+import process from 'node:process';
 import { parseArgs } from './args.js';
-import { buildDictionary, fetchLawTitleById, resolveLawIdByTitle } from './api.js';
-import { processLawGraph } from './process.js';
-import { loadDictionary, mergeUnresolvedRecords } from './storage.js';
+export { defaultCliOptions, parseArgs } from './args.js';
+export {
+  ApiResponseShapeError,
+  compareByPromulgation,
+  describeJsonShape,
+  fetchJson,
+  fetchLawContents,
+  getApiProfile,
+  isRepealedStatus,
+  lawTitleFallbackQueries,
+  parseLawDataResponse,
+  parseLawsPage,
+  parseLawSuccessor,
+  searchLawCandidates,
+  searchLawCandidatesByLawNum,
+  searchLawCandidatesWithFallback,
+  selectBestCandidate,
+  selectCandidateNonInteractive,
+} from './api.js';
+import {
+  AmbiguousLawTitleError,
+  buildDictionary,
+  fetchLawTitleById,
+  resolveLawIdByTitle,
+  searchLawCandidates,
+  searchLawCandidatesByLawNum,
+} from './api.js';
+import { findCandidateChoice } from './choices.js';
+import { acceptDictionaryEntry, findLawIdByAlias, importDictionary, verifyDictionary } from './dictionary.js';
+import { resolvePendingUnresolvedRefs, resolveUnresolvedRef } from './unresolved.js';
+import { findLawIdByLawNum, normalizeLawNum } from './lawnum.js';
+import {
+  processAllCandidates,
+  processLawGraph,
+  parseTitleList,
+  processTitleFilteredLaws,
+  processTitleList,
+  retryFailedLaws,
+} from './process.js';
+import {
+  isSearchCacheFresh,
+  loadDictionaries,
+  loadDictionary,
+  mergeUnresolvedRecords,
+  recoverUnresolvedLog,
+  writeJson,
+} from './storage.js';
+import { runRepl } from './repl.js';
+import { exportNotes } from './export.js';
+import { buildRunSummary, formatApiStats, formatSlowestLaws, getApiStats, getRunStats } from './stats.js';
+import type { CliOptions } from './types.js';
+import { getThrottleWaitTotalMs, installCancelHandler } from './utils.js';
 
-export { extractLawDocumentFromPage } from './scrape.js';
-export { renderMarkdownForTest, parseLawIdFromHref } from './render.js';
+export { extractLawDocumentFromPage, formatUnsupportedElements, loadLawDocument } from './scrape.js';
+export { collectNode, ELEMENT_COLLECTORS, serializeCollectors } from './collectors.js';
+export { getLawPageUrl, isCancelRequested, setCancelRequested } from './utils.js';
+export {
+  collectCitedLaws,
+  collectReferencedLawIds,
+  computeLawStats,
+  countStructures,
+  countTags,
+  extractBacklinkLines,
+  figureEmbed,
+  heading,
+  linkAnchor,
+  mergeBacklinksSection,
+  paragraphBlockId,
+  parseLawIdFromHref,
+  renderBacklinksSection,
+  renderCitationSection,
+  renderCitedRevisionsField,
+  renderMarkdown,
+  renderMarkdownForTest,
+  renderMarkdownTable,
+  renderReferencedArticleFields,
+  renderReferencedByFields,
+  renderSummaryCallout,
+  renderSupersedesField,
+  replaceBacklinksSection,
+  replaceCitationSection,
+  replaceSummaryCallout,
+  toCalloutLines,
+  uniqueArticleHeadings,
+} from './render.js';
 export {
   buildExistingNoteIndex,
+  buildLawNumIndex,
+  classifyLawType,
+  displayTitle,
+  getFileName,
+  noteLinkTarget,
+  parseLawIdFromNoteFileName,
   resolveExistingNotePath,
+  resolveNoteFileName,
+  resolveNoteRelativePath,
   scanReferencedLawIdsFromMarkdown,
+  setFrontmatterField,
   toSafeTitle,
+  updateFrontmatterDepth,
 } from './notes.js';
-export { mergeUnresolvedRecords };
+export {
+  candidateGraphRoots,
+  fetchLawMetadata,
+  parseTitleList,
+  processLawGraphRoots,
+  recordVisit,
+  requeueFailedLaws,
+  takeQueueItem,
+  unfinishedQueueRecords,
+} from './process.js';
+export {
+  acceptDictionaryEntry,
+  addDictionaryAlias,
+  applyDictionaryFixes,
+  checkDictionaryEntry,
+  findDictionaryNameIssues,
+  findLawIdByAlias,
+  findLawsByTitle,
+  mergeDictionaries,
+} from './dictionary.js';
+export { decodeMessagePack, encodeMessagePack, startsWithMessagePackMap } from './msgpack.js';
+export { decodeDictionaryContent, resolveDictFormat } from './storage.js';
+export { stripVolatileFrontmatter, unifiedDiff } from './diff.js';
+export { diffArticles, prependChangelogEntry, splitArticleSections } from './changelog.js';
+export { findMissingLinks, isStubNote, renderStubNote, unlinkMissingLinks } from './links.js';
+export { parseReplCommand } from './repl.js';
+export {
+  apiEndpointName,
+  buildRunSummary,
+  countRunStat,
+  formatApiStats,
+  formatLawTiming,
+  formatSlowestLaws,
+  getApiStats,
+  getRunStats,
+  recordApiRequest,
+  recordCacheLookup,
+  recordLawTiming,
+  recordRunFailure,
+  resetApiStats,
+  resetRunStats,
+} from './stats.js';
+export {
+  joinFrontmatter,
+  parseFrontmatterLines,
+  renderFrontmatter,
+  renderFrontmatterField,
+  splitFrontmatter,
+  yamlScalar,
+} from './frontmatter.js';
+export {
+  anchorArticleLabel,
+  articleHeading,
+  articleLabel,
+  parseProvisionId,
+  provisionNumber,
+  recentProvisionOf,
+  splitSameLawRefs,
+  splitSameProvisionRefs,
+  stripLeadingArticleTitle,
+} from './provision.js';
+export { resolveSectionNote, sectionNotePath, splitLawSections, topLevelDivision } from './split.js';
+export { buildSupplProvisionIndex, lawIdFromLawNum, splitSupplProvisionRefs } from './suppl.js';
+export {
+  classifyRefKind,
+  defaultRefPolicies,
+  isBlockInRefScope,
+  isPlausibleLawName,
+  parseRefPolicy,
+  refLawName,
+  refPolicyFor,
+} from './refpolicy.js';
+export { LINKIFY_KINDS, linkifyKindForAnchor, parseLinkifyRules } from './linkify.js';
+export { canonicalizeKanji } from './kanji.js';
+export { candidateChoiceKey, findCandidateChoice, recordCandidateChoice } from './choices.js';
+export { buildZip, concatNotes } from './export.js';
+export { buildProvisionTree, noteFileForFormat, renderHtml, renderPlainText } from './formats.js';
+export { findReferenceCycles } from './cycles.js';
+export { openLawIndex } from './indexdb.js';
+export { promulgationYear, renderTimelineNote, TIMELINE_NOTE_NAME } from './timeline.js';
+export { renderMocNote, writeMocNote } from './moc.js';
+export { collectUnparsedRefTexts, rememberInvalidRefNames, resolveRefTexts } from './refsearch.js';
+export {
+  applyPartialRange,
+  isAnchorOutsidePartialRange,
+  isBlockInPartialRange,
+  parsePartialRange,
+} from './partial.js';
+export {
+  canonicalLawNum,
+  extractYearFromLawId,
+  findLawIdByLawNum,
+  formatLawNum,
+  formatLawNumShort,
+  normalizeLawNum,
+  parseJapaneseNumber,
+  toKanjiNumber,
+} from './lawnum.js';
+export {
+  appendFailedLaws,
+  appendUnresolved,
+  appendUnresolvedLog,
+  clearUnresolvedLog,
+  dictionaryEntryConfidence,
+  excludeInheritedEntries,
+  dictionaryProvenance,
+  decodeNoteText,
+  encodeNoteText,
+  FileLockTimeoutError,
+  findDictionaryKeyConflicts,
+  loadFailedLaws,
+  loadLawDataArchive,
+  mergeDictionaryForSave,
+  parseDictionaryFile,
+  readNoteText,
+  recoverUnresolvedLog,
+  reportDictionaryKeyConflicts,
+  saveDictionary,
+  saveLawDataArchive,
+  unresolvedLogPath,
+  saveLawPageArchive,
+  withFileLock,
+  writeFileAtomic,
+} from './storage.js';
+export {
+  relinkRefText,
+  resolvePendingUnresolvedRefs,
+  retryPendingRefs,
+  updateUnresolvedStatus,
+} from './unresolved.js';
+export { isSearchCacheFresh, loadDictionaries, loadDictionary, mergeUnresolvedRecords };
 
 function isFallbackTitle(title: string): boolean {
   return /^law_[A-Za-z0-9]+$/.test(title);
 }
 
+/**
+ * CLIのメイン処理が例外で終わったときの終了コードを返す。候補が曖昧なときは `2`、それ以外は `1`。
+ */
+export function exitCodeForError(error: unknown): number {
+  return error instanceof AmbiguousLawTitleError ? error.exitCode : 1;
+}
+
+async function writeRunSummary(summaryJsonPath: string, startedAt: number, error?: unknown): Promise<void> {
+  const exitCode = error !== undefined ? exitCodeForError(error) : Number(process.exitCode ?? 0);
+  try {
+    await writeJson(
+      summaryJsonPath,
+      buildRunSummary(getRunStats(), getApiStats(), getThrottleWaitTotalMs(), startedAt, Date.now(), exitCode, error),
+    );
+  } catch (writeError) {
+    // サマリを書けなくても本来の処理結果（終了コード）は変えない。
+    const message = writeError instanceof Error ? writeError.message : String(writeError);
+    process.stderr.write(`警告: 実行サマリを書き出せませんでした: ${summaryJsonPath}: ${message}\n`);
+  }
+}
+
 /**
  * CLIのメイン処理を実行する。
+ * 失敗で終わった実行でもe-Govへの負荷を把握できるよう、API統計とリクエスト間隔の待機時間は終了時に必ず標準エラーへ出す。
+ * `--summary-json` があれば、人向けの出力とは別に機械可読の実行サマリも必ず書き出す。
  */
 export async function runCli(argv: string[]): Promise<void> {
   const options = parseArgs(argv);
+  const startedAt = Date.now();
+  // `--repl` の Ctrl+C は対話入力（readline）が受け取るため、中断要求として扱わない。
+  const removeCancelHandler = options.repl ? undefined : installCancelHandler();
+  let failure: unknown;
+  try {
+    await runCommand(options);
+  } catch (error) {
+    failure = error;
+    throw error;
+  } finally {
+    removeCancelHandler?.();
+    if (options.summaryJsonPath) {
+      await writeRunSummary(options.summaryJsonPath, startedAt, failure);
+    }
+    const stats = getApiStats();
+    const summary = formatApiStats(stats, Date.now() - startedAt);
+    if (summary) {
+      process.stderr.write(`${summary}\n`);
+    }
+    if (options.requestIntervalMs > 0) {
+      process.stderr.write(`リクエスト間隔待機: 合計 ${getThrottleWaitTotalMs()}ms\n`);
+    }
+    const slowest = formatSlowestLaws(stats, options.slowestLaws);
+    if (slowest) {
+      process.stderr.write(`${slowest}\n`);
+    }
+  }
+}
+
+async function runCommand(options: CliOptions): Promise<void> {
 
   if (options.buildDictionary) {
     await buildDictionary(options);
     return;
   }
+  if (options.importDictionaryPath) {
+    await importDictionary(options);
+    return;
+  }
+  if (options.resolveRef) {
+    await resolveUnresolvedRef(options);
+    return;
+  }
+  if (options.verifyDictionary) {
+    await verifyDictionary(options);
+    return;
+  }
+  if (options.exportFormat) {
+    await exportNotes(options);
+    return;
+  }
 
-  const dictionary = await loadDictionary(options.dictionaryPath);
+  const dictionary = await loadDictionaries(options.dictionaryPaths, options);
+  if (!options.dryRun) {
+    // `--flush-each` の実行が途中で終わっていれば、ログに残った未解決参照を先に取り込む。
+    const recovered = await recoverUnresolvedLog(options.unresolvedPath, options.lockTimeoutMs);
+    if (recovered > 0) {
+      process.stderr.write(`前回の実行で保存しきれなかった未解決参照を取り込みました: ${recovered}件\n`);
+    }
+  }
+  if (options.resolveUnresolved) {
+    await resolvePendingUnresolvedRefs(options, dictionary);
+    return;
+  }
+  if (options.repl) {
+    await runRepl(options, dictionary);
+    return;
+  }
+  if (options.retryFailed) {
+    await retryFailedLaws(options, dictionary);
+    return;
+  }
+  if (options.titleFilter !== undefined) {
+    await processTitleFilteredLaws(options, dictionary);
+    return;
+  }
+  if (options.titlesFromStdin) {
+    const chunks: Buffer[] = [];
+    for await (const chunk of process.stdin) {
+      chunks.push(chunk as Buffer);
+    }
+    await processTitleList(options, dictionary, parseTitleList(Buffer.concat(chunks).toString('utf8')));
+    return;
+  }
 
   let rootLawId = options.lawId;
   let rootTitle = options.lawTitle;
 
+  if (!rootLawId && rootTitle) {
+    // 以前に選んだ法令（選択履歴）があれば、曖昧な法令名でも候補一覧で止めず、辞書や検索より優先して使う。
+    const chosen = await findCandidateChoice(options, rootTitle);
+    if (chosen) {
+      rootLawId = chosen.law_id;
+      rootTitle = chosen.law_title;
+    }
+  }
+
+  if (!rootLawId && rootTitle) {
+    // 検索し直した候補から以前に確定した入力は辞書に別名として残っているため、検索し直さずにその法令を使う。
+    rootLawId = findLawIdByAlias(dictionary, rootTitle, (lawId, entry) => acceptDictionaryEntry(options, lawId, entry));
+    if (rootLawId) {
+      rootTitle = dictionary[rootLawId].title;
+    }
+  }
+
+  if (!rootLawId && rootTitle && normalizeLawNum(rootTitle)) {
+    // 法令番号で指定された場合は、表記ゆれを吸収して辞書から引き、無ければ法令一覧APIを法令番号で引く。
+    // どちらでも1件に決まらなければ法令名検索に回す。
+    rootLawId = findLawIdByLawNum(dictionary, rootTitle, (lawId, entry) =>
+      acceptDictionaryEntry(options, lawId, entry),
+    );
+    if (rootLawId) {
+      rootTitle = dictionary[rootLawId].title;
+    } else {
+      const candidates = (await searchLawCandidatesByLawNum(options, rootTitle)).filter(
+        (candidate) => candidate.law_id,
+      );
+      if (candidates.length === 1) {
+        rootLawId = candidates[0].law_id;
+        rootTitle = candidates[0].law_title;
+      }
+    }
+  }
+
+  if (!rootLawId && rootTitle && options.fetchAllCandidates) {
+    const candidates = (await searchLawCandidates(options, rootTitle)).filter((candidate) => candidate.law_id);
+    if (candidates.length > 1) {
+      await processAllCandidates(options, candidates, dictionary);
+      return;
+    }
+    if (candidates.length === 1) {
+      rootLawId = candidates[0].law_id;
+      rootTitle = candidates[0].law_title;
+    }
+  }
+
   if (!rootLawId && rootTitle) {
     const resolved = await resolveLawIdByTitle(options, rootTitle);
     if (!resolved.law_id) {
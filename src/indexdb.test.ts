@@ -0,0 +1,49 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+
+import { openLawIndex } from './index.js';
+
+// node:sqlite の無い Node.js では `--index-db` 自体が使えないため、テストも飛ばす。
+const sqlite = await import('node:sqlite').catch(() => undefined);
+
+const record = (lawId: string, title: string, depth: number) => ({
+  law_id: lawId,
+  title,
+  depth,
+  note_path: `laws/${title}_${lawId}.md`,
+  updated_at: '2026-01-01T00:00:00.000Z',
+});
+
+test('openLawIndex: 再実行しても法令と参照を重複させず、参照元ごとに参照を入れ替える', { skip: !sqlite }, async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-indexdb-'));
+  const dbPath = path.join(tmp, 'index', 'laws.sqlite');
+
+  let index = await openLawIndex(dbPath);
+  index.upsertLaw(record('334AC0000000121', '特許法', 0), ['129AC0000000089', '132AC0000000048', '129AC0000000089']);
+  index.upsertLaw(record('129AC0000000089', '民法', 1), []);
+  index.close();
+
+  index = await openLawIndex(dbPath);
+  index.upsertLaw({ ...record('334AC0000000121', '特許法', 0), law_num: '昭和三十四年法律第百二十一号' }, [
+    '129AC0000000089',
+    '334AC0000000121',
+  ]);
+  index.updateDepth('129AC0000000089', 0);
+  index.close();
+
+  const db = new sqlite!.DatabaseSync(dbPath);
+  // node:sqlite の行はプロトタイプを持たないため、比較用に通常のオブジェクトへ写す。
+  const rows = (sql: string) => db.prepare(sql).all().map((row) => ({ ...row }));
+  assert.deepEqual(rows('SELECT law_id, law_num, depth FROM laws ORDER BY law_id'), [
+    { law_id: '129AC0000000089', law_num: null, depth: 0 },
+    { law_id: '334AC0000000121', law_num: '昭和三十四年法律第百二十一号', depth: 0 },
+  ]);
+  // 再取得で減った参照は消え、自分自身への参照は記録しない。
+  assert.deepEqual(rows('SELECT source_law_id, target_law_id FROM "references"'), [
+    { source_law_id: '334AC0000000121', target_law_id: '129AC0000000089' },
+  ]);
+  db.close();
+});
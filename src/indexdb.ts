@@ -0,0 +1,99 @@
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import type { DatabaseSync } from 'node:sqlite';
+import type { LawIndexRecord } from './types.js';
+
+// `references` はSQLの予約語なので、テーブル名は常に引用符で囲む。
+const SCHEMA = `
+CREATE TABLE IF NOT EXISTS laws (
+  law_id TEXT PRIMARY KEY,
+  title TEXT NOT NULL,
+  law_num TEXT,
+  promulgation_date TEXT,
+  law_type TEXT,
+  depth INTEGER,
+  note_path TEXT,
+  updated_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS "references" (
+  source_law_id TEXT NOT NULL,
+  target_law_id TEXT NOT NULL,
+  PRIMARY KEY (source_law_id, target_law_id)
+);
+CREATE INDEX IF NOT EXISTS references_target ON "references" (target_law_id);
+`;
+
+/**
+ * `--index-db` のSQLiteインデックス。取得した法令のメタ情報と参照関係を、ノートを書き込むたびに書き足す。
+ */
+export interface LawIndex {
+  upsertLaw(record: LawIndexRecord, targetLawIds: string[]): void;
+  updateDepth(lawId: string, depth: number): void;
+  close(): void;
+}
+
+/**
+ * SQLiteインデックスを開き、無ければテーブルを作る。依存ライブラリを増やさないよう Node.js 組み込みの `node:sqlite` を使い、
+ * `--index-db` を指定したときにだけ読み込む。対応していない Node.js ではエラーにする。
+ */
+export async function openLawIndex(dbPath: string): Promise<LawIndex> {
+  let sqlite: typeof import('node:sqlite');
+  try {
+    sqlite = await import('node:sqlite');
+  } catch {
+    throw new Error(`--index-db には node:sqlite を使える Node.js（22.13以降）が必要です（現在: ${process.version}）`);
+  }
+  await fs.mkdir(path.dirname(dbPath), { recursive: true });
+  const db: DatabaseSync = new sqlite.DatabaseSync(dbPath);
+  db.exec(SCHEMA);
+  const upsertLaw = db.prepare(`
+    INSERT INTO laws (law_id, title, law_num, promulgation_date, law_type, depth, note_path, updated_at)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+    ON CONFLICT (law_id) DO UPDATE SET
+      title = excluded.title,
+      law_num = excluded.law_num,
+      promulgation_date = excluded.promulgation_date,
+      law_type = excluded.law_type,
+      depth = excluded.depth,
+      note_path = excluded.note_path,
+      updated_at = excluded.updated_at
+  `);
+  const deleteReferences = db.prepare('DELETE FROM "references" WHERE source_law_id = ?');
+  const insertReference = db.prepare('INSERT OR IGNORE INTO "references" (source_law_id, target_law_id) VALUES (?, ?)');
+  const updateDepth = db.prepare('UPDATE laws SET depth = ? WHERE law_id = ?');
+
+  return {
+    upsertLaw(record, targetLawIds) {
+      // 再取得で参照が減った場合に古い参照が残らないよう、参照元ごとに入れ替える。
+      db.exec('BEGIN');
+      try {
+        upsertLaw.run(
+          record.law_id,
+          record.title,
+          record.law_num ?? null,
+          record.promulgation_date ?? null,
+          record.law_type ?? null,
+          record.depth,
+          record.note_path,
+          record.updated_at,
+        );
+        deleteReferences.run(record.law_id);
+        for (const targetLawId of new Set(targetLawIds)) {
+          if (targetLawId !== record.law_id) {
+            insertReference.run(record.law_id, targetLawId);
+          }
+        }
+        db.exec('COMMIT');
+      } catch (error) {
+        db.exec('ROLLBACK');
+        throw error;
+      }
+    },
+    updateDepth(lawId, depth) {
+      updateDepth.run(depth, lawId);
+    },
+    close() {
+      db.close();
+    },
+  };
+}
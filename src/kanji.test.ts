@@ -0,0 +1,49 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { canonicalizeKanji, findDictionaryNameIssues, selectCandidateNonInteractive } from './index.js';
+
+test('canonicalizeKanji: 旧字体・異体字・踊り字・全角英数字の表記ゆれを照合用にそろえる', () => {
+  const cases: Array<[string, string]> = [
+    ['國會法', '国会法'],
+    ['附則', '付則'],
+    ['各々の', '各各の'],
+    ['ＷＴＯ協定', 'WTO協定'],
+    ['醫師法', '医師法'],
+    ['鑛業法', '鉱業法'],
+  ];
+  for (const [text, normalized] of cases) {
+    assert.equal(canonicalizeKanji(text), canonicalizeKanji(normalized), text);
+  }
+  assert.equal(canonicalizeKanji('國會法'), '国会法');
+  // 先頭の踊り字は展開する字が無いためそのまま残す。
+  assert.equal(canonicalizeKanji('々'), '々');
+});
+
+test('selectCandidateNonInteractive: 表記ゆれのある法令名でも完全一致の候補を選ぶ', () => {
+  const candidates = [
+    { law_id: '322AC0000000079', law_title: '国会法' },
+    { law_id: '322AC0000000080', law_title: '国会職員法' },
+  ];
+  assert.equal(selectCandidateNonInteractive('國會法', candidates)?.law_id, '322AC0000000079');
+});
+
+test('findDictionaryNameIssues: 表記ゆれだけが違う法令名も重複として検出し、原文の法令名を示す', () => {
+  const entry = (title: string, lawId: string) => ({
+    title,
+    safe_title: title,
+    file_name: `${title}_${lawId}.md`,
+    updated_at: '2026-02-20T00:00:00Z',
+  });
+  const issues = findDictionaryNameIssues({
+    '322AC0000000079': entry('国会法', '322AC0000000079'),
+    '999AC0000000001': entry('國會法', '999AC0000000001'),
+  });
+  assert.deepEqual(
+    issues.map((issue) => [issue.lawId, issue.kind, issue.current]),
+    [
+      ['322AC0000000079', 'duplicate_title', '国会法'],
+      ['999AC0000000001', 'duplicate_title', '國會法'],
+    ],
+  );
+});
@@ -0,0 +1,80 @@
+// 法令名に現れやすい旧字体と異体字だけの最小の対応表。照合用なので、読みや意味が同じ字を1字に寄せられればよい。
+// 「附則」「付則」のように新字体どうしの書き分けも、照合では同じ字として扱う。
+const KANJI_VARIANTS: Record<string, string> = {
+  亞: '亜',
+  惡: '悪',
+  壓: '圧',
+  圍: '囲',
+  爲: '為',
+  醫: '医',
+  營: '営',
+  驛: '駅',
+  應: '応',
+  假: '仮',
+  價: '価',
+  畫: '画',
+  會: '会',
+  關: '関',
+  歸: '帰',
+  氣: '気',
+  舊: '旧',
+  擧: '挙',
+  區: '区',
+  經: '経',
+  縣: '県',
+  權: '権',
+  廣: '広',
+  鑛: '鉱',
+  號: '号',
+  國: '国',
+  濟: '済',
+  齒: '歯',
+  兒: '児',
+  實: '実',
+  寫: '写',
+  壽: '寿',
+  處: '処',
+  條: '条',
+  證: '証',
+  燒: '焼',
+  眞: '真',
+  稅: '税',
+  戰: '戦',
+  體: '体',
+  對: '対',
+  臺: '台',
+  團: '団',
+  廳: '庁',
+  鐵: '鉄',
+  傳: '伝',
+  轉: '転',
+  圖: '図',
+  當: '当',
+  黨: '党',
+  發: '発',
+  廢: '廃',
+  附: '付',
+  辨: '弁',
+  辯: '弁',
+  變: '変',
+  與: '与',
+  藥: '薬',
+  樂: '楽',
+  禮: '礼',
+  勞: '労',
+  學: '学',
+};
+const ITERATION_MARK = '々';
+
+/**
+ * 法令名を照合用のキーに正規化する。NFKC正規化のうえ、旧字体・異体字を新字体へ寄せ、踊り字（々）を直前の字に展開する。
+ * 可逆ではないため照合にだけ使い、表示やファイル名には原文の法令名を使う。
+ */
+export function canonicalizeKanji(text: string): string {
+  const chars: string[] = [];
+  for (const char of text.normalize('NFKC')) {
+    const previous = chars[chars.length - 1];
+    chars.push(char === ITERATION_MARK && previous ? previous : (KANJI_VARIANTS[char] ?? char));
+  }
+  return chars.join('');
+}
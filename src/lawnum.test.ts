@@ -0,0 +1,89 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  canonicalLawNum,
+  extractYearFromLawId,
+  findLawIdByLawNum,
+  formatLawNumShort,
+  normalizeLawNum,
+  parseJapaneseNumber,
+  toKanjiNumber,
+} from './index.js';
+
+test('parseJapaneseNumber: 位取り・桁並べ・算用数字・全角数字を数値にする', () => {
+  assert.equal(parseJapaneseNumber('百二十一'), 121);
+  assert.equal(parseJapaneseNumber('二十二'), 22);
+  assert.equal(parseJapaneseNumber('千九百'), 1900);
+  assert.equal(parseJapaneseNumber('一二一'), 121);
+  assert.equal(parseJapaneseNumber('67'), 67);
+  assert.equal(parseJapaneseNumber('６７'), 67);
+  assert.equal(parseJapaneseNumber('abc'), undefined);
+});
+
+test('normalizeLawNum: 表記ゆれのある法令番号を同じキーにする', () => {
+  const expected = normalizeLawNum('昭和二十二年法律第六十七号');
+  assert.deepEqual(expected, { era: '昭和', year: 22, lawType: '法律', number: 67, key: '昭和二十二年法律第六十七号' });
+  for (const variant of [
+    '昭和22年法律第67号',
+    '昭和２２年法律第６７号',
+    '（昭和二十二年法律第六十七号）',
+    '昭和 22 年法律第 67 号',
+    '昭22法67',
+  ]) {
+    assert.equal(normalizeLawNum(variant)?.key, expected?.key, variant);
+  }
+  assert.equal(normalizeLawNum('令和1年政令第1号')?.key, '令和元年政令第一号');
+  assert.equal(normalizeLawNum('平成十五年厚生労働省令第百二十号')?.lawType, '厚生労働省令');
+  assert.equal(normalizeLawNum('特許法'), undefined);
+});
+
+test('findLawIdByLawNum: 辞書の法令番号と表記が違っても一致させる', () => {
+  const dictionary = {
+    '334AC0000000121': {
+      title: '特許法',
+      safe_title: '特許法',
+      file_name: '特許法_334AC0000000121.md',
+      updated_at: '2026-02-20T00:00:00Z',
+      law_num: '昭和三十四年法律第百二十一号',
+    },
+  };
+  assert.equal(findLawIdByLawNum(dictionary, '昭和34年法律第121号'), '334AC0000000121');
+  assert.equal(findLawIdByLawNum(dictionary, '昭和34年法律第122号'), undefined);
+});
+
+test('normalizeLawNum: すべての元号と太政官布告などの例外形式を解釈する', () => {
+  const cases: Array<[string, string, string]> = [
+    ['明治二十九年法律第八十九号', '明治二十九年法律第八十九号', '明29法89'],
+    ['明治六年太政官布告第六十五号', '明治六年太政官布告第六十五号', '明6布65'],
+    ['大正11年勅令第113号', '大正十一年勅令第百十三号', '大11勅113'],
+    ['昭34法121', '昭和三十四年法律第百二十一号', '昭34法121'],
+    ['平成十一年法律第百二十五号', '平成十一年法律第百二十五号', '平11法125'],
+    ['令和五年政令第三百号', '令和五年政令第三百号', '令5政300'],
+    ['平成十五年厚生労働省令第百二十号', '平成十五年厚生労働省令第百二十号', '平15厚生労働省令120'],
+  ];
+  for (const [input, canonical, short] of cases) {
+    const parsed = normalizeLawNum(input);
+    assert.equal(parsed?.key, canonical, input);
+    assert.equal(parsed && formatLawNumShort(parsed), short, input);
+  }
+});
+
+test('canonicalLawNum: 解釈できない値は原文のまま返す', () => {
+  assert.equal(canonicalLawNum('昭和34年法律第121号'), '昭和三十四年法律第百二十一号');
+  assert.equal(canonicalLawNum('大日本帝国憲法'), '大日本帝国憲法');
+  assert.equal(toKanjiNumber(1010), '千十');
+});
+
+test('extractYearFromLawId: 元号コードと元号での年を西暦にし、読めない law_id は undefined にする', () => {
+  assert.equal(extractYearFromLawId('129AC0000000089'), 1896);
+  assert.equal(extractYearFromLawId('201AC0000000001'), 1912);
+  assert.equal(extractYearFromLawId('334AC0000000121'), 1959);
+  assert.equal(extractYearFromLawId('364AC0000000001'), 1989);
+  assert.equal(extractYearFromLawId('401AC0000000001'), 1989);
+  assert.equal(extractYearFromLawId('417AC0000000086'), 2005);
+  assert.equal(extractYearFromLawId('505AC0000000001'), 2023);
+  assert.equal(extractYearFromLawId('600AC0000000001'), undefined);
+  assert.equal(extractYearFromLawId('300AC0000000001'), undefined);
+  assert.equal(extractYearFromLawId('abc'), undefined);
+});
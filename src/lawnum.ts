@@ -0,0 +1,179 @@
+import type { LawDictionary, LawDictionaryEntry } from './types.js';
+
+/**
+ * 元号・年・法令種別・番号に分解した法令番号。
+ * `key` は表記ゆれ（漢数字・算用数字・全角数字・元年・短縮表記）を吸収した比較用の文字列で、
+ * 正規化表記（漢数字フル表記）と同じ値になる。
+ */
+export interface LawNum {
+  era: string;
+  year: number;
+  lawType: string;
+  number: number;
+  key: string;
+}
+
+const ERAS = ['明治', '大正', '昭和', '平成', '令和'];
+/** `ERAS` と同じ並びの、各元号の元年の西暦。law_id の先頭1桁は `ERAS` の並び順（明治=1〜令和=5）。 */
+const ERA_FIRST_YEARS = [1868, 1912, 1926, 1989, 2019];
+const ERA_ABBREVIATIONS: Record<string, string> = { 明: '明治', 大: '大正', 昭: '昭和', 平: '平成', 令: '令和' };
+/** 「昭34法121」形式の短縮表記で使われる法令種別の略記。 */
+const LAW_TYPE_ABBREVIATIONS: Record<string, string> = { 法: '法律', 政: '政令', 勅: '勅令', 布: '太政官布告' };
+const KANJI_DIGITS: Record<string, number> = {
+  〇: 0,
+  零: 0,
+  一: 1,
+  二: 2,
+  三: 3,
+  四: 4,
+  五: 5,
+  六: 6,
+  七: 7,
+  八: 8,
+  九: 9,
+};
+const KANJI_UNITS: Record<string, number> = { 十: 10, 百: 100, 千: 1000 };
+const NUMBER_CHARS = '0-9０-９〇零一二三四五六七八九十百千';
+const LAW_NUM_PATTERN = new RegExp(`^(${ERAS.join('|')})(元|[${NUMBER_CHARS}]+)年(.+?)第([${NUMBER_CHARS}]+)号$`);
+const SHORT_LAW_NUM_PATTERN = new RegExp(
+  `^([${Object.keys(ERA_ABBREVIATIONS).join('')}])(元|[${NUMBER_CHARS}]+)(${Object.keys(LAW_TYPE_ABBREVIATIONS).join('|')})([${NUMBER_CHARS}]+)$`,
+);
+
+/**
+ * 漢数字・算用数字・全角数字で書かれた数を数値にする。
+ * 「百二十一」のような位取り表記と「一二一」のような桁並べ表記の両方を受け付ける。
+ */
+export function parseJapaneseNumber(text: string): number | undefined {
+  const normalized = text.replace(/[０-９]/g, (digit) => String.fromCharCode(digit.charCodeAt(0) - 0xfee0));
+  if (/^[0-9]+$/.test(normalized)) {
+    return Number(normalized);
+  }
+  if ([...normalized].every((char) => char in KANJI_DIGITS)) {
+    return Number([...normalized].map((char) => KANJI_DIGITS[char]).join(''));
+  }
+  let total = 0;
+  let current = 0;
+  for (const char of normalized) {
+    if (char in KANJI_DIGITS) {
+      current = KANJI_DIGITS[char];
+    } else if (char in KANJI_UNITS) {
+      // 「十」「百」単独は一十・一百として扱う。
+      total += (current || 1) * KANJI_UNITS[char];
+      current = 0;
+    } else {
+      return undefined;
+    }
+  }
+  return total + current;
+}
+
+/**
+ * 1〜9999 の数を「百二十一」のような位取りの漢数字にする。
+ */
+export function toKanjiNumber(value: number): string {
+  const digits = ['', '一', '二', '三', '四', '五', '六', '七', '八', '九'];
+  const units: Array<[number, string]> = [
+    [1000, '千'],
+    [100, '百'],
+    [10, '十'],
+  ];
+  let rest = value;
+  let text = '';
+  for (const [unit, label] of units) {
+    const count = Math.floor(rest / unit);
+    if (count > 0) {
+      // 法令番号の表記慣行どおり「一百」「一十」とは書かない。
+      text += `${count === 1 ? '' : digits[count]}${label}`;
+    }
+    rest %= unit;
+  }
+  return text + digits[rest];
+}
+
+/**
+ * 法令番号の正規化表記（漢数字フル表記。例: 「昭和三十四年法律第百二十一号」）を返す。
+ */
+export function formatLawNum(lawNum: Omit<LawNum, 'key'>): string {
+  const year = lawNum.year === 1 ? '元' : toKanjiNumber(lawNum.year);
+  return `${lawNum.era}${year}年${lawNum.lawType}第${toKanjiNumber(lawNum.number)}号`;
+}
+
+/**
+ * 法令番号の短縮表記（例: 「昭34法121」）を返す。
+ * 略記の定まっていない法令種別（府省令など）は種別名をそのまま使う。
+ */
+export function formatLawNumShort(lawNum: Omit<LawNum, 'key'>): string {
+  const era = Object.keys(ERA_ABBREVIATIONS).find((key) => ERA_ABBREVIATIONS[key] === lawNum.era) ?? lawNum.era;
+  const lawType =
+    Object.keys(LAW_TYPE_ABBREVIATIONS).find((key) => LAW_TYPE_ABBREVIATIONS[key] === lawNum.lawType) ?? lawNum.lawType;
+  return `${era}${lawNum.year}${lawType}${lawNum.number}`;
+}
+
+/**
+ * 法令番号（例: 「昭和二十二年法律第六十七号」「昭和22年法律第67号」「昭22法67」）を構造化して正規化する。
+ * 法令番号の形をしていない文字列には undefined を返す。
+ */
+export function normalizeLawNum(text: string): LawNum | undefined {
+  const compact = text.replace(/\s+/g, '').replace(/^（|）$/g, '');
+  const full = compact.match(LAW_NUM_PATTERN);
+  const short = full ? undefined : compact.match(SHORT_LAW_NUM_PATTERN);
+  const matched = full ?? short;
+  if (!matched) {
+    return undefined;
+  }
+  const [, eraText, yearText, lawTypeText, numberText] = matched;
+  const era = short ? ERA_ABBREVIATIONS[eraText] : eraText;
+  const lawType = short ? LAW_TYPE_ABBREVIATIONS[lawTypeText] : lawTypeText;
+  const year = yearText === '元' ? 1 : parseJapaneseNumber(yearText);
+  const number = parseJapaneseNumber(numberText);
+  if (!year || !number) {
+    return undefined;
+  }
+  const parsed = { era, year, lawType, number };
+  return { ...parsed, key: formatLawNum(parsed) };
+}
+
+/**
+ * law_id の先頭3桁（元号コード1桁と元号での年2桁。例: `334AC0000000121` は昭和34年）から公布年を西暦で返す。
+ * 年は法令番号の年なので、公布日が分かるならそちらを優先する。読めない law_id には undefined を返す。
+ */
+export function extractYearFromLawId(lawId: string): number | undefined {
+  const matched = lawId.match(/^([1-9])(\d{2})/);
+  const firstYear = matched ? ERA_FIRST_YEARS[Number(matched[1]) - 1] : undefined;
+  if (!matched || firstYear === undefined || Number(matched[2]) === 0) {
+    return undefined;
+  }
+  return firstYear + Number(matched[2]) - 1;
+}
+
+/**
+ * frontmatterなどへ出力する法令番号を正規化表記に揃える。
+ * 法令番号として解釈できない値は、情報を落とさないよう原文のまま返す。
+ */
+export function canonicalLawNum(text: string | undefined): string | undefined {
+  if (!text) {
+    return undefined;
+  }
+  return normalizeLawNum(text)?.key ?? text;
+}
+
+/**
+ * 表記ゆれを吸収して、辞書から法令番号に一致する law_id を探す。`accept` で使わないエントリを除ける。
+ */
+export function findLawIdByLawNum(
+  dictionary: LawDictionary,
+  lawNum: string,
+  accept: (lawId: string, entry: LawDictionaryEntry) => boolean = () => true,
+): string | undefined {
+  const target = normalizeLawNum(lawNum);
+  if (!target) {
+    return undefined;
+  }
+  for (const [lawId, entry] of Object.entries(dictionary)) {
+    const key = entry.law_num_key ?? (entry.law_num ? normalizeLawNum(entry.law_num)?.key : undefined);
+    if (key === target.key && accept(lawId, entry)) {
+      return lawId;
+    }
+  }
+  return undefined;
+}
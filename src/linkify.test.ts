@@ -0,0 +1,89 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { defaultCliOptions, linkifyKindForAnchor, parseLinkifyRules, renderMarkdown } from './index.js';
+import type { LinkifyKind } from './types.js';
+
+const doc = {
+  lawId: '334AC0000000121',
+  title: '特許法',
+  sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+  blocks: [
+    {
+      id: 'Mp-At_1',
+      heading: '第一条',
+      paragraphs: [
+        {
+          anchor: 'Mp-At_1-Pr_1',
+          segments: [
+            { type: 'link' as const, text: '第二条', href: '#Mp-At_2' },
+            { type: 'link' as const, text: '前項', href: '#Mp-At_2-Pr_1' },
+            { type: 'link' as const, text: '第一号', href: '#Mp-At_2-Pr_1-It_1' },
+            { type: 'link' as const, text: '民法第三条', href: '/law/129AC0000000089#Mp-At_3' },
+            { type: 'link' as const, text: '商法第五条', href: 'javascript:void(0)' },
+          ],
+        },
+      ],
+    },
+    { id: 'Mp-At_2', heading: '第二条', paragraphs: [] },
+  ],
+};
+
+const renderWith = (linkifyRules: LinkifyKind[]) => {
+  const context = {
+    rootLawId: doc.lawId,
+    rootLawTitle: doc.title,
+    unresolved: [],
+    unresolvedSeen: new Set<string>(),
+    failures: [],
+    references: [],
+  };
+  const rendered = renderMarkdown(doc, {}, { ...defaultCliOptions(), linkifyRules }, context, 0);
+  return { ...rendered, unresolved: context.unresolved as Array<{ raw_text: string }> };
+};
+
+test('parseLinkifyRules: カンマ区切りの種別を解釈し、未知の種別はエラーにする', () => {
+  assert.deepEqual(parseLinkifyRules('article,external'), ['article', 'external']);
+  assert.deepEqual(parseLinkifyRules('item、item'), ['item']);
+  assert.deepEqual(parseLinkifyRules(''), []);
+  assert.throws(() => parseLinkifyRules('article,chapter'), /--linkify-rules/);
+});
+
+test('linkifyKindForAnchor: アンカーの末尾の単位で条・項・号を判定する', () => {
+  assert.equal(linkifyKindForAnchor('Mp-Ch_1-At_2'), 'article');
+  assert.equal(linkifyKindForAnchor('Mp-At_2-Pr_1'), 'paragraph');
+  assert.equal(linkifyKindForAnchor('Mp-At_2-Pr_1-It_1'), 'item');
+  assert.equal(linkifyKindForAnchor('411AC0000000087-Sp'), 'article');
+  assert.equal(linkifyKindForAnchor(undefined), 'article');
+});
+
+test('renderMarkdown: --linkify-rules で外した種別はリンクにせず、未解決参照にも記録しない', () => {
+  const cases: Array<{ rules: LinkifyKind[]; linked: string[] }> = [
+    { rules: ['article', 'external', 'paragraph', 'item'], linked: ['第二条', '前項', '第一号', '民法第三条'] },
+    { rules: ['article', 'external'], linked: ['第二条', '民法第三条'] },
+    { rules: ['article'], linked: ['第二条'] },
+    { rules: ['paragraph', 'item'], linked: ['前項', '第一号'] },
+    { rules: ['external'], linked: ['民法第三条'] },
+    { rules: [], linked: [] },
+  ];
+  for (const { rules, linked } of cases) {
+    const { markdown, unresolved, referencedLawIds } = renderWith(rules);
+    for (const text of ['第二条', '前項', '第一号', '民法第三条']) {
+      const pattern = new RegExp(`\\|${text}\\]\\]`);
+      if (linked.includes(text)) {
+        assert.match(markdown, pattern, `${rules.join(',')}: ${text}`);
+      } else {
+        assert.doesNotMatch(markdown, pattern, `${rules.join(',')}: ${text}`);
+        assert.ok(markdown.includes(text), `${rules.join(',')}: ${text}`);
+      }
+    }
+    const external = rules.includes('external');
+    assert.deepEqual(referencedLawIds, external ? ['129AC0000000089'] : [], rules.join(','));
+    // 外部参照をリンク化しないときは、law_id を読めない参照も意図して外したものとして記録しない。
+    assert.equal(
+      unresolved.some((record) => record.raw_text === '商法第五条'),
+      external,
+      rules.join(','),
+    );
+  }
+});
@@ -0,0 +1,34 @@
+import type { LinkifyKind } from './types.js';
+
+/** `--linkify-rules` で指定できるリンク化の種別。 */
+export const LINKIFY_KINDS: LinkifyKind[] = ['article', 'external', 'paragraph', 'item'];
+
+/**
+ * `--linkify-rules`（例: 「article,external」）を解釈し、リンク化する種別の一覧を返す。
+ */
+export function parseLinkifyRules(text: string): LinkifyKind[] {
+  const kinds = text
+    .split(/[,、]/)
+    .map((value) => value.trim())
+    .filter(Boolean);
+  for (const kind of kinds) {
+    if (!LINKIFY_KINDS.includes(kind as LinkifyKind)) {
+      throw new Error(`--linkify-rules は ${LINKIFY_KINDS.join('/')} を「,」区切りで指定してください: ${kind}`);
+    }
+  }
+  return [...new Set(kinds as LinkifyKind[])];
+}
+
+/**
+ * 同一法令内のアンカーがどの単位を指すかを返す（例: `Mp-At_3-Pr_2-It_1` は号、`Mp-At_3-Pr_2` は項）。
+ * 条より上の単位（章・附則など）や条項を持たないアンカーは条として扱う。
+ */
+export function linkifyKindForAnchor(anchor: string | undefined): LinkifyKind {
+  if (anchor && /-It_/.test(anchor)) {
+    return 'item';
+  }
+  if (anchor && /-Pr_/.test(anchor)) {
+    return 'paragraph';
+  }
+  return 'article';
+}
@@ -0,0 +1,69 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { findMissingLinks, isStubNote, renderStubNote, unlinkMissingLinks } from './index.js';
+
+test('findMissingLinks: どのリンク形式でも行き先のノートが無いWikiリンクだけを行番号つきで列挙する', () => {
+  const notes = [
+    {
+      path: '法律/特許法_334AC0000000121.md',
+      markdown: [
+        '---',
+        'referenced_by:',
+        '  - "[[laws/法律/会社法_417AC0000000086.md|会社法]]"',
+        '---',
+        '[[laws/法律/民法_129AC0000000089.md#Mp-At_90|民法第九十条]]及び[[#Mp-At_2|次条]]',
+        '[[会社法_417AC0000000086.md|会社法]]の[[../政令/施行令_335CO0000000016.md|施行令]]',
+        '![[fig1.jpg]]%%図: https://laws.e-gov.go.jp/data/fig1.jpg%%',
+      ].join('\n'),
+    },
+    { path: '法律/会社法_417AC0000000086.md', markdown: '[[特許法_334AC0000000121]]' },
+  ];
+  assert.deepEqual(findMissingLinks(notes), [
+    { note: '法律/特許法_334AC0000000121.md', line: 5, target: 'laws/法律/民法_129AC0000000089.md' },
+    { note: '法律/特許法_334AC0000000121.md', line: 6, target: '../政令/施行令_335CO0000000016.md' },
+  ]);
+});
+
+test('renderStubNote / isStubNote: frontmatterだけのスタブを作り、本体のノートと区別できる', () => {
+  const stub = renderStubNote('129AC0000000089', '民法', 'https://laws.e-gov.go.jp/law/129AC0000000089');
+  assert.equal(
+    stub,
+    [
+      '---',
+      'law_id: 129AC0000000089',
+      'title: "民法"',
+      'source_url: https://laws.e-gov.go.jp/law/129AC0000000089',
+      'stub: true',
+      '---',
+      '',
+    ].join('\n'),
+  );
+  assert.equal(isStubNote(stub), true);
+  assert.equal(isStubNote(['---', 'law_id: 129AC0000000089', '---', '', 'stub: true'].join('\n')), false);
+});
+
+test('unlinkMissingLinks: 行き先のノートが無いリンクだけを文言に戻し、frontmatterと図の埋め込みは残す', () => {
+  const notes = new Set(['法律/特許法_334AC0000000121.md', '法律/会社法_417AC0000000086.md']);
+  const markdown = [
+    '---',
+    'referenced_by:',
+    '  - "[[laws/法律/民法_129AC0000000089.md|民法]]"',
+    '---',
+    '[[laws/法律/民法_129AC0000000089.md#Mp-At_90|民法第九十条]]及び[[#Mp-At_2|次条]]',
+    '[[laws/法律/会社法_417AC0000000086.md|会社法]]の[施行令](../政令/施行令%20第一章_335CO0000000016.md#Mp-At_1)',
+    '![[fig1.jpg]]',
+  ].join('\n');
+  assert.equal(
+    unlinkMissingLinks({ path: '法律/特許法_334AC0000000121.md', markdown }, notes),
+    [
+      '---',
+      'referenced_by:',
+      '  - "[[laws/法律/民法_129AC0000000089.md|民法]]"',
+      '---',
+      '民法第九十条及び[[#Mp-At_2|次条]]',
+      '[[laws/法律/会社法_417AC0000000086.md|会社法]]の施行令',
+      '![[fig1.jpg]]',
+    ].join('\n'),
+  );
+});
@@ -0,0 +1,171 @@
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import process from 'node:process';
+import { VAULT_LAWS_DIR } from './config.js';
+import { joinFrontmatter, renderFrontmatterField, splitFrontmatter } from './frontmatter.js';
+import { buildLawNumIndex, notePath, parseLawIdFromNoteFileName, resolveNoteRelativePath } from './notes.js';
+import { encodeNoteText, readNoteText, writeJson } from './storage.js';
+import { getLawPageUrl } from './utils.js';
+import type { CliOptions, LawDictionary, MissingLinkRecord } from './types.js';
+
+// 図の埋め込み（`![[...]]`）はノートではないため対象外にし、アンカーと表示名を除いたリンク先だけを取り出す。
+const WIKI_LINK_PATTERN = /(?<!!)\[\[([^\]|#]+)(?:#[^\]|]*)?(?:\|[^\]]*)?\]\]/g;
+// `--link-only-existing` でリンクを文言へ戻すため、表示名も取り出す。
+const WIKI_LINK_WITH_LABEL_PATTERN = /(?<!!)\[\[([^\]|#]+)(?:#[^\]|]*)?(?:\|([^\]]*))?\]\]/g;
+// `--standard-links` のノートへのリンク（リンク元からの相対パス）。外部URLや同じノート内のアンカーは対象外。
+const STANDARD_NOTE_LINK_PATTERN = /(?<!!)\[([^\]]*)\]\(((?![\w+.-]+:)[^)\s#]+\.md)(?:#[^)\s]*)?\)/g;
+
+function toNoteFileName(target: string): string {
+  return target.endsWith('.md') ? target : `${target}.md`;
+}
+
+/**
+ * Wikiリンクのリンク先が既存ノートのいずれかを指すかを判定する。
+ * 出力済みノートが `--link-style` の異なる実行で作られていることもあるため、Vaultルートからのパス・
+ * リンク元からの相対パス・ファイル名のみ（最短形式）のどの解釈でも見つかれば存在するとみなす。
+ */
+function linkResolves(target: string, fromNote: string, notes: Set<string>, fileNames: Set<string>): boolean {
+  const fileName = toNoteFileName(target.trim());
+  const candidates = [
+    fileName.startsWith(`${VAULT_LAWS_DIR}/`) ? fileName.slice(VAULT_LAWS_DIR.length + 1) : fileName,
+    path.posix.join(path.posix.dirname(fromNote), fileName),
+  ];
+  return candidates.some((candidate) => notes.has(candidate)) || (!fileName.includes('/') && fileNames.has(fileName));
+}
+
+/**
+ * ノート群（出力ディレクトリからの相対パスと本文）から、行き先のノートが存在しないWikiリンクを列挙する。
+ * 同じ行に同じリンク先が複数あっても1件にまとめる。
+ */
+export function findMissingLinks(notes: Array<{ path: string; markdown: string }>): MissingLinkRecord[] {
+  const notePaths = new Set(notes.map((note) => note.path));
+  const fileNames = new Set(notes.map((note) => path.posix.basename(note.path)));
+  const missing: MissingLinkRecord[] = [];
+  for (const note of notes) {
+    for (const [index, line] of note.markdown.split('\n').entries()) {
+      const targets = new Set([...line.matchAll(WIKI_LINK_PATTERN)].map((matched) => matched[1].trim()));
+      for (const target of targets) {
+        if (!linkResolves(target, note.path, notePaths, fileNames)) {
+          missing.push({ note: note.path, line: index + 1, target });
+        }
+      }
+    }
+  }
+  return missing;
+}
+
+/**
+ * ノート本文のうち、行き先のノートが `notePaths`（出力ディレクトリからの相対パス）に無いリンクを文言だけに戻す。
+ * `--link-only-existing` 用。frontmatterのリンクは取得した法令を指すものだけなので書き換えない。
+ */
+export function unlinkMissingLinks(note: { path: string; markdown: string }, notePaths: Set<string>): string {
+  const parts = splitFrontmatter(note.markdown);
+  const fileNames = new Set([...notePaths].map((notePath) => path.posix.basename(notePath)));
+  const body = parts.body
+    .replace(WIKI_LINK_WITH_LABEL_PATTERN, (whole, target: string, label: string | undefined) =>
+      linkResolves(target, note.path, notePaths, fileNames) ? whole : (label ?? target.trim()),
+    )
+    .replace(STANDARD_NOTE_LINK_PATTERN, (whole, label: string, target: string) => {
+      const resolved = path.posix.join(path.posix.dirname(note.path), target.replace(/%20/g, ' '));
+      return notePaths.has(resolved) ? whole : label;
+    });
+  return body === parts.body ? note.markdown : joinFrontmatter({ ...parts, body });
+}
+
+/**
+ * `--stub-missing` で作ったスタブノートかを判定する。スタブは本取得まで仮置きしているだけなので、
+ * `--if-exists skip` でも既存ノートとして扱わない。
+ */
+export function isStubNote(markdown: string): boolean {
+  return splitFrontmatter(markdown).frontmatter?.stub === true;
+}
+
+/**
+ * 行き先の無い法令のスタブノート（frontmatterだけ）を生成する。
+ */
+export function renderStubNote(lawId: string, title: string, sourceUrl: string): string {
+  const lines = [
+    '---',
+    ...renderFrontmatterField('law_id', lawId),
+    ...renderFrontmatterField('title', title),
+    ...renderFrontmatterField('source_url', sourceUrl),
+    ...renderFrontmatterField('stub', true),
+    '---',
+  ];
+  return `${lines.join('\n')}\n`;
+}
+
+/**
+ * 出力ディレクトリ配下のノート（`.md`）を、出力ディレクトリからの相対パスで列挙する。隠しディレクトリは対象外にする。
+ */
+export async function listNotes(outputDir: string, relativeDir = ''): Promise<string[]> {
+  let entries;
+  try {
+    entries = await fs.readdir(path.join(outputDir, relativeDir), { withFileTypes: true });
+  } catch (error) {
+    if ((error as NodeJS.ErrnoException).code === 'ENOENT') {
+      return [];
+    }
+    throw error;
+  }
+  const notes: string[] = [];
+  for (const entry of entries) {
+    const relativePath = path.posix.join(relativeDir, entry.name);
+    if (entry.isDirectory() && !entry.name.startsWith('.')) {
+      notes.push(...(await listNotes(outputDir, relativePath)));
+    } else if (entry.isFile() && entry.name.endsWith('.md')) {
+      notes.push(relativePath);
+    }
+  }
+  return notes;
+}
+
+/**
+ * 出力ディレクトリの全ノートを走査し、行き先の無いWikiリンクを `--missing-links-path` へ書き出す。
+ * `--max-depth` を超えた参照先はノートが作られないため、Obsidianのグラフに未作成ノードが残る原因を一覧できるようにする。
+ * `--stub-missing` 指定時は、リンク先が法令ノートと判別できるものについてスタブノートを生成する。
+ */
+export async function checkMissingLinks(options: CliOptions, dictionary: LawDictionary): Promise<MissingLinkRecord[]> {
+  const notes = await Promise.all(
+    (await listNotes(options.outputDir)).map(async (relativePath) => ({
+      path: relativePath,
+      markdown: await readNoteText(path.join(options.outputDir, relativePath)),
+    })),
+  );
+  const missing = findMissingLinks(notes);
+  await writeJson(options.missingLinksPath, missing);
+  if (missing.length > 0) {
+    process.stderr.write(`行き先のないリンク: ${missing.length}件（${options.missingLinksPath} に記録）\n`);
+  }
+  if (!options.stubMissing) {
+    return missing;
+  }
+
+  let stubCount = 0;
+  const stubbed = new Set<string>();
+  const lawNumIndex = buildLawNumIndex(dictionary);
+  for (const record of missing) {
+    const lawId = parseLawIdFromNoteFileName(path.posix.basename(toNoteFileName(record.target)), lawNumIndex);
+    if (!lawId || stubbed.has(lawId)) {
+      continue;
+    }
+    stubbed.add(lawId);
+    const title = dictionary[lawId]?.title ?? `law_${lawId}`;
+    // 本取得したときと同じ場所に置き、次回の取得でそのまま本体に上書きされるようにする。
+    const entry = dictionary[lawId] ?? { title, safe_title: title, file_name: `law_${lawId}.md`, updated_at: '' };
+    const stubPath = notePath(options.outputDir, resolveNoteRelativePath(lawId, entry, options));
+    try {
+      await fs.mkdir(path.dirname(stubPath), { recursive: true });
+      const markdown = renderStubNote(lawId, title, getLawPageUrl(options, lawId));
+      await fs.writeFile(stubPath, encodeNoteText(markdown, options), { encoding: 'utf8', flag: 'wx' });
+      stubCount += 1;
+    } catch (error) {
+      // 別名のリンクで同じ法令のノートが既にある場合は、そのノートを残す。
+      if ((error as NodeJS.ErrnoException).code !== 'EEXIST') {
+        throw error;
+      }
+    }
+  }
+  process.stderr.write(`スタブノートを生成しました: ${stubCount}件\n`);
+  return missing;
+}
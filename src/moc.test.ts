@@ -0,0 +1,51 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { defaultCliOptions, renderMocNote } from './index.js';
+
+test('renderMocNote: 種別ごとに法令名順でリンクを並べ、公布日と被参照数を添える', () => {
+  const markdown = renderMocNote(
+    [
+      {
+        lawId: '334AC0000000121',
+        title: '特許法',
+        path: '特許法_334AC0000000121.md',
+        referencedLawIds: ['129AC0000000089', '335CO0000000016', '334AC0000000121'],
+      },
+      { lawId: '335CO0000000016', title: '特許法施行令', path: '特許法施行令_335CO0000000016.md', referencedLawIds: [] },
+      { lawId: '129AC0000000089', title: '民法', path: '民法_129AC0000000089.md', referencedLawIds: [] },
+      { lawId: 'unknown', title: '不明な法令', path: 'unknown.md', referencedLawIds: ['129AC0000000089'] },
+    ],
+    {
+      '129AC0000000089': {
+        title: '民法',
+        safe_title: '民法',
+        file_name: '民法_129AC0000000089.md',
+        updated_at: '',
+        promulgation_date: '1896-04-27',
+      },
+    },
+    '_MOC.md',
+    defaultCliOptions(),
+  );
+  assert.equal(
+    markdown,
+    [
+      '# 法令一覧',
+      '',
+      '## 法律',
+      '',
+      '- [[laws/民法_129AC0000000089.md|民法]]（公布日: 1896-04-27、被参照: 2件）',
+      '- [[laws/特許法_334AC0000000121.md|特許法]]',
+      '',
+      '## 政令',
+      '',
+      '- [[laws/特許法施行令_335CO0000000016.md|特許法施行令]]（被参照: 1件）',
+      '',
+      '## 種別不明',
+      '',
+      '- [[laws/unknown.md|不明な法令]]',
+      '',
+    ].join('\n'),
+  );
+});
@@ -0,0 +1,87 @@
+import path from 'node:path';
+import process from 'node:process';
+import { classifyLawType, displayTitle } from './notes.js';
+import { REF_KINDS } from './refpolicy.js';
+import { heading, noteLink } from './render.js';
+import { encodeNoteText, writeFileAtomic } from './storage.js';
+import type { CliOptions, LawDictionary } from './types.js';
+
+/**
+ * 一覧ノートに載せる法令1件。`path` は出力ディレクトリからの相対パス、`referencedLawIds` はその法令が参照する法令。
+ */
+export interface MocEntry {
+  lawId: string;
+  title: string;
+  path: string;
+  referencedLawIds: string[];
+}
+
+// 起点法令を複数処理する実行（法令名リスト・`--repl` など）でも全体の一覧を作れるよう、実行中に書き出した法令を溜める。
+const mocEntries = new Map<string, MocEntry>();
+
+/**
+ * 書き出した法令を一覧ノートの対象に加える。同じ法令を書き直した場合は後の内容で置き換える。
+ */
+export function recordMocEntry(entry: MocEntry): void {
+  mocEntries.set(entry.lawId, entry);
+}
+
+/**
+ * 一覧ノートを生成する。法令を種別（法律・政令・省令…）ごとの見出しに分けて法令名順に並べ、
+ * 各法令の横に公布日と、一覧に載る法令のうちその法令を参照している法令の数を添える。
+ */
+export function renderMocNote(
+  entries: MocEntry[],
+  dictionary: LawDictionary,
+  mocName: string,
+  options: Pick<CliOptions, 'linkStyle' | 'outputFormat' | 'standardLinks' | 'headingBaseLevel'>,
+): string {
+  const referencedCounts = new Map<string, number>();
+  for (const entry of entries) {
+    for (const lawId of new Set(entry.referencedLawIds)) {
+      if (lawId !== entry.lawId) {
+        referencedCounts.set(lawId, (referencedCounts.get(lawId) ?? 0) + 1);
+      }
+    }
+  }
+  const byKind = new Map<string | undefined, MocEntry[]>();
+  for (const entry of entries) {
+    const kind = classifyLawType(entry.lawId, dictionary[entry.lawId]);
+    byKind.set(kind, [...(byKind.get(kind) ?? []), entry]);
+  }
+  // 種別は `--ref-policy` と同じ並びにし、一覧に無い種別と種別不明は末尾に回す。
+  const kindOrder = (kind: string | undefined) => {
+    const index = kind === undefined ? -1 : REF_KINDS.indexOf(kind);
+    return index === -1 ? REF_KINDS.length + (kind === undefined ? 1 : 0) : index;
+  };
+  const kinds = [...byKind.keys()].sort((a, b) => kindOrder(a) - kindOrder(b) || (a ?? '').localeCompare(b ?? ''));
+  const lines = [heading(options.headingBaseLevel - 1, '法令一覧')];
+  for (const kind of kinds) {
+    lines.push('', heading(options.headingBaseLevel, kind ?? '種別不明'), '');
+    const sorted = (byKind.get(kind) ?? []).sort(
+      (a, b) => displayTitle(a.title).localeCompare(displayTitle(b.title)) || a.lawId.localeCompare(b.lawId),
+    );
+    for (const entry of sorted) {
+      const promulgationDate = dictionary[entry.lawId]?.promulgation_date;
+      const referencedCount = referencedCounts.get(entry.lawId);
+      const annotations = [
+        promulgationDate && `公布日: ${promulgationDate}`,
+        referencedCount && `被参照: ${referencedCount}件`,
+      ].filter(Boolean);
+      const annotation = annotations.length > 0 ? `（${annotations.join('、')}）` : '';
+      lines.push(`- ${noteLink(options, mocName, entry.path, undefined, displayTitle(entry.title))}${annotation}`);
+    }
+  }
+  return `${lines.join('\n')}\n`;
+}
+
+/**
+ * `--moc` で、この実行で書き出した法令すべての一覧ノートを作り直す。出力ディレクトリは読み直さない。
+ */
+export async function writeMocNote(options: CliOptions, dictionary: LawDictionary): Promise<void> {
+  const mocName = options.mocName as string;
+  const entries = [...mocEntries.values()];
+  const mocPath = path.join(options.outputDir, mocName);
+  await writeFileAtomic(mocPath, encodeNoteText(renderMocNote(entries, dictionary, mocName, options), options));
+  process.stdout.write(`法令一覧を書き出しました: ${mocPath} (${entries.length}件)\n`);
+}
@@ -0,0 +1,31 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { decodeMessagePack, encodeMessagePack, startsWithMessagePackMap } from './index.js';
+
+test('encodeMessagePack / decodeMessagePack: JSONで表せる値を往復させ、undefined のキーは書き出さない', () => {
+  const value = {
+    schema_version: 2,
+    laws: { '129AC0000000089': { title: '民法', updated_at: '2026-02-20T00:00:00Z', confidence: 0.75 } },
+    numbers: [0, 127, 128, 255, 256, 65_535, 65_536, 2 ** 32, 2 ** 40, -1, -32, -33, -129, -(2 ** 31), -(2 ** 40), 1.5],
+    flags: [true, false, null],
+    empty: { list: [], map: {}, text: '' },
+    long: 'あ'.repeat(100),
+    longer: 'x'.repeat(70_000),
+    many: Array.from({ length: 20 }, (_, index) => index),
+  };
+  const encoded = encodeMessagePack(value);
+  assert.equal(startsWithMessagePackMap(encoded), true);
+  assert.deepEqual(decodeMessagePack(encoded), value);
+  assert.deepEqual(decodeMessagePack(encodeMessagePack({ title: '民法', abbrev: undefined })), { title: '民法' });
+});
+
+test('decodeMessagePack: 途中で切れたデータや後ろに余分なバイトのあるデータはエラーにする', () => {
+  const encoded = encodeMessagePack({ title: '民法' });
+  assert.throws(() => decodeMessagePack(encoded.subarray(0, encoded.length - 1)), /途中で終わっています/);
+  assert.throws(() => decodeMessagePack(Uint8Array.from([...encoded, 0xc0])), /余分なバイト/);
+  assert.throws(() => decodeMessagePack(Uint8Array.from([0xc4, 0x00])), /未対応の型です: 0xc4/);
+  // JSONの辞書は先頭のバイトがマップを表す値にならないため、MessagePackと取り違えない。
+  assert.equal(startsWithMessagePackMap(new TextEncoder().encode('{"laws":{}}')), false);
+  assert.equal(startsWithMessagePackMap(new TextEncoder().encode('\ufeff{}')), false);
+});
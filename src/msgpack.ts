@@ -0,0 +1,221 @@
+/**
+ * 辞書の保存に使うMessagePackの符号化・復号。扱うのはJSONで表せる値（null・真偽値・数値・文字列・配列・オブジェクト）だけで、
+ * バイナリや拡張型は使わないため、依存を増やさずにここで実装する。
+ * JSONと同じく、オブジェクトの値が undefined のキーは書き出さない。
+ */
+
+const textEncoder = new TextEncoder();
+const textDecoder = new TextDecoder('utf-8', { fatal: true });
+
+/**
+ * 先頭のバイトがMessagePackのマップ（fixmap・map16・map32）を表すかを返す。
+ * JSONのオブジェクトは `{` か空白・BOMで始まり、これらのバイトにはならないため、辞書ファイルの形式の判別に使う。
+ */
+export function startsWithMessagePackMap(bytes: Uint8Array): boolean {
+  const first = bytes[0];
+  return first !== undefined && ((first >= 0x80 && first <= 0x8f) || first === 0xde || first === 0xdf);
+}
+
+/**
+ * JSONで表せる値をMessagePackへ符号化する。
+ */
+export function encodeMessagePack(value: unknown): Uint8Array {
+  let buffer = new Uint8Array(1024);
+  let view = new DataView(buffer.buffer);
+  let length = 0;
+  // 数万件の辞書でも書き込みのたびに配列を作り直さないよう、足りなくなったら倍に広げる。
+  const reserve = (size: number): number => {
+    if (length + size > buffer.length) {
+      const grown = new Uint8Array(Math.max(buffer.length * 2, length + size));
+      grown.set(buffer.subarray(0, length));
+      buffer = grown;
+      view = new DataView(buffer.buffer);
+    }
+    const start = length;
+    length += size;
+    return start;
+  };
+  // reserve が buffer を広げ直すことがあるため、書き込み先は必ず reserve の後に取る。
+  const pushByte = (byte: number): void => {
+    const start = reserve(1);
+    buffer[start] = byte;
+  };
+  const pushView = (size: number, write: (target: DataView, offset: number) => void): void => {
+    const start = reserve(size);
+    write(view, start);
+  };
+  // 長さの表し方は型ごとに、固定長形式に収まる上限と 8/16/32 ビット長の型コードが違う（8ビット長の無い型は undefined）。
+  const pushHeader = (size: number, fix: number, fixMax: number, codes: [number | undefined, number, number]): void => {
+    if (size <= fixMax) {
+      pushByte(fix | size);
+    } else if (codes[0] !== undefined && size <= 0xff) {
+      pushByte(codes[0]);
+      pushByte(size);
+    } else if (size <= 0xffff) {
+      pushByte(codes[1]);
+      pushView(2, (target, offset) => target.setUint16(offset, size));
+    } else {
+      pushByte(codes[2]);
+      pushView(4, (target, offset) => target.setUint32(offset, size));
+    }
+  };
+  const encodeNumber = (number: number): void => {
+    if (!Number.isSafeInteger(number)) {
+      // 整数で表せない値はJSONと同じく倍精度浮動小数点数として書く。
+      pushByte(0xcb);
+      pushView(8, (target, offset) => target.setFloat64(offset, number));
+    } else if (number >= 0 && number <= 0x7f) {
+      pushByte(number);
+    } else if (number >= 0 && number <= 0xff) {
+      pushByte(0xcc);
+      pushByte(number);
+    } else if (number >= 0 && number <= 0xffff) {
+      pushByte(0xcd);
+      pushView(2, (target, offset) => target.setUint16(offset, number));
+    } else if (number >= 0 && number <= 0xffffffff) {
+      pushByte(0xce);
+      pushView(4, (target, offset) => target.setUint32(offset, number));
+    } else if (number >= 0) {
+      pushByte(0xcf);
+      pushView(8, (target, offset) => target.setBigUint64(offset, BigInt(number)));
+    } else if (number >= -32) {
+      pushByte(number & 0xff);
+    } else if (number >= -0x80000000) {
+      pushByte(0xd2);
+      pushView(4, (target, offset) => target.setInt32(offset, number));
+    } else {
+      pushByte(0xd3);
+      pushView(8, (target, offset) => target.setBigInt64(offset, BigInt(number)));
+    }
+  };
+  const encode = (item: unknown): void => {
+    if (item === null || item === undefined) {
+      pushByte(0xc0);
+    } else if (typeof item === 'boolean') {
+      pushByte(item ? 0xc3 : 0xc2);
+    } else if (typeof item === 'number') {
+      encodeNumber(item);
+    } else if (typeof item === 'string') {
+      const bytes = textEncoder.encode(item);
+      pushHeader(bytes.length, 0xa0, 31, [0xd9, 0xda, 0xdb]);
+      const start = reserve(bytes.length);
+      buffer.set(bytes, start);
+    } else if (Array.isArray(item)) {
+      pushHeader(item.length, 0x90, 15, [undefined, 0xdc, 0xdd]);
+      for (const element of item) {
+        encode(element);
+      }
+    } else if (typeof item === 'object') {
+      const entries = Object.entries(item).filter(([, element]) => element !== undefined);
+      pushHeader(entries.length, 0x80, 15, [undefined, 0xde, 0xdf]);
+      for (const [key, element] of entries) {
+        encode(key);
+        encode(element);
+      }
+    } else {
+      throw new Error(`MessagePackに変換できない値です: ${typeof item}`);
+    }
+  };
+  encode(value);
+  return buffer.slice(0, length);
+}
+
+/**
+ * MessagePackを復号する。JSONで表せない型（バイナリ・拡張型）や、途中で切れたデータはエラーにする。
+ */
+export function decodeMessagePack(bytes: Uint8Array): unknown {
+  const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  let offset = 0;
+  const take = (length: number): number => {
+    if (offset + length > bytes.length) {
+      throw new Error('MessagePackのデータが途中で終わっています');
+    }
+    const start = offset;
+    offset += length;
+    return start;
+  };
+  const readUint = (length: number): number => {
+    const start = take(length);
+    let number = 0;
+    for (let index = 0; index < length; index += 1) {
+      number = number * 256 + bytes[start + index];
+    }
+    return number;
+  };
+  const readString = (length: number): string => {
+    const start = take(length);
+    return textDecoder.decode(bytes.subarray(start, start + length));
+  };
+  const readArray = (length: number): unknown[] => Array.from({ length }, () => decode());
+  const readMap = (length: number): Record<string, unknown> => {
+    const map: Record<string, unknown> = {};
+    for (let index = 0; index < length; index += 1) {
+      const key = decode();
+      if (typeof key !== 'string') {
+        throw new Error('MessagePackのマップのキーが文字列ではありません');
+      }
+      map[key] = decode();
+    }
+    return map;
+  };
+  const decode = (): unknown => {
+    const code = bytes[take(1)];
+    if (code <= 0x7f) {
+      return code;
+    }
+    if (code >= 0xe0) {
+      return code - 0x100;
+    }
+    if (code >= 0x80 && code <= 0x8f) {
+      return readMap(code & 0x0f);
+    }
+    if (code >= 0x90 && code <= 0x9f) {
+      return readArray(code & 0x0f);
+    }
+    if (code >= 0xa0 && code <= 0xbf) {
+      return readString(code & 0x1f);
+    }
+    switch (code) {
+      case 0xc0:
+        return null;
+      case 0xc2:
+        return false;
+      case 0xc3:
+        return true;
+      case 0xca:
+        return view.getFloat32(take(4));
+      case 0xcb:
+        return view.getFloat64(take(8));
+      case 0xcc:
+      case 0xcd:
+      case 0xce:
+      case 0xcf:
+        return readUint(2 ** (code - 0xcc));
+      case 0xd0:
+        return view.getInt8(take(1));
+      case 0xd1:
+        return view.getInt16(take(2));
+      case 0xd2:
+        return view.getInt32(take(4));
+      case 0xd3:
+        return Number(view.getBigInt64(take(8)));
+      case 0xd9:
+      case 0xda:
+      case 0xdb:
+        return readString(readUint(2 ** (code - 0xd9)));
+      case 0xdc:
+      case 0xdd:
+        return readArray(readUint(code === 0xdc ? 2 : 4));
+      case 0xde:
+      case 0xdf:
+        return readMap(readUint(code === 0xde ? 2 : 4));
+      default:
+        throw new Error(`MessagePackの未対応の型です: 0x${code.toString(16)}`);
+    }
+  };
+  const value = decode();
+  if (offset !== bytes.length) {
+    throw new Error('MessagePackのデータの後ろに余分なバイトがあります');
+  }
+  return value;
+}
@@ -6,7 +6,15 @@ import path from 'node:path';
 
 import {
   buildExistingNoteIndex,
+  buildLawNumIndex,
+  classifyLawType,
+  displayTitle,
+  getFileName,
+  noteLinkTarget,
+  parseLawIdFromNoteFileName,
   resolveExistingNotePath,
+  resolveNoteFileName,
+  resolveNoteRelativePath,
   scanReferencedLawIdsFromMarkdown,
   toSafeTitle,
 } from './index.js';
@@ -19,24 +27,134 @@ test('toSafeTitle: ファイル名禁則文字を置換し80文字に制限す
   assert.equal(toSafeTitle(long).length, 80);
 });
 
+test('toSafeTitle: 全角スラッシュ・改行・制御文字と末尾のドットを除く', () => {
+  assert.equal(toSafeTitle('特許法／施行令'), '特許法_施行令');
+  assert.equal(toSafeTitle('特許法\n施行令\u0007'), '特許法施行令');
+  assert.equal(toSafeTitle('法令名。。. '), '法令名。。');
+});
+
+test('toSafeTitle: 空になる場合はフォールバックし、getFileName は law_id だけを使う', () => {
+  assert.equal(toSafeTitle('\u0000 \t'), 'law');
+  assert.equal(getFileName('334AC0000000121', '...'), '334AC0000000121.md');
+});
+
+test('displayTitle: 末尾の法令番号の括弧書きだけを除き、意味のある括弧書きは残す', () => {
+  assert.equal(displayTitle('日本国憲法の改正手続に関する法律（平成十九年法律第五十一号）'), '日本国憲法の改正手続に関する法律');
+  assert.equal(displayTitle('特許法(昭和34年法律第121号)'), '特許法');
+  assert.equal(displayTitle('地方自治法施行令（昭和二十二年五月三日政令第十六号）'), '地方自治法施行令');
+  assert.equal(displayTitle('○○に関する法律（仮称）'), '○○に関する法律（仮称）');
+  assert.equal(displayTitle('（昭和二十二年法律第六十七号）'), '（昭和二十二年法律第六十七号）');
+  assert.equal(getFileName('419AC0000000051', '特許法（昭和三十四年法律第百二十一号）'), '特許法_419AC0000000051.md');
+});
+
+test('toSafeTitle: Windowsの予約名を避ける', () => {
+  assert.equal(toSafeTitle('CON'), 'CON_');
+  assert.equal(toSafeTitle('com1.txt'), 'com1.txt_');
+  assert.equal(toSafeTitle('CONSOLE'), 'CONSOLE');
+});
+
+test('toSafeTitle: 長い法令名はバイト長内に切り詰め、元の名前のハッシュを付ける', () => {
+  const base = '𠮷'.repeat(70);
+  const a = toSafeTitle(`${base}甲`);
+  const b = toSafeTitle(`${base}乙`);
+  assert.notEqual(a, b);
+  assert.match(a, /~[0-9a-f]{8}$/);
+  assert.ok(Buffer.byteLength(a) <= 230);
+  assert.ok(Buffer.byteLength(getFileName('334AC0000000121', `${base}甲`)) <= 255);
+  assert.equal(toSafeTitle(`${base}甲`), a);
+});
+
 test('scanReferencedLawIdsFromMarkdown: Obsidianリンクからlaw_idを抽出', () => {
   const markdown = [
     '[[laws/特許法_334AC0000000121.md|特許法]]',
     '[[laws/law_345AC0000000082.md#Mp-At_1|地方道路公社法]]',
     '[[laws/特許法_334AC0000000121.md#TOC|重複]]',
+    '[[刑法_140AC0000000045.md#Mp-At_1|刑法]]',
+    '[[../法律/民法_129AC0000000089.md|民法]]',
   ].join('\n');
   const scanned = scanReferencedLawIdsFromMarkdown(markdown);
-  assert.deepEqual(scanned.referencedLawIds.sort(), ['334AC0000000121', '345AC0000000082']);
+  assert.deepEqual(scanned.referencedLawIds.sort(), [
+    '129AC0000000089',
+    '140AC0000000045',
+    '334AC0000000121',
+    '345AC0000000082',
+  ]);
+});
+
+test('scanReferencedLawIdsFromMarkdown: --heading-base-level の見出しの被参照セクションのリンクは数えない', () => {
+  const markdown = [
+    '## 会社法',
+    '',
+    '### 第一条',
+    '[[laws/民法_129AC0000000089.md|民法]]',
+    '',
+    '### 被参照',
+    '',
+    '- [[laws/商法_132AC0000000048.md|商法]] から 第一条 が参照されています',
+  ].join('\n');
+  const scanned = scanReferencedLawIdsFromMarkdown(markdown, 'all', undefined, 3);
+  assert.deepEqual(scanned.referencedLawIds, ['129AC0000000089']);
+  // 既定のレベル（`##`）の見出しを探すため、`###` の被参照セクションは本文として数える。
+  assert.equal(scanReferencedLawIdsFromMarkdown(markdown).referencedLawIds.length, 2);
+});
+
+test('scanReferencedLawIdsFromMarkdown: scope が main なら本則以外のブロックのリンクを数えない', () => {
+  const markdown = [
+    '## 第一条',
+    '<a id="Mp-At_1"></a>',
+    '[[laws/民法_129AC0000000089.md|民法]]',
+    '## 附則第一条',
+    '<a id="Sp-At_1"></a>',
+    '[[laws/商法_132AC0000000048.md|商法]]',
+    '## 別表第一',
+    '<a id="AppdxTable_1"></a>',
+    '[[laws/刑法_140AC0000000045.md|刑法]]',
+  ].join('\n');
+  assert.deepEqual(scanReferencedLawIdsFromMarkdown(markdown, 'main').referencedLawIds, ['129AC0000000089']);
+  assert.equal(scanReferencedLawIdsFromMarkdown(markdown).referencedLawIds.length, 3);
+});
+
+test('scanReferencedLawIdsFromMarkdown / parseLawIdFromNoteFileName: law-num スキームのリンクは辞書の法令番号からlaw_idを引く', () => {
+  const entry = (title: string, lawNum: string) => ({
+    title,
+    safe_title: title,
+    file_name: `${title}.md`,
+    updated_at: '',
+    law_num: lawNum,
+  });
+  const lawNumIndex = buildLawNumIndex({
+    '129AC0000000089': entry('民法', '明治二十九年法律第八十九号'),
+    '334AC0000000121': entry('特許法', '昭和３４年法律第１２１号'),
+  });
+  const markdown = [
+    '[[laws/明治二十九年法律第八十九号.md#Mp-At_1|民法第一条]]',
+    '[[../法律/昭和三十四年法律第百二十一号.md|特許法]]',
+    '[[laws/平成十一年法律第百六十号.md|辞書に無い法令]]',
+    '[特許法](../法律/昭和三十四年法律第百二十一号.md#Mp-At_2)',
+    '[[laws/特許法_334AC0000000121_changelog.md|変更履歴]]',
+  ].join('\n');
+  assert.deepEqual(scanReferencedLawIdsFromMarkdown(markdown, 'all', lawNumIndex).referencedLawIds.sort(), [
+    '129AC0000000089',
+    '334AC0000000121',
+  ]);
+  // 索引が無ければ、law_id を含まないファイル名からは引けない。
+  assert.deepEqual(scanReferencedLawIdsFromMarkdown(markdown).referencedLawIds, []);
+  assert.equal(parseLawIdFromNoteFileName('昭和三十四年法律第百二十一号.md', lawNumIndex), '334AC0000000121');
+  assert.equal(parseLawIdFromNoteFileName('昭和三十四年法律第百二十一号.md'), undefined);
+  assert.equal(parseLawIdFromNoteFileName('特許法_334AC0000000121.md', lawNumIndex), '334AC0000000121');
 });
 
 test('buildExistingNoteIndex: laws配下からlaw_id索引を構築できる', async () => {
   const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-index-'));
   await fs.writeFile(path.join(tmp, '特許法_334AC0000000121.md'), '# dummy', 'utf8');
   await fs.writeFile(path.join(tmp, 'note.md'), '# noop', 'utf8');
+  // `--changelog` の変更履歴ノートは末尾が law_id と同じ形でも索引しない。
+  await fs.writeFile(path.join(tmp, '特許法_changelog.md'), '# 特許法 変更履歴', 'utf8');
 
   const index = await buildExistingNoteIndex(tmp);
   assert.deepEqual(index.get('334AC0000000121'), [path.join(tmp, '特許法_334AC0000000121.md')]);
   assert.equal(index.get('NO_SUCH_ID'), undefined);
+  assert.deepEqual([...index.keys()], ['334AC0000000121']);
 });
 
 test('resolveExistingNotePath: 辞書名がなくてもlaw_id一致の既存ノートを返す', async () => {
@@ -48,3 +166,64 @@ test('resolveExistingNotePath: 辞書名がなくてもlaw_id一致の既存ノ
   const resolved = await resolveExistingNotePath(tmp, '334AC0000000121', 'law_334AC0000000121.md', index);
   assert.equal(resolved, existingPath);
 });
+
+test('noteLinkTarget: --link-style ごとにリンク先パスを組み立てる', () => {
+  const from = '特許法_334AC0000000121.md';
+  const to = '民法_129AC0000000089.md';
+  assert.equal(noteLinkTarget({ linkStyle: 'absolute' }, from, to), 'laws/民法_129AC0000000089.md');
+  assert.equal(noteLinkTarget({ linkStyle: 'shortest' }, from, to), '民法_129AC0000000089.md');
+  assert.equal(noteLinkTarget({ linkStyle: 'relative' }, from, to), '民法_129AC0000000089.md');
+  assert.equal(
+    noteLinkTarget({ linkStyle: 'relative' }, '法律/特許法_334AC0000000121.md', '政令/施行令_335CO0000000016.md'),
+    '../政令/施行令_335CO0000000016.md',
+  );
+});
+
+test('resolveNoteFileName: スキームごとにファイル名を決め、情報が無ければ法令名へフォールバックする', () => {
+  const entry = {
+    title: '特許法',
+    safe_title: '特許法',
+    file_name: '特許法_334AC0000000121.md',
+    updated_at: '2026-02-20T00:00:00Z',
+    law_num: '昭和三十四年法律第百二十一号',
+  };
+  assert.equal(resolveNoteFileName('334AC0000000121', entry, 'title'), '特許法_334AC0000000121.md');
+  assert.equal(resolveNoteFileName('334AC0000000121', entry, 'law-id'), '334AC0000000121.md');
+  assert.equal(resolveNoteFileName('334AC0000000121', entry, 'law-num'), '昭和三十四年法律第百二十一号.md');
+  assert.equal(
+    resolveNoteFileName('334AC0000000121', { ...entry, law_num: undefined }, 'law-num'),
+    '特許法_334AC0000000121.md',
+  );
+});
+
+test('buildExistingNoteIndex: law_id を含まないファイル名はfrontmatterから索引する', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-index-num-'));
+  const numPath = path.join(tmp, '昭和三十四年法律第百二十一号.md');
+  await fs.writeFile(numPath, '---\nlaw_id: 334AC0000000121\n---\n', 'utf8');
+  await fs.writeFile(path.join(tmp, '345AC0000000082.md'), '# dummy', 'utf8');
+
+  const index = await buildExistingNoteIndex(tmp);
+  assert.deepEqual(index.get('334AC0000000121'), [numPath]);
+  assert.deepEqual(index.get('345AC0000000082'), [path.join(tmp, '345AC0000000082.md')]);
+});
+
+test('classifyLawType: law_type・法令番号・law_id の順で種別を判定する', () => {
+  assert.equal(classifyLawType('334AC0000000121', { law_type: 'Act' }), '法律');
+  assert.equal(classifyLawType('335CO0000000016', { law_num: '昭和三十五年政令第十六号' }), '政令');
+  assert.equal(classifyLawType('335M50000400010'), '省令');
+  assert.equal(classifyLawType('UNKNOWN'), undefined);
+});
+
+test('resolveNoteRelativePath: --organize-by-type 時は種別サブフォルダを付ける', () => {
+  const entry = {
+    title: '特許法',
+    safe_title: '特許法',
+    file_name: '特許法_334AC0000000121.md',
+    updated_at: '2026-02-20T00:00:00Z',
+  };
+  const flat = { filenameScheme: 'title' as const, organizeByType: false };
+  const byType = { filenameScheme: 'title' as const, organizeByType: true };
+  assert.equal(resolveNoteRelativePath('334AC0000000121', entry, flat), '特許法_334AC0000000121.md');
+  assert.equal(resolveNoteRelativePath('334AC0000000121', entry, byType), path.join('法律', '特許法_334AC0000000121.md'));
+  assert.equal(resolveNoteRelativePath('X', { ...entry, file_name: 'x_X.md' }, byType), 'x_X.md');
+});
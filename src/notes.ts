@@ -1,35 +1,257 @@
+import { createHash } from 'node:crypto';
 import fs from 'node:fs/promises';
 import type { Dirent } from 'node:fs';
 import path from 'node:path';
-import type { ExistingNoteIndex, ExistingReferenceScanResult } from './types.js';
+import { BACKLINKS_TITLE, CHANGELOG_SUFFIX, DEFAULT_HEADING_BASE_LEVEL, VAULT_LAWS_DIR } from './config.js';
+import { joinFrontmatter, renderFrontmatterField, splitFrontmatter } from './frontmatter.js';
+import { normalizeLawNum } from './lawnum.js';
+import { isBlockInRefScope } from './refpolicy.js';
+import type {
+  CliOptions,
+  ExistingNoteIndex,
+  FilenameScheme,
+  LawDictionary,
+  LawDictionaryEntry,
+  ExistingReferenceScanResult,
+  RefScope,
+} from './types.js';
 
-export function toSafeTitle(title: string): string {
-  const normalized = title
+const WINDOWS_RESERVED_NAME = /^(?:CON|PRN|AUX|NUL|COM[1-9]|LPT[1-9])(?:\..*)?$/i;
+const MAX_TITLE_CHARS = 80;
+// 多くのファイルシステムはファイル名を255バイトまでに制限するため、`_<law_id>.md` の付加分を残した予算にする。
+const MAX_TITLE_BYTES = 230;
+const TRAILING_PAREN_PATTERN = /\s*[（(]([^（）()]*)[）)]\s*$/;
+
+function truncateUtf8(text: string, maxBytes: number): string {
+  let result = '';
+  let bytes = 0;
+  for (const char of text) {
+    const charBytes = Buffer.byteLength(char);
+    if (bytes + charBytes > maxBytes) {
+      break;
+    }
+    result += char;
+    bytes += charBytes;
+  }
+  return result;
+}
+
+/**
+ * 法令名の末尾に付いた法令番号の括弧書き（「○○法（平成十一年法律第百六十号）」）を除いた表示用の名前を返す。
+ * 括弧の中が法令番号（公布日付きを含む）と読めるときだけ除き、「（仮称）」のような意味のある括弧書きは残す。
+ * ファイル名とリンクの表示名はこれを通し、frontmatterの `title` には原文を残す。
+ */
+export function displayTitle(title: string): string {
+  const matched = title.match(TRAILING_PAREN_PATTERN);
+  if (!matched?.index || !normalizeLawNum(matched[1])) {
+    return title;
+  }
+  return title.slice(0, matched.index);
+}
+
+/**
+ * 法令名をファイル名に使える形へサニタイズする。
+ * ノートの書き出しとリンク生成の双方が辞書の `file_name` 経由でこの結果を使うため、
+ * 末尾の法令番号の括弧書きの除去・切り詰め・ハッシュ付与の規則はここだけに置く。
+ */
+export function toSafeTitle(title: string, fallback = 'law'): string {
+  const normalized = displayTitle(title)
+    // 全角スラッシュ「／」などはNFKCで半角になり、次の置換で除かれる。
     .normalize('NFKC')
+    .replace(/\p{Cc}/gu, '')
     .replace(/[\\/:*?"<>|]/g, '_')
     .replace(/\s+/g, ' ')
-    .trim();
-  if (normalized.length <= 80) {
-    return normalized || 'law';
+    .trim()
+    // Windowsは末尾のドット・空白を黙って落とし、リンク先と実ファイル名がずれるため先に除く。
+    .replace(/[. ]+$/, '');
+  if (!normalized) {
+    return fallback;
+  }
+  const safe = WINDOWS_RESERVED_NAME.test(normalized) ? `${normalized}_` : normalized;
+  const chars = [...safe];
+  if (chars.length <= MAX_TITLE_CHARS && Buffer.byteLength(safe) <= MAX_TITLE_BYTES) {
+    return safe;
   }
-  return normalized.slice(0, 80).trim() || 'law';
+  // 先頭が同じ長い法令名同士が同名にならないよう、切り詰め前の名前のハッシュを付ける。
+  const suffix = `~${createHash('sha1').update(safe).digest('hex').slice(0, 8)}`;
+  // サロゲートペアを分断しないよう、文字数の切り詰めもコードポイント単位で行う。
+  const head = truncateUtf8(chars.slice(0, MAX_TITLE_CHARS - suffix.length).join(''), MAX_TITLE_BYTES - suffix.length);
+  return `${head.trim()}${suffix}`;
 }
 
+/**
+ * 法令名ベースのノートファイル名を返す。サニタイズ後に法令名が空になる場合は law_id だけを使う。
+ */
 export function getFileName(lawId: string, title: string): string {
-  const safeTitle = toSafeTitle(title);
-  return `${safeTitle}_${lawId}.md`;
+  const safeTitle = toSafeTitle(title, '');
+  return safeTitle ? `${safeTitle}_${lawId}.md` : `${lawId}.md`;
+}
+
+/**
+ * `--filename-scheme` に従って、実際に書き出すノートのファイル名を決める。
+ * 辞書の `file_name` は法令名ベースの名前として保持し、スキームの適用はここに集約する。
+ * law_id / 法令番号が無い場合は法令名ベースへフォールバックする。
+ */
+export function resolveNoteFileName(lawId: string, entry: LawDictionaryEntry, scheme: FilenameScheme): string {
+  if (scheme === 'law-id' && lawId) {
+    return `${lawId}.md`;
+  }
+  if (scheme === 'law-num' && entry.law_num) {
+    return `${toSafeTitle(entry.law_num)}.md`;
+  }
+  return entry.file_name;
+}
+
+const LAW_TYPE_FOLDERS: Record<string, string> = {
+  Constitution: '憲法',
+  Act: '法律',
+  CabinetOrder: '政令',
+  ImperialOrder: '勅令',
+  MinisterialOrdinance: '省令',
+  Rule: '規則',
+};
+
+// law_id の4〜5文字目は法令種別を表す（例: 334AC... は法律、335CO... は政令）。
+const LAW_ID_TYPE_CODES: Array<[RegExp, string]> = [
+  [/^\d{3}CONSTITUTION/, '憲法'],
+  [/^\d{3}AC/, '法律'],
+  [/^\d{3}CO/, '政令'],
+  [/^\d{3}IO/, '勅令'],
+  [/^\d{3}M/, '省令'],
+  [/^\d{3}RJ/, '規則'],
+];
+
+const LAW_NUM_TYPES: Array<[RegExp, string]> = [
+  [/法律第/, '法律'],
+  [/政令第/, '政令'],
+  [/勅令第/, '勅令'],
+  [/(?:省令|府令)第/, '省令'],
+  [/規則第/, '規則'],
+  [/告示第/, '告示'],
+  [/訓令第/, '訓令'],
+  [/規程第/, '規程'],
+  [/条例第/, '条例'],
+  [/条約第/, '条約'],
+];
+
+/**
+ * 辞書・候補の略称（例: 「独占禁止法,独禁法」）を個々の略称に分ける。
+ */
+export function splitAbbrevs(abbrev: string | undefined): string[] {
+  return (abbrev ?? '')
+    .split(/[,、，]/)
+    .map((value) => value.trim())
+    .filter(Boolean);
+}
+
+/**
+ * 辞書エントリを法令名で引くときに照合する名前（法令名・略称・確定した入力の別名）を返す。
+ */
+export function dictionaryEntryNames(entry: LawDictionaryEntry): string[] {
+  return [entry.title, ...splitAbbrevs(entry.abbrev), ...(entry.aliases ?? [])];
+}
+
+/**
+ * 法令種別（法律・政令・省令…）を判定する。
+ * APIの law_type を優先し、無ければ法令番号、最後に law_id の種別コードから推定する。
+ */
+export function classifyLawType(
+  lawId: string,
+  entry?: Pick<LawDictionaryEntry, 'law_num' | 'law_type'>,
+): string | undefined {
+  if (entry?.law_type && LAW_TYPE_FOLDERS[entry.law_type]) {
+    return LAW_TYPE_FOLDERS[entry.law_type];
+  }
+  if (entry?.law_num) {
+    const matched = LAW_NUM_TYPES.find(([re]) => re.test(entry.law_num ?? ''));
+    if (matched) {
+      return matched[1];
+    }
+  }
+  return LAW_ID_TYPE_CODES.find(([re]) => re.test(lawId))?.[1];
+}
+
+/**
+ * 出力ディレクトリからのノート相対パスを決める。
+ * `--organize-by-type` 時は種別サブフォルダを付け、種別不明なら出力ディレクトリ直下に置く。
+ */
+export function resolveNoteRelativePath(
+  lawId: string,
+  entry: LawDictionaryEntry,
+  options: Pick<CliOptions, 'filenameScheme' | 'organizeByType'>,
+): string {
+  const fileName = resolveNoteFileName(lawId, entry, options.filenameScheme);
+  if (!options.organizeByType) {
+    return fileName;
+  }
+  const folder = classifyLawType(lawId, entry);
+  return folder ? path.join(folder, fileName) : fileName;
 }
 
 export function notePath(outputDir: string, fileName: string): string {
   return path.join(outputDir, fileName);
 }
 
-function parseLawIdFromNoteFileName(fileName: string): string | undefined {
-  const matched = fileName.match(/_([A-Za-z0-9]+)\.md$/);
-  if (!matched) {
+/**
+ * ノート間Wikiリンクのリンク先パスを `--link-style` に従って組み立てる。
+ * 引数はいずれも出力ディレクトリからの相対パスで、リンク生成はすべてこの関数を経由させる。
+ */
+export function noteLinkTarget(
+  options: Pick<CliOptions, 'linkStyle'>,
+  fromNotePath: string,
+  toNotePath: string,
+): string {
+  const to = toNotePath.split(path.sep).join('/');
+  if (options.linkStyle === 'shortest') {
+    return path.posix.basename(to);
+  }
+  if (options.linkStyle === 'relative') {
+    const fromDir = path.posix.dirname(fromNotePath.split(path.sep).join('/'));
+    return path.posix.relative(fromDir, to);
+  }
+  return `${VAULT_LAWS_DIR}/${to}`;
+}
+
+/**
+ * 辞書の法令番号から law_id を引く索引を作る。キーは `normalizeLawNum` の照合キーで、
+ * law-num スキームのファイル名（法令番号）を law_id に戻すために使う。
+ */
+export function buildLawNumIndex(dictionary: LawDictionary): Map<string, string> {
+  const index = new Map<string, string>();
+  for (const [lawId, entry] of Object.entries(dictionary)) {
+    const key = entry.law_num_key ?? (entry.law_num ? normalizeLawNum(entry.law_num)?.key : undefined);
+    if (key && !index.has(key)) {
+      index.set(key, lawId);
+    }
+  }
+  return index;
+}
+
+/**
+ * ノートのファイル名から law_id を読み取る。
+ * law-num スキームの名前は law_id を含まないため、`lawNumIndex`（`buildLawNumIndex`）があればそこから引き、無ければ undefined を返す。
+ */
+export function parseLawIdFromNoteFileName(
+  fileName: string,
+  lawNumIndex?: ReadonlyMap<string, string>,
+): string | undefined {
+  // title スキームは `<safe_title>_<law_id>.md`、law-id スキームは `<law_id>.md`。
+  // `--changelog` の変更履歴ノートは末尾が law_id と同じ形になるため除く。
+  if (fileName.endsWith(CHANGELOG_SUFFIX)) {
     return undefined;
   }
-  return matched[1];
+  const matched = fileName.match(/_([A-Za-z0-9]+)\.md$/) ?? fileName.match(/^(\d{3}[A-Za-z0-9]+)\.md$/);
+  if (matched) {
+    return matched[1];
+  }
+  // law-num スキームは `<法令番号>.md`。表記ゆれ（全角数字・略記）があっても同じ法令番号として照合する。
+  const key = lawNumIndex ? normalizeLawNum(fileName.replace(/\.md$/, ''))?.key : undefined;
+  return key ? lawNumIndex?.get(key) : undefined;
+}
+
+async function readLawIdFromFrontmatter(filePath: string): Promise<string | undefined> {
+  const lawId = splitFrontmatter(await fs.readFile(filePath, 'utf8')).frontmatter?.law_id;
+  return typeof lawId === 'string' && /^[A-Za-z0-9]+$/.test(lawId) ? lawId : undefined;
 }
 
 export function addExistingNoteIndex(index: ExistingNoteIndex, lawId: string, filePath: string): void {
@@ -57,14 +279,26 @@ export async function buildExistingNoteIndex(outputDir: string): Promise<Existin
     throw error;
   }
   for (const entry of entries) {
+    if (entry.isDirectory() && !entry.name.startsWith('.')) {
+      // --organize-by-type の種別サブフォルダ配下も索引に含める。
+      const subIndex = await buildExistingNoteIndex(path.join(outputDir, entry.name));
+      for (const [lawId, paths] of subIndex) {
+        for (const filePath of paths) {
+          addExistingNoteIndex(index, lawId, filePath);
+        }
+      }
+      continue;
+    }
     if (!entry.isFile() || !entry.name.endsWith('.md')) {
       continue;
     }
-    const lawId = parseLawIdFromNoteFileName(entry.name);
+    const filePath = path.join(outputDir, entry.name);
+    // law-num スキームのファイル名には law_id が含まれないため、frontmatterから補う。
+    const lawId = parseLawIdFromNoteFileName(entry.name) ?? (await readLawIdFromFrontmatter(filePath));
     if (!lawId) {
       continue;
     }
-    addExistingNoteIndex(index, lawId, path.join(outputDir, entry.name));
+    addExistingNoteIndex(index, lawId, filePath);
   }
   return index;
 }
@@ -92,15 +326,80 @@ export async function resolveExistingNotePath(
   return candidates[0];
 }
 
+// --link-style によりフォルダ部分の有無・形が変わるため、末尾のファイル名だけを取り出して判定する。
+// 再帰取得で既存ノートを多数走査するため、モジュール読込時に一度だけ生成して使い回す。
+const NOTE_LINK_PATTERN = /\[\[(?:[^\]|#]*\/)?([^\]|#\/]+\.md)(?:#[^\]|]+)?(?:\|[^\]]+)?\]\]/g;
+// `--output-format plain --standard-links` で出力したノートの相対パスリンク。
+const STANDARD_NOTE_LINK_PATTERN = /\]\((?:[^)#\s]*\/)?([^)#\/\s]+\.md)(?:#[^)\s]*)?\)/g;
+const BLOCK_ANCHOR_PATTERN = /^<a id="([^"]+)"><\/a>$/;
+
 /**
- * 既存Markdown中のObsidianリンクから参照先law_idを抽出する。
+ * 既存Markdown中のObsidianリンク（plain出力では標準Markdownリンク）から参照先law_idを抽出する。
+ * `scope` が `main` なら、本文ブロックのアンカー（`<a id="...">`）から本則以外と分かる範囲のリンクは数えない。
+ * law-num スキームのノートへのリンクは、`lawNumIndex` があれば法令番号から law_id を引く。
+ * 「被参照」セクションは、ノートを書いたときの `--heading-base-level`（`headingBaseLevel`）の見出しで探す。
  */
-export function scanReferencedLawIdsFromMarkdown(markdown: string): ExistingReferenceScanResult {
+export function scanReferencedLawIdsFromMarkdown(
+  markdown: string,
+  scope: RefScope = 'all',
+  lawNumIndex?: ReadonlyMap<string, string>,
+  headingBaseLevel = DEFAULT_HEADING_BASE_LEVEL,
+): ExistingReferenceScanResult {
   const ids = new Set<string>();
-  const re = /\[\[laws\/[^\]]*?_([A-Za-z0-9]+)\.md(?:#[^\]|]+)?(?:\|[^\]]+)?\]\]/g;
-  let match: RegExpExecArray | null;
-  while ((match = re.exec(markdown)) !== null) {
-    ids.add(match[1]);
+  // 「被参照」セクションのリンクは参照元であり、再帰取得の子ではないため走査対象から外す。
+  const backlinksIndex = markdown.indexOf(`\n${'#'.repeat(headingBaseLevel)} ${BACKLINKS_TITLE}\n`);
+  if (backlinksIndex >= 0) {
+    markdown = markdown.slice(0, backlinksIndex);
+  }
+  if (scope !== 'all') {
+    let inScope = true;
+    markdown = markdown
+      .split('\n')
+      .filter((line) => {
+        const anchor = line.match(BLOCK_ANCHOR_PATTERN)?.[1];
+        inScope = anchor ? isBlockInRefScope(anchor, scope) : inScope;
+        return inScope;
+      })
+      .join('\n');
+  }
+  // matchAll は正規表現を複製して走査するため、共有インスタンスの lastIndex に影響されない。
+  for (const match of [...markdown.matchAll(NOTE_LINK_PATTERN), ...markdown.matchAll(STANDARD_NOTE_LINK_PATTERN)]) {
+    const lawId = parseLawIdFromNoteFileName(match[1], lawNumIndex);
+    if (lawId) {
+      ids.add(lawId);
+    }
   }
   return { referencedLawIds: [...ids] };
 }
+
+/**
+ * frontmatterのトップレベルキーを、指定した行（キー行＋インデントされた値行）で置き換える。
+ * キーが無ければ末尾へ追加し、`valueLines` が空ならキーごと削除する。
+ * 実行終了時の後追い更新で、本文を作り直さずにfrontmatterだけを書き換えるために使う。
+ */
+export function setFrontmatterField(markdown: string, key: string, valueLines: string[]): string {
+  const parts = splitFrontmatter(markdown);
+  if (!parts.frontmatter) {
+    return markdown;
+  }
+  const lines = [...parts.lines];
+  const start = lines.findIndex((line) => line.startsWith(`${key}:`));
+  if (start >= 0) {
+    let stop = start + 1;
+    while (stop < lines.length && /^\s/.test(lines[stop])) {
+      stop += 1;
+    }
+    lines.splice(start, stop - start, ...valueLines);
+  } else {
+    lines.push(...valueLines);
+  }
+  return joinFrontmatter({ ...parts, lines });
+}
+
+/**
+ * frontmatterの `depth` を書き換える。
+ * 本文を再取得せずに、より浅い経路で再発見された法令の深さだけを反映するために使う。
+ */
+export function updateFrontmatterDepth(markdown: string, depth: number): string {
+  return setFrontmatterField(markdown, 'depth', renderFrontmatterField('depth', depth));
+}
@@ -0,0 +1,104 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  applyPartialRange,
+  defaultCliOptions,
+  isAnchorOutsidePartialRange,
+  isBlockInPartialRange,
+  parsePartialRange,
+  renderMarkdown,
+} from './index.js';
+
+test('parsePartialRange: 条の範囲と編・章の指定を解釈する', () => {
+  assert.equal(parsePartialRange(), undefined);
+  assert.deepEqual(parsePartialRange('第二十六条-第五十六条'), {
+    label: '第二十六条-第五十六条',
+    articles: { from: [26], to: [56] },
+  });
+  assert.deepEqual(parsePartialRange('第二十七条の二から第三十条まで')?.articles, { from: [27, 2], to: [30] });
+  assert.deepEqual(parsePartialRange('26')?.articles, { from: [26], to: [26] });
+  assert.deepEqual(parsePartialRange(undefined, '第二編第一章,第三編'), {
+    label: '第二編第一章,第三編',
+    chapters: [['Pa_2', 'Ch_1'], ['Pa_3']],
+  });
+});
+
+test('parsePartialRange: 解釈できない指定はエラー', () => {
+  assert.throws(() => parsePartialRange('第五十六条-第二十六条'), /--articles/);
+  assert.throws(() => parsePartialRange('総則'), /--articles/);
+  assert.throws(() => parsePartialRange(undefined, '第二編総則'), /--chapters/);
+});
+
+test('isBlockInPartialRange: 本則の条だけを範囲で判定する', () => {
+  const range = parsePartialRange('第二十六条-第五十六条', '第二編');
+  assert.ok(range);
+  assert.ok(isBlockInPartialRange('Mp-Pa_2-Ch_1-At_26', range));
+  assert.ok(isBlockInPartialRange('Mp-Pa_2-Ch_2-Se_1-At_27_2', range));
+  assert.ok(!isBlockInPartialRange('Mp-Pa_2-Ch_3-At_56_2', range));
+  assert.ok(!isBlockInPartialRange('Mp-Pa_1-At_30', range));
+  assert.ok(!isBlockInPartialRange('417AC0000000086-Sp-At_30', range));
+});
+
+test('applyPartialRange: 範囲内の条に絞り込み、範囲外アンカーを判定できる', () => {
+  const block = (id: string) => ({ id, heading: id, paragraphs: [] });
+  const doc = {
+    lawId: '417AC0000000086',
+    title: '会社法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/417AC0000000086',
+    blocks: [block('Mp-Pa_1-At_25'), block('Mp-Pa_2-At_26'), block('Mp-Pa_2-At_27_2'), block('417AC0000000086-Sp')],
+  };
+  const range = parsePartialRange('26-56');
+  assert.ok(range);
+  const partial = applyPartialRange(doc, range);
+  assert.deepEqual(
+    partial.doc.blocks.map((b) => b.id),
+    ['Mp-Pa_2-At_26', 'Mp-Pa_2-At_27_2'],
+  );
+  assert.equal(partial.doc.partialRange, '26-56');
+  assert.ok(!isAnchorOutsidePartialRange('Mp-At_27_2-Pr_1', partial.articleKeys));
+  assert.ok(isAnchorOutsidePartialRange('Mp-Pa_1-At_25', partial.articleKeys));
+  assert.ok(!isAnchorOutsidePartialRange('417AC0000000086-Sp', partial.articleKeys));
+  assert.throws(() => applyPartialRange(doc, { label: '第百条', articles: { from: [100], to: [100] } }), /該当する条/);
+});
+
+test('renderMarkdown: 部分ノートの範囲外の条へのリンクは未解決参照として記録する', () => {
+  const doc = {
+    lawId: '417AC0000000086',
+    title: '会社法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/417AC0000000086',
+    partialRange: '26-56',
+    blocks: [
+      {
+        id: 'Mp-Pa_2-At_26',
+        heading: '第二十六条',
+        paragraphs: [
+          {
+            anchor: 'Mp-Pa_2-At_26-Pr_1',
+            segments: [
+              { type: 'link' as const, text: '第二十五条', href: '#Mp-Pa_1-At_25' },
+              { type: 'link' as const, text: '第二十七条', href: '#Mp-Pa_2-At_27' },
+            ],
+          },
+        ],
+      },
+    ],
+  };
+  const context = {
+    rootLawId: doc.lawId,
+    rootLawTitle: doc.title,
+    unresolved: [],
+    unresolvedSeen: new Set<string>(),
+    failures: [],
+    references: [],
+    partialScopes: new Map([[doc.lawId, new Set(['26', '27'])]]),
+  };
+  const { markdown } = renderMarkdown(doc, {}, defaultCliOptions(), context, 0);
+  assert.match(markdown, /^partial: true$/m);
+  assert.match(markdown, /^partial_range: "26-56"$/m);
+  assert.match(markdown, /第二十五条\[\[#Mp-Pa_2-At_27\|第二十七条\]\]/);
+  assert.deepEqual(
+    context.unresolved.map((record: { href: string; reason: string }) => [record.href, record.reason]),
+    [['#Mp-Pa_1-At_25', 'outside_partial_range']],
+  );
+});
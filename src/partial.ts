@@ -0,0 +1,129 @@
+import { parseJapaneseNumber } from './lawnum.js';
+import { anchorArticleKey, parseProvisionId } from './provision.js';
+import type { PartialRange, ScrapedLawDocument } from './types.js';
+
+const NUMBER_CHARS = '0-9０-９〇一二三四五六七八九十百千';
+const ARTICLE_SPEC_PATTERN = new RegExp(`^第?([${NUMBER_CHARS}]+)条?((?:の[${NUMBER_CHARS}]+)*)$`);
+const ARTICLE_RANGE_SEPARATOR = /\s*(?:-|〜|～|から)\s*/;
+const DIVISION_SPEC_PATTERN = new RegExp(`第([${NUMBER_CHARS}]+)(編|章|節|款|目)`, 'g');
+/** 編・章・節・款・目に対応するe-Gov本文DOMの要素IDセグメント名。 */
+const DIVISION_ID_PREFIXES: Record<string, string> = { 編: 'Pa', 章: 'Ch', 節: 'Se', 款: 'Ss', 目: 'Di' };
+
+function parseArticleSpec(text: string): number[] | undefined {
+  const matched = text.trim().replace(/まで$/, '').match(ARTICLE_SPEC_PATTERN);
+  if (!matched) {
+    return undefined;
+  }
+  const numbers = [matched[1], ...matched[2].split('の').filter(Boolean)].map(parseJapaneseNumber);
+  return numbers.every((value): value is number => value !== undefined && value > 0) ? numbers : undefined;
+}
+
+function parseDivisionSpec(text: string): string[] | undefined {
+  const spec = text.trim();
+  const segments: string[] = [];
+  let consumed = '';
+  for (const matched of spec.matchAll(DIVISION_SPEC_PATTERN)) {
+    const value = parseJapaneseNumber(matched[1]);
+    if (!value) {
+      return undefined;
+    }
+    segments.push(`${DIVISION_ID_PREFIXES[matched[2]]}_${value}`);
+    consumed += matched[0];
+  }
+  return segments.length > 0 && consumed === spec ? segments : undefined;
+}
+
+function compareNumbers(a: number[], b: number[]): number {
+  for (let index = 0; index < Math.max(a.length, b.length); index += 1) {
+    // 枝番の無い条は枝番0として比べ、第五十六条の二を第五十六条より後ろに並べる。
+    const diff = (a[index] ?? 0) - (b[index] ?? 0);
+    if (diff !== 0) {
+      return diff;
+    }
+  }
+  return 0;
+}
+
+function containsSegments(blockId: string, segments: string[]): boolean {
+  const blockSegments = blockId.split('-');
+  let position = 0;
+  for (const segment of blockSegments) {
+    if (segment === segments[position]) {
+      position += 1;
+      if (position === segments.length) {
+        return true;
+      }
+    }
+  }
+  return false;
+}
+
+/**
+ * `--articles`（例: 「第二十六条-第五十六条」「26-56」）と `--chapters`（例: 「第二編」「第二編第一章,第三編」）を解釈する。
+ * 両方を指定した場合は、両方に含まれる条だけを対象にする。どちらも未指定なら undefined を返す。
+ */
+export function parsePartialRange(articles?: string, chapters?: string): PartialRange | undefined {
+  if (articles === undefined && chapters === undefined) {
+    return undefined;
+  }
+  const range: PartialRange = { label: [chapters, articles].filter(Boolean).join(' ') };
+  if (articles !== undefined) {
+    const [fromText, toText = fromText, ...rest] = articles.split(ARTICLE_RANGE_SEPARATOR);
+    const from = parseArticleSpec(fromText);
+    const to = parseArticleSpec(toText);
+    if (!from || !to || rest.length > 0 || compareNumbers(from, to) > 0) {
+      throw new Error(`--articles は「第二十六条-第五十六条」や「26-56」の形で指定してください: ${articles}`);
+    }
+    range.articles = { from, to };
+  }
+  if (chapters !== undefined) {
+    const divisions = chapters.split(/[,、]/).map(parseDivisionSpec);
+    if (divisions.length === 0 || divisions.some((division) => division === undefined)) {
+      throw new Error(`--chapters は「第二編」や「第二編第一章,第三編」の形で指定してください: ${chapters}`);
+    }
+    range.chapters = divisions as string[][];
+  }
+  return range;
+}
+
+/**
+ * 本文ブロックが取得範囲に含まれるかを判定する。範囲指定は本則の条にだけ適用する。
+ */
+export function isBlockInPartialRange(blockId: string, range: PartialRange): boolean {
+  const parsed = parseProvisionId(blockId);
+  if (!blockId.startsWith('Mp-') || parsed?.kind !== 'article') {
+    return false;
+  }
+  if (
+    range.articles &&
+    (compareNumbers(parsed.from, range.articles.from) < 0 || compareNumbers(parsed.from, range.articles.to) > 0)
+  ) {
+    return false;
+  }
+  return !range.chapters || range.chapters.some((segments) => containsSegments(blockId, segments));
+}
+
+/**
+ * 本文を取得範囲の条だけに絞り込み、含まれる条の番号キー（「27_2」形式）を返す。
+ * 範囲に1条も含まれない場合は、指定の誤りとしてエラーにする。
+ */
+export function applyPartialRange(
+  doc: ScrapedLawDocument,
+  range: PartialRange,
+): { doc: ScrapedLawDocument; articleKeys: Set<string> } {
+  const blocks = doc.blocks.filter((block) => isBlockInPartialRange(block.id, range));
+  if (blocks.length === 0) {
+    throw new Error(`取得範囲に該当する条がありません: ${range.label}`);
+  }
+  const articleKeys = new Set(blocks.map((block) => (parseProvisionId(block.id)?.from ?? []).join('_')));
+  return { doc: { ...doc, blocks, partialRange: range.label }, articleKeys };
+}
+
+/**
+ * アンカー（例: `Mp-Ch_1-At_2-Pr_1`）が部分ノートの範囲外の条を指すかを判定する。
+ * 条を特定できないアンカー（法令全体や附則）は範囲外と断定できないため false を返す。
+ */
+export function isAnchorOutsidePartialRange(anchor: string, articleKeys: Set<string>): boolean {
+  const key = anchorArticleKey(anchor);
+  return key !== undefined && !articleKeys.has(key);
+}
@@ -0,0 +1,393 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import http from 'node:http';
+import type { AddressInfo } from 'node:net';
+import os from 'node:os';
+import path from 'node:path';
+
+import {
+  buildRunSummary,
+  candidateGraphRoots,
+  defaultCliOptions,
+  fetchLawMetadata,
+  getApiStats,
+  getFileName,
+  getRunStats,
+  isRepealedStatus,
+  parseLawSuccessor,
+  parseTitleList,
+  processLawGraphRoots,
+  recordVisit,
+  requeueFailedLaws,
+  resetRunStats,
+  setFrontmatterField,
+  unfinishedQueueRecords,
+  updateFrontmatterDepth,
+  writeMocNote,
+} from './index.js';
+import type { LawGraphFetchers } from './process.js';
+
+test('recordVisit: 浅い経路で再発見された法令の最小depthを更新する', () => {
+  // A -> B -> C -> D と A -> D の経路があり、先に深い経路で D を訪問したケース。
+  const edges: Record<string, string[]> = { A: ['D', 'B'], B: ['C'], C: ['D'], D: [] };
+  const visited = new Map<string, number>();
+  const states: Array<[string, number, string]> = [];
+  const stack: Array<[string, number]> = [['A', 0]];
+  while (stack.length > 0) {
+    const [lawId, depth] = stack.pop() as [string, number];
+    const state = recordVisit(visited, lawId, depth);
+    states.push([lawId, depth, state]);
+    if (state === 'seen') {
+      continue;
+    }
+    for (const child of edges[lawId]) {
+      stack.push([child, depth + 1]);
+    }
+  }
+
+  assert.equal(visited.get('D'), 1);
+  assert.ok(states.some(([lawId, depth, state]) => lawId === 'D' && depth === 1 && state === 'shallower'));
+  assert.equal(recordVisit(visited, 'D', 3), 'seen');
+});
+
+test('updateFrontmatterDepth: frontmatterのdepthのみ書き換える', () => {
+  const markdown = ['---', 'law_id: 334AC0000000121', 'depth: 2', '---', '', 'depth: 本文'].join('\n');
+  assert.equal(
+    updateFrontmatterDepth(markdown, 1),
+    ['---', 'law_id: 334AC0000000121', 'depth: 1', '---', '', 'depth: 本文'].join('\n'),
+  );
+  assert.equal(updateFrontmatterDepth('# no frontmatter', 1), '# no frontmatter');
+});
+
+test('setFrontmatterField: 配列値のキーを置き換え・削除できる', () => {
+  const markdown = ['---', 'law_id: X', 'referenced_articles:', '  - "第一条"', 'depth: 1', '---', '本文'].join('\n');
+  assert.equal(
+    setFrontmatterField(markdown, 'referenced_articles', ['referenced_articles:', '  - "第二条"']),
+    ['---', 'law_id: X', 'referenced_articles:', '  - "第二条"', 'depth: 1', '---', '本文'].join('\n'),
+  );
+  assert.equal(
+    setFrontmatterField(markdown, 'referenced_articles', []),
+    ['---', 'law_id: X', 'depth: 1', '---', '本文'].join('\n'),
+  );
+});
+
+test('parseTitleList: 空行とコメント行を除いて1行1法令名を返す', () => {
+  assert.deepEqual(parseTitleList('# 道路関係\n道路法\r\n\n  道路交通法  \n#メモ\n昭和三十四年法律第百二十一号\n'), [
+    '道路法',
+    '道路交通法',
+    '昭和三十四年法律第百二十一号',
+  ]);
+});
+
+test('requeueFailedLaws: 失敗した法令を訪問済みから外し、最も浅い深さで再投入する', () => {
+  const failure = (lawId: string, depth: number) => ({
+    timestamp: '2026-02-20T00:00:00Z',
+    law_id: lawId,
+    title: `law_${lawId}`,
+    depth,
+    error: 'timeout',
+  });
+  const visited = new Map([
+    ['129AC0000000089', 2],
+    ['140AC0000000045', 1],
+    ['334AC0000000121', 0],
+  ]);
+  const items = requeueFailedLaws([failure('129AC0000000089', 2), failure('140AC0000000045', 3)], visited);
+  assert.deepEqual(items, [
+    { lawId: '129AC0000000089', titleHint: 'law_129AC0000000089', depth: 2 },
+    { lawId: '140AC0000000045', titleHint: 'law_140AC0000000045', depth: 1 },
+  ]);
+  assert.deepEqual([...visited.keys()], ['334AC0000000121']);
+});
+
+test('unfinishedQueueRecords: 中断時の未取得キューを最も浅い深さで失敗記録にし、取得済みと上限超えを除く', () => {
+  const queue = [
+    { lawId: 'B', depth: 2 },
+    { lawId: 'A', depth: 1 },
+    { lawId: 'B', depth: 1, titleHint: '法令B' },
+    { lawId: 'C', depth: 1 },
+    { lawId: 'D', depth: 3 },
+  ];
+  const visited = new Map([['C', 1]]);
+  const dictionary = { A: { title: '法令A', safe_title: '法令A', file_name: '法令A_A.md', updated_at: '' } };
+  const records = unfinishedQueueRecords(queue, visited, 2, dictionary);
+  assert.deepEqual(
+    records.map(({ law_id, title, depth, error }) => ({ law_id, title, depth, error })),
+    [
+      { law_id: 'B', title: '法令B', depth: 1, error: '中断により未取得' },
+      { law_id: 'A', title: '法令A', depth: 1, error: '中断により未取得' },
+    ],
+  );
+});
+
+test('fetchLawMetadata: 既定の実行では law_data を取得せず、一覧APIの revision_info から廃止と新法を読む', async () => {
+  const paths: string[] = [];
+  const server = http.createServer((req, res) => {
+    const url = new URL(req.url ?? '/', 'http://localhost');
+    paths.push(url.pathname);
+    res.writeHead(200, { 'content-type': 'application/json' });
+    res.end(
+      JSON.stringify({
+        laws: [
+          {
+            law_info: { law_id: url.searchParams.get('law_id') },
+            revision_info: {
+              law_title: '商法特例法',
+              repeal_status: 'Repeal',
+              amendment_law_id: '417AC0000000087',
+              amendment_law_title: '会社法の施行に伴う関係法律の整備等に関する法律',
+            },
+          },
+        ],
+        total_count: 1,
+      }),
+    );
+  });
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+  const { port } = server.address() as AddressInfo;
+  try {
+    const options = { ...defaultCliOptions(), apiBaseUrl: `http://127.0.0.1:${port}`, requestIntervalMs: 0 };
+    const { lawData, revisionInfo } = await fetchLawMetadata(options, '349AC0000000022', true);
+    assert.equal(lawData, undefined);
+    assert.deepEqual(paths, ['/api/2/laws']);
+    assert.equal(isRepealedStatus(revisionInfo?.repeal_status), true);
+    assert.deepEqual(parseLawSuccessor(revisionInfo), {
+      lawId: '417AC0000000087',
+      title: '会社法の施行に伴う関係法律の整備等に関する法律',
+    });
+  } finally {
+    server.closeAllConnections();
+    await new Promise((resolve) => server.close(resolve));
+  }
+});
+
+test('processLawGraphRoots: --if-exists skip で取得し直さなかった法令も一覧ノートに載せる', async () => {
+  const outputDir = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-moc-skip-'));
+  const note = (lawId: string, body: string) => ['---', `law_id: ${lawId}`, '---', '', body, ''].join('\n');
+  await fs.writeFile(
+    path.join(outputDir, '特許法_334AC0000000121.md'),
+    note('334AC0000000121', '[[laws/民法_129AC0000000089.md#Mp-At_1|民法第一条]]'),
+  );
+  await fs.writeFile(path.join(outputDir, '民法_129AC0000000089.md'), note('129AC0000000089', '本文'));
+  const entry = (title: string, lawId: string) => ({
+    title,
+    safe_title: title,
+    file_name: `${title}_${lawId}.md`,
+    updated_at: '',
+  });
+  const dictionary = {
+    '334AC0000000121': entry('特許法', '334AC0000000121'),
+    '129AC0000000089': entry('民法', '129AC0000000089'),
+  };
+  const options = {
+    ...defaultCliOptions(),
+    outputDir,
+    unresolvedPath: path.join(outputDir, 'unresolved.json'),
+    ifExists: 'skip' as const,
+    maxDepth: 1,
+    mocName: '_MOC.md',
+    requestIntervalMs: 0,
+  };
+
+  const result = await processLawGraphRoots(options, [{ lawId: '334AC0000000121', title: '特許法' }], dictionary, {
+    unresolved: [],
+    failures: [],
+  });
+  await writeMocNote(options, dictionary);
+
+  assert.deepEqual([result.written, result.skipped], [0, 2]);
+  const moc = await fs.readFile(path.join(outputDir, '_MOC.md'), 'utf8');
+  assert.match(moc, /^- \[\[laws\/特許法_334AC0000000121\.md\|特許法\]\]$/m);
+  assert.match(moc, /^- \[\[laws\/民法_129AC0000000089\.md\|民法\]\]（被参照: 1件）$/m);
+});
+
+test('processLawGraphRoots: 既存ノートの法令はスキップとして数え、その参照先の取得は続ける', async () => {
+  const outputDir = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-skip-count-'));
+  await fs.writeFile(
+    path.join(outputDir, '特許法_334AC0000000121.md'),
+    ['---', 'law_id: 334AC0000000121', '---', '', '[[laws/民法_129AC0000000089.md#Mp-At_1|民法第一条]]', ''].join('\n'),
+  );
+  const loaded: string[] = [];
+  const fetchers: LawGraphFetchers = {
+    loadLawDocument: async (lawId) => {
+      loaded.push(lawId);
+      return { lawId, title: '民法', sourceUrl: `https://laws.e-gov.go.jp/law/${lawId}`, blocks: [] };
+    },
+    fetchLawMetadata: async () => ({}),
+    fetchLawTitleById: async () => assert.fail('辞書にある法令の法令名は問い合わせない'),
+    searchLawCandidates: async () => assert.fail('法令名では検索しない'),
+  };
+  const entry = (title: string, lawId: string) => ({
+    title,
+    safe_title: title,
+    file_name: `${title}_${lawId}.md`,
+    updated_at: '',
+  });
+  const dictionary = {
+    '334AC0000000121': entry('特許法', '334AC0000000121'),
+    '129AC0000000089': entry('民法', '129AC0000000089'),
+  };
+  const options = {
+    ...defaultCliOptions(),
+    outputDir,
+    unresolvedPath: path.join(outputDir, 'unresolved.json'),
+    ifExists: 'skip' as const,
+    maxDepth: 1,
+    requestIntervalMs: 0,
+  };
+
+  resetRunStats();
+  const result = await processLawGraphRoots(
+    options,
+    [{ lawId: '334AC0000000121', title: '特許法' }],
+    dictionary,
+    { unresolved: [], failures: [] },
+    fetchers,
+  );
+
+  assert.deepEqual(loaded, ['129AC0000000089']);
+  assert.deepEqual([result.written, result.skipped], [1, 1]);
+  const summary = buildRunSummary(getRunStats(), getApiStats(), 0, 0, 0, 0);
+  assert.deepEqual([summary.fetched, summary.skipped], [1, 1]);
+  resetRunStats();
+});
+
+test('processLawGraphRoots: --no-follow-existing では既存ノートの参照先を取得キューに積まない', async () => {
+  const traverse = async (followExisting: boolean) => {
+    const outputDir = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-follow-existing-'));
+    await fs.writeFile(
+      path.join(outputDir, '特許法_334AC0000000121.md'),
+      ['---', 'law_id: 334AC0000000121', '---', '', '[[laws/民法_129AC0000000089.md|民法]]', ''].join('\n'),
+    );
+    const loaded: string[] = [];
+    const fetchers: LawGraphFetchers = {
+      loadLawDocument: async (lawId) => {
+        loaded.push(lawId);
+        return { lawId, title: '民法', sourceUrl: `https://laws.e-gov.go.jp/law/${lawId}`, blocks: [] };
+      },
+      fetchLawMetadata: async () => ({}),
+      fetchLawTitleById: async () => assert.fail('辞書にある法令の法令名は問い合わせない'),
+      searchLawCandidates: async () => assert.fail('法令名では検索しない'),
+    };
+    const dictionary = {
+      '334AC0000000121': { title: '特許法', safe_title: '特許法', file_name: '特許法_334AC0000000121.md', updated_at: '' },
+      '129AC0000000089': { title: '民法', safe_title: '民法', file_name: '民法_129AC0000000089.md', updated_at: '' },
+    };
+    const options = {
+      ...defaultCliOptions(),
+      outputDir,
+      unresolvedPath: path.join(outputDir, 'unresolved.json'),
+      ifExists: 'skip' as const,
+      followExisting,
+      maxDepth: 1,
+      requestIntervalMs: 0,
+    };
+    const result = await processLawGraphRoots(
+      options,
+      [{ lawId: '334AC0000000121', title: '特許法' }],
+      dictionary,
+      { unresolved: [], failures: [] },
+      fetchers,
+    );
+    return { loaded, skipped: result.skipped };
+  };
+
+  assert.deepEqual(await traverse(true), { loaded: ['129AC0000000089'], skipped: 1 });
+  assert.deepEqual(await traverse(false), { loaded: [], skipped: 1 });
+});
+
+test('processLawGraphRoots: 複数の起点法令と共有する参照先を、BFSでもDFSでも1回ずつ取得する', async () => {
+  // 特許法 -> 実用新案法・民法、実用新案法 -> 民法。実用新案法は起点法令でもあり、DFSでは特許法の参照先として先に取得する。
+  const links: Record<string, string[]> = {
+    '334AC0000000121': ['334AC0000000123', '129AC0000000089'],
+    '334AC0000000123': ['129AC0000000089'],
+    '129AC0000000089': [],
+  };
+  const titles: Record<string, string> = {
+    '334AC0000000121': '特許法',
+    '334AC0000000123': '実用新案法',
+    '129AC0000000089': '民法',
+  };
+  const traverse = async (traversal: 'bfs' | 'dfs') => {
+    const outputDir = await fs.mkdtemp(path.join(os.tmpdir(), `laws-roots-${traversal}-`));
+    const loaded: string[] = [];
+    const metadata: string[] = [];
+    const fetchers: LawGraphFetchers = {
+      loadLawDocument: async (lawId) => {
+        loaded.push(lawId);
+        return {
+          lawId,
+          title: titles[lawId],
+          sourceUrl: `https://laws.e-gov.go.jp/law/${lawId}`,
+          blocks: [
+            {
+              id: 'Mp-At_1',
+              heading: '第一条',
+              paragraphs: [
+                {
+                  anchor: 'Mp-At_1-Pr_1',
+                  segments: links[lawId].map((target) => ({
+                    type: 'link' as const,
+                    text: titles[target],
+                    href: `/law/${target}`,
+                  })),
+                },
+              ],
+            },
+          ],
+        };
+      },
+      fetchLawMetadata: async (_options, lawId) => {
+        metadata.push(lawId);
+        return {};
+      },
+      fetchLawTitleById: async () => assert.fail('辞書にある法令の法令名は問い合わせない'),
+      searchLawCandidates: async () => assert.fail('リンクから law_id を読める参照は法令名で検索しない'),
+    };
+    const dictionary = Object.fromEntries(
+      Object.entries(titles).map(([lawId, title]) => [
+        lawId,
+        { title, safe_title: title, file_name: `${title}_${lawId}.md`, updated_at: '' },
+      ]),
+    );
+    const options = {
+      ...defaultCliOptions(),
+      outputDir,
+      unresolvedPath: path.join(outputDir, 'unresolved.json'),
+      maxDepth: 2,
+      traversal,
+      requestIntervalMs: 0,
+    };
+    const roots = [
+      { lawId: '334AC0000000121', title: '特許法' },
+      { lawId: '334AC0000000123', title: '実用新案法' },
+    ];
+    const result = await processLawGraphRoots(options, roots, dictionary, { unresolved: [], failures: [] }, fetchers);
+    const utilityModel = await fs.readFile(path.join(outputDir, '実用新案法_334AC0000000123.md'), 'utf8');
+    return { loaded, metadata, written: result.written, utilityModel };
+  };
+
+  const bfs = await traverse('bfs');
+  const dfs = await traverse('dfs');
+  assert.deepEqual(bfs.loaded, ['334AC0000000121', '334AC0000000123', '129AC0000000089']);
+  assert.deepEqual(dfs.loaded, ['334AC0000000121', '129AC0000000089', '334AC0000000123']);
+  for (const run of [bfs, dfs]) {
+    assert.deepEqual(run.metadata, run.loaded);
+    assert.equal(run.written, 3);
+    // DFSで参照先として深さ1で取得した起点法令も、起点法令としての深さ0に直す。
+    assert.match(run.utilityModel, /^depth: 0$/m);
+  }
+});
+
+test('candidateGraphRoots: 同名の候補は法令番号（無ければ公布日）でファイル名を分ける', () => {
+  const roots = candidateGraphRoots([
+    { law_id: '322AC0000000067', law_title: '地方自治法', law_num: '昭和22年法律第67号' },
+    { law_id: '322AC0000000999', law_title: '地方自治法', promulgation_date: '1947-04-17' },
+    { law_title: '地方自治法', law_num: '昭和22年法律第68号' },
+  ]);
+  assert.deepEqual(
+    roots.map((root) => getFileName(root.lawId, root.fileStem ?? root.title)),
+    ['地方自治法_昭和二十二年法律第六十七号_322AC0000000067.md', '地方自治法_1947-04-17_322AC0000000999.md'],
+  );
+});
@@ -1,19 +1,96 @@
 import fs from 'node:fs/promises';
 import path from 'node:path';
-import { fetchLawTitleById } from './api.js';
+import {
+  fetchLawContents,
+  fetchLawTitleById,
+  fetchRevisionInfoById,
+  isRepealedStatus,
+  parseLawSuccessor,
+  searchLawCandidates,
+  searchLawCandidatesWithFallback,
+  selectBestCandidate,
+} from './api.js';
+import { findCandidateChoice } from './choices.js';
+import { writeLawChangelog } from './changelog.js';
+import { findReferenceCycles } from './cycles.js';
+import { stripVolatileFrontmatter, unifiedDiff } from './diff.js';
+import { acceptDictionaryEntry, findLawIdByAlias, findLawsByTitle } from './dictionary.js';
+import { noteFileForFormat, renderHtml, renderPlainText } from './formats.js';
+import { renderFrontmatterField, splitFrontmatter } from './frontmatter.js';
+import { openLawIndex } from './indexdb.js';
+import { canonicalLawNum, findLawIdByLawNum, normalizeLawNum } from './lawnum.js';
+import { checkMissingLinks, isStubNote, listNotes, unlinkMissingLinks } from './links.js';
+import { applyPartialRange, parsePartialRange } from './partial.js';
 import {
   addExistingNoteIndex,
   buildExistingNoteIndex,
+  buildLawNumIndex,
   getFileName,
   notePath,
   resolveExistingNotePath,
+  resolveNoteRelativePath,
   scanReferencedLawIdsFromMarkdown,
+  setFrontmatterField,
   toSafeTitle,
+  updateFrontmatterDepth,
 } from './notes.js';
-import { collectReferencedLawIds, renderMarkdown } from './render.js';
-import { scrapeLawDocumentWithRetry } from './scrape.js';
-import { appendUnresolved, writeJson } from './storage.js';
-import type { CliOptions, ExistingNoteIndex, LawDictionary, ProcessContext, QueueItem } from './types.js';
+import {
+  collectCitedLaws,
+  collectReferencedLawIds,
+  countStructures,
+  countTags,
+  extractBacklinkLines,
+  mergeBacklinksSection,
+  renderBacklinksSection,
+  renderCitationSection,
+  renderCitedRevisionsField,
+  renderMarkdown,
+  renderReferencedArticleFields,
+  renderReferencedByFields,
+  renderSupersedesField,
+  replaceBacklinksSection,
+  replaceCitationSection,
+} from './render.js';
+import { formatUnsupportedElements, loadLawDocument } from './scrape.js';
+import {
+  appendFailedLaws,
+  appendUnresolved,
+  appendUnresolvedLog,
+  clearUnresolvedLog,
+  dictionaryProvenance,
+  encodeNoteText,
+  findDictionaryKeyConflicts,
+  loadExistingUnresolved,
+  loadFailedLaws,
+  loadLawDataArchive,
+  readNoteText,
+  reportDictionaryKeyConflicts,
+  saveDictionary,
+  writeFileAtomic,
+  writeJson,
+} from './storage.js';
+import { refLawName } from './refpolicy.js';
+import { collectUnparsedRefTexts, rememberInvalidRefNames, resolveRefTexts } from './refsearch.js';
+import { recordMocEntry, writeMocNote } from './moc.js';
+import { writeTimelineNote } from './timeline.js';
+import { countRunStat, formatLawTiming, recordLawTiming, recordRunFailure } from './stats.js';
+import { confirm, isCancelRequested } from './utils.js';
+import type {
+  CliOptions,
+  DeferredSaves,
+  ExistingNoteIndex,
+  FailedLawRecord,
+  LawCandidate,
+  LawDataResponse,
+  LawDictionary,
+  LawDictionaryEntry,
+  LawGraphResult,
+  LawGraphRoot,
+  ProcessContext,
+  QueueItem,
+  ScrapedLawDocument,
+  Traversal,
+} from './types.js';
 
 async function ensureOutputDir(outputDir: string): Promise<void> {
   await fs.mkdir(outputDir, { recursive: true });
@@ -43,7 +120,7 @@ async function removeOldNoteIfRenamed(
     }
   }
   const current = existingIndex.get(lawId) ?? [];
-  const filtered = current.filter((candidate) => path.basename(candidate) !== oldFileName);
+  const filtered = current.filter((candidate) => candidate !== oldPath);
   if (filtered.length > 0) {
     existingIndex.set(lawId, filtered);
   } else {
@@ -51,43 +128,437 @@ async function removeOldNoteIfRenamed(
   }
 }
 
+export type VisitState = 'new' | 'shallower' | 'seen';
+
+/**
+ * 訪問済み法令の最小depthを更新し、今回の訪問の扱いを返す。
+ * 取得済みの法令がより浅い経路で再発見された場合は `shallower` を返し、
+ * 呼び出し側でfrontmatterのdepth更新と子参照の再投入を行えるようにする。
+ */
+export function recordVisit(visited: Map<string, number>, lawId: string, depth: number): VisitState {
+  const previous = visited.get(lawId);
+  if (previous === undefined) {
+    visited.set(lawId, depth);
+    return 'new';
+  }
+  if (depth < previous) {
+    visited.set(lawId, depth);
+    return 'shallower';
+  }
+  return 'seen';
+}
+
+/**
+ * 取得順（`--traversal`）に従って次に処理するキュー項目を取り出す。
+ * DFSでは後から積んだ参照先を先に処理するため、同じ法令を深い経路で先に訪問することがある。
+ * その場合も浅い経路での再発見時に `recordVisit` がdepthを直して子を再投入するので、取得される法令の集合はBFSと変わらない。
+ */
+export function takeQueueItem(queue: QueueItem[], traversal: Traversal): QueueItem | undefined {
+  return traversal === 'dfs' ? queue.pop() : queue.shift();
+}
+
+/**
+ * 中断したときにキューに残っていた、まだ取得していない法令を失敗記録の形にする。`--retry-failed` で続きから取り直せる。
+ * 同じ法令が複数の経路で積まれていれば最も浅いものを残し、`--max-depth` を超えるものと取得済みのものは除く。
+ */
+export function unfinishedQueueRecords(
+  queue: QueueItem[],
+  visited: Map<string, number>,
+  maxDepth: number,
+  dictionary: LawDictionary,
+): FailedLawRecord[] {
+  const depths = new Map<string, QueueItem>();
+  for (const item of queue) {
+    const previous = depths.get(item.lawId);
+    if (item.depth <= maxDepth && !visited.has(item.lawId) && (!previous || item.depth < previous.depth)) {
+      depths.set(item.lawId, item);
+    }
+  }
+  const timestamp = new Date().toISOString();
+  return [...depths.values()].map((item) => ({
+    timestamp,
+    law_id: item.lawId,
+    title: item.titleHint ?? dictionary[item.lawId]?.title ?? `law_${item.lawId}`,
+    depth: item.depth,
+    error: '中断により未取得',
+  }));
+}
+
+/**
+ * 取得に失敗した法令を再試行用のキュー項目に戻し、訪問済みの記録から外す。
+ * 失敗後により浅い経路で再発見されている場合は、その深さで取り直す。
+ */
+export function requeueFailedLaws(failures: FailedLawRecord[], visited: Map<string, number>): QueueItem[] {
+  return failures.map((failure) => {
+    const depth = Math.min(failure.depth, visited.get(failure.law_id) ?? failure.depth);
+    visited.delete(failure.law_id);
+    return { lawId: failure.law_id, titleHint: failure.title, depth };
+  });
+}
+
+interface ProcessedNote {
+  filePath?: string;
+  referencedLawIds: string[];
+}
+
+/**
+ * 1法令分の `law_data` と、廃止状態・前後関係・リビジョンIDを読む `revision_info`。取得しなかった・できなかった値は undefined。
+ */
+export interface LawMetadata {
+  lawData?: LawDataResponse;
+  revisionInfo?: Record<string, unknown>;
+}
+
+/**
+ * `--api-stats`（JSON形式のタグ木から構造要素数を数える）・`--dump-tags`・`--archive-dir` のために `law_data` を取得する。
+ * いずれも不要な場合や取得失敗時は `lawData` を undefined にし、統計はDOMからの概算にフォールバックさせる。
+ * `revision_info` は `superseded_by` / `supersedes` / `repealed` を既定の実行でも欠かさないよう、
+ * `law_data` を取得しなかったときは本文を含まない一覧API（`/api/2/laws?law_id=`）から読む。
+ * 失敗時は、保存や前後関係が欠けたことが分かるよう警告する。
+ */
+export async function fetchLawMetadata(
+  options: CliOptions,
+  lawId: string,
+  countsStructures: boolean,
+): Promise<LawMetadata> {
+  // 統計のためだけに法令ごとの本文取得を増やさないよう、既定では他の用途で取得したときだけ構造を数える。
+  const needed = (countsStructures && options.apiStats) || options.dumpTags || Boolean(options.archiveDir);
+  if (needed) {
+    try {
+      const lawData = (await fetchLawContents(options, lawId)).data;
+      return { lawData, revisionInfo: lawData.revision_info };
+    } catch (error) {
+      warnMetadataFailure('law_data', lawId, error);
+    }
+  }
+  try {
+    return { revisionInfo: await fetchRevisionInfoById(options, lawId) };
+  } catch (error) {
+    warnMetadataFailure('revision_info', lawId, error);
+    return {};
+  }
+}
+
+function warnMetadataFailure(name: string, lawId: string, error: unknown): void {
+  const message = error instanceof Error ? error.message : String(error);
+  process.stderr.write(`警告: ${name} を取得できませんでした: ${lawId}: ${message}\n`);
+}
+
+/**
+ * グラフ探索で法令ページ・e-Gov APIを読む関数。テストでは通信せずに探索を確かめるため差し替える。
+ */
+export interface LawGraphFetchers {
+  loadLawDocument: (lawId: string, options: CliOptions) => Promise<ScrapedLawDocument>;
+  fetchLawMetadata: (options: CliOptions, lawId: string, countsStructures: boolean) => Promise<LawMetadata>;
+  fetchLawTitleById: (options: CliOptions, lawId: string) => Promise<string | undefined>;
+  searchLawCandidates: (options: CliOptions, lawTitle: string) => Promise<LawCandidate[]>;
+}
+
+const defaultLawGraphFetchers: LawGraphFetchers = {
+  loadLawDocument,
+  fetchLawMetadata,
+  fetchLawTitleById,
+  searchLawCandidates,
+};
+
+/**
+ * `--dump-tags` 用に、1法令分の `law_full_text` に出現したタグの種類と出現回数を標準エラーへ出力する。
+ */
+function dumpTagCounts(title: string, lawId: string, fullText: unknown): void {
+  if (fullText === undefined) {
+    process.stderr.write(`タグ統計: law_full_text を取得できませんでした: ${title} (${lawId})\n`);
+    return;
+  }
+  const counts = Object.entries(countTags(fullText)).sort(
+    ([a, countA], [b, countB]) => countB - countA || a.localeCompare(b),
+  );
+  process.stderr.write(`タグ統計: ${title} (${lawId}) ${counts.length}種類\n`);
+  for (const [tag, count] of counts) {
+    process.stderr.write(`  ${tag}\t${count}\n`);
+  }
+}
+
+/**
+ * `--split-by chapter` で分割したセクションノートを書き出す。
+ * セクションノートは辞書に載らないため、差分の比較元は同じパスの既存ファイルとする。
+ */
+async function writeSectionNotes(
+  options: CliOptions,
+  pendingNotes: Map<string, PendingNote>,
+  sectionNotes: Array<{ relativePath: string; markdown: string }>,
+): Promise<string[]> {
+  const sectionPaths: string[] = [];
+  for (const section of sectionNotes) {
+    const sectionPath = notePath(options.outputDir, section.relativePath);
+    sectionPaths.push(sectionPath);
+    if (options.diff || options.dryRun) {
+      const basePath = (await fileExists(sectionPath)) ? sectionPath : undefined;
+      await writeNote(options, pendingNotes, sectionPath, section.markdown, basePath);
+      continue;
+    }
+    await fs.mkdir(path.dirname(sectionPath), { recursive: true });
+    await writeNote(options, pendingNotes, sectionPath, section.markdown);
+  }
+  return sectionPaths;
+}
+
+async function fileExists(filePath: string): Promise<boolean> {
+  try {
+    await fs.access(filePath);
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * `--diff` / `--dry-run` でディスクへ書かずに保持している生成結果。
+ * `basePath` は差分の比較元となる既存ノート（新規ならundefined）。
+ */
+interface PendingNote {
+  basePath?: string;
+  markdown: string;
+}
+
+async function readNote(pendingNotes: Map<string, PendingNote>, filePath: string): Promise<string> {
+  return pendingNotes.get(filePath)?.markdown ?? (await readNoteText(filePath));
+}
+
+async function writeNote(
+  options: CliOptions,
+  pendingNotes: Map<string, PendingNote>,
+  filePath: string,
+  markdown: string,
+  basePath?: string,
+): Promise<void> {
+  if (options.diff || options.dryRun) {
+    // 後追い更新でも比較元は最初に決めた既存ノートのまま保つ。
+    pendingNotes.set(filePath, { basePath: pendingNotes.get(filePath)?.basePath ?? basePath, markdown });
+    return;
+  }
+  await writeFileAtomic(filePath, encodeNoteText(markdown, options));
+}
+
+/**
+ * `--link-only-existing` で、この実行で書いたノートから行き先の無いリンクを外す。
+ * 再帰取得の途中では取得される法令が確定しないため、全取得を終えてから出力ディレクトリ（と書き込み保留中のノート）を見て判定する。
+ */
+async function unlinkMissingNoteLinks(
+  options: CliOptions,
+  pendingNotes: Map<string, PendingNote>,
+  filePaths: string[],
+): Promise<void> {
+  const toRelative = (filePath: string) => path.relative(options.outputDir, filePath).split(path.sep).join('/');
+  const notePaths = new Set([...(await listNotes(options.outputDir)), ...[...pendingNotes.keys()].map(toRelative)]);
+  for (const filePath of new Set(filePaths)) {
+    const markdown = await readNote(pendingNotes, filePath);
+    const updated = unlinkMissingLinks({ path: toRelative(filePath), markdown }, notePaths);
+    if (updated !== markdown) {
+      await writeNote(options, pendingNotes, filePath, updated);
+    }
+  }
+}
+
+async function refreshNoteDepth(
+  options: CliOptions,
+  pendingNotes: Map<string, PendingNote>,
+  filePath: string,
+  depth: number,
+): Promise<void> {
+  const markdown = await readNote(pendingNotes, filePath);
+  const updated = updateFrontmatterDepth(markdown, depth);
+  if (updated !== markdown) {
+    await writeNote(options, pendingNotes, filePath, updated);
+  }
+}
+
+/**
+ * 保持しておいた生成結果と既存ノートの unified diff を表示し、変更のあったノート数を返す。
+ */
+async function printNoteDiffs(pendingNotes: Map<string, PendingNote>): Promise<number> {
+  let changedCount = 0;
+  for (const [filePath, note] of pendingNotes) {
+    const before = note.basePath ? await readNoteText(note.basePath) : '';
+    const diff = unifiedDiff(
+      stripVolatileFrontmatter(before),
+      stripVolatileFrontmatter(note.markdown),
+      note.basePath ?? '/dev/null',
+      filePath,
+    );
+    if (!diff) {
+      process.stdout.write(`変更なし: ${filePath}\n`);
+      continue;
+    }
+    changedCount += 1;
+    process.stdout.write(diff);
+  }
+  process.stdout.write(`差分: 変更あり ${changedCount}件 / 変更なし ${pendingNotes.size - changedCount}件\n`);
+  return changedCount;
+}
+
 /**
  * BFSで法令を取得し、Markdownへ変換して保存する。
+ * `rootFileStem` を渡すと、起点法令のファイル名だけ法令名の代わりにその文字列から作る。
  */
 export async function processLawGraph(
   options: CliOptions,
   rootLawId: string,
   rootLawTitle: string,
   dictionary: LawDictionary,
-): Promise<void> {
+  rootFileStem?: string,
+  deferred?: DeferredSaves,
+): Promise<LawGraphResult> {
+  return processLawGraphRoots(
+    options,
+    [{ lawId: rootLawId, title: rootLawTitle, fileStem: rootFileStem }],
+    dictionary,
+    deferred,
+  );
+}
+
+/**
+ * 複数の起点法令を1つの取得キューに積み、1回のグラフ探索で取得する。
+ * 起点法令ごとに探索し直すと、共有する参照先を取得し直して上書きし、被参照の反映でも先の起点法令からの参照を失うため、
+ * 訪問済みの記録と参照関係を全起点法令で共有する。深さ0の起点法令1件だけを取得するときだけ、その取得失敗で例外を投げる。
+ */
+export async function processLawGraphRoots(
+  options: CliOptions,
+  roots: LawGraphRoot[],
+  dictionary: LawDictionary,
+  deferred?: DeferredSaves,
+  fetchers: LawGraphFetchers = defaultLawGraphFetchers,
+): Promise<LawGraphResult> {
   await ensureOutputDir(options.outputDir);
-  const existingIndex: ExistingNoteIndex =
-    options.ifExists === 'skip' ? await buildExistingNoteIndex(options.outputDir) : new Map();
+  // overwrite時も、ファイル名スキーム変更などで別名の既存ノートが残っていないかを警告するため索引を作る。
+  const existingIndex: ExistingNoteIndex = await buildExistingNoteIndex(options.outputDir);
+  // 実行サマリの辞書追加件数は、この時点で辞書に無かった法令を数える。
+  const knownLawIds = new Set(Object.keys(dictionary));
 
-  const queue: QueueItem[] = [{ lawId: rootLawId, titleHint: rootLawTitle, depth: 0 }];
-  const visited = new Set<string>();
+  const partialRange = parsePartialRange(options.articles, options.chapters);
+  const rootsById = new Map(roots.map((root) => [root.lawId, root]));
+  // 深さ0のものだけが起点法令で、途中の深さから取り直す法令は参照先として扱う。
+  const rootLawIds = new Set(roots.filter((root) => !root.depth).map((root) => root.lawId));
+  const resumedLawIds = new Set(roots.filter((root) => root.depth).map((root) => root.lawId));
+  // DFSでも先頭の起点法令から取り出すよう、積む順を取得順に合わせる。
+  const queue: QueueItem[] = roots.map((root) => ({
+    lawId: root.lawId,
+    titleHint: root.title,
+    depth: root.depth ?? 0,
+  }));
+  if (options.traversal === 'dfs') {
+    queue.reverse();
+  }
+  // 未解決参照の記録に残す起点法令は、その法令を最初に積んだ起点法令とする。
+  const originRoots = new Map(roots.map((root) => [root.lawId, root]));
+  const visited = new Map<string, number>();
+  const processedNotes = new Map<string, ProcessedNote>();
+  // frontmatterの `referenced_by` 用に、法令ごとに取得対象へ積んだ参照元を集める。
+  const referencedBy = new Map<string, Set<string>>();
+  // frontmatterの `supersedes` 用に、取得した旧法の law_id から新法の law_id への対応を集める。
+  const successors = new Map<string, string>();
+  // `--citation-style` 用に、取得した法令の law_id からリビジョンIDへの対応を集める。
+  const revisionIds = new Map<string, string>();
+  // 書き直す前の既存ノートにあった被参照セクションの項目。この実行で取得しない参照元の項目を失わないよう、
+  // 後処理で今回の参照関係から作った項目と合わせる。
+  const previousBacklinks = new Map<string, string[]>();
+  // この実行で本文を描画し直した法令。これらの法令からの既存の被参照項目は、今回の参照関係で置き換える。
+  const renderedLawIds = new Set<string>();
+  // 途中の深さから取り直す法令は参照元をこの実行で取得しないため、`referenced_by` で参照元を失わないよう、
+  // 出力先の既存ノートのリンクから参照元を補う。
+  if (resumedLawIds.size > 0) {
+    const lawNumIndex = buildLawNumIndex(dictionary);
+    for (const [sourceLawId, filePaths] of existingIndex) {
+      // 取り直す法令自身のノートは描画し直すため、そのリンクは今回の参照関係に含まれる。
+      if (resumedLawIds.has(sourceLawId)) {
+        continue;
+      }
+      for (const filePath of filePaths) {
+        const markdown = await readNoteText(filePath);
+        const { referencedLawIds } = scanReferencedLawIdsFromMarkdown(
+          markdown,
+          options.refScope,
+          lawNumIndex,
+          options.headingBaseLevel,
+        );
+        for (const lawId of referencedLawIds) {
+          if (resumedLawIds.has(lawId)) {
+            referencedBy.set(lawId, (referencedBy.get(lawId) ?? new Set()).add(sourceLawId));
+          }
+        }
+      }
+    }
+  }
+  const enqueueReferences = (sourceLawId: string, lawIds: string[], depth: number): void => {
+    for (const lawId of lawIds) {
+      queue.push({ lawId, depth });
+      if (!originRoots.has(lawId)) {
+        originRoots.set(lawId, originRoots.get(sourceLawId) as LawGraphRoot);
+      }
+      if (lawId !== sourceLawId) {
+        referencedBy.set(lawId, (referencedBy.get(lawId) ?? new Set()).add(sourceLawId));
+      }
+    }
+  };
+  const pendingNotes = new Map<string, PendingNote>();
+  const writesNotes = !options.diff && !options.dryRun;
+  const sectionNotePaths: string[] = [];
+  let writtenCount = 0;
+  let skippedCount = 0;
+  const existingUnresolved = await loadExistingUnresolved(options.unresolvedPath);
   const context: ProcessContext = {
-    rootLawId,
-    rootLawTitle,
+    rootLawId: roots[0].lawId,
+    rootLawTitle: roots[0].title,
     unresolved: [],
     unresolvedSeen: new Set(),
+    failures: [],
+    references: [],
+    ignoredRefs: new Set(
+      existingUnresolved.filter((record) => record.status === 'ignored').map((record) => record.href),
+    ),
+    invalidRefNames: new Set(
+      existingUnresolved.filter((record) => record.status === 'invalid').map((record) => refLawName(record.raw_text)),
+    ),
   };
 
-  while (queue.length > 0) {
-    const item = queue.shift() as QueueItem;
-    if (item.depth > options.maxDepth) {
-      continue;
+  // `--flush-each` では途中で終わっても成果が残るよう、法令を書き込むたびに未解決参照を追記専用ログへ書き足し、
+  // 辞書は全量の書き込みが重いため一定件数ごとに保存する。ログは実行終了時の保存後に消す。
+  let flushedUnresolvedCount = 0;
+  let unsavedLawCount = 0;
+  const flushProgress = async (): Promise<void> => {
+    await appendUnresolvedLog(options.unresolvedPath, context.unresolved.slice(flushedUnresolvedCount));
+    flushedUnresolvedCount = context.unresolved.length;
+    unsavedLawCount += 1;
+    if (unsavedLawCount >= options.flushDictionaryEvery) {
+      await saveDictionary(options, dictionary);
+      unsavedLawCount = 0;
     }
-    if (visited.has(item.lawId)) {
-      continue;
+  };
+
+  // `--index-db` はノートを書き込む実行でだけ更新する（`--dry-run` / `--diff` では触らない）。
+  const lawIndex = writesNotes && options.indexDbPath ? await openLawIndex(options.indexDbPath) : undefined;
+
+  // 法令1件分の取得・書き出し。失敗はループ側でまとめて扱う。
+  const processQueueItem = async (item: QueueItem, visitState: VisitState): Promise<void> => {
+    if (visitState === 'shallower') {
+      // 本文は再取得せず、frontmatterのdepthだけを実際の最短距離へ直す。
+      // 浅くなった分だけ max-depth 内に入る子参照が増え得るため、子は再投入する。
+      const processed = processedNotes.get(item.lawId);
+      if (processed) {
+        if (processed.filePath) {
+          await refreshNoteDepth(options, pendingNotes, processed.filePath, item.depth);
+          lawIndex?.updateDepth(item.lawId, item.depth);
+        }
+        enqueueReferences(item.lawId, processed.referencedLawIds, item.depth + 1);
+      }
+      return;
     }
-    visited.add(item.lawId);
 
     const dictEntry = dictionary[item.lawId] ?? {
       title: item.titleHint ?? `law_${item.lawId}`,
       safe_title: toSafeTitle(item.titleHint ?? `law_${item.lawId}`),
       file_name: getFileName(item.lawId, item.titleHint ?? `law_${item.lawId}`),
       updated_at: new Date().toISOString(),
+      ...dictionaryProvenance(item.titleHint ? 'candidate' : 'fallback'),
     };
     dictionary[item.lawId] = dictEntry;
 
@@ -95,38 +566,71 @@ export async function processLawGraph(
       const existingNotePath = await resolveExistingNotePath(
         options.outputDir,
         item.lawId,
-        dictEntry.file_name,
+        resolveNoteRelativePath(item.lawId, dictEntry, options),
         existingIndex,
       );
-      if (existingNotePath) {
-        const existingMarkdown = await fs.readFile(existingNotePath, 'utf8');
-        const scan = scanReferencedLawIdsFromMarkdown(existingMarkdown);
-        for (const lawId of scan.referencedLawIds) {
-          queue.push({ lawId, depth: item.depth + 1 });
-        }
+      const existingMarkdown = existingNotePath ? await readNoteText(existingNotePath) : undefined;
+      // スタブノートは本取得までの仮置きなので、skip 指定でも本体で上書きする。
+      if (existingNotePath && existingMarkdown !== undefined && !isStubNote(existingMarkdown)) {
+        // 既存ノートのリンクから子参照だけは積み、再取得せずにグラフ探索を続ける。
+        // `--no-follow-existing` では既存ノートの先を辿らず、取得済みとして扱うだけにする。
+        const referencedLawIds = options.followExisting
+          ? scanReferencedLawIdsFromMarkdown(
+              existingMarkdown,
+              options.refScope,
+              buildLawNumIndex(dictionary),
+              options.headingBaseLevel,
+            ).referencedLawIds
+          : [];
+        enqueueReferences(item.lawId, referencedLawIds, item.depth + 1);
+        processedNotes.set(item.lawId, { referencedLawIds });
+        // 取得し直さない法令も探索で辿った法令なので一覧ノートに載せる。リンク先は実際にある既存ノートにする。
+        recordMocEntry({
+          lawId: item.lawId,
+          title: dictEntry.title,
+          path: path.relative(options.outputDir, existingNotePath),
+          referencedLawIds,
+        });
         const existingFileName = path.basename(existingNotePath);
-        if (dictEntry.file_name !== existingFileName) {
+        // 辞書の file_name は法令名ベースの名前なので、他スキームのファイル名では上書きしない。
+        if (options.filenameScheme === 'title' && dictEntry.file_name !== existingFileName) {
           dictionary[item.lawId] = {
             ...dictEntry,
             file_name: existingFileName,
             updated_at: new Date().toISOString(),
           };
         }
-        process.stdout.write(`skip existing: ${existingFileName}\n`);
-        continue;
+        skippedCount += 1;
+        countRunStat('skipped');
+        process.stdout.write(`スキップ: ${existingNotePath}（既存）\n`);
+        return;
       }
     }
 
     process.stdout.write(`取得中: ${dictEntry.title} (${item.lawId}) depth=${item.depth}\n`);
+    const fetchStartedAt = Date.now();
 
-    const scraped = await scrapeLawDocumentWithRetry(item.lawId, options);
-    const previousFileName = dictEntry.file_name;
+    let scraped = await fetchers.loadLawDocument(item.lawId, options);
+    if (scraped.unsupportedElements) {
+      process.stderr.write(
+        `警告: 本文に出力できない要素がありました: ${scraped.title} (${item.lawId}): ` +
+          `${formatUnsupportedElements(scraped.unsupportedElements)}\n`,
+      );
+    }
+    if (rootLawIds.has(item.lawId) && partialRange) {
+      // 範囲指定は起点法令だけに適用し、参照先の法令は通常どおり全体を取得する。
+      // DFSでは起点法令を他の起点法令の参照先として先に取得することもあるため、深さではなく law_id で判定する。
+      const partial = applyPartialRange(scraped, partialRange);
+      scraped = partial.doc;
+      context.partialScopes = (context.partialScopes ?? new Map()).set(item.lawId, partial.articleKeys);
+    }
+    const previousFileName = resolveNoteRelativePath(item.lawId, dictEntry, options);
 
     let resolvedTitle = dictEntry.title;
     if (!resolvedTitle || isFallbackTitle(resolvedTitle)) {
       if (options.dictionaryAutoupdate) {
         try {
-          const fetchedTitle = await fetchLawTitleById(options, item.lawId);
+          const fetchedTitle = await fetchers.fetchLawTitleById(options, item.lawId);
           if (fetchedTitle) {
             resolvedTitle = fetchedTitle;
           }
@@ -139,30 +643,63 @@ export async function processLawGraph(
       }
     }
 
-    const freshFileName = getFileName(item.lawId, resolvedTitle);
-    dictionary[item.lawId] = {
+    const lawNum = scraped.lawNum ?? dictEntry.law_num;
+    const freshEntry = {
       title: resolvedTitle,
       safe_title: toSafeTitle(resolvedTitle),
-      file_name: freshFileName,
+      file_name: getFileName(item.lawId, rootsById.get(item.lawId)?.fileStem ?? resolvedTitle),
       updated_at: new Date().toISOString(),
+      law_num: lawNum,
+      law_type: dictEntry.law_type,
+      promulgation_date: dictEntry.promulgation_date,
+      abbrev: dictEntry.abbrev,
+      aliases: dictEntry.aliases,
+      law_num_key: lawNum ? normalizeLawNum(lawNum)?.key : undefined,
+      ...dictionaryProvenance('fetched'),
     };
+    dictionary[item.lawId] = freshEntry;
+    // 新しく登録した法令か、取得で法令名が変わったときだけ調べ、既知の重なりを取得のたびに警告し直さない。
+    if (freshEntry.title !== dictEntry.title || dictEntry.source === 'candidate') {
+      await reportDictionaryKeyConflicts(options, findDictionaryKeyConflicts(dictionary, 'fetched', [item.lawId]));
+    }
+    const freshFileName = resolveNoteRelativePath(item.lawId, freshEntry, options);
 
-    const referencedIds = collectReferencedLawIds(scraped);
+    // hrefから law_id を読めないリンクは法令名で引く。参照先（depth>0）でも同じ法令名が繰り返し現れるため、
+    // 検索結果は同一実行内で使い回す。
+    context.refTitleResolutions = await resolveRefTexts(
+      options,
+      collectUnparsedRefTexts(scraped, options),
+      dictionary,
+      rememberInvalidRefNames(
+        (lawTitle) => fetchers.searchLawCandidates(options, lawTitle),
+        context.invalidRefNames as Set<string>,
+      ),
+      (lawTitle, candidates) => selectBestCandidate(lawTitle, candidates, options),
+    );
+    // 他の法令への参照をリンク化しない場合は参照先を取得しないため、辞書へ仮登録もしない。
+    const referencedIds = options.linkifyRules.includes('external')
+      ? [...new Set([...collectReferencedLawIds(scraped, options.refScope), ...context.refTitleResolutions.values()])]
+      : [];
     for (const referencedLawId of referencedIds) {
       if (dictionary[referencedLawId]) {
         continue;
       }
       if (options.dictionaryAutoupdate) {
         try {
-          const resolvedTitle = await fetchLawTitleById(options, referencedLawId);
+          const resolvedTitle = await fetchers.fetchLawTitleById(options, referencedLawId);
           if (resolvedTitle) {
             const safeTitle = toSafeTitle(resolvedTitle);
             dictionary[referencedLawId] = {
               title: resolvedTitle,
               safe_title: safeTitle,
-              file_name: `${safeTitle}_${referencedLawId}.md`,
+              file_name: getFileName(referencedLawId, resolvedTitle),
               updated_at: new Date().toISOString(),
+              ...dictionaryProvenance('api_lookup'),
             };
+            await reportDictionaryKeyConflicts(
+              options,
+              findDictionaryKeyConflicts(dictionary, 'api_lookup', [referencedLawId]),
+            );
             continue;
           }
         } catch {
@@ -174,30 +711,589 @@ export async function processLawGraph(
         safe_title: `law_${referencedLawId}`,
         file_name: `law_${referencedLawId}.md`,
         updated_at: new Date().toISOString(),
+        ...dictionaryProvenance('fallback'),
       };
     }
 
-    const rendered = renderMarkdown(scraped, dictionary, options, context, item.depth);
-    if (rendered.dictionaryDirty) {
-      await writeJson(options.dictionaryPath, dictionary);
+    // 構造の集計は法令全体のJSONから行うため、部分ノートでは本文ブロックからの集計に任せる。
+    const countsStructures = options.stats && options.fullTextFormat === 'json' && !scraped.partialRange;
+    const { lawData, revisionInfo } = await fetchers.fetchLawMetadata(options, item.lawId, countsStructures);
+    const fullText = lawData?.law_full_text ?? undefined;
+    const successor = parseLawSuccessor(revisionInfo);
+    const revisionId = revisionInfo?.law_revision_id;
+    if (typeof revisionId === 'string' && revisionId) {
+      revisionIds.set(item.lawId, revisionId);
+    }
+    scraped.repealed = isRepealedStatus(revisionInfo?.repeal_status) || undefined;
+    if (successor) {
+      scraped.supersededBy = successor;
+      successors.set(item.lawId, successor.lawId);
+      // 新法をまだ取得していなくても、取得したときと同じノート名でリンクできるよう辞書へ仮登録する。
+      dictionary[successor.lawId] ??= {
+        title: successor.title,
+        safe_title: toSafeTitle(successor.title),
+        file_name: getFileName(successor.lawId, successor.title),
+        updated_at: new Date().toISOString(),
+        ...dictionaryProvenance('api_lookup'),
+      };
+      if (options.followSuccession) {
+        enqueueReferences(item.lawId, [successor.lawId], item.depth + 1);
+      }
+    }
+    if (options.dumpTags) {
+      dumpTagCounts(scraped.title, item.lawId, fullText);
+    }
+    const structureCounts = countsStructures && fullText ? countStructures(fullText) : undefined;
+    const rendered = renderMarkdown(scraped, dictionary, options, context, item.depth, structureCounts);
+    const timing = recordLawTiming(item.lawId, resolvedTitle, Date.now() - fetchStartedAt);
+    if (options.verbose) {
+      process.stderr.write(`計測: ${formatLawTiming(timing)}\n`);
+    }
+    if (rendered.dictionaryDirty && !options.dryRun && !deferred) {
+      await saveDictionary(options, dictionary);
     }
 
     const freshFilePath = notePath(options.outputDir, freshFileName);
-    await fs.writeFile(freshFilePath, rendered.markdown, 'utf8');
-    await removeOldNoteIfRenamed(
-      options.outputDir,
-      previousFileName,
-      freshFileName,
-      existingIndex,
-      item.lawId,
+    // Markdown以外の形式は同じ名前で拡張子だけ替えて書く。ノートとして読み戻せないため、
+    // 参照関係のfrontmatter・被参照セクション・リネーム時の旧ノート削除といった後処理の対象にはしない。
+    const convertedFileName = noteFileForFormat(freshFileName, options.noteFormat);
+    const converted =
+      options.noteFormat === 'md'
+        ? undefined
+        : {
+            filePath: notePath(options.outputDir, convertedFileName),
+            content:
+              options.noteFormat === 'txt'
+                ? renderPlainText(scraped)
+                : renderHtml(scraped, splitFrontmatter(rendered.markdown).frontmatter ?? {}),
+          };
+    renderedLawIds.add(item.lawId);
+    // 描画し直したノートには被参照セクションが無いため、上書きする前に既存ノート（リネーム前のものを含む）から読んでおく。
+    const previousNotes = existingIndex.get(item.lawId) ?? [];
+    const previousNotePath = previousNotes.includes(freshFilePath) ? freshFilePath : previousNotes[0];
+    if (!converted && options.backlinksSection && previousNotePath) {
+      const previousMarkdown = await readNote(pendingNotes, previousNotePath);
+      previousBacklinks.set(item.lawId, extractBacklinkLines(previousMarkdown, options.headingBaseLevel));
+    }
+    if (!writesNotes) {
+      if (converted) {
+        const basePath = (await fileExists(converted.filePath)) ? converted.filePath : undefined;
+        await writeNote(options, pendingNotes, converted.filePath, converted.content, basePath);
+      } else {
+        // リネームで出力先が変わる場合も、同じ法令の既存ノートを比較元にする。
+        await writeNote(options, pendingNotes, freshFilePath, rendered.markdown, previousNotePath);
+        sectionNotePaths.push(...(await writeSectionNotes(options, pendingNotes, rendered.sectionNotes)));
+      }
+      processedNotes.set(item.lawId, {
+        filePath: converted ? undefined : freshFilePath,
+        referencedLawIds: referencedIds,
+      });
+      writtenCount += 1;
+      countRunStat('fetched');
+      enqueueReferences(item.lawId, rendered.referencedLawIds, item.depth + 1);
+      return;
+    }
+    if (converted) {
+      await fs.mkdir(path.dirname(converted.filePath), { recursive: true });
+      await writeNote(options, pendingNotes, converted.filePath, converted.content);
+    } else {
+      await fs.mkdir(path.dirname(freshFilePath), { recursive: true });
+      if (options.changelog) {
+        // 書き直すと前回の本文が残らないため、書き込む前に既存ノート（リネーム前のものを含む）と比べる。
+        await writeLawChangelog(
+          options,
+          previousNotePath,
+          freshFilePath,
+          freshEntry.title,
+          freshEntry.safe_title,
+          rendered.markdown,
+        );
+      }
+      await writeNote(options, pendingNotes, freshFilePath, rendered.markdown);
+      sectionNotePaths.push(...(await writeSectionNotes(options, pendingNotes, rendered.sectionNotes)));
+      await removeOldNoteIfRenamed(
+        options.outputDir,
+        previousFileName,
+        freshFileName,
+        existingIndex,
+        item.lawId,
+      );
+      const otherNotes = (existingIndex.get(item.lawId) ?? []).filter((candidate) => candidate !== freshFilePath);
+      for (const otherNote of otherNotes) {
+        process.stderr.write(`警告: 別名の既存ノートあり: ${otherNote}（今回の出力: ${freshFilePath}）\n`);
+      }
+      addExistingNoteIndex(existingIndex, item.lawId, freshFilePath);
+    }
+    processedNotes.set(item.lawId, {
+      filePath: converted ? undefined : freshFilePath,
+      referencedLawIds: referencedIds,
+    });
+    recordMocEntry({
+      lawId: item.lawId,
+      title: freshEntry.title,
+      path: freshFileName,
+      referencedLawIds: referencedIds,
+    });
+    writtenCount += 1;
+    countRunStat('fetched');
+    lawIndex?.upsertLaw(
+      {
+        law_id: item.lawId,
+        title: freshEntry.title,
+        law_num: freshEntry.law_num,
+        promulgation_date: freshEntry.promulgation_date,
+        law_type: freshEntry.law_type,
+        depth: item.depth,
+        note_path: convertedFileName,
+        updated_at: freshEntry.updated_at,
+      },
+      context.references.filter((edge) => edge.source_law_id === item.lawId).map((edge) => edge.target_law_id),
     );
-    addExistingNoteIndex(existingIndex, item.lawId, freshFilePath);
+    if (options.flushEach) {
+      await flushProgress();
+    }
+
+    enqueueReferences(item.lawId, rendered.referencedLawIds, item.depth + 1);
+  };
+
+  let retryPass = 0;
+  let cancelled = false;
+  let rootFailure: { error: unknown } | undefined;
+  while (queue.length > 0 || (context.failures.length > 0 && retryPass < options.retryFailedPasses)) {
+    if (isCancelRequested()) {
+      // 法令1件の処理を終えた境目で抜け、以降の保存処理はそのまま行う。未取得の参照先は失敗記録に残す。
+      context.failures.push(...unfinishedQueueRecords(queue.splice(0), visited, options.maxDepth, dictionary));
+      cancelled = true;
+      break;
+    }
+    if (queue.length === 0) {
+      // e-Gov側の一時的な不安定さが収まっていることを期待し、メインキューを処理し終えてからまとめて再試行する。
+      // 再試行でも失敗したものだけが最終的な失敗として残る。
+      retryPass += 1;
+      process.stderr.write(
+        `取得に失敗した参照先を再試行します（${retryPass}/${options.retryFailedPasses}回目）: ${context.failures.length}件\n`,
+      );
+      queue.push(...requeueFailedLaws(context.failures.splice(0), visited));
+      continue;
+    }
+    const item = takeQueueItem(queue, options.traversal) as QueueItem;
+    // 途中の深さから取り直す法令そのものは、--max-depth を下げて再試行しても取得する。
+    if (item.depth > Math.max(options.maxDepth, rootsById.get(item.lawId)?.depth ?? 0)) {
+      continue;
+    }
+    const visitState = recordVisit(visited, item.lawId, item.depth);
+    if (visitState === 'seen') {
+      continue;
+    }
+    const origin = originRoots.get(item.lawId) ?? roots[0];
+    context.rootLawId = origin.lawId;
+    context.rootLawTitle = origin.title;
+    try {
+      await processQueueItem(item, visitState);
+    } catch (error) {
+      // 起点法令が取れなければ出力の意味がないため失敗させるが、それまでの成果は保存してから投げ直す。
+      // 参照先1件や複数の起点法令のうち1件の障害で残りのキューまで失わないよう、それ以外は記録してスキップする。
+      if (item.depth === 0 && roots.length === 1) {
+        rootFailure = { error };
+        break;
+      }
+      const title = dictionary[item.lawId]?.title ?? item.titleHint ?? `law_${item.lawId}`;
+      const message = error instanceof Error ? error.message : String(error);
+      context.failures.push({
+        timestamp: new Date().toISOString(),
+        law_id: item.lawId,
+        title,
+        depth: item.depth,
+        error: message,
+      });
+      process.stderr.write(`警告: 取得に失敗したためスキップします: ${title} (${item.lawId}): ${message}\n`);
+    }
+  }
+  lawIndex?.close();
+
+  const saveResults = async (): Promise<void> => {
+    countRunStat('unresolved', context.unresolved.length);
+    countRunStat('dictionaryAdded', Object.keys(dictionary).filter((lawId) => !knownLawIds.has(lawId)).length);
+    for (const failure of context.failures) {
+      recordRunFailure({ law_id: failure.law_id, title: failure.title, error: failure.error });
+    }
+    if (deferred) {
+      // 辞書は呼び出し側が同じオブジェクトを保持しているため、未解決参照と失敗記録だけを引き渡す。
+      deferred.unresolved.push(...context.unresolved);
+      deferred.failures.push(...context.failures);
+    } else {
+      await flushDeferredSaves(options, dictionary, { unresolved: context.unresolved, failures: context.failures });
+    }
+  };
+  if (rootFailure) {
+    // 起点法令の失敗でも、それまでに登録した辞書・未解決参照・取得失敗の記録は保存してから失敗させる。
+    await saveResults();
+    throw rootFailure.error;
+  }
 
-    for (const lawId of rendered.referencedLawIds) {
-      queue.push({ lawId, depth: item.depth + 1 });
+  // 相互に参照し合う法令は、取得中には閉路と分からないため、全取得後の参照関係から求める。
+  const cycles = findReferenceCycles(context.references);
+  const cycleLawIds = new Set(cycles.flat());
+  if (cycles.length > 0) {
+    process.stderr.write(`相互に参照し合う法令: ${cycles.length}組\n`);
+    for (const cycle of cycles) {
+      process.stderr.write(`  - ${cycle.map((lawId) => dictionary[lawId]?.title ?? lawId).join(' ⇄ ')}\n`);
     }
   }
 
-  await writeJson(options.dictionaryPath, dictionary);
-  await appendUnresolved(options.unresolvedPath, context.unresolved);
+  // この実行で取得しなかった参照先のリビジョンIDは、アーカイブ済みの `law_data` があればそこから補う。
+  if (options.citationStyle !== 'none' && options.archiveDir) {
+    for (const lawId of new Set(context.references.map((ref) => ref.target_law_id))) {
+      const archived = revisionIds.has(lawId) ? undefined : await loadLawDataArchive(options.archiveDir, lawId);
+      if (archived?.revision_id) {
+        revisionIds.set(lawId, archived.revision_id);
+      }
+    }
+  }
+
+  // 参照元は参照先より後に取得されることもあるため、参照関係の反映は全取得後にまとめて行う。
+  const backlinkLawNumIndex = buildLawNumIndex(dictionary);
+  for (const [lawId, processed] of processedNotes) {
+    if (!processed.filePath) {
+      continue;
+    }
+    const markdown = await readNote(pendingNotes, processed.filePath);
+    const fields = renderReferencedArticleFields(lawId, context.references, dictionary);
+    let updated = setFrontmatterField(markdown, 'referenced_articles', fields.referencedArticles);
+    updated = setFrontmatterField(updated, 'referenced_from', fields.referencedFrom);
+    const referencedByFields = renderReferencedByFields(
+      lawId,
+      referencedBy.get(lawId) ?? [],
+      rootLawIds,
+      dictionary,
+      options,
+    );
+    updated = setFrontmatterField(updated, 'root', referencedByFields.root);
+    updated = setFrontmatterField(updated, 'referenced_by', referencedByFields.referencedBy);
+    updated = setFrontmatterField(updated, 'supersedes', renderSupersedesField(lawId, successors, dictionary, options));
+    updated = setFrontmatterField(
+      updated,
+      'in_cycle',
+      renderFrontmatterField('in_cycle', cycleLawIds.has(lawId) ? true : undefined),
+    );
+    if (options.backlinksSection) {
+      const section = renderBacklinksSection(
+        lawId,
+        context.references,
+        dictionary,
+        options,
+        context.sectionNotes?.get(lawId),
+      );
+      const previousLines = previousBacklinks.get(lawId) ?? [];
+      updated = replaceBacklinksSection(
+        updated,
+        mergeBacklinksSection(section, previousLines, options.headingBaseLevel, renderedLawIds, backlinkLawNumIndex),
+        options.headingBaseLevel,
+      );
+    }
+    if (options.citationStyle !== 'none') {
+      const cited = collectCitedLaws(lawId, context.references, dictionary, revisionIds);
+      const style = options.citationStyle;
+      updated = setFrontmatterField(
+        updated,
+        'cited_revisions',
+        style === 'frontmatter' ? renderCitedRevisionsField(cited) : [],
+      );
+      const citationSection = style === 'section' ? renderCitationSection(cited, options.headingBaseLevel) : [];
+      updated = replaceCitationSection(updated, citationSection, options.headingBaseLevel);
+    }
+    if (updated !== markdown) {
+      await writeNote(options, pendingNotes, processed.filePath, updated);
+    }
+  }
+
+  if (options.linkOnlyExisting) {
+    const writtenPaths = [...processedNotes.values()].flatMap((processed) => processed.filePath ?? []);
+    await unlinkMissingNoteLinks(options, pendingNotes, [...writtenPaths, ...sectionNotePaths]);
+  }
+
+  await saveResults();
+  if (options.diff) {
+    const changedCount = await printNoteDiffs(pendingNotes);
+    // 差分の有無をスクリプトから判定できるよう、変更ありは専用の終了コードにする。
+    if (changedCount > 0) {
+      process.exitCode = 4;
+    }
+  } else if (options.dryRun) {
+    for (const filePath of pendingNotes.keys()) {
+      process.stdout.write(`書き込み省略（--dry-run）: ${filePath}\n`);
+    }
+  }
+  process.stdout.write(`完了: 生成 ${writtenCount}件 / スキップ ${skippedCount}件\n`);
+  if (context.failures.length > 0) {
+    process.stderr.write(`取得に失敗した法令: ${context.failures.length}件（${options.failedPath} に記録）\n`);
+    for (const failure of context.failures) {
+      process.stderr.write(`  - ${failure.title} (${failure.law_id}) depth=${failure.depth}: ${failure.error}\n`);
+    }
+    // 部分成功であることを呼び出し側が判別できるよう、全失敗(1)・候補曖昧(2)と別の終了コードにする。
+    process.exitCode = 3;
+  }
+  if (cancelled) {
+    process.stderr.write(`中断しました。未取得の法令は --retry-failed で続きから取得できます（${options.failedPath}）\n`);
+    process.exitCode = 130;
+  }
+  return {
+    written: writtenCount,
+    skipped: skippedCount,
+    failed: context.failures.length,
+    failedLawIds: context.failures.map((failure) => failure.law_id),
+  };
+}
+
+/**
+ * 辞書・未解決参照・取得失敗の記録を保存し、行き先の無いリンクを検査する。
+ * 通常は起点法令ごとに、`--repl` では終了時に一度だけ呼ぶ。
+ */
+export async function flushDeferredSaves(
+  options: CliOptions,
+  dictionary: LawDictionary,
+  saves: DeferredSaves,
+): Promise<void> {
+  if (!options.dryRun) {
+    await saveDictionary(options, dictionary);
+    await appendUnresolved(options.unresolvedPath, saves.unresolved, options.lockTimeoutMs);
+    if (options.flushEach) {
+      await clearUnresolvedLog(options.unresolvedPath);
+    }
+    await appendFailedLaws(options.failedPath, saves.failures);
+  }
+  if (!options.diff && !options.dryRun) {
+    if (options.timeline) {
+      await writeTimelineNote(options, dictionary);
+    }
+    if (options.mocName) {
+      await writeMocNote(options, dictionary);
+    }
+    await checkMissingLinks(options, dictionary);
+  }
+}
+
+/**
+ * 候補を起点法令にする。同名ノートが並ぶため、ファイル名には法令番号（無ければ公布日）を含める。
+ */
+export function candidateGraphRoots(candidates: LawCandidate[]): LawGraphRoot[] {
+  return candidates.flatMap((candidate): LawGraphRoot[] => {
+    if (!candidate.law_id) {
+      return [];
+    }
+    const distinguisher = canonicalLawNum(candidate.law_num) ?? candidate.promulgation_date;
+    const fileStem = distinguisher ? `${candidate.law_title}_${distinguisher}` : candidate.law_title;
+    return [{ lawId: candidate.law_id, title: candidate.law_title, fileStem }];
+  });
+}
+
+/**
+ * `--fetch-all-candidates` 時に、曖昧な法令名の全候補をそれぞれ起点法令としてノート化する。
+ * 参照先はlaw_idで一意に決まるため、全件化するのは起点法令だけでよい。
+ */
+export async function processAllCandidates(
+  options: CliOptions,
+  candidates: LawCandidate[],
+  dictionary: LawDictionary,
+): Promise<void> {
+  process.stdout.write(`候補 ${candidates.length}件をすべて取得します\n`);
+  await processGraphRootsWithWarning(options, candidateGraphRoots(candidates), dictionary, '候補');
+}
+
+/**
+ * 複数の起点法令をまとめて取得する。起点法令が1件だけのときは `processLawGraphRoots` が取得失敗で例外を投げるため、
+ * 起点法令が複数ある場合に失敗が記録されるのと揃えて、警告と部分成功の終了コードに留める。
+ */
+async function processGraphRootsWithWarning(
+  options: CliOptions,
+  roots: LawGraphRoot[],
+  dictionary: LawDictionary,
+  label: string,
+): Promise<LawGraphResult | undefined> {
+  if (roots.length === 0) {
+    return undefined;
+  }
+  if (roots.length > 1) {
+    return processLawGraphRoots(options, roots, dictionary);
+  }
+  const [root] = roots;
+  try {
+    return await processLawGraphRoots(options, roots, dictionary);
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    process.stderr.write(`警告: ${label}の取得に失敗しました: ${root.title} (${root.lawId}): ${message}\n`);
+    recordRunFailure({ law_id: root.lawId, title: root.title, error: message });
+    process.exitCode = 3;
+    return undefined;
+  }
+}
+
+/**
+ * `--title-filter` にマッチする辞書上の法令を、それぞれ起点法令として一括取得する。
+ * 大量取得はe-Govへの負荷と実行時間が大きいため、件数を示して確認を取ってから始める。
+ */
+export async function processTitleFilteredLaws(options: CliOptions, dictionary: LawDictionary): Promise<void> {
+  const matched = findLawsByTitle(dictionary, new RegExp(options.titleFilter ?? ''), (lawId, entry) =>
+    acceptDictionaryEntry(options, lawId, entry),
+  );
+  if (matched.length === 0) {
+    process.stdout.write(`--title-filter にマッチする法令は辞書にありません: ${options.titleFilter}\n`);
+    return;
+  }
+  const targets = options.maxLaws !== undefined ? matched.slice(0, options.maxLaws) : matched;
+  process.stdout.write(
+    `--title-filter にマッチした法令: ${matched.length}件` +
+      (targets.length < matched.length ? `（--max-laws により先頭 ${targets.length}件を取得）` : '') +
+      '\n',
+  );
+  for (const [lawId, entry] of targets) {
+    process.stdout.write(`  - ${entry.title} (${lawId})\n`);
+  }
+
+  if (!options.nonInteractive) {
+    if (!process.stdin.isTTY) {
+      throw new Error('確認プロンプトを表示できません。端末以外から実行する場合は --non-interactive を指定してください');
+    }
+    if (!(await confirm(`${targets.length}件を取得します。続行しますか？ [y/N] `))) {
+      process.stdout.write('中止しました\n');
+      return;
+    }
+  }
+
+  const roots = targets.map(([lawId, entry]) => ({ lawId, title: entry.title }));
+  await processGraphRootsWithWarning(options, roots, dictionary, '法令');
+}
+
+/**
+ * 標準入力などから受け取った法令名リストを1行1法令名として解釈する。空行と `#` で始まるコメント行は無視する。
+ */
+export function parseTitleList(text: string): string[] {
+  return text
+    .split(/\r?\n/)
+    .map((line) => line.trim())
+    .filter((line) => line.length > 0 && !line.startsWith('#'));
+}
+
+/**
+ * 法令名（法令番号も可）を対話なしで1件の法令に確定する。確定できなければ `selected` を持たず、検索した候補を返す。
+ * 同じ法令名でユーザーが以前に選んだ法令（選択履歴）があれば、辞書や検索より優先してそれを使う。
+ * 辞書に別名（`aliases`）として登録された入力は、検索せずにその法令に確定する。
+ * 法令名で見つからず短くした法令名で検索し直した場合は、その検索語を `fallbackQuery` に入れ、候補が1件でも確定しない。
+ */
+export async function resolveTitleNonInteractive(
+  options: CliOptions,
+  dictionary: LawDictionary,
+  title: string,
+): Promise<{ selected?: { law_id: string; law_title: string }; candidates: LawCandidate[]; fallbackQuery?: string }> {
+  const chosen = await findCandidateChoice(options, title);
+  if (chosen) {
+    return { selected: { law_id: chosen.law_id, law_title: chosen.law_title }, candidates: [] };
+  }
+  const accept = (id: string, entry: LawDictionaryEntry) => acceptDictionaryEntry(options, id, entry);
+  // 検索し直した候補から以前に確定した入力は辞書に別名として残っているため、検索し直さずにその法令を使う。
+  const lawId =
+    findLawIdByAlias(dictionary, title, accept) ??
+    (normalizeLawNum(title) ? findLawIdByLawNum(dictionary, title, accept) : undefined);
+  if (lawId) {
+    return { selected: { law_id: lawId, law_title: dictionary[lawId].title }, candidates: [] };
+  }
+  const { query, candidates } = await searchLawCandidatesWithFallback(options, title);
+  if (query !== title) {
+    return { candidates, fallbackQuery: query };
+  }
+  const selected = selectBestCandidate(title, candidates, options);
+  return {
+    selected: selected?.law_id ? { law_id: selected.law_id, law_title: selected.law_title } : undefined,
+    candidates,
+  };
+}
+
+/**
+ * 法令名リストの法令をまとめて取得する。対話で候補を選べないため、候補を確定できない法令は警告してスキップする。
+ * 確定した法令は1回のグラフ探索の起点法令として取得する。
+ * 結果の件数は、標準出力を他のコマンドへ渡しても混ざらないよう標準エラーへ出す。
+ */
+export async function processTitleList(options: CliOptions, dictionary: LawDictionary, titles: string[]): Promise<void> {
+  let skipped = 0;
+  let failed = 0;
+  const roots: LawGraphRoot[] = [];
+  for (const title of titles) {
+    if (isCancelRequested()) {
+      break;
+    }
+    try {
+      const resolved = await resolveTitleNonInteractive(options, dictionary, title);
+      if (!resolved.selected) {
+        const fallback = resolved.fallbackQuery ? `「${resolved.fallbackQuery}」で検索し直した` : '';
+        process.stderr.write(
+          `警告: 法令を1件に確定できないためスキップします: ${title} (${fallback}候補 ${resolved.candidates.length}件)\n`,
+        );
+        skipped += 1;
+        countRunStat('skipped');
+        continue;
+      }
+      roots.push({ lawId: resolved.selected.law_id, title: resolved.selected.law_title });
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      process.stderr.write(`警告: 法令の検索に失敗しました: ${title}: ${message}\n`);
+      recordRunFailure({ title, error: message });
+      failed += 1;
+    }
+  }
+  const attempted = roots.length > 0 && !isCancelRequested();
+  const result = attempted ? await processGraphRootsWithWarning(options, roots, dictionary, '法令') : undefined;
+  // 起点法令が1件だけで取得に失敗した場合は結果が返らない。
+  const failedRoots = result ? roots.filter((root) => result.failedLawIds.includes(root.lawId)).length : roots.length;
+  const succeeded = attempted ? roots.length - failedRoots : 0;
+  failed += attempted ? failedRoots : 0;
+  process.stderr.write(`法令名リストの処理結果: 成功 ${succeeded}件 / スキップ ${skipped}件 / 失敗 ${failed}件\n`);
+  if (skipped > 0 || failed > 0) {
+    process.exitCode = 3;
+  }
+}
+
+/**
+ * `--failed-path` に記録された取得失敗法令だけを再取得する。
+ * 再試行でも失敗した法令は記録に残し、成功した法令は記録から外す。
+ * 記録した法令は失敗時の深さから1回のグラフ探索でまとめて取り直し、起点法令（`root: true`）にはしない。
+ */
+export async function retryFailedLaws(options: CliOptions, dictionary: LawDictionary): Promise<void> {
+  const records = await loadFailedLaws(options.failedPath);
+  if (records.length === 0) {
+    process.stdout.write(`再試行対象の失敗記録はありません: ${options.failedPath}\n`);
+    return;
+  }
+  // 記録を空にしてから再実行し、今回も失敗したものだけが追記されるようにする。
+  if (!options.dryRun) {
+    await writeJson(options.failedPath, []);
+  }
+
+  for (const record of records) {
+    process.stdout.write(`再試行: ${record.title} (${record.law_id}) depth=${record.depth}\n`);
+  }
+  // 元の取得経路での深さから取り直し、本来の --max-depth を超えて参照先を辿らないようにする。
+  // 再試行でも失敗した法令と中断で取り直せなかった法令は、グラフ探索の中で失敗記録へ追記される。
+  const roots = records.map((record) => ({ lawId: record.law_id, title: record.title, depth: record.depth }));
+  let failed: number;
+  try {
+    const result = await processLawGraphRoots(options, roots, dictionary);
+    failed = records.filter((record) => result.failedLawIds.includes(record.law_id)).length;
+  } catch (error) {
+    // 深さ0の法令1件だけを取り直して失敗した場合などは、失敗記録へ追記されずに例外になるため、記録を書き戻す。
+    const message = error instanceof Error ? error.message : String(error);
+    const timestamp = new Date().toISOString();
+    if (!options.dryRun) {
+      await appendFailedLaws(
+        options.failedPath,
+        records.map((record) => ({ ...record, timestamp, error: message })),
+      );
+    }
+    for (const record of records) {
+      process.stderr.write(`警告: 再試行でも取得に失敗しました: ${record.title} (${record.law_id}): ${message}\n`);
+      recordRunFailure({ law_id: record.law_id, title: record.title, error: message });
+    }
+    process.exitCode = 3;
+    failed = records.length;
+  }
+  process.stdout.write(`再試行完了: 成功 ${records.length - failed}件 / 失敗 ${failed}件\n`);
 }
@@ -0,0 +1,92 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  anchorArticleLabel,
+  articleHeading,
+  articleLabel,
+  parseProvisionId,
+  provisionNumber,
+  recentProvisionOf,
+  splitSameProvisionRefs,
+  stripLeadingArticleTitle,
+} from './index.js';
+
+test('parseProvisionId: 要素IDの末尾から条・項・号の番号を読み取る', () => {
+  assert.deepEqual(parseProvisionId('Mp-Ch_2-At_27_2'), { kind: 'article', from: [27, 2], to: undefined });
+  assert.deepEqual(parseProvisionId('Mp-Ch_3-At_55:63'), { kind: 'article', from: [55], to: [63] });
+  assert.deepEqual(parseProvisionId('Mp-Ch_1-At_2-Pr_3-It_1'), { kind: 'item', from: [1], to: undefined });
+  assert.equal(parseProvisionId('334AC0000000121-Sp'), undefined);
+  assert.equal(parseProvisionId(undefined), undefined);
+});
+
+test('articleHeading: IDがあれば条番号をIDから作る', () => {
+  assert.equal(articleHeading('Mp-Ch_1-At_2', '（定義）', '第二条　'), '第二条（定義）');
+  assert.equal(articleHeading('Mp-Ch_2-At_27_2', '', '第２７条の２'), '第二十七条の二');
+  assert.equal(articleHeading('Mp-Ch_3-At_55:63', '', '第五十五条から第六十三条まで　'), '第五十五条から第六十三条まで');
+});
+
+test('articleHeading: IDから読めない場合は本文の条名テキストを使う', () => {
+  assert.equal(articleHeading('Enf-1', '（趣旨）', '第一条の二　'), '第一条の二（趣旨）');
+  assert.equal(articleHeading('334AC0000000121-Sp', '', '附　則'), '附　則');
+  assert.equal(articleHeading('', '', ''), '条文');
+});
+
+test('provisionNumber: IDがあれば項・号番号をIDから作る', () => {
+  assert.equal(provisionNumber('Mp-Ch_1-At_2-Pr_1', '第二条　'), undefined);
+  assert.equal(provisionNumber('Mp-Ch_1-At_2-Pr_2', '2'), '２');
+  assert.equal(provisionNumber('Mp-Ch_1-At_2-Pr_3-It_3_2', '三の二　'), '三の二');
+});
+
+test('provisionNumber: IDが無い場合は本文の番号テキストを使う', () => {
+  assert.equal(provisionNumber(undefined, '第一条　'), undefined);
+  assert.equal(provisionNumber(undefined, '２　'), '２');
+  assert.equal(provisionNumber('Mp-Ch_1-At_2', 'イ　'), 'イ');
+  assert.equal(provisionNumber(undefined, ''), undefined);
+});
+
+test('articleLabel / anchorArticleLabel: 見出しとリンク先の条名を同じ表記で作る', () => {
+  assert.equal(articleLabel('Mp-Ch_2-At_27_2'), '第二十七条の二');
+  assert.equal(articleLabel('Mp-Ch_2-At_27_2', 'arabic'), '第27条の2');
+  assert.equal(articleLabel('Mp-Ch_3-At_55:63', 'arabic'), '第55条から第63条まで');
+  assert.equal(articleLabel('334AC0000000121-Sp'), undefined);
+  assert.equal(anchorArticleLabel('Mp-Ch_2-At_27_2-Pr_3-It_1', 'kanji'), '第二十七条の二');
+  assert.equal(anchorArticleLabel('Mp-At_195', 'arabic'), '第195条');
+  assert.equal(anchorArticleLabel('334AC0000000121-Sp-At_1', 'kanji'), undefined);
+});
+
+test('stripLeadingArticleTitle: 見出しと同じ条名の行だけ取り除き、本文中の参照は残す', () => {
+  assert.equal(stripLeadingArticleTitle('第一条　この法律は、', '第一条'), 'この法律は、');
+  assert.equal(stripLeadingArticleTitle('第一条（目的）　この法律は、', '第一条（目的）'), 'この法律は、');
+  assert.equal(stripLeadingArticleTitle('第二条の二（定義）この法律で', '第二条の二'), 'この法律で');
+  assert.equal(stripLeadingArticleTitle('第一条の規定により', '第一条'), '第一条の規定により');
+  assert.equal(stripLeadingArticleTitle('第一条　の規定は', '第三条'), '第一条　の規定は');
+  assert.equal(stripLeadingArticleTitle('第一条の二　この法律は、', '第一条'), '第一条の二　この法律は、');
+});
+
+test('recentProvisionOf: 参照先のアンカーから条・項のアンカーを求める', () => {
+  assert.deepEqual(recentProvisionOf('Mp-Ch_1-At_3-Pr_2-It_1'), {
+    article: 'Mp-Ch_1-At_3',
+    paragraph: 'Mp-Ch_1-At_3-Pr_2',
+  });
+  assert.deepEqual(recentProvisionOf('Mp-At_27_2'), { article: 'Mp-At_27_2', paragraph: undefined });
+  assert.deepEqual(recentProvisionOf('411AC0000000087-Sp-Pr_3'), {
+    article: undefined,
+    paragraph: '411AC0000000087-Sp-Pr_3',
+  });
+});
+
+test('splitSameProvisionRefs: 「同条」「同項」を直前の条・項へ対応付け、「同条例」は参照にしない', () => {
+  assert.deepEqual(
+    splitSameProvisionRefs('同項に定める者及び同条例並びに同条', { article: 'Mp-At_3', paragraph: 'Mp-At_3-Pr_2' }),
+    [
+      { type: 'same_ref', text: '同項', anchor: 'Mp-At_3-Pr_2' },
+      { type: 'text', text: 'に定める者及び同条例並びに' },
+      { type: 'same_ref', text: '同条', anchor: 'Mp-At_3' },
+    ],
+  );
+  assert.deepEqual(splitSameProvisionRefs('同項の規定', { article: 'Mp-At_3' }), [
+    { type: 'same_ref', text: '同項', anchor: undefined },
+    { type: 'text', text: 'の規定' },
+  ]);
+});
@@ -0,0 +1,227 @@
+import { parseJapaneseNumber, toKanjiNumber } from './lawnum.js';
+import type { AnchorStyle } from './types.js';
+
+/**
+ * e-Gov本文DOMの要素ID（例: `Mp-Ch_1-At_27_2-Pr_3-It_1`）の末尾から読み取った条・項・号の番号。
+ * 枝番は `[27, 2]`（第二十七条の二）、範囲は `to` に終端（`At_55:63`）を持つ。
+ */
+export interface ProvisionNumber {
+  kind: 'article' | 'paragraph' | 'item';
+  from: number[];
+  to?: number[];
+}
+
+const PROVISION_ID_PATTERN = /(?:^|-)(At|Pr|It)_(\d+(?:_\d+)*)(?::(\d+(?:_\d+)*))?$/;
+const PROVISION_KINDS = { At: 'article', Pr: 'paragraph', It: 'item' } as const;
+const ANCHOR_ARTICLE_PATTERN = /^Mp-(?:.+-)?At_(\d+(?:_\d+)*)/;
+const ANCHOR_PROVISION_PATTERN = /^Mp-(?:.+-)?(At_\d+(?:_\d+)*(?:-Pr_\d+(?:-It_\d+(?:_\d+)*)?)?)$/;
+const ARTICLE_TITLE_PATTERN = /^第[〇一二三四五六七八九十百千]+条(?:の[〇一二三四五六七八九十百千]+)*/;
+// 「同条例」「同条約」は条例・条約そのものを指すため、条の参照には含めない。
+const SAME_PROVISION_PATTERN = /同(条|項)(?![例約])/g;
+// 「同法第三条の二第二項」のように続く条・項も1つの参照として受ける。「同法人」は法令の参照ではない。
+const SAME_LAW_PATTERN =
+  /同法(?!人)(?:第([〇一二三四五六七八九十百千]+)条((?:の[〇一二三四五六七八九十百千]+)*)(?:第([〇一二三四五六七八九十百千]+)項)?)?/g;
+const RECENT_ARTICLE_PATTERN = /^(.*At_\d+(?:_\d+)*)(?:-|$)/;
+const RECENT_PARAGRAPH_PATTERN = /^(.*-Pr_\d+(?:_\d+)*)(?:-|$)/;
+
+function parseNumbers(text: string): number[] {
+  return text.split('_').map(Number);
+}
+
+function formatBranchNumber(numbers: number[]): string {
+  return numbers.map((value) => toKanjiNumber(value)).join('の');
+}
+
+function formatArticleNumber(numbers: number[], style: HeadingNumberStyle = 'kanji'): string {
+  const format = style === 'arabic' ? String : toKanjiNumber;
+  const [main, ...branches] = numbers;
+  return `第${format(main)}条${branches.map((value) => `の${format(value)}`).join('')}`;
+}
+
+type HeadingNumberStyle = Exclude<AnchorStyle, 'id'>;
+
+function toFullWidthDigits(value: number): string {
+  return String(value).replace(/[0-9]/g, (digit) => String.fromCharCode(digit.charCodeAt(0) + 0xfee0));
+}
+
+/**
+ * 要素IDの末尾セグメントから条・項・号の番号を読み取る。
+ * 本文テキストと違い表記ゆれが無いため、番号の一次情報として使う。読み取れないIDには undefined を返す。
+ */
+export function parseProvisionId(elementId: string | undefined): ProvisionNumber | undefined {
+  const matched = elementId?.match(PROVISION_ID_PATTERN);
+  if (!matched) {
+    return undefined;
+  }
+  const [, kind, from, to] = matched;
+  return {
+    kind: PROVISION_KINDS[kind as keyof typeof PROVISION_KINDS],
+    from: parseNumbers(from),
+    to: to ? parseNumbers(to) : undefined,
+  };
+}
+
+/**
+ * 本則の条・項・号のアンカー（例: `Mp-Ch_1-At_27_2-Pr_1`）から、条の番号キー（「27_2」形式）を取り出す。
+ * 章などの階層はリンク元によって省略されること（`Mp-At_195`）があるため、条の特定には番号だけを使う。
+ */
+export function anchorArticleKey(anchor: string): string | undefined {
+  return anchor.match(ANCHOR_ARTICLE_PATTERN)?.[1];
+}
+
+/**
+ * 本則の条・項・号のアンカーから、章などの階層を除いた条・項・号の部分（例: `At_2-Pr_2-It_1`）を取り出す。
+ * 階層を省いたリンク元のアンカーと、ノート内の要素IDを突き合わせるために使う。
+ */
+export function anchorProvisionKey(anchor: string): string | undefined {
+  return anchor.match(ANCHOR_PROVISION_PATTERN)?.[1];
+}
+
+/**
+ * ブロックIDから条名（例: 「第二条」「第2条の2」「第五十五条から第六十三条まで」）を作る。
+ * 条のIDでなければ undefined を返す。
+ */
+export function articleLabel(blockId: string, style: HeadingNumberStyle = 'kanji'): string | undefined {
+  const parsed = parseProvisionId(blockId);
+  if (parsed?.kind !== 'article') {
+    return undefined;
+  }
+  return parsed.to
+    ? `${formatArticleNumber(parsed.from, style)}から${formatArticleNumber(parsed.to, style)}まで`
+    : formatArticleNumber(parsed.from, style);
+}
+
+/**
+ * 本則の条・項・号のアンカーから、`--anchor-style` の見出しと同じ表記の条名（リンク先の見出し）を作る。
+ * 項・号は見出しを持たないため、それを含む条の見出しを指す。本則の条でなければ undefined を返す。
+ */
+export function anchorArticleLabel(anchor: string, style: HeadingNumberStyle): string | undefined {
+  const key = anchorArticleKey(anchor);
+  return key ? formatArticleNumber(parseNumbers(key), style) : undefined;
+}
+
+/**
+ * 条の見出し（例: 「第二条（定義）」「第五十五条から第六十三条まで」）を生成する。
+ * 条番号はブロックIDから作り、IDから読めないとき（附則の見出しなど）だけ本文の条名テキストを使う。
+ */
+export function articleHeading(blockId: string, caption: string, titleText: string): string {
+  const label = articleLabel(blockId) ?? titleText.trim().match(ARTICLE_TITLE_PATTERN)?.[0];
+  if (label) {
+    return `${label}${caption}`;
+  }
+  return caption || titleText.trim() || blockId || '条文';
+}
+
+/**
+ * 条の第1項の行頭に原文の条名（と括弧書きの見出し）が残っていれば取り除く（例: 「第一条　この法律は」→「この法律は」）。
+ * 条見出しを別に出すため、そのままでは同じ条名が2度並ぶ。条名の直後が空白か見出しの括弧のときだけ条名の行とみなし、
+ * 「第一条の規定により」のような本文中の参照は残す。`heading` と違う条名で始まる文も参照として残す。
+ */
+export function stripLeadingArticleTitle(text: string, heading: string): string {
+  const trimmed = text.trimStart();
+  const label = trimmed.match(ARTICLE_TITLE_PATTERN)?.[0];
+  if (!label || heading.match(ARTICLE_TITLE_PATTERN)?.[0] !== label) {
+    return text;
+  }
+  const rest = trimmed.slice(label.length);
+  const title = rest.match(/^(?:（[^）]*）)?\s+|^（[^）]*）/)?.[0];
+  return title ? rest.slice(title.length) : text;
+}
+
+/**
+ * 項番号（「２」）・号番号（「一」「三の二」）を返す。第1項は法令慣行どおり番号を出さない。
+ * 番号は要素IDから作り、IDが無いときだけ本文の見出しテキストを使う。
+ */
+export function provisionNumber(elementId: string | undefined, numberText: string): string | undefined {
+  const parsed = parseProvisionId(elementId);
+  if (parsed?.kind === 'item') {
+    const from = formatBranchNumber(parsed.from);
+    return parsed.to ? `${from}から${formatBranchNumber(parsed.to)}まで` : from;
+  }
+  if (parsed?.kind === 'paragraph') {
+    return parsed.from[0] > 1 ? toFullWidthDigits(parsed.from[0]) : undefined;
+  }
+  const text = numberText.trim();
+  // 第1項の見出し位置には条名（第一条）が入るため、項番号としては扱わない。
+  return text && !ARTICLE_TITLE_PATTERN.test(text) ? text : undefined;
+}
+
+/**
+ * 「同条」「同項」の受け先になる、直前に参照した条・項のアンカー。
+ */
+export interface RecentProvision {
+  article?: string;
+  paragraph?: string;
+}
+
+/**
+ * 本文を「同条」「同項」とそれ以外に分けた断片。`anchor` が無い参照は受け先を特定できなかったもの。
+ */
+export type SameProvisionPart = { type: 'text'; text: string } | { type: 'same_ref'; text: string; anchor?: string };
+
+/**
+ * 参照先のアンカーから、その参照が指す条・項のアンカーを求める（例: `Mp-At_3-Pr_2-It_1` → 条 `Mp-At_3`・項 `Mp-At_3-Pr_2`）。
+ * 条だけの参照では項を持たせず、それより前の項を「同項」で受けないようにする。
+ */
+export function recentProvisionOf(anchor: string): RecentProvision {
+  return {
+    article: anchor.match(RECENT_ARTICLE_PATTERN)?.[1],
+    paragraph: anchor.match(RECENT_PARAGRAPH_PATTERN)?.[1],
+  };
+}
+
+/**
+ * e-Govがリンクしていない本文中の「同条」「同項」を探し、直前に参照した条・項（`recent`）のアンカーを対応付ける。
+ */
+export function splitSameProvisionRefs(text: string, recent: RecentProvision): SameProvisionPart[] {
+  const parts: SameProvisionPart[] = [];
+  let position = 0;
+  for (const matched of text.matchAll(SAME_PROVISION_PATTERN)) {
+    const start = matched.index ?? 0;
+    if (start > position) {
+      parts.push({ type: 'text', text: text.slice(position, start) });
+    }
+    parts.push({ type: 'same_ref', text: matched[0], anchor: matched[1] === '条' ? recent.article : recent.paragraph });
+    position = start + matched[0].length;
+  }
+  if (position < text.length) {
+    parts.push({ type: 'text', text: text.slice(position) });
+  }
+  return parts;
+}
+
+/**
+ * 本文を「同法」（「同法第三条第二項」のように続く条・項を含む）とそれ以外に分けた断片。
+ * `anchor` は参照先の法令のノート内の条・項のアンカーで、法令だけを指す「同法」には無い。
+ */
+export type SameLawPart = { type: 'text'; text: string } | { type: 'same_law'; text: string; anchor?: string };
+
+/**
+ * e-Govがリンクしていない本文中の「同法」を探し、続く条・項から参照先の法令のアンカー（例: `Mp-At_3_2-Pr_2`）を組み立てる。
+ * どの法令を指すかは文脈で決まるため、ここでは決めない。
+ */
+export function splitSameLawRefs(text: string): SameLawPart[] {
+  const parts: SameLawPart[] = [];
+  let position = 0;
+  for (const matched of text.matchAll(SAME_LAW_PATTERN)) {
+    const start = matched.index ?? 0;
+    if (start > position) {
+      parts.push({ type: 'text', text: text.slice(position, start) });
+    }
+    const [, article, branches, paragraph] = matched;
+    const numbers = article
+      ? [article, ...branches.split('の').filter(Boolean)].map((value) => parseJapaneseNumber(value))
+      : [];
+    const paragraphNumber = paragraph ? parseJapaneseNumber(paragraph) : undefined;
+    const anchor =
+      numbers.length > 0 && numbers.every((value) => value !== undefined)
+        ? `Mp-At_${numbers.join('_')}${paragraphNumber !== undefined ? `-Pr_${paragraphNumber}` : ''}`
+        : undefined;
+    parts.push({ type: 'same_law', text: matched[0], anchor });
+    position = start + matched[0].length;
+  }
+  if (position < text.length) {
+    parts.push({ type: 'text', text: text.slice(position) });
+  }
+  return parts;
+}
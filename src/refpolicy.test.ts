@@ -0,0 +1,113 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  classifyRefKind,
+  collectReferencedLawIds,
+  defaultCliOptions,
+  defaultRefPolicies,
+  parseRefPolicy,
+  refPolicyFor,
+  renderMarkdown,
+} from './index.js';
+
+test('classifyRefKind: 参照文言の語尾から種別を推定する', () => {
+  assert.equal(classifyRefKind('東京都情報公開条例'), '条例');
+  assert.equal(classifyRefKind('東京都情報公開条例第三条第一項'), '条例');
+  assert.equal(classifyRefKind('特許法'), '法律');
+  assert.equal(classifyRefKind('内閣府令'), '省令');
+  assert.equal(classifyRefKind('○○に関する告示'), '告示');
+  assert.equal(classifyRefKind('職員服務規程'), '規程');
+  assert.equal(classifyRefKind('第三条'), undefined);
+});
+
+test('parseRefPolicy: 種別ごとのポリシーを既定値に上書きする', () => {
+  const policies = parseRefPolicy('告示=ignore、条例=search', defaultRefPolicies());
+  assert.deepEqual(policies, { 条例: 'search', 告示: 'ignore' });
+  assert.equal(refPolicyFor(defaultRefPolicies(), '条例'), 'external');
+  assert.equal(refPolicyFor(defaultRefPolicies(), '法律'), 'search');
+  assert.equal(refPolicyFor(defaultRefPolicies(), undefined), 'search');
+  assert.throws(() => parseRefPolicy('条例=skip', defaultRefPolicies()), /--ref-policy/);
+  assert.throws(() => parseRefPolicy('通達=ignore', defaultRefPolicies()), /--ref-policy/);
+});
+
+test('renderMarkdown: 外部扱いの参照は未解決参照に溜めず external_refs に残す', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-At_1',
+        heading: '第一条',
+        paragraphs: [
+          {
+            anchor: 'Mp-At_1-Pr_1',
+            segments: [
+              { type: 'link' as const, text: '東京都情報公開条例', href: 'https://www.reiki.metro.tokyo.lg.jp/1' },
+              { type: 'link' as const, text: '大阪府情報公開条例', href: 'javascript:void(0)' },
+              { type: 'link' as const, text: '○○に関する告示', href: 'javascript:void(0)' },
+              { type: 'link' as const, text: '民法', href: '/law/129AC0000000089' },
+            ],
+          },
+        ],
+      },
+    ],
+  };
+  const context = {
+    rootLawId: doc.lawId,
+    rootLawTitle: doc.title,
+    unresolved: [],
+    unresolvedSeen: new Set<string>(),
+    failures: [],
+    references: [],
+  };
+  const options = { ...defaultCliOptions(), refPolicies: parseRefPolicy('法律=external', defaultRefPolicies()) };
+  const rendered = renderMarkdown(doc, {}, options, context, 0);
+  assert.match(rendered.markdown, /^external_refs:\n {2}- "東京都情報公開条例"\n {2}- "大阪府情報公開条例"\n {2}- "民法"$/m);
+  assert.match(rendered.markdown, /\[民法\]\(https:\/\/laws\.e-gov\.go\.jp\/law\/129AC0000000089\)/);
+  assert.deepEqual(rendered.referencedLawIds, []);
+  assert.deepEqual(
+    context.unresolved.map((record: { raw_text: string }) => record.raw_text),
+    ['○○に関する告示'],
+  );
+});
+
+test('renderMarkdown / collectReferencedLawIds: --ref-scope main では附則・別表の参照先を取得対象にしない', () => {
+  const block = (id: string, text: string, href: string) => ({
+    id,
+    heading: id,
+    paragraphs: [{ anchor: `${id}-Pr_1`, segments: [{ type: 'link' as const, text, href }] }],
+  });
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      block('Mp-At_1', '民法', '/law/129AC0000000089'),
+      block('Sp-At_1', '商法', '/law/132AC0000000048'),
+      block('AppdxTable_1', '刑法', '/law/140AC0000000045'),
+    ],
+  };
+  const context = {
+    rootLawId: doc.lawId,
+    rootLawTitle: doc.title,
+    unresolved: [],
+    unresolvedSeen: new Set<string>(),
+    failures: [],
+    references: [],
+  };
+  const options = { ...defaultCliOptions(), refScope: 'main' as const };
+  const rendered = renderMarkdown(doc, {}, options, context, 0);
+  assert.deepEqual(rendered.referencedLawIds, ['129AC0000000089']);
+  // リンクは残すが、取得しないため未解決参照に記録する。
+  assert.match(rendered.markdown, /\[\[[^\]|]*law_132AC0000000048\.md\|商法\]\]/);
+  assert.deepEqual(
+    context.unresolved
+      .filter((record: { reason: string }) => record.reason === 'outside_ref_scope')
+      .map((record: { raw_text: string }) => record.raw_text),
+    ['商法', '刑法'],
+  );
+  assert.deepEqual(collectReferencedLawIds(doc, 'main'), ['129AC0000000089']);
+  assert.deepEqual(collectReferencedLawIds(doc), ['129AC0000000089', '132AC0000000048', '140AC0000000045']);
+});
@@ -0,0 +1,102 @@
+import type { RefPolicy, RefScope } from './types.js';
+
+/** `--ref-policy` で指定できる参照先の法令種別。 */
+export const REF_KINDS = ['憲法', '法律', '政令', '勅令', '省令', '規則', '告示', '訓令', '規程', '条例', '条約'];
+const REF_POLICIES: RefPolicy[] = ['search', 'external', 'ignore'];
+const REF_KIND_SUFFIX_PATTERN = /(憲法|法律|法|政令|勅令|府令|省令|規則|告示|訓令|規程|条例|条約)$/;
+// 「東京都情報公開条例第三条第一項」のように条項まで続く参照文言は、条項を外して法令名の語尾で判定する。
+const ARTICLE_SUFFIX_PATTERN = /第[〇一二三四五六七八九十百千0-9０-９]+[条項号].*$/;
+const REF_KIND_ALIASES: Record<string, string> = { 法: '法律', 府令: '省令' };
+// 種別や総称だけの文言。法令名として検索しても無関係な法令に当たるか、何も当たらない。
+const GENERIC_LAW_NAMES = new Set([
+  '法',
+  '法律',
+  '政令',
+  '勅令',
+  '省令',
+  '府令',
+  '命令',
+  '規則',
+  '告示',
+  '訓令',
+  '規程',
+  '条例',
+  '条約',
+  '施行令',
+  '施行規則',
+  '関係法律',
+  '改正法',
+  '特別法',
+]);
+// 「に関する法律」のように法令名の途中から始まる文言は、リンク文言の切り出し損ねで法令名の一部しか無い。
+const FRAGMENT_NAME_PATTERN = /^(?:に関する|関する|に係る|に基づく|の|及び|並びに|又は|若しくは|等)/;
+// 英数字・記号だけの文言（「A」「（１）」など）は法令名になり得ない。
+const NON_NAME_PATTERN = /^[A-Za-zＡ-Ｚａ-ｚ\p{N}\p{P}\p{S}\s]*$/u;
+
+/**
+ * 種別ごとの参照ポリシーの既定値。条例は地方公共団体の法令でe-Gov法令APIに存在しないため、最初から外部扱いにする。
+ */
+export function defaultRefPolicies(): Record<string, RefPolicy> {
+  return { 条例: 'external' };
+}
+
+/**
+ * 参照文言から条項の部分を除き、法令名だけを返す（例: 「民法第三条第一項」→「民法」）。
+ */
+export function refLawName(text: string): string {
+  return text.trim().replace(ARTICLE_SUFFIX_PATTERN, '');
+}
+
+/**
+ * 条項を除いた法令名が、法令名での検索にかける価値のあるものかを判定する。
+ * 2文字未満の名前、種別や総称だけの名前、法令名の途中から始まる断片、英数字・記号だけの名前は弾く。
+ */
+export function isPlausibleLawName(name: string): boolean {
+  return (
+    [...name].length >= 2 &&
+    !GENERIC_LAW_NAMES.has(name) &&
+    !FRAGMENT_NAME_PATTERN.test(name) &&
+    !NON_NAME_PATTERN.test(name)
+  );
+}
+
+/**
+ * 参照文言（リンク文言）の語尾から参照先の法令種別を推定する。判定できない場合は undefined を返す。
+ */
+export function classifyRefKind(text: string): string | undefined {
+  const kind = refLawName(text).match(REF_KIND_SUFFIX_PATTERN)?.[1];
+  return kind ? (REF_KIND_ALIASES[kind] ?? kind) : undefined;
+}
+
+/**
+ * `--ref-policy`（例: 「条例=external,告示=ignore」）を解釈し、`base` に上書きしたポリシーを返す。
+ */
+export function parseRefPolicy(text: string, base: Record<string, RefPolicy>): Record<string, RefPolicy> {
+  const policies = { ...base };
+  for (const pair of text.split(/[,、]/)) {
+    const [kind, policy] = pair.split('=').map((value) => value.trim());
+    if (!REF_KINDS.includes(kind) || !REF_POLICIES.includes(policy as RefPolicy)) {
+      throw new Error(
+        `--ref-policy は「条例=external」の形で、種別は ${REF_KINDS.join('/')}、` +
+          `ポリシーは ${REF_POLICIES.join('/')} を指定してください: ${pair}`,
+      );
+    }
+    policies[kind] = policy as RefPolicy;
+  }
+  return policies;
+}
+
+/**
+ * 種別に適用する参照ポリシーを返す。種別が不明な参照や指定の無い種別は従来どおり取得対象にする。
+ */
+export function refPolicyFor(policies: Record<string, RefPolicy>, kind: string | undefined): RefPolicy {
+  return (kind && policies[kind]) || 'search';
+}
+
+/**
+ * 本文ブロックが `--ref-scope` の対象（参照先を取得する範囲）かを判定する。
+ * `main` では本則の条（要素IDが `Mp-` で始まる）だけを対象にし、附則・別表から参照される法令までは辿らない。
+ */
+export function isBlockInRefScope(blockId: string, scope: RefScope): boolean {
+  return scope === 'all' || blockId.startsWith('Mp-');
+}
@@ -0,0 +1,111 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  collectUnparsedRefTexts,
+  defaultCliOptions,
+  isPlausibleLawName,
+  refLawName,
+  rememberInvalidRefNames,
+  resolveRefTexts,
+  selectCandidateNonInteractive,
+} from './index.js';
+import type { LawCandidate, LawDictionary } from './types.js';
+
+const link = (text: string, href: string) => ({ type: 'link' as const, text, href });
+
+test('collectUnparsedRefTexts: hrefから law_id を読めない法令名のリンクだけを集める', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: '',
+    blocks: [
+      {
+        id: 'Mp-At_1',
+        heading: '第一条',
+        paragraphs: [
+          {
+            anchor: 'Mp-At_1-Pr_1',
+            segments: [
+              link('民法第三条', '/law/search?q=民法'),
+              link('民法第五条', '/law/search?q=民法'),
+              link('商法', '/law/132AC0000000048'),
+              link('次条', '#Mp-At_2'),
+              link('同法', '/law/search?q=同法'),
+              link('東京都情報公開条例', '/law/search?q=条例'),
+              link('官報', 'https://kanpou.npb.go.jp/'),
+              link('法律', '/law/search?q=法律'),
+              link('に関する法律', '/law/search?q=に関する法律'),
+            ],
+          },
+        ],
+      },
+    ],
+  };
+  assert.deepEqual(collectUnparsedRefTexts(doc, defaultCliOptions()), ['民法第三条', '民法第五条']);
+  assert.equal(refLawName('民法第三条第一項'), '民法');
+});
+
+test('resolveRefTexts: 検索結果を同一実行内で使い回し、見つからなかった法令名も再検索しない', async () => {
+  const searched: string[] = [];
+  const search = async (lawTitle: string): Promise<LawCandidate[]> => {
+    searched.push(lawTitle);
+    return lawTitle === '民法' ? [{ law_id: '129AC0000000089', law_title: '民法', law_num: '明治二十九年法律第八十九号' }] : [];
+  };
+  const texts = ['民法第三条', '民法第五条', '存在しない法'];
+  const options = defaultCliOptions();
+
+  // キャッシュを共有しない場合は、出現する法令（参照元の法令）ごとに検索し直す。
+  for (let i = 0; i < 3; i += 1) {
+    await resolveRefTexts(options, texts, {}, search, selectCandidateNonInteractive, new Map());
+  }
+  const uncachedCount = searched.length;
+  assert.equal(uncachedCount, 6);
+
+  searched.length = 0;
+  const dictionary: LawDictionary = {};
+  const cache = new Map<string, string | null>();
+  for (let i = 0; i < 3; i += 1) {
+    const resolved = await resolveRefTexts(options, texts, dictionary, search, selectCandidateNonInteractive, cache);
+    assert.deepEqual(
+      [...resolved],
+      [
+        ['民法第三条', '129AC0000000089'],
+        ['民法第五条', '129AC0000000089'],
+      ],
+    );
+  }
+  assert.deepEqual(searched, ['民法', '存在しない法']);
+  assert.ok(searched.length < uncachedCount);
+  // 解決できた法令は辞書にも登録する。
+  assert.equal(dictionary['129AC0000000089'].title, '民法');
+  assert.equal(cache.get('存在しない法'), null);
+});
+
+test('isPlausibleLawName: 総称・断片・英数字記号だけの名前を弾く', () => {
+  for (const name of ['民法', '憲法', '刑法', '特許法施行規則', 'ＩＴ基本法']) {
+    assert.equal(isPlausibleLawName(name), true, name);
+  }
+  for (const name of ['法', '法律', '施行規則', '関係法律', 'に関する法律', '及び同法', 'ＡＢ', '（１）', '12-3']) {
+    assert.equal(isPlausibleLawName(name), false, name);
+  }
+});
+
+test('rememberInvalidRefNames: APIで0件だった法令名を覚え、次の実行では検索しない', async () => {
+  const searched: string[] = [];
+  const search = async (lawTitle: string): Promise<LawCandidate[]> => {
+    searched.push(lawTitle);
+    return lawTitle === '民法' ? [{ law_id: '129AC0000000089', law_title: '民法', law_num: '明治二十九年法律第八十九号' }] : [];
+  };
+  const invalidNames = new Set<string>();
+  const options = defaultCliOptions();
+  const texts = ['民法第三条', '存在しない法'];
+  const remembering = rememberInvalidRefNames(search, invalidNames);
+  await resolveRefTexts(options, texts, {}, remembering, selectCandidateNonInteractive, new Map());
+  assert.deepEqual([...invalidNames], ['存在しない法']);
+
+  // 実行をまたぐとキャッシュは空だが、invalid として残った法令名は検索しない。
+  searched.length = 0;
+  await resolveRefTexts(options, texts, {}, remembering, selectCandidateNonInteractive, new Map());
+  assert.deepEqual(searched, ['民法']);
+});
@@ -0,0 +1,152 @@
+import { findCandidateChoice } from './choices.js';
+import { acceptDictionaryEntry } from './dictionary.js';
+import { canonicalizeKanji } from './kanji.js';
+import { dictionaryEntryNames, getFileName, toSafeTitle } from './notes.js';
+import { classifyRefKind, isBlockInRefScope, isPlausibleLawName, refLawName, refPolicyFor } from './refpolicy.js';
+import { parseLawIdFromHref } from './render.js';
+import {
+  dictionaryProvenance,
+  findDictionaryKeyConflicts,
+  isFallbackDictionaryEntry,
+  reportDictionaryKeyConflicts,
+} from './storage.js';
+import type { CliOptions, LawCandidate, LawDictionary, ScrapedLawDocument } from './types.js';
+
+// 「同法」「この法律」などは法令名ではなく、検索しても当たらないか別の法令に当たるため対象外にする。
+const DEICTIC_NAME_PATTERN = /^(?:同|この|その|当該)|^(?:新|旧)(?:法|令)$/;
+
+/**
+ * 法令名の検索結果の同一実行内キャッシュ（法令名 → law_id、見つからなかった法令名は null）。
+ * 再帰取得では同じ法令名が何十回も現れるため、一度失敗した法令名も覚えて再検索しない。
+ */
+export type RefSearchCache = Map<string, string | null>;
+
+const sessionCache: RefSearchCache = new Map();
+
+/**
+ * 本文のリンクのうち、hrefから law_id を読めず法令名での検索が必要なもののリンク文言を集める。
+ * 同一法令内のリンク・Webページへのリンク・検索しない種別（`--ref-policy`）の参照と、法令名になり得ない文言は対象外にする。
+ * 他の法令への参照をリンク化しない場合（`--linkify-rules` に `external` が無い場合）は何も集めない。
+ */
+export function collectUnparsedRefTexts(
+  doc: ScrapedLawDocument,
+  options: Pick<CliOptions, 'refPolicies' | 'linkifyRules'> & Partial<Pick<CliOptions, 'refScope'>>,
+): string[] {
+  const texts = new Set<string>();
+  if (!options.linkifyRules.includes('external')) {
+    return [];
+  }
+  for (const block of doc.blocks.filter((candidate) => isBlockInRefScope(candidate.id, options.refScope ?? 'all'))) {
+    for (const paragraph of block.paragraphs) {
+      for (const segment of paragraph.segments) {
+        if (segment.type !== 'link') {
+          continue;
+        }
+        const href = segment.href.trim();
+        const text = segment.text.trim();
+        if (!href || !text || href.startsWith('#') || /^https?:\/\//.test(href) || parseLawIdFromHref(href)) {
+          continue;
+        }
+        const name = refLawName(text);
+        const kind = classifyRefKind(name);
+        if (
+          kind &&
+          !DEICTIC_NAME_PATTERN.test(name) &&
+          isPlausibleLawName(name) &&
+          refPolicyFor(options.refPolicies, kind) === 'search'
+        ) {
+          texts.add(text);
+        }
+      }
+    }
+  }
+  return [...texts];
+}
+
+/**
+ * 法令名（略称・確定した入力の別名を含む）が表記ゆれを除いて一致する法令が辞書に1件だけあり、`--min-confidence` を満たせばその law_id を返す。
+ */
+export function findDictionaryLawId(
+  options: Pick<CliOptions, 'minConfidence'>,
+  dictionary: LawDictionary,
+  name: string,
+): string | undefined {
+  // 「國」「国」のような表記ゆれで辞書を引き損ねないよう、照合用に正規化した法令名どうしで比べる。
+  const key = canonicalizeKanji(name);
+  const matched = Object.entries(dictionary).filter(
+    ([lawId, entry]) =>
+      !isFallbackDictionaryEntry(lawId, entry) &&
+      dictionaryEntryNames(entry).some((title) => canonicalizeKanji(title) === key),
+  );
+  return matched.length === 1 && acceptDictionaryEntry(options, ...matched[0]) ? matched[0][0] : undefined;
+}
+
+/**
+ * 法令名の検索を包み、`invalidNames` にある法令名は検索せず、APIで0件だった法令名を `invalidNames` に加える。
+ * `invalidNames` は未解決参照に `invalid` として残り、次回以降の実行でも同じ法令名を検索し直さない。
+ */
+export function rememberInvalidRefNames(
+  search: (lawTitle: string) => Promise<LawCandidate[]>,
+  invalidNames: Set<string>,
+): (lawTitle: string) => Promise<LawCandidate[]> {
+  return async (lawTitle) => {
+    if (invalidNames.has(lawTitle)) {
+      return [];
+    }
+    const candidates = await search(lawTitle);
+    if (candidates.length === 0) {
+      invalidNames.add(lawTitle);
+    }
+    return candidates;
+  };
+}
+
+/**
+ * リンク文言から参照先の法令を解決し、リンク文言 → law_id の対応を返す。
+ * ユーザーが選んだ選択履歴があればそれを辞書より優先し、辞書にも無い法令名だけをAPIで検索し、成功した結果は辞書へ登録する。検索結果は `cache` に残し、同じ法令名は再検索しない。
+ * 解決できなかったリンクは対応に含めず、従来どおり未解決参照として出現ごとに記録させる。
+ */
+export async function resolveRefTexts(
+  options: CliOptions,
+  texts: string[],
+  dictionary: LawDictionary,
+  search: (lawTitle: string) => Promise<LawCandidate[]>,
+  select: (lawTitle: string, candidates: LawCandidate[]) => LawCandidate | undefined,
+  cache: RefSearchCache = sessionCache,
+): Promise<Map<string, string>> {
+  const resolved = new Map<string, string>();
+  for (const text of texts) {
+    const name = refLawName(text);
+    const chosen = await findCandidateChoice(options, name);
+    let lawId = chosen?.law_id ?? findDictionaryLawId(options, dictionary, name) ?? cache.get(name);
+    if (lawId === undefined) {
+      let selected: LawCandidate | undefined;
+      try {
+        selected = select(name, await search(name));
+      } catch {
+        // 一時的なAPI障害は解決失敗として覚えず、次に現れたときに再検索させる。
+        continue;
+      }
+      lawId = selected?.law_id ?? null;
+      cache.set(name, lawId);
+      if (selected?.law_id && !dictionary[selected.law_id]) {
+        dictionary[selected.law_id] = {
+          title: selected.law_title,
+          safe_title: toSafeTitle(selected.law_title),
+          file_name: getFileName(selected.law_id, selected.law_title),
+          updated_at: new Date().toISOString(),
+          law_num: selected.law_num,
+          law_type: selected.law_type,
+          promulgation_date: selected.promulgation_date,
+          abbrev: selected.abbrev,
+          ...dictionaryProvenance('fuzzy'),
+        };
+        await reportDictionaryKeyConflicts(options, findDictionaryKeyConflicts(dictionary, 'fuzzy', [selected.law_id]));
+      }
+    }
+    if (lawId) {
+      resolved.set(text, lawId);
+    }
+  }
+  return resolved;
+}
@@ -1,7 +1,37 @@
 import test from 'node:test';
 import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
 
-import { parseLawIdFromHref } from './index.js';
+import {
+  collectCitedLaws,
+  computeLawStats,
+  countStructures,
+  countTags,
+  defaultCliOptions,
+  extractBacklinkLines,
+  figureEmbed,
+  heading,
+  linkAnchor,
+  mergeBacklinksSection,
+  formatUnsupportedElements,
+  parseLawIdFromHref,
+  renderBacklinksSection,
+  renderCitationSection,
+  renderCitedRevisionsField,
+  renderMarkdown,
+  renderMarkdownForTest,
+  renderMarkdownTable,
+  renderReferencedArticleFields,
+  renderReferencedByFields,
+  renderSummaryCallout,
+  renderSupersedesField,
+  replaceBacklinksSection,
+  replaceCitationSection,
+  replaceSummaryCallout,
+  toCalloutLines,
+  uniqueArticleHeadings,
+} from './index.js';
+import type { ItemStyle, LawReferenceEdge, ScrapedLawDocument } from './types.js';
 
 test('parseLawIdFromHref: 相対URLを解析できる', () => {
   const parsed = parseLawIdFromHref('/law/334AC0000000121#Mp-At_1');
@@ -17,3 +47,1281 @@ test('parseLawIdFromHref: 非法令URLは未解釈', () => {
   assert.equal(parseLawIdFromHref('https://example.com/x'), undefined);
   assert.equal(parseLawIdFromHref('/api/2/laws'), undefined);
 });
+
+test('renderBacklinksSection: 同一参照を1行にまとめ、参照元ノートへリンクする', () => {
+  const dictionary = {
+    '417AC0000000086': {
+      title: '会社法',
+      safe_title: '会社法',
+      file_name: '会社法_417AC0000000086.md',
+      updated_at: '2026-02-20T00:00:00Z',
+    },
+  };
+  const references = [
+    { source_law_id: '417AC0000000086', target_law_id: '129AC0000000089', anchor: 'Mp-At_90', text: '第九十条' },
+    { source_law_id: '417AC0000000086', target_law_id: '129AC0000000089', anchor: 'Mp-At_90', text: '第九十条' },
+    { source_law_id: '417AC0000000086', target_law_id: '140AC0000000045', anchor: 'Mp-At_1', text: '第一条' },
+  ];
+  assert.deepEqual(renderBacklinksSection('129AC0000000089', references, dictionary, defaultCliOptions()), [
+    '## 被参照',
+    '',
+    '- [[laws/会社法_417AC0000000086.md|会社法]] から [[#Mp-At_90|第九十条]] が参照されています',
+  ]);
+});
+
+test('heading: 見出しレベルを1〜6に収める', () => {
+  assert.equal(heading(2, '第一条'), '## 第一条');
+  assert.equal(heading(0, '民法'), '# 民法');
+  assert.equal(heading(-1, '民法'), '# 民法');
+  assert.equal(heading(8, '第一条'), '###### 第一条');
+});
+
+test('renderMarkdown / renderBacklinksSection: --heading-base-level に合わせて末尾のセクションも条見出しと同じレベルにする', () => {
+  const options = { ...defaultCliOptions(), headingBaseLevel: 3 };
+  const doc = {
+    lawId: '129AC0000000089',
+    title: '民法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/129AC0000000089',
+    blocks: [{ id: 'Mp-At_1', heading: '第一条', paragraphs: [] }],
+  };
+  const { markdown } = renderMarkdown(
+    doc,
+    {},
+    options,
+    {
+      rootLawId: doc.lawId,
+      rootLawTitle: doc.title,
+      unresolved: [],
+      unresolvedSeen: new Set(),
+      failures: [],
+      references: [],
+    },
+    0,
+  );
+  assert.match(markdown, /^## 民法$/m);
+  assert.match(markdown, /^### 第一条$/m);
+
+  const dictionary = {
+    '417AC0000000086': {
+      title: '会社法',
+      safe_title: '会社法',
+      file_name: '会社法_417AC0000000086.md',
+      updated_at: '2026-02-20T00:00:00Z',
+    },
+  };
+  const references = [
+    { source_law_id: '417AC0000000086', target_law_id: '129AC0000000089', anchor: 'Mp-At_1', text: '第一条' },
+  ];
+  const backlinks = renderBacklinksSection('129AC0000000089', references, dictionary, options);
+  assert.equal(backlinks[0], '### 被参照');
+  const citations = renderCitationSection([{ lawId: '132AC0000000048', title: '商法', revisionId: undefined }], 3);
+  assert.equal(citations[0], '### 参照した法令とリビジョン');
+
+  // 差し替え・読み取りも同じレベルの見出しで探し、再適用してもセクションを重複させない。
+  const once = replaceCitationSection(replaceBacklinksSection(markdown, backlinks, 3), citations, 3);
+  assert.equal(replaceCitationSection(replaceBacklinksSection(once, backlinks, 3), citations, 3), once);
+  assert.deepEqual(extractBacklinkLines(once, 3), backlinks.slice(2));
+  assert.deepEqual(mergeBacklinksSection([], backlinks.slice(2), 3), backlinks);
+  // 別のレベルの同名の見出しは、このノートのセクションとはみなさない。
+  assert.deepEqual(extractBacklinkLines(once, 2), []);
+});
+
+test('replaceBacklinksSection: 再適用しても被参照セクションを重複させない', () => {
+  const section = ['## 被参照', '', '- [[laws/会社法_417AC0000000086.md|会社法]] から 第九十条 が参照されています'];
+  const once = replaceBacklinksSection('# 民法\n\n本文\n', section, 2);
+  const twice = replaceBacklinksSection(once, section, 2);
+  assert.equal(twice, once);
+  assert.equal(replaceBacklinksSection(once, [], 2), '# 民法\n\n本文\n');
+});
+
+test('extractBacklinkLines / mergeBacklinksSection: 既存ノートの被参照を残したまま今回の参照を加える', () => {
+  const previous = '- [[laws/会社法_417AC0000000086.md|会社法]] から 第九十条 が参照されています';
+  const note = `# 民法\n\n本文\n\n## 被参照\n\n${previous}\n\n## 参照した法令とリビジョン\n\n- 商法\n`;
+  assert.deepEqual(extractBacklinkLines(note, 2), [previous]);
+  assert.deepEqual(extractBacklinkLines('# 民法\n\n本文\n', 2), []);
+
+  const current = '- [[laws/商法_132AC0000000048.md|商法]] から 第一条 が参照されています';
+  assert.deepEqual(mergeBacklinksSection(['## 被参照', '', current, previous], [previous], 2), [
+    '## 被参照',
+    '',
+    current,
+    previous,
+  ]);
+  assert.deepEqual(mergeBacklinksSection([], [previous], 2), ['## 被参照', '', previous]);
+  assert.deepEqual(mergeBacklinksSection([], [], 2), []);
+
+  // この実行で描画し直した参照元の既存の項目は、今回の項目で置き換える。
+  const stale = '- [[laws/会社法_417AC0000000086.md|会社法]] から 第九十三条 が参照されています';
+  const unknown = '- law_132AC0000000048 から 第二条 が参照されています';
+  const refreshed = new Set(['417AC0000000086', '132AC0000000048']);
+  assert.deepEqual(mergeBacklinksSection(['## 被参照', '', previous], [stale, unknown, current], 2, refreshed), [
+    '## 被参照',
+    '',
+    previous,
+    current,
+  ]);
+  // law-num スキームの参照元も、辞書の法令番号から law_id を引いて置き換える。
+  const byLawNum = '- [[laws/平成十七年法律第八十六号.md|会社法]] から 第九十三条 が参照されています';
+  const lawNumIndex = new Map([['平成十七年法律第八十六号', '417AC0000000086']]);
+  assert.deepEqual(mergeBacklinksSection([], [byLawNum], 2, refreshed, lawNumIndex), []);
+  assert.deepEqual(mergeBacklinksSection([], [byLawNum], 2, refreshed), ['## 被参照', '', byLawNum]);
+});
+
+test('collectCitedLaws / replaceCitationSection: 参照した法令をリビジョンIDつきで被参照セクションの前に置く', () => {
+  const dictionary = {
+    '129AC0000000089': { title: '民法', safe_title: '民法', file_name: '民法.md', updated_at: '2026-10-16T00:00:00Z' },
+  };
+  const references = [
+    { source_law_id: '417AC0000000086', target_law_id: '129AC0000000089', anchor: 'Mp-At_90', text: '第九十条' },
+    { source_law_id: '417AC0000000086', target_law_id: '129AC0000000089', anchor: 'Mp-At_93', text: '第九十三条' },
+    { source_law_id: '417AC0000000086', target_law_id: '132AC0000000048', text: '商法' },
+    { source_law_id: '417AC0000000086', target_law_id: '417AC0000000086', anchor: 'Mp-At_2', text: '第二条' },
+    { source_law_id: '129AC0000000089', target_law_id: '417AC0000000086', text: '会社法' },
+  ];
+  const revisionIds = new Map([['129AC0000000089', '129AC0000000089_20260401_507AC0000000057']]);
+  const cited = collectCitedLaws('417AC0000000086', references, dictionary, revisionIds);
+  assert.deepEqual(cited, [
+    { lawId: '129AC0000000089', title: '民法', revisionId: '129AC0000000089_20260401_507AC0000000057' },
+    { lawId: '132AC0000000048', title: 'law_132AC0000000048', revisionId: undefined },
+  ]);
+  const section = renderCitationSection(cited, 2);
+  assert.deepEqual(section, [
+    '## 参照した法令とリビジョン',
+    '',
+    '- 民法（129AC0000000089）: 129AC0000000089_20260401_507AC0000000057',
+    '- law_132AC0000000048（132AC0000000048）: リビジョン不明',
+  ]);
+  assert.deepEqual(renderCitedRevisionsField(cited), [
+    'cited_revisions:',
+    '  "民法":',
+    '    - 129AC0000000089_20260401_507AC0000000057',
+    '  law_132AC0000000048:',
+    '    - "不明"',
+  ]);
+
+  const backlinks = ['## 被参照', '', '- [[laws/商法.md|商法]] から 第一条 が参照されています'];
+  const base = replaceBacklinksSection('# 会社法\n\n本文\n', backlinks, 2);
+  const once = replaceCitationSection(base, section, 2);
+  assert.equal(once, `# 会社法\n\n本文\n\n${section.join('\n')}\n\n${backlinks.join('\n')}\n`);
+  assert.equal(replaceCitationSection(once, section, 2), once);
+  // 被参照セクションを差し替えても一覧は残る。
+  assert.equal(replaceBacklinksSection(once, backlinks, 2), once);
+  assert.equal(replaceCitationSection(once, [], 2), base);
+  assert.equal(replaceCitationSection('# 会社法\n\n本文\n', section, 2), `# 会社法\n\n本文\n\n${section.join('\n')}\n`);
+});
+
+test('renderMarkdownForTest: 項番号・号番号を行頭に出し、第1項は番号を省略する', () => {
+  const markdown = renderMarkdownForTest({
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_1-At_2',
+        heading: '第二条',
+        paragraphs: [
+          { anchor: 'Mp-Ch_1-At_2-Pr_1', segments: [{ type: 'text', text: '第一項の本文。' }] },
+          { anchor: 'Mp-Ch_1-At_2-Pr_3', number: '３', segments: [{ type: 'text', text: '第三項の本文。' }] },
+          { anchor: 'Mp-Ch_1-At_2-Pr_3-It_1', number: '一', segments: [{ type: 'text', text: '第一号の本文' }] },
+        ],
+      },
+    ],
+  });
+  assert.match(markdown, /^第一項の本文。$/m);
+  assert.match(markdown, /^\*\*３\*\* 第三項の本文。$/m);
+  assert.match(markdown, /^一　第一号の本文$/m);
+});
+
+test('renderMarkdown: --item-style で号・細分を階層に応じて字下げし、list では入れ子のリストにする', () => {
+  const doc: ScrapedLawDocument = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: '',
+    blocks: [
+      {
+        id: 'Mp-At_67',
+        heading: '第六十七条',
+        paragraphs: [
+          { anchor: 'Mp-At_67-Pr_3', number: '３', segments: [{ type: 'text', text: '次の各号に掲げる期間' }] },
+          { anchor: 'Mp-At_67-It_7', number: '七', level: 1, segments: [{ type: 'text', text: '次の  期間' }] },
+          { anchor: 'Mp-At_67-Si_1', number: 'イ', level: 2, segments: [{ type: 'text', text: '審決' }] },
+        ],
+      },
+    ],
+  };
+  const render = (itemStyle: ItemStyle) =>
+    renderMarkdown(
+      doc,
+      {},
+      { ...defaultCliOptions(), itemStyle },
+      {
+        rootLawId: doc.lawId,
+        rootLawTitle: doc.title,
+        unresolved: [],
+        unresolvedSeen: new Set(),
+        failures: [],
+        references: [],
+      },
+      0,
+    ).markdown;
+
+  assert.match(render('plain'), /^<a id="Mp-At_67-It_7"><\/a>\n七　次の 期間\n<a id="Mp-At_67-Si_1"><\/a>\nイ　審決$/m);
+  assert.match(render('indent'), /^\*\*３\*\* 次の各号に掲げる期間$/m);
+  assert.match(render('indent'), /^ {2}七　次の 期間\n<a id="Mp-At_67-Si_1"><\/a>\n {4}イ　審決$/m);
+  assert.match(
+    render('list'),
+    /^\*\*３\*\* 次の各号に掲げる期間\n\n- <a id="Mp-At_67-It_7"><\/a>七　次の 期間\n {2}- <a id="Mp-At_67-Si_1"><\/a>イ　審決$/m,
+  );
+});
+
+test('renderMarkdownForTest: 第1項の行頭に残った原文の条名は見出しと重複させない', () => {
+  const markdown = renderMarkdownForTest({
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-At_1',
+        heading: '第一条（目的）',
+        paragraphs: [{ anchor: 'Mp-At_1-Pr_1', segments: [{ type: 'text', text: '第一条　この法律は、' }] }],
+      },
+      {
+        id: 'Mp-At_2',
+        heading: '第二条',
+        paragraphs: [
+          { anchor: 'Mp-At_2-Pr_1', segments: [{ type: 'text', text: '第二条の規定は、' }] },
+          { anchor: 'Mp-At_2-Pr_2', number: '２', segments: [{ type: 'text', text: '第二条　前項の' }] },
+        ],
+      },
+    ],
+  });
+  assert.match(markdown, /^この法律は、$/m);
+  assert.match(markdown, /^第二条の規定は、$/m);
+  // 第2項以降の行頭は条名の行ではないため手を付けない。
+  assert.match(markdown, /^\*\*２\*\* 第二条 前項の$/m);
+});
+
+test('renderMarkdown: --paragraph-block-ids で項の行末にブロックIDを付ける', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_1-At_2',
+        heading: '第二条',
+        paragraphs: [
+          { anchor: 'Mp-Ch_1-At_2-p1', segments: [{ type: 'text', text: '第一項の本文。' }] },
+          { anchor: 'Mp-Ch_1-At_2-p2', number: '２', segments: [{ type: 'text', text: '第二項の本文。' }] },
+          { anchor: 'Mp-Ch_1-At_2-p3', number: '一', segments: [{ type: 'text', text: '第一号の本文' }] },
+        ],
+      },
+    ],
+  };
+  const { markdown } = renderMarkdown(
+    doc,
+    {},
+    { ...defaultCliOptions(), paragraphBlockIds: true },
+    {
+      rootLawId: doc.lawId,
+      rootLawTitle: doc.title,
+      unresolved: [],
+      unresolvedSeen: new Set(),
+      failures: [],
+      references: [],
+    },
+    0,
+  );
+  assert.match(markdown, /^第一項の本文。 \^Mp-Ch-1-At-2-Pr-1$/m);
+  assert.match(markdown, /^\*\*２\*\* 第二項の本文。 \^Mp-Ch-1-At-2-Pr-2$/m);
+  assert.match(markdown, /^一　第一号の本文$/m);
+});
+
+test('renderMarkdown: --paragraph-block-ids では、リストにしない号を1つずつ空行で区切りブロックIDを段落の末尾に置く', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-At_2',
+        heading: '第二条',
+        paragraphs: [
+          { anchor: 'Mp-At_2-Pr_1', segments: [{ type: 'text', text: '次に掲げる者' }] },
+          { anchor: 'Mp-At_2-Pr_1-It_1', number: '一', level: 1, segments: [{ type: 'text', text: '第一号' }] },
+          { anchor: 'Mp-At_2-Pr_1-It_1-Sub_1', number: 'イ', level: 2, segments: [{ type: 'text', text: '細分' }] },
+          { anchor: 'Mp-At_2-Pr_1-It_2', number: '二', level: 1, segments: [{ type: 'text', text: '第二号' }] },
+        ],
+      },
+    ],
+  };
+  const render = (itemStyle: 'plain' | 'list', paragraphBlockIds: boolean) =>
+    renderMarkdown(
+      doc,
+      {},
+      { ...defaultCliOptions(), itemStyle, paragraphBlockIds },
+      {
+        rootLawId: doc.lawId,
+        rootLawTitle: doc.title,
+        unresolved: [],
+        unresolvedSeen: new Set(),
+        failures: [],
+        references: [],
+      },
+      0,
+    ).markdown;
+  assert.ok(
+    render('plain', true).includes(
+      [
+        '<a id="Mp-At_2-Pr_1-It_1"></a>',
+        '一　第一号 ^Mp-At-2-Pr-1-It-1',
+        '',
+        '<a id="Mp-At_2-Pr_1-It_1-Sub_1"></a>',
+        'イ　細分',
+        '',
+        '<a id="Mp-At_2-Pr_1-It_2"></a>',
+        '二　第二号 ^Mp-At-2-Pr-1-It-2',
+      ].join('\n'),
+    ),
+  );
+  // ブロックIDが無ければ従来どおり号の並びを詰める。リストは項目ごとにブロックIDを付けられるため詰めたままにする。
+  assert.ok(render('plain', false).includes('一　第一号\n<a id="Mp-At_2-Pr_1-It_1-Sub_1"></a>\nイ　細分'));
+  assert.match(render('list', true), /^- <a id="Mp-At_2-Pr_1-It_1"><\/a>一　第一号 \^Mp-At-2-Pr-1-It-1\n {2}- /m);
+});
+
+test('renderMarkdown: --anchor-style kanji と --paragraph-block-ids では項・号へのリンクを条見出しでなくブロックIDへ向ける', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_1-At_2',
+        heading: '第二条',
+        paragraphs: [
+          { anchor: 'Mp-Ch_1-At_2-Pr_1', segments: [{ type: 'text', text: '第一項の本文。' }] },
+          { anchor: 'Mp-Ch_1-At_2-Pr_2', number: '２', segments: [{ type: 'text', text: '第二項の本文。' }] },
+          { anchor: 'Mp-Ch_1-At_2-Pr_2-It_1', number: '一', level: 1, segments: [{ type: 'text', text: '号の本文' }] },
+        ],
+      },
+      {
+        id: 'Mp-Ch_1-At_3',
+        heading: '第三条',
+        paragraphs: [
+          {
+            anchor: 'Mp-Ch_1-At_3-Pr_1',
+            segments: [
+              { type: 'link', text: '前条', href: '#Mp-Ch_1-At_2' },
+              // リンク元のアンカーは章を省くことがある。
+              { type: 'link', text: '前条第二項', href: '#Mp-At_2-Pr_2' },
+              { type: 'link', text: '同項第一号', href: '#Mp-Ch_1-At_2-Pr_2-It_1' },
+            ],
+          },
+        ],
+      },
+    ],
+  };
+  const render = (paragraphBlockIds: boolean) =>
+    renderMarkdown(
+      doc,
+      {},
+      { ...defaultCliOptions(), anchorStyle: 'kanji', paragraphBlockIds },
+      {
+        rootLawId: doc.lawId,
+        rootLawTitle: doc.title,
+        unresolved: [],
+        unresolvedSeen: new Set(),
+        failures: [],
+        references: [],
+      },
+      0,
+    ).markdown;
+  const markdown = render(true);
+  assert.match(markdown, /^\*\*２\*\* 第二項の本文。 \^Mp-Ch-1-At-2-Pr-2$/m);
+  assert.match(markdown, /^一　号の本文 \^Mp-Ch-1-At-2-Pr-2-It-1$/m);
+  assert.match(markdown, /\[\[#第二条\|前条\]\]\[\[#\^Mp-Ch-1-At-2-Pr-2\|前条第二項\]\]\[\[#\^Mp-Ch-1-At-2-Pr-2-It-1\|同項第一号\]\]/);
+  // ブロックIDを付けなければ、従来どおり項・号へのリンクも条の見出しへ向ける。
+  assert.match(render(false), /\[\[#第二条\|前条\]\]\[\[#第二条\|前条第二項\]\]\[\[#第二条\|同項第一号\]\]/);
+});
+
+test('renderReferencedArticleFields: 参照された条を参照元ごとにマージして出力する', () => {
+  const dictionary = {
+    '417AC0000000086': {
+      title: '会社法',
+      safe_title: '会社法',
+      file_name: '会社法_417AC0000000086.md',
+      updated_at: '2026-02-20T00:00:00Z',
+    },
+    '405AC0000000088': {
+      title: '行政手続法',
+      safe_title: '行政手続法',
+      file_name: '行政手続法_405AC0000000088.md',
+      updated_at: '2026-02-20T00:00:00Z',
+    },
+  };
+  const references = [
+    { source_law_id: '417AC0000000086', target_law_id: '129AC0000000089', anchor: 'Mp-At_90', text: '第九十条' },
+    { source_law_id: '405AC0000000088', target_law_id: '129AC0000000089', anchor: 'Mp-At_90', text: '第九十条' },
+    { source_law_id: '405AC0000000088', target_law_id: '129AC0000000089', anchor: 'Mp-At_95', text: '第九十五条' },
+    { source_law_id: '405AC0000000088', target_law_id: '129AC0000000089', text: '民法' },
+  ];
+  const fields = renderReferencedArticleFields('129AC0000000089', references, dictionary);
+  assert.deepEqual(fields.referencedArticles, ['referenced_articles:', '  - "第九十条"', '  - "第九十五条"']);
+  assert.deepEqual(fields.referencedFrom, [
+    'referenced_from:',
+    '  "会社法":',
+    '    - "第九十条"',
+    '  "行政手続法":',
+    '    - "第九十条"',
+    '    - "第九十五条"',
+  ]);
+});
+
+test('renderReferencedByFields: 参照元ノートへのリンクを列挙し、起点法令には root を付ける', () => {
+  const dictionary = {
+    '417AC0000000086': {
+      title: '会社法',
+      safe_title: '会社法',
+      file_name: '会社法_417AC0000000086.md',
+      updated_at: '2026-02-20T00:00:00Z',
+    },
+  };
+  const options = { ...defaultCliOptions(), linkStyle: 'shortest' as const };
+  const fields = renderReferencedByFields(
+    '129AC0000000089',
+    new Set(['417AC0000000086', '405AC0000000088']),
+    new Set(['417AC0000000086']),
+    dictionary,
+    options,
+  );
+  assert.deepEqual(fields, {
+    root: [],
+    referencedBy: ['referenced_by:', '  - "[[会社法_417AC0000000086.md|会社法]]"', '  - law_405AC0000000088'],
+  });
+  const rootFields = renderReferencedByFields('417AC0000000086', [], new Set(['417AC0000000086']), dictionary, options);
+  assert.deepEqual(rootFields, { root: ['root: true'], referencedBy: [] });
+});
+
+test('computeLawStats: 枝番条を1条として数え、附則・自法令への参照は除く', () => {
+  const stats = computeLawStats({
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_1-At_1',
+        heading: '第一条',
+        paragraphs: [{ anchor: 'Mp-Ch_1-At_1-p1', segments: [{ type: 'text', text: '本文' }] }],
+      },
+      {
+        id: 'Mp-Ch_2-At_46_2',
+        heading: '第四十六条の二',
+        paragraphs: [
+          {
+            anchor: 'Mp-Ch_2-At_46_2-p1',
+            segments: [{ type: 'link', text: '民法', href: '/law/129AC0000000089' }],
+          },
+          { anchor: 'Mp-Ch_2-At_46_2-p2', number: '一', segments: [{ type: 'text', text: '第一号' }] },
+          {
+            anchor: 'Mp-Ch_2-At_46_2-p3',
+            number: '２',
+            segments: [{ type: 'link', text: '第一条', href: '/law/334AC0000000121#Mp-At_1' }],
+          },
+        ],
+      },
+      {
+        id: 'Sup_1-At_1',
+        heading: '第一条',
+        paragraphs: [{ anchor: 'Sup_1-At_1-p1', segments: [{ type: 'text', text: '附則' }] }],
+      },
+      { id: 'AppdxTable_1', heading: '別表（第百九十五条関係）', paragraphs: [] },
+    ],
+  });
+  assert.deepEqual(stats, {
+    article_count: 2,
+    paragraph_count: 3,
+    item_count: 1,
+    char_count: 12,
+    external_ref_count: 1,
+    appendix_count: 1,
+  });
+});
+
+test('replaceSummaryCallout: frontmatter直後にサマリを入れ、再適用しても二重挿入しない', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    lawNum: '昭和三十四年法律第百二十一号',
+    revisionTitle: '令和7年6月1日 施行',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [],
+  };
+  const callout = renderSummaryCallout(doc, undefined, {
+    article_count: 200,
+    paragraph_count: 0,
+    item_count: 0,
+    char_count: 0,
+    external_ref_count: 0,
+    appendix_count: 0,
+  });
+  assert.equal(callout[0], '> [!info] 特許法（昭和三十四年法律第百二十一号）');
+  assert.ok(callout.includes('> - 条数: 200'));
+
+  const markdown = renderMarkdownForTest(doc);
+  assert.match(markdown, /^---\n[\s\S]*?\n---\n\n> \[!info\] 特許法（昭和三十四年法律第百二十一号）\n/);
+  assert.equal(replaceSummaryCallout(markdown, callout), markdown);
+  assert.equal((replaceSummaryCallout(markdown, callout).match(/\[!info\]/g) ?? []).length, 1);
+});
+
+test('renderMarkdown: 廃止された法令には superseded_by・repealed とコールアウトの注記で新法へのリンクを出す', () => {
+  const markdown = renderMarkdownForTest({
+    lawId: '338AC0000000125',
+    title: '有限会社法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/338AC0000000125',
+    supersededBy: { lawId: '417AC0000000087', title: '会社法の施行に伴う関係法律の整備等に関する法律' },
+    repealed: true,
+    blocks: [],
+  });
+  assert.match(markdown, /^repealed: true$/m);
+  assert.match(markdown, /^superseded_by: "\[\[[^\]|]*law_417AC0000000087\.md\|会社法の施行に伴う関係法律の整備等に関する法律\]\]"$/m);
+  assert.match(markdown, /^> - この法令は \[\[[^\]]+\]\] により廃止されました$/m);
+  const current = renderMarkdownForTest({ lawId: '417AC0000000086', title: '会社法', sourceUrl: '', blocks: [] });
+  assert.doesNotMatch(current, /superseded_by|repealed|廃止/);
+});
+
+test('renderSupersedesField: 同じ実行で取得した旧法のうち、この法令に廃止されたものへリンクする', () => {
+  const dictionary = {
+    '338AC0000000125': {
+      title: '有限会社法',
+      safe_title: '有限会社法',
+      file_name: '有限会社法_338AC0000000125.md',
+      updated_at: '2026-02-20T00:00:00Z',
+    },
+  };
+  const options = { ...defaultCliOptions(), linkStyle: 'shortest' as const };
+  const successors = new Map([
+    ['338AC0000000125', '417AC0000000087'],
+    ['341AC0000000050', '417AC0000000087'],
+    ['129AC0000000089', '999AC0000000001'],
+  ]);
+  assert.deepEqual(renderSupersedesField('417AC0000000087', successors, dictionary, options), [
+    'supersedes:',
+    '  - "[[有限会社法_338AC0000000125.md|有限会社法]]"',
+    '  - law_341AC0000000050',
+  ]);
+  assert.deepEqual(renderSupersedesField('129AC0000000089', successors, dictionary, options), []);
+});
+
+test('toCalloutLines: 改行を含む値も各行を引用行にする', () => {
+  assert.deepEqual(toCalloutLines(['- 最終改正: A\nB', '']), ['> - 最終改正: A', '> B', '>']);
+});
+
+test('countStructures: law_full_text のタグ木から附則を除いた条・項・号・別表を数える', async () => {
+  const payload = JSON.parse(await fs.readFile('tests/fixtures/law_data_tokkyoho.json', 'utf8')) as {
+    law_full_text: unknown;
+  };
+  assert.deepEqual(countStructures(payload.law_full_text), {
+    article_count: 302,
+    paragraph_count: 812,
+    item_count: 323,
+    appendix_count: 1,
+  });
+});
+
+test('countTags: law_full_text に出現したタグの種類と出現回数を数える', () => {
+  const tree = {
+    tag: 'Law',
+    children: [
+      { tag: 'Article', children: [{ tag: 'Paragraph', children: ['本文', { tag: 'Fig', attr: { src: 'a.jpg' } }] }] },
+      { tag: 'Article', children: [{ tag: 'Paragraph', children: [] }] },
+    ],
+  };
+  assert.deepEqual(countTags(tree), { Law: 1, Article: 2, Paragraph: 2, Fig: 1 });
+  assert.deepEqual(countTags('<Law Era="Showa"><Article><Sentence>本文</Sentence><Fig src="a.jpg"/></Article></Law>'), {
+    Law: 1,
+    Article: 1,
+    Sentence: 1,
+    Fig: 1,
+  });
+});
+
+test('uniqueArticleHeadings: 附則や重複する条見出しにサフィックスを付けて一意化する', () => {
+  const block = (id: string, heading: string) => ({ id, heading, paragraphs: [] });
+  assert.deepEqual(
+    uniqueArticleHeadings([
+      block('Mp-At_1', '第一条'),
+      block('Mp-At_2', '第二条'),
+      block('334AC0000000121-Sp-At_1', '第一条'),
+      block('411AC0000000041-Sp-At_1', '第一条'),
+      block('Mp-At_2_dup', '第二条'),
+      block('411AC0000000041-Sp-At_3', '第三条'),
+    ]),
+    ['第一条', '第二条', '第一条 (附則)', '第一条 (附則 2)', '第二条 (2)', '第三条'],
+  );
+});
+
+test('renderMarkdownForTest: 読み替え規定・みなし規定の鉤括弧内の参照はリンクにせず、括弧の外はリンクにする', async () => {
+  const doc = JSON.parse(await fs.readFile('tests/fixtures/scraped_suppl_yomikae.json', 'utf8')) as ScrapedLawDocument;
+  const markdown = renderMarkdownForTest(doc);
+  const lines = markdown.split('\n');
+  const line = (anchor: string): string => lines[lines.indexOf(`<a id="${anchor}"></a>`) + 1];
+
+  assert.equal(
+    line('334AC0000000121-Sp-At_2-Pr_1'),
+    'この法律の施行前にした特許出願については、[[#Mp-At_10|第十条]]中「第三十条」とあるのは「第三十一条」と読み替えて、同条の規定を適用する。',
+  );
+  // 読み替え規定でない項の鉤括弧は、従来どおりリンクにする。
+  assert.match(line('334AC0000000121-Sp-At_2-Pr_2'), /「\[\[#Mp-At_30\|第三十条\]\]」の例による/);
+  // 鉤括弧の無いみなし規定は、参照をそのままリンクにする。
+  assert.match(line('334AC0000000121-Sp-At_3-Pr_1'), /\[\[#Mp-At_6\|第六条\]\]の規定によりされた処分とみなす/);
+  assert.match(line('334AC0000000121-Sp-At_3-Pr_1'), /\|旧特許法第五条\]\]/);
+  // 入れ子の括弧（『』）の中の参照もリンクにしない。`a[href]` の無い附則参照は既定ではリンクにしない。
+  assert.equal(
+    line('334AC0000000121-Sp-At_4-Pr_1'),
+    '[[#Mp-At_20-Pr_2|第二十条第二項]]中「次条の規定による『第一項の期間』」とあるのは「附則第二条の規定による期間」と、' +
+      '[[#Mp-At_10|第十条]]中「三十日」とあるのは「六十日」とする。' +
+      '附則第一条の規定にかかわらず、同項の規定を準用する。',
+  );
+});
+
+test('renderMarkdown: --self-links に応じて同一法令への参照のリンク先を切り替える', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mpat_1',
+        heading: '別表',
+        paragraphs: [
+          {
+            anchor: 'Mpat_1-p1',
+            segments: [
+              { type: 'link' as const, text: '第百九十五条', href: '/law/334AC0000000121#Mp-At_195' },
+              { type: 'text' as const, text: '関係' },
+            ],
+          },
+        ],
+      },
+    ],
+  };
+  const dictionary = {
+    '334AC0000000121': {
+      title: '特許法',
+      safe_title: '特許法',
+      file_name: '特許法_334AC0000000121.md',
+      updated_at: '2026-01-01T00:00:00.000Z',
+    },
+  };
+  const render = (selfLinks: 'path' | 'internal') =>
+    renderMarkdown(
+      doc,
+      { ...dictionary },
+      { ...defaultCliOptions(), selfLinks },
+      {
+        rootLawId: doc.lawId,
+        rootLawTitle: doc.title,
+        unresolved: [],
+        unresolvedSeen: new Set(),
+        failures: [],
+        references: [],
+      },
+      0,
+    ).markdown;
+  assert.match(render('path'), /\[\[laws\/特許法_334AC0000000121\.md#Mp-At_195\|第百九十五条\]\]関係/);
+  assert.match(render('internal'), /\[\[#Mp-At_195\|第百九十五条\]\]関係/);
+});
+
+test('renderMarkdown: --link-text-refs で「同条」「同項」を同じ文の直前の参照へリンクする', () => {
+  const text = (value: string) => ({ type: 'text' as const, text: value });
+  const link = (value: string, href: string) => ({ type: 'link' as const, text: value, href });
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-At_5',
+        heading: '第五条',
+        paragraphs: [
+          {
+            anchor: 'Mp-At_5-Pr_1',
+            segments: [
+              link('前条第二項', '#Mp-At_4-Pr_2'),
+              text('の規定により同項に定める期間を、同条の規定にかかわらず延長し、'),
+              link('民法第三条', '/law/129AC0000000089#Mp-At_3'),
+              text('及び同条の規定を準用する。'),
+            ],
+          },
+          { anchor: 'Mp-At_5-Pr_2', segments: [text('同項の期間は、六十日とする。')] },
+        ],
+      },
+    ],
+  };
+  const render = (linkTextRefs: boolean) => {
+    const context = {
+      rootLawId: doc.lawId,
+      rootLawTitle: doc.title,
+      unresolved: [],
+      unresolvedSeen: new Set<string>(),
+      failures: [],
+      references: [],
+    };
+    const { markdown } = renderMarkdown(doc, {}, { ...defaultCliOptions(), linkTextRefs }, context, 0);
+    return { lines: markdown.split('\n'), unresolved: context.unresolved };
+  };
+  const { lines, unresolved } = render(true);
+  const line = (anchor: string): string => lines[lines.indexOf(`<a id="${anchor}"></a>`) + 1];
+
+  assert.match(line('Mp-At_5-Pr_1'), /^\[\[#Mp-At_4-Pr_2\|前条第二項\]\]の規定により\[\[#Mp-At_4-Pr_2\|同項\]\]に定める/);
+  assert.match(line('Mp-At_5-Pr_1'), /期間を、\[\[#Mp-At_4\|同条\]\]の規定にかかわらず/);
+  // 他の法令の条を受ける「同条」と、次の項の文の「同項」は受け先をこのノートで特定できないため文言のまま残す。
+  assert.match(line('Mp-At_5-Pr_1'), /\]\]及び同条の規定を準用する。$/);
+  assert.match(line('Mp-At_5-Pr_2'), /同項の期間は/);
+  assert.doesNotMatch(line('Mp-At_5-Pr_2'), /\[\[/);
+  assert.equal(unresolved.filter((record) => record.raw_text.startsWith('同')).length, 0);
+  assert.doesNotMatch(render(false).lines.join('\n'), /\|同項\]\]/);
+});
+
+test('renderMarkdown: --link-text-refs で「同法」を同じ文の直前にリンクした他の法令へリンクする', () => {
+  const text = (value: string) => ({ type: 'text' as const, text: value });
+  const link = (value: string, href: string) => ({ type: 'link' as const, text: value, href });
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-At_5',
+        heading: '第五条',
+        paragraphs: [
+          {
+            anchor: 'Mp-At_5-Pr_1',
+            segments: [
+              link('民法第一条', '/law/129AC0000000089#Mp-At_1'),
+              text('及び同法第三条の二第二項の規定は、同法の特例として同条の期間に準用する。'),
+            ],
+          },
+          { anchor: 'Mp-At_5-Pr_2', segments: [text('同法第五条の規定は、適用しない。')] },
+          {
+            anchor: 'Mp-At_5-Pr_3',
+            segments: [
+              link('民法第一条', '/law/129AC0000000089#Mp-At_1'),
+              text('、'),
+              link('東京都条例', 'https://www.reiki.metro.tokyo.lg.jp/'),
+              text('及び同法'),
+            ],
+          },
+        ],
+      },
+    ],
+  };
+  const dictionary = {
+    '129AC0000000089': {
+      title: '民法',
+      safe_title: '民法',
+      file_name: '民法_129AC0000000089.md',
+      updated_at: '2026-01-01T00:00:00.000Z',
+    },
+  };
+  const render = (linkTextRefs: boolean) => {
+    const references: LawReferenceEdge[] = [];
+    const context = {
+      rootLawId: doc.lawId,
+      rootLawTitle: doc.title,
+      unresolved: [],
+      unresolvedSeen: new Set<string>(),
+      failures: [],
+      references,
+    };
+    const { markdown } = renderMarkdown(doc, { ...dictionary }, { ...defaultCliOptions(), linkTextRefs }, context, 0);
+    return { lines: markdown.split('\n'), references };
+  };
+  const { lines, references } = render(true);
+  const line = (anchor: string): string => lines[lines.indexOf(`<a id="${anchor}"></a>`) + 1];
+
+  assert.match(
+    line('Mp-At_5-Pr_1'),
+    /及び\[\[laws\/民法_129AC0000000089\.md#Mp-At_3_2-Pr_2\|同法第三条の二第二項\]\]の規定は、\[\[laws\/民法_129AC0000000089\.md\|同法\]\]の特例/,
+  );
+  // 他の法令を受けた後の「同条」はこのノートのアンカーでは指せないため、文言のまま残す。
+  assert.match(line('Mp-At_5-Pr_1'), /として同条の期間に準用する。$/);
+  assert.deepEqual(
+    references.filter((reference) => reference.text.startsWith('同法')).map(({ anchor, text }) => ({ anchor, text })),
+    [
+      { anchor: 'Mp-At_3_2-Pr_2', text: '同法第三条の二第二項' },
+      { anchor: undefined, text: '同法' },
+    ],
+  );
+  // 項・号の文をまたいでは受けず、法令を特定できない参照の後の「同法」も文言のまま残す。
+  assert.match(line('Mp-At_5-Pr_2'), /同法第五条の規定は、適用しない。$/);
+  assert.doesNotMatch(line('Mp-At_5-Pr_2'), /\[\[/);
+  assert.match(line('Mp-At_5-Pr_3'), /\)及び同法$/);
+  assert.doesNotMatch(render(false).lines.join('\n'), /\|同法/);
+});
+
+test('renderMarkdown: --link-text-refs の「同法」は、間にあるこのノートの法令への参照を受けない', () => {
+  const text = (value: string) => ({ type: 'text' as const, text: value });
+  const link = (value: string, href: string) => ({ type: 'link' as const, text: value, href });
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-At_6',
+        heading: '第六条',
+        paragraphs: [
+          {
+            anchor: 'Mp-At_6-Pr_1',
+            segments: [
+              link('民法第三条', '/law/129AC0000000089#Mp-At_3'),
+              text('及び'),
+              link('本法第五条', '/law/334AC0000000121#Mp-At_5'),
+              text('の規定は、同法の規定にかかわらず適用する。'),
+            ],
+          },
+        ],
+      },
+    ],
+  };
+  const dictionary = {
+    '129AC0000000089': {
+      title: '民法',
+      safe_title: '民法',
+      file_name: '民法_129AC0000000089.md',
+      updated_at: '2026-01-01T00:00:00.000Z',
+    },
+  };
+  const render = (selfLinks: 'path' | 'internal'): string => {
+    const context = {
+      rootLawId: doc.lawId,
+      rootLawTitle: doc.title,
+      unresolved: [],
+      unresolvedSeen: new Set<string>(),
+      failures: [],
+      references: [],
+    };
+    const options = { ...defaultCliOptions(), linkTextRefs: true, selfLinks };
+    const { markdown } = renderMarkdown(doc, { ...dictionary }, options, context, 0);
+    const lines = markdown.split('\n');
+    return lines[lines.indexOf('<a id="Mp-At_6-Pr_1"></a>') + 1];
+  };
+
+  // `--self-links` の指定によらず、「同法」は自法令ではなく民法を指す。
+  for (const selfLinks of ['path', 'internal'] as const) {
+    assert.match(render(selfLinks), /の規定は、\[\[laws\/民法_129AC0000000089\.md\|同法\]\]の規定にかかわらず/);
+  }
+});
+
+test('renderMarkdown: --short-ref-labels で同じ法令への連続参照の2つ目以降から法令名を除く', () => {
+  const text = (value: string) => ({ type: 'text' as const, text: value });
+  const link = (value: string, href: string) => ({ type: 'link' as const, text: value, href });
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-At_5',
+        heading: '第五条',
+        paragraphs: [
+          {
+            anchor: 'Mp-At_5-Pr_1',
+            segments: [
+              link('民法第二条', '/law/129AC0000000089#Mp-At_2'),
+              text('、'),
+              link('民法第三条', '/law/129AC0000000089#Mp-At_3'),
+              text('及び'),
+              link('第四条', '/law/129AC0000000089#Mp-At_4'),
+              text('並びに'),
+              link('民法第五条', '/law/129AC0000000089#Mp-At_5'),
+              text('の規定は、'),
+              link('民法第六条', '/law/129AC0000000089#Mp-At_6'),
+              text('、'),
+              link('商法第一条', '/law/132AC0000000048#Mp-At_1'),
+              text('について準用する。'),
+            ],
+          },
+        ],
+      },
+    ],
+  };
+  const dictionary = {
+    '129AC0000000089': {
+      title: '民法',
+      safe_title: '民法',
+      file_name: '民法_129AC0000000089.md',
+      updated_at: '2026-01-01T00:00:00.000Z',
+    },
+  };
+  const render = (shortRefLabels: boolean) => {
+    const references: LawReferenceEdge[] = [];
+    const context = {
+      rootLawId: doc.lawId,
+      rootLawTitle: doc.title,
+      unresolved: [],
+      unresolvedSeen: new Set<string>(),
+      failures: [],
+      references,
+    };
+    const { markdown } = renderMarkdown(doc, { ...dictionary }, { ...defaultCliOptions(), shortRefLabels }, context, 0);
+    return { markdown, references };
+  };
+  const { markdown, references } = render(true);
+  const noteRef = (anchor: string, label: string) => `[[laws/民法_129AC0000000089.md#Mp-At_${anchor}|${label}]]`;
+
+  assert.ok(
+    markdown.includes(
+      `${noteRef('2', '民法第二条')}、${noteRef('3', '第三条')}及び${noteRef('4', '第四条')}並びに${noteRef('5', '第五条')}`,
+    ),
+  );
+  // 間に読点・接続詞以外の文言がある参照や、別の法令への参照は省略しない。
+  assert.ok(markdown.includes(`の規定は、${noteRef('6', '民法第六条')}、`));
+  assert.match(markdown, /\|商法第一条\]\]について準用する。/);
+  // 参照関係に記録する文言は原文のまま。
+  assert.equal(references[1].text, '民法第三条');
+  assert.ok(render(false).markdown.includes(`${noteRef('2', '民法第二条')}、${noteRef('3', '民法第三条')}`));
+});
+
+test('renderMarkdownForTest: 条見出しの前・項の間に空行を置き、号の並びは詰める（スナップショット）', () => {
+  const text = (value: string) => ({ type: 'text' as const, text: value });
+  const markdown = renderMarkdownForTest({
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_1-At_1',
+        heading: '第一条（目的）',
+        paragraphs: [
+          {
+            anchor: 'Mp-Ch_1-At_1-Pr_1',
+            segments: [
+              text('この法律は、発明の保護及び利用を図ることにより、発明を奨励し、もつて産業の発達に寄与することを目的とする。'),
+            ],
+          },
+        ],
+      },
+      {
+        id: 'Mp-Ch_1-At_2',
+        heading: '第二条（定義）',
+        paragraphs: [
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_1',
+            segments: [text('この法律で「発明」とは、自然法則を利用した技術的思想の創作のうち高度のものをいう。')],
+          },
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_2',
+            number: '２',
+            segments: [text('この法律で「特許発明」とは、特許を受けている発明をいう。')],
+          },
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_3',
+            number: '３',
+            segments: [text('この法律で発明について「実施」とは、次に掲げる行為をいう。')],
+          },
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_3-It_1',
+            number: '一',
+            segments: [text('物の発明にあつては、その物の生産、使用、譲渡等をする行為')],
+          },
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_3-It_2',
+            number: '二',
+            segments: [text('方法の発明にあつては、その方法の使用をする行為')],
+          },
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_3-It_3',
+            number: '三',
+            segments: [
+              text('物を生産する方法の発明にあつては、'),
+              { type: 'link', text: '前号', href: '#Mp-Ch_1-At_2-Pr_3-It_2' },
+              text('に掲げるもののほか、その方法により生産した物の使用をする行為'),
+            ],
+          },
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_4',
+            number: '４',
+            segments: [text('この法律で「プログラム等」とは、プログラムその他電子計算機による処理の用に供する情報をいう。')],
+          },
+        ],
+      },
+      {
+        id: 'Mp-Ch_1-At_3',
+        heading: '第三条（期間の計算）',
+        paragraphs: [
+          {
+            anchor: 'Mp-Ch_1-At_3-Pr_1',
+            segments: [text('この法律又はこの法律に基く命令の規定による期間の計算は、次の規定による。')],
+          },
+          {
+            anchor: 'Mp-Ch_1-At_3-Pr_1-It_1',
+            number: '一',
+            segments: [text('期間の初日は、算入しない。')],
+          },
+          {
+            anchor: 'Mp-Ch_1-At_3-Pr_1-It_2',
+            number: '二',
+            segments: [text('期間を定めるのに月又は年をもつてしたときは、暦に従う。')],
+          },
+        ],
+      },
+    ],
+  });
+  const body = markdown.slice(markdown.indexOf('## 第一条'));
+  assert.equal(
+    body,
+    [
+      '## 第一条（目的）',
+      '<a id="Mp-Ch_1-At_1"></a>',
+      '',
+      '<a id="Mp-Ch_1-At_1-Pr_1"></a>',
+      'この法律は、発明の保護及び利用を図ることにより、発明を奨励し、もつて産業の発達に寄与することを目的とする。',
+      '',
+      '## 第二条（定義）',
+      '<a id="Mp-Ch_1-At_2"></a>',
+      '',
+      '<a id="Mp-Ch_1-At_2-Pr_1"></a>',
+      'この法律で「発明」とは、自然法則を利用した技術的思想の創作のうち高度のものをいう。',
+      '',
+      '<a id="Mp-Ch_1-At_2-Pr_2"></a>',
+      '**２** この法律で「特許発明」とは、特許を受けている発明をいう。',
+      '',
+      '<a id="Mp-Ch_1-At_2-Pr_3"></a>',
+      '**３** この法律で発明について「実施」とは、次に掲げる行為をいう。',
+      '',
+      '<a id="Mp-Ch_1-At_2-Pr_3-It_1"></a>',
+      '一　物の発明にあつては、その物の生産、使用、譲渡等をする行為',
+      '<a id="Mp-Ch_1-At_2-Pr_3-It_2"></a>',
+      '二　方法の発明にあつては、その方法の使用をする行為',
+      '<a id="Mp-Ch_1-At_2-Pr_3-It_3"></a>',
+      '三　物を生産する方法の発明にあつては、[[#Mp-Ch_1-At_2-Pr_3-It_2|前号]]に掲げるもののほか、その方法により生産した物の使用をする行為',
+      '',
+      '<a id="Mp-Ch_1-At_2-Pr_4"></a>',
+      '**４** この法律で「プログラム等」とは、プログラムその他電子計算機による処理の用に供する情報をいう。',
+      '',
+      '## 第三条（期間の計算）',
+      '<a id="Mp-Ch_1-At_3"></a>',
+      '',
+      '<a id="Mp-Ch_1-At_3-Pr_1"></a>',
+      'この法律又はこの法律に基く命令の規定による期間の計算は、次の規定による。',
+      '',
+      '<a id="Mp-Ch_1-At_3-Pr_1-It_1"></a>',
+      '一　期間の初日は、算入しない。',
+      '<a id="Mp-Ch_1-At_3-Pr_1-It_2"></a>',
+      '二　期間を定めるのに月又は年をもつてしたときは、暦に従う。',
+      '',
+    ].join('\n'),
+  );
+});
+
+test('renderMarkdownForTest: 図は参照先ファイル名の埋め込みプレースホルダにし、元のURLを残す', () => {
+  const src = 'https://laws.e-gov.go.jp/data/Fig/411AC0000000127_001.jpg';
+  assert.equal(figureEmbed(src), `![[411AC0000000127_001.jpg]]%%図: ${src}%%`);
+  const markdown = renderMarkdownForTest({
+    lawId: '411AC0000000127',
+    title: '国旗及び国歌に関する法律',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/411AC0000000127',
+    blocks: [
+      {
+        id: 'Mpat_1',
+        heading: '別記第一',
+        paragraphs: [
+          {
+            anchor: 'Mpat_1-p1',
+            segments: [
+              { type: 'text', text: '日章旗の制式' },
+              { type: 'figure', text: '', src },
+            ],
+          },
+        ],
+      },
+    ],
+  });
+  assert.match(markdown, /^日章旗の制式!\[\[411AC0000000127_001\.jpg\]\]%%図: https:/m);
+});
+
+test('renderMarkdownForTest: 条文中の表は前後を空行で区切ったMarkdownの表にし、セル内のリンクも描画する', () => {
+  const cell = { type: 'table_break' as const, text: '' as const, boundary: 'cell' as const };
+  const row = { type: 'table_break' as const, text: '' as const, boundary: 'row' as const };
+  const markdown = renderMarkdownForTest({
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_2-At_43_3',
+        heading: '第四十三条の三',
+        paragraphs: [
+          { anchor: 'Mp-Ch_2-At_43_3-Pr_1', segments: [{ type: 'text', text: '次の表の上欄に掲げる者が' }] },
+          {
+            anchor: 'Mp-Ch_2-At_43_3-table1',
+            segments: [
+              { type: 'text', text: '日本国民（' },
+              { type: 'link', text: '次項', href: '#Mp-Ch_2-At_43_3-Pr_2' },
+              { type: 'text', text: 'において同じ。）' },
+              cell,
+              { type: 'text', text: '世界貿易機関の\n加盟国' },
+              cell,
+              row,
+              { type: 'text', text: '加盟国の国民' },
+              cell,
+              { type: 'text', text: '同盟国' },
+              cell,
+              row,
+            ],
+          },
+          { anchor: 'Mp-Ch_2-At_43_3-Pr_2', number: '２', segments: [{ type: 'text', text: 'いずれにも該当しない国' }] },
+        ],
+      },
+    ],
+  });
+  assert.ok(
+    markdown.includes(
+      [
+        '<a id="Mp-Ch_2-At_43_3-table1"></a>',
+        '',
+        '|  |  |',
+        '| --- | --- |',
+        '| 日本国民（[[#Mp-Ch_2-At_43_3-Pr_2\\|次項]]において同じ。） | 世界貿易機関の 加盟国 |',
+        '| 加盟国の国民 | 同盟国 |',
+        '',
+        '<a id="Mp-Ch_2-At_43_3-Pr_2"></a>',
+      ].join('\n'),
+    ),
+    markdown,
+  );
+  // 原文の見出し行はそのまま表の見出しにし、列の足りない行は空のセルで埋める。
+  assert.deepEqual(
+    renderMarkdownTable([
+      { cells: ['区分', '金額'], header: true },
+      { cells: ['一'], header: false },
+    ]),
+    ['| 区分 | 金額 |', '| --- | --- |', '| 一 |  |'],
+  );
+});
+
+test('formatUnsupportedElements: 未対応要素を多い順に一覧する', () => {
+  assert.equal(formatUnsupportedElements({ object: 1, 'svg.formula': 3, canvas: 1 }), 'svg.formula×3, canvas×1, object×1');
+});
+
+test('renderMarkdown: --anchor-style で条見出しとリンク先を同じ条名の表記に揃える', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_1-At_2',
+        heading: '第二条（定義）',
+        caption: '（定義）',
+        paragraphs: [
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_1',
+            segments: [
+              { type: 'link' as const, text: '次条第一号', href: '#Mp-Ch_1-At_3-Pr_1-It_1' },
+              { type: 'link' as const, text: '民法第九十条', href: '/law/129AC0000000089#Mp-Pa_1-Ch_5-At_90' },
+              { type: 'link' as const, text: '附則', href: '#334AC0000000121-Sp' },
+            ],
+          },
+        ],
+      },
+      { id: 'Mp-Ch_1-At_3', heading: '第三条', paragraphs: [] },
+    ],
+  };
+  const render = (anchorStyle: 'id' | 'kanji' | 'arabic') =>
+    renderMarkdown(
+      doc,
+      {},
+      { ...defaultCliOptions(), anchorStyle, linkStyle: 'shortest' },
+      {
+        rootLawId: doc.lawId,
+        rootLawTitle: doc.title,
+        unresolved: [],
+        unresolvedSeen: new Set<string>(),
+        failures: [],
+        references: [],
+      },
+      0,
+    ).markdown;
+
+  const arabic = render('arabic');
+  assert.match(arabic, /^## 第2条\n<a id="Mp-Ch_1-At_2"><\/a>\n\n（定義）$/m);
+  assert.match(arabic, /^## 第3条$/m);
+  assert.match(arabic, /\[\[#第3条\|次条第一号\]\]/);
+  assert.match(arabic, /\[\[law_129AC0000000089\.md#第90条\|民法第九十条\]\]/);
+  assert.match(arabic, /\[\[#334AC0000000121-Sp\|附則\]\]/);
+
+  const kanji = render('kanji');
+  assert.match(kanji, /^## 第二条$/m);
+  assert.match(kanji, /\[\[#第三条\|次条第一号\]\]/);
+
+  assert.match(render('id'), /^## 第二条（定義）$/m);
+  assert.equal(linkAnchor('Mp-Ch_1-At_3-Pr_1', 'id'), 'Mp-Ch_1-At_3-Pr_1');
+});
+
+test('renderMarkdown: --output-format plain はWikiリンクを出さず、Obsidian向けと同じ本文テキストにする', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      {
+        id: 'Mp-Ch_1-At_2',
+        heading: '第二条',
+        paragraphs: [
+          {
+            anchor: 'Mp-Ch_1-At_2-Pr_1',
+            segments: [
+              { type: 'link' as const, text: '次条', href: '#Mp-Ch_1-At_3' },
+              { type: 'text' as const, text: '及び' },
+              { type: 'link' as const, text: '民法第九十条', href: '/law/129AC0000000089#Mp-Pa_1-Ch_5-At_90' },
+              { type: 'text' as const, text: 'の規定' },
+            ],
+          },
+        ],
+      },
+      { id: 'Mp-Ch_1-At_3', heading: '第三条', paragraphs: [] },
+    ],
+  };
+  const render = (outputFormat: 'obsidian' | 'plain', standardLinks = false) =>
+    renderMarkdown(
+      doc,
+      {
+        '129AC0000000089': {
+          title: '民法',
+          safe_title: '民法',
+          file_name: '民法_129AC0000000089.md',
+          updated_at: '2026-01-01T00:00:00.000Z',
+        },
+      },
+      { ...defaultCliOptions(), outputFormat, standardLinks, anchorStyle: 'kanji', filenameScheme: 'law-id' },
+      {
+        rootLawId: doc.lawId,
+        rootLawTitle: doc.title,
+        unresolved: [],
+        unresolvedSeen: new Set<string>(),
+        failures: [],
+        references: [],
+      },
+      0,
+    );
+  const body = (markdown: string) => markdown.slice(markdown.indexOf('\n---\n') + 5);
+
+  const obsidian = render('obsidian');
+  const plain = render('plain');
+  assert.match(obsidian.markdown, /^aliases:/m);
+  assert.doesNotMatch(plain.markdown, /\[\[|^aliases:/m);
+  assert.equal(body(plain.markdown), body(obsidian.markdown).replace(/\[\[[^\]|]*\|([^\]]+)\]\]/g, '$1'));
+  assert.match(plain.markdown, /^次条及び民法第九十条の規定$/m);
+  // リンクを出さなくても参照抽出と再帰取得は従来どおり行う。
+  assert.deepEqual(plain.referencedLawIds, ['129AC0000000089']);
+
+  const standard = render('plain', true).markdown;
+  assert.match(standard, /^\[次条\]\(#第三条\)及び\[民法第九十条\]\(129AC0000000089\.md#第九十条\)の規定$/m);
+  assert.equal(
+    figureEmbed('https://laws.e-gov.go.jp/data/fig1.jpg', 'plain'),
+    '![図](https://laws.e-gov.go.jp/data/fig1.jpg)',
+  );
+});
@@ -1,37 +1,90 @@
+import { defaultCliOptions } from './args.js';
+import { BACKLINKS_TITLE, CITATIONS_TITLE } from './config.js';
+import { joinFrontmatter, renderFrontmatter, renderFrontmatterField, splitFrontmatter } from './frontmatter.js';
+import { canonicalLawNum } from './lawnum.js';
+import { linkifyKindForAnchor } from './linkify.js';
 import {
-  DEFAULT_API_BASE,
-  DEFAULT_DICTIONARY_PATH,
-  DEFAULT_OUTPUT_DIR,
-  DEFAULT_UNRESOLVED_PATH,
-} from './config.js';
-import { unresolvedKey } from './storage.js';
+  classifyLawType,
+  displayTitle,
+  noteLinkTarget,
+  resolveNoteRelativePath,
+  scanReferencedLawIdsFromMarkdown,
+  setFrontmatterField,
+  splitAbbrevs,
+} from './notes.js';
+import { isAnchorOutsidePartialRange } from './partial.js';
+import {
+  anchorArticleLabel,
+  anchorProvisionKey,
+  articleLabel,
+  parseProvisionId,
+  recentProvisionOf,
+  splitSameLawRefs,
+  splitSameProvisionRefs,
+  stripLeadingArticleTitle,
+  type RecentProvision,
+  type SameLawPart,
+  type SameProvisionPart,
+} from './provision.js';
+import { classifyRefKind, isBlockInRefScope, isPlausibleLawName, refLawName, refPolicyFor } from './refpolicy.js';
+import { resolveSectionNote, splitLawSections } from './split.js';
+import { dictionaryProvenance, isFallbackDictionaryEntry, unresolvedKey } from './storage.js';
+import { buildSupplProvisionIndex, splitSupplProvisionRefs, type SupplRefPart } from './suppl.js';
+import { getLawPageUrl } from './utils.js';
 import type {
+  AnchorStyle,
+  ArticleBlock,
   CliOptions,
   LawDictionary,
   LawDictionaryEntry,
+  LawReferenceEdge,
+  LawStats,
+  LawStructureCounts,
+  NoteFrontmatter,
+  OutputFormat,
   ProcessContext,
+  RefScope,
   ScrapedLawDocument,
+  SectionNoteIndex,
   UnresolvedRefRecord,
+  UnresolvedRefStatus,
 } from './types.js';
 
-function escapeYaml(value: string): string {
-  return JSON.stringify(value);
+// 参照抽出は段落内の全リンクで呼ばれるため、正規表現はモジュール単位で一度だけ生成する。
+const LAW_HREF_PATTERN = /^(?:https?:\/\/laws\.e-gov\.go\.jp)?\/law\/([A-Za-z0-9]+)\/?(?:#([A-Za-z0-9_-]+))?$/;
+const MAIN_ARTICLE_ID_PATTERN = /^Mp-(?:.+-)?At_\d+(?:_\d+)*$/;
+const APPENDIX_ID_PATTERN = /^(?:AppdxTable|Mpat)_/;
+const ARABIC_NUMBER_PATTERN = /^[0-9０-９]+$/;
+const KANJI_NUMBER_PATTERN = /^[一二三四五六七八九十百千]+(?:の[一二三四五六七八九十百千]+)*$/;
+const WHITESPACE_RUN_PATTERN = /\s+/g;
+const SUPPL_PROVISION_ID_PATTERN = /(?:^|-)Sp(?:-|$)/;
+const XML_OPEN_TAG_PATTERN = /<([A-Za-z][A-Za-z0-9]*)[\s/>]/g;
+// 「民法第二条、民法第三条及び民法第四条」のように、参照どうしが読点や接続詞だけで続いているか。
+const CONSECUTIVE_REF_SEPARATOR_PATTERN = /^(?:[、，・\s]|及び|並びに|又は|若しくは)+$/;
+// 読み替え規定・みなし規定の目印。「と読み替えて適用する」のような活用も拾う。
+const READING_REPLACEMENT_PATTERN = /とあるのは|と読み替え|とみなす/;
+
+/**
+ * Markdown見出し行を生成する。
+ * 見出しレベルの算出を一箇所に集約し、`--heading-base-level` 変更時も階層関係を保つ。
+ */
+export function heading(level: number, text: string): string {
+  const clamped = Math.min(Math.max(level, 1), 6);
+  return `${'#'.repeat(clamped)} ${text}`;
 }
 
 export function parseLawIdFromHref(href: string): { lawId: string; anchor?: string } | undefined {
   const normalizedHref = href.trim();
-  const matched = normalizedHref.match(
-    /^(?:https?:\/\/laws\.e-gov\.go\.jp)?\/law\/([A-Za-z0-9]+)\/?(?:#([A-Za-z0-9_-]+))?$/,
-  );
+  const matched = normalizedHref.match(LAW_HREF_PATTERN);
   if (!matched) {
     return undefined;
   }
   return { lawId: matched[1], anchor: matched[2] };
 }
 
-export function collectReferencedLawIds(doc: ScrapedLawDocument): string[] {
+export function collectReferencedLawIds(doc: ScrapedLawDocument, scope: RefScope = 'all'): string[] {
   const ids = new Set<string>();
-  for (const block of doc.blocks) {
+  for (const block of doc.blocks.filter((candidate) => isBlockInRefScope(candidate.id, scope))) {
     for (const paragraph of block.paragraphs) {
       for (const segment of paragraph.segments) {
         if (segment.type !== 'link') {
@@ -47,8 +100,328 @@ export function collectReferencedLawIds(doc: ScrapedLawDocument): string[] {
   return [...ids];
 }
 
-function isFallbackDictionaryEntry(lawId: string, entry: LawDictionaryEntry): boolean {
-  return entry.file_name === `law_${lawId}.md`;
+/**
+ * `law_full_text`（JSON形式）のタグ木を走査し、本則の条・項・号・別表の数を数える。
+ * DOM上では項と文の区別や別表の取得が不完全なため、取得できる場合はこちらを正とする。
+ */
+export function countStructures(value: unknown): LawStructureCounts {
+  const counts: LawStructureCounts = { article_count: 0, paragraph_count: 0, item_count: 0, appendix_count: 0 };
+  const walk = (node: unknown): void => {
+    if (typeof node !== 'object' || node === null) {
+      return;
+    }
+    const element = node as { tag?: unknown; children?: unknown };
+    // 附則は改正のたびに増え、法令本体の規模を表さないため数えない。
+    if (element.tag === 'SupplProvision') {
+      return;
+    }
+    if (element.tag === 'Article') {
+      counts.article_count += 1;
+    } else if (element.tag === 'Paragraph') {
+      counts.paragraph_count += 1;
+    } else if (element.tag === 'Item') {
+      counts.item_count += 1;
+    } else if (element.tag === 'AppdxTable') {
+      counts.appendix_count += 1;
+    }
+    if (Array.isArray(element.children)) {
+      element.children.forEach(walk);
+    }
+  };
+  walk(value);
+  return counts;
+}
+
+/**
+ * `law_full_text` に出現したタグの種類ごとの出現回数を数える。
+ * JSON形式はタグ木を走査し、XML形式（文字列）は開始タグを数える。
+ */
+export function countTags(value: unknown): Record<string, number> {
+  const counts: Record<string, number> = {};
+  const add = (tag: string): void => {
+    counts[tag] = (counts[tag] ?? 0) + 1;
+  };
+  if (typeof value === 'string') {
+    for (const matched of value.matchAll(XML_OPEN_TAG_PATTERN)) {
+      add(matched[1]);
+    }
+    return counts;
+  }
+  const walk = (node: unknown): void => {
+    if (typeof node !== 'object' || node === null) {
+      return;
+    }
+    const element = node as { tag?: unknown; children?: unknown };
+    if (typeof element.tag === 'string') {
+      add(element.tag);
+    }
+    if (Array.isArray(element.children)) {
+      element.children.forEach(walk);
+    }
+  };
+  walk(value);
+  return counts;
+}
+
+/**
+ * 本文ブロックから条数・項数・号数・文字数・別表数・外部参照法令数を集計する。
+ * 枝番条（第X条の二）は独立した1条として数え、附則の条は本則の条数に含めない。
+ * `structureCounts` を渡した場合、構造要素の数はそちらを優先する。
+ */
+export function computeLawStats(doc: ScrapedLawDocument, structureCounts?: LawStructureCounts): LawStats {
+  let articleCount = 0;
+  let paragraphCount = 0;
+  let itemCount = 0;
+  let charCount = 0;
+  let appendixCount = 0;
+  for (const block of doc.blocks) {
+    for (const paragraph of block.paragraphs) {
+      // 読了時間の目安なので、リンク記法を除いた表示上の文字数を数える。
+      const text = paragraph.segments
+        .map((segment) => segment.text)
+        .join('')
+        .replace(WHITESPACE_RUN_PATTERN, ' ')
+        .trim();
+      charCount += [...text].length;
+    }
+    if (APPENDIX_ID_PATTERN.test(block.id) || block.heading.startsWith('別表')) {
+      appendixCount += 1;
+      continue;
+    }
+    if (!MAIN_ARTICLE_ID_PATTERN.test(block.id)) {
+      continue;
+    }
+    articleCount += 1;
+    // 第1項は項番号を持たず、号は漢数字の番号を持つため、算用数字の番号だけを第2項以降として数える。
+    const numberedParagraphs = block.paragraphs.filter((paragraph) => ARABIC_NUMBER_PATTERN.test(paragraph.number ?? ''));
+    paragraphCount += block.paragraphs.length > 0 ? 1 + numberedParagraphs.length : 0;
+    itemCount += block.paragraphs.filter((paragraph) => KANJI_NUMBER_PATTERN.test(paragraph.number ?? '')).length;
+  }
+  const externalRefCount = collectReferencedLawIds(doc).filter((lawId) => lawId !== doc.lawId).length;
+  return {
+    article_count: structureCounts?.article_count ?? articleCount,
+    paragraph_count: structureCounts?.paragraph_count ?? paragraphCount,
+    item_count: structureCounts?.item_count ?? itemCount,
+    char_count: charCount,
+    external_ref_count: externalRefCount,
+    appendix_count: structureCounts?.appendix_count ?? appendixCount,
+  };
+}
+
+/**
+ * 複数行になり得るテキストを、改行ごとに `> ` を付けた引用行へ変換する。
+ * 途中に素の改行が残るとコールアウトがそこで途切れるため、各行を個別に引用化する。
+ */
+export function toCalloutLines(lines: string[]): string[] {
+  return lines.flatMap((line) => line.split(/\r?\n/)).map((line) => (line ? `> ${line}` : '>'));
+}
+
+/**
+ * 閲覧用に、法令番号・公布日・最終改正・条数・e-Govへのリンクをまとめたコールアウトを生成する。
+ * Dataview向けの値はfrontmatterが担うため、ここでは人が読む体裁を優先する。
+ */
+export function renderSummaryCallout(
+  doc: ScrapedLawDocument,
+  entry: LawDictionaryEntry | undefined,
+  stats: LawStats,
+  supersededByLink?: string,
+): string[] {
+  const lawNum = canonicalLawNum(doc.lawNum ?? entry?.law_num);
+  // コールアウトの種別行は1行でなければならないため、法令名中の改行は空白に畳む。
+  const title = doc.title.replace(WHITESPACE_RUN_PATTERN, ' ').trim();
+  const body: string[] = [];
+  if (lawNum) {
+    body.push(`- 法令番号: ${lawNum}`);
+  }
+  if (entry?.promulgation_date) {
+    body.push(`- 公布日: ${entry.promulgation_date}`);
+  }
+  if (doc.revisionTitle || doc.amendingLawTitle) {
+    body.push(`- 最終改正: ${[doc.amendingLawTitle, doc.revisionTitle].filter(Boolean).join(' ')}`);
+  }
+  body.push(`- 条数: ${stats.article_count}`);
+  body.push(`- e-Gov: [${title}](${doc.sourceUrl})`);
+  if (supersededByLink) {
+    body.push(`- この法令は ${supersededByLink} により廃止されました`);
+  }
+  return [`> [!info] ${lawNum ? `${title}（${lawNum}）` : title}`, ...toCalloutLines(body)];
+}
+
+/**
+ * frontmatter直後のサマリコールアウトを差し替える。
+ * 既存ノートへ再適用してもコールアウトが二重にならないよう、直後にある `> [!info]` ブロックを置き換える。
+ */
+export function replaceSummaryCallout(markdown: string, calloutLines: string[]): string {
+  const parts = splitFrontmatter(markdown);
+  // frontmatterが無くても先頭のBOMは残す。
+  const head = joinFrontmatter({ ...parts, body: '' });
+  const lines = parts.body.split('\n');
+  let index = 0;
+  while (index < lines.length && lines[index] === '') {
+    index += 1;
+  }
+  if (lines[index]?.startsWith('> [!info]')) {
+    while (index < lines.length && lines[index].startsWith('>')) {
+      index += 1;
+    }
+    while (index < lines.length && lines[index] === '') {
+      index += 1;
+    }
+  }
+  const rest = lines.slice(index).join('\n');
+  const callout = calloutLines.length > 0 ? `${calloutLines.join('\n')}\n\n` : '';
+  return `${head}${parts.frontmatter ? '\n' : ''}${callout}${rest}`;
+}
+
+/**
+ * 条文中の表（`--inline-tables`）をMarkdownの表の行にする。セル内の空白は詰め、表の区切りと紛れる `|` はエスケープする。
+ * Markdownの表は見出し行が必須なため、原文の先頭行が見出し行でなければ空の見出し行を置く。
+ */
+export function renderMarkdownTable(rows: Array<{ cells: string[]; header: boolean }>): string[] {
+  const width = Math.max(...rows.map((row) => row.cells.length));
+  const line = (cells: string[]): string => {
+    const texts = Array.from({ length: width }, (_, index) =>
+      (cells[index] ?? '').replace(WHITESPACE_RUN_PATTERN, ' ').trim().replace(/\|/g, '\\|'),
+    );
+    return `| ${texts.join(' | ')} |`;
+  };
+  const [first, ...rest] = rows;
+  const body = first.header ? rest : rows;
+  return [line(first.header ? first.cells : []), `|${' --- |'.repeat(width)}`, ...body.map((row) => line(row.cells))];
+}
+
+/**
+ * 図の埋め込みプレースホルダを生成する。画像の実体は取得しないため、
+ * 参照先のファイル名で `![[...]]` を置き、元のURLはObsidianのコメントとして残す。
+ * `--output-format plain` ではObsidianの記法が使えないため、元のURLを標準の画像記法で参照する。
+ */
+export function figureEmbed(src: string, outputFormat: OutputFormat = 'obsidian'): string {
+  if (outputFormat === 'plain') {
+    return `![図](${src})`;
+  }
+  const fileName = src.split(/[?#]/)[0].split('/').pop() || src;
+  return `![[${fileName}]]%%図: ${src}%%`;
+}
+
+/**
+ * ノート（`toNotePath` 省略時は同じノート）へのリンクを `--output-format` に合わせて生成する。
+ * plain では文言だけを残し、`--standard-links` 指定時はリンク元ノートからの相対パスで標準Markdownリンクにする。
+ */
+export function noteLink(
+  options: Pick<CliOptions, 'linkStyle' | 'outputFormat' | 'standardLinks'>,
+  fromNotePath: string,
+  toNotePath: string | undefined,
+  anchor: string | undefined,
+  text: string,
+): string {
+  const fragment = anchor ? `#${anchor}` : '';
+  if (options.outputFormat === 'obsidian') {
+    return `[[${toNotePath ? noteLinkTarget(options, fromNotePath, toNotePath) : ''}${fragment}|${text}]]`;
+  }
+  if (!options.standardLinks) {
+    return text;
+  }
+  // 標準Markdownのリンク先は空白を含められないため、セクションノート名などの空白だけをエンコードする。
+  const target = toNotePath ? noteLinkTarget({ linkStyle: 'relative' }, fromNotePath, toNotePath) : '';
+  return `[${text}](${`${target}${fragment}`.replace(/ /g, '%20')})`;
+}
+
+/**
+ * 文言を読み進めた後の鉤括弧（「」『』）の入れ子の深さを返す。対応しない閉じ括弧は無視する。
+ */
+function quoteDepthAfter(text: string, depth: number): number {
+  let current = depth;
+  for (const char of text) {
+    if (char === '「' || char === '『') {
+      current += 1;
+    } else if ((char === '」' || char === '』') && current > 0) {
+      current -= 1;
+    }
+  }
+  return current;
+}
+
+function pushBlankLine(lines: string[]): void {
+  // 空行を重ねると区切りの意味が薄れるため、直前が空行なら追加しない。
+  if (lines.length > 0 && lines[lines.length - 1] !== '') {
+    lines.push('');
+  }
+}
+
+/**
+ * 法令内で重複する条見出しを一意化し、`blocks` と同じ並びの見出し配列を返す。
+ * Obsidianは同名見出しが複数あると最初のものにしか飛べないため、2つ目以降の附則の見出しには
+ * 「(附則)」を、それでも重複する場合は連番を付ける。
+ */
+export function uniqueArticleHeadings(blocks: ArticleBlock[]): string[] {
+  const used = new Set<string>();
+  return blocks.map((block) => {
+    let candidate = block.heading;
+    if (used.has(candidate) && SUPPL_PROVISION_ID_PATTERN.test(block.id)) {
+      candidate = `${block.heading} (附則)`;
+    }
+    const base = candidate;
+    for (let suffix = 2; used.has(candidate); suffix += 1) {
+      candidate = base.endsWith(')') ? `${base.slice(0, -1)} ${suffix})` : `${base} (${suffix})`;
+    }
+    used.add(candidate);
+    return candidate;
+  });
+}
+
+/**
+ * リンク先のアンカーを `--anchor-style` に合わせる。見出しと同じ表記の条名にし、
+ * 条の見出しを指せないアンカー（附則・別表など）は要素IDのまま使う。
+ */
+export function linkAnchor(anchor: string, style: AnchorStyle): string {
+  return (style === 'id' ? undefined : anchorArticleLabel(anchor, style)) ?? anchor;
+}
+
+/**
+ * 項に付けるObsidianブロックIDを生成する。
+ * ブロックIDはラテン文字・数字・ハイフンしか使えず、`第二条-第二項` のような条名の形にはできないため、
+ * e-Govの条IDを変換して項番号を付ける。
+ */
+export function paragraphBlockId(blockId: string, paragraphNumber: number): string {
+  return `${blockId.replace(/[^A-Za-z0-9-]/g, '-')}-Pr-${paragraphNumber}`;
+}
+
+/**
+ * 条の項・号のアンカーから、`--paragraph-block-ids` で付けるブロックIDへの対応を作る。
+ * 号は、属する項のブロックIDに要素IDの号番号を続けたもの（`...-Pr-2-It-1`）にし、項と号を区別できるようにする。
+ * 号番号を要素IDから読めない文（細分など）にはブロックIDを付けない。
+ */
+function blockIdsByAnchor(block: ArticleBlock): Map<string, string> {
+  const ids = new Map<string, string>();
+  let paragraphId = '';
+  for (const [index, paragraph] of block.paragraphs.entries()) {
+    // 第1項は番号を持たないため、条の先頭の文と算用数字の番号を持つ文を項の始まりとみなす。
+    if (index === 0 || ARABIC_NUMBER_PATTERN.test(paragraph.number ?? '')) {
+      const paragraphNumber = paragraph.number
+        ? Number(paragraph.number.replace(/[０-９]/g, (digit) => String.fromCharCode(digit.charCodeAt(0) - 0xfee0)))
+        : 1;
+      paragraphId = paragraphBlockId(block.id, paragraphNumber);
+      ids.set(paragraph.anchor, paragraphId);
+      continue;
+    }
+    const item = parseProvisionId(paragraph.anchor);
+    if (item?.kind === 'item') {
+      ids.set(paragraph.anchor, `${paragraphId}-It-${item.from.join('-')}`);
+    }
+  }
+  return ids;
+}
+
+/**
+ * frontmatterの `aliases` を作る。ファイル名が識別子になるスキームでは、Obsidian上の表示・検索用に法令名を、
+ * 略称のある法令では略称でもリンク・検索できるよう略称を別名登録する。
+ */
+function noteAliases(title: string, entry: LawDictionaryEntry | undefined, options: CliOptions): string[] | undefined {
+  const aliases = [
+    ...(options.filenameScheme !== 'title' ? [title] : []),
+    ...splitAbbrevs(entry?.abbrev).filter((abbrev) => abbrev !== title),
+  ];
+  return aliases.length > 0 ? aliases : undefined;
 }
 
 /**
@@ -60,52 +433,353 @@ export function renderMarkdown(
   options: CliOptions,
   context: ProcessContext,
   currentDepth: number,
-): { markdown: string; referencedLawIds: string[]; dictionaryDirty: boolean } {
+  structureCounts?: LawStructureCounts,
+): {
+  markdown: string;
+  sectionNotes: Array<{ relativePath: string; markdown: string }>;
+  referencedLawIds: string[];
+  dictionaryDirty: boolean;
+} {
   const lines: string[] = [];
   const referencedLawIds: string[] = [];
   const referencedLawIdSet = new Set<string>();
+  const externalRefs = new Set<string>();
   let dictionaryDirty = false;
+  const currentEntry = dictionary[doc.lawId];
+  const currentFileName = currentEntry
+    ? resolveNoteRelativePath(doc.lawId, currentEntry, options)
+    : `law_${doc.lawId}.md`;
 
-  lines.push('---');
-  lines.push(`law_id: ${doc.lawId}`);
-  lines.push(`title: ${escapeYaml(doc.title)}`);
-  lines.push(`source_url: ${doc.sourceUrl}`);
-  lines.push(`fetched_at: ${new Date().toISOString()}`);
-  lines.push('---');
+  const stats = computeLawStats(doc, structureCounts);
+  // 新法のノートがまだ無くても、後で取得したときにつながるよう辞書どおりのノート名でリンクする。
+  const successorEntry = doc.supersededBy ? dictionary[doc.supersededBy.lawId] : undefined;
+  const supersededByLink = doc.supersededBy
+    ? noteLink(
+        options,
+        currentFileName,
+        successorEntry
+          ? resolveNoteRelativePath(doc.supersededBy.lawId, successorEntry, options)
+          : `law_${doc.supersededBy.lawId}.md`,
+        undefined,
+        displayTitle(doc.supersededBy.title),
+      )
+    : undefined;
+  const frontmatter: NoteFrontmatter = {
+    law_id: doc.lawId,
+    title: doc.title,
+    law_num: canonicalLawNum(doc.lawNum ?? currentEntry?.law_num),
+    aliases: options.outputFormat === 'obsidian' ? noteAliases(doc.title, currentEntry, options) : undefined,
+    source_url: doc.sourceUrl,
+    fetched_at: new Date().toISOString(),
+    depth: currentDepth,
+    partial: doc.partialRange ? true : undefined,
+    partial_range: doc.partialRange,
+    superseded_by: supersededByLink,
+    repealed: doc.repealed ? true : undefined,
+    ...(options.stats ? stats : {}),
+  };
+  lines.push(...renderFrontmatter(frontmatter));
   lines.push('');
-  lines.push(`# ${doc.title}`);
+  // 法令名は条見出しの1つ上の階層に置く。
+  lines.push(heading(options.headingBaseLevel - 1, doc.title));
   lines.push('');
 
-  for (const block of doc.blocks) {
-    lines.push(`## ${block.heading}`);
+  // 分割出力では、条リンクが分割後のノートを指せるよう、本文を書く前にアンカー→ノートの対応表を登録する。
+  const split = options.splitBy === 'chapter' ? splitLawSections(doc, currentFileName) : undefined;
+  const supplIndex = buildSupplProvisionIndex(doc);
+  const linkify = new Set(options.linkifyRules);
+  const sectionLines = new Map<string, string[]>();
+  const blockNotePaths = new Map<ArticleBlock, string>();
+  if (split && split.sections.length > 0) {
+    context.sectionNotes = (context.sectionNotes ?? new Map()).set(doc.lawId, split.index);
+    lines.push(heading(options.headingBaseLevel, '目次'));
+    lines.push('');
+    for (const section of split.sections) {
+      lines.push(`- ${noteLink(options, currentFileName, section.relativePath, undefined, section.label)}`);
+      const sectionFrontmatter = [
+        ...renderFrontmatterField('title', `${doc.title} ${section.label}`),
+        ...renderFrontmatterField('parent_law_id', doc.lawId),
+        ...renderFrontmatterField('law_section', section.label),
+        ...renderFrontmatterField('source_url', doc.sourceUrl),
+        ...renderFrontmatterField('fetched_at', frontmatter.fetched_at),
+      ];
+      sectionLines.set(section.relativePath, [
+        '---',
+        ...sectionFrontmatter,
+        '---',
+        '',
+        heading(options.headingBaseLevel - 1, `${doc.title} ${section.label}`),
+        '',
+        noteLink(options, section.relativePath, currentFileName, undefined, displayTitle(doc.title)),
+      ]);
+      for (const block of section.blocks) {
+        blockNotePaths.set(block, section.relativePath);
+      }
+    }
+  }
+
+  // `--anchor-style kanji|arabic` では、リンクから見出しを一意に引けるよう、条の見出しを条名だけにして括弧書きは本文へ移す。
+  const articleLabels = doc.blocks.map((block) =>
+    options.anchorStyle === 'id' ? undefined : articleLabel(block.id, options.anchorStyle),
+  );
+  const headings = uniqueArticleHeadings(
+    doc.blocks.map((block, index) => ({ ...block, heading: articleLabels[index] ?? block.heading })),
+  );
+  // 条名の見出しへ向けると項・号へのリンクがすべて条の見出しに潰れるため、ブロックIDがあれば項・号のブロックへ向ける。
+  // リンク元のアンカーは章などの階層を省くことがあるため、条・項・号の番号で突き合わせる。
+  const blockRefs = new Map<string, string>();
+  if (options.paragraphBlockIds && options.anchorStyle !== 'id' && options.outputFormat === 'obsidian') {
+    for (const block of doc.blocks) {
+      for (const [anchor, blockId] of blockIdsByAnchor(block)) {
+        const key = anchorProvisionKey(anchor);
+        if (key) {
+          blockRefs.set(key, blockId);
+        }
+      }
+    }
+  }
+  for (const [blockIndex, block] of doc.blocks.entries()) {
+    const blockNotePath = blockNotePaths.get(block) ?? currentFileName;
+    const out = sectionLines.get(blockNotePath) ?? lines;
+    pushBlankLine(out);
+    out.push(heading(options.headingBaseLevel, headings[blockIndex]));
     if (block.id) {
-      lines.push(`<a id="${block.id}"></a>`);
+      out.push(`<a id="${block.id}"></a>`);
+    }
+    if (articleLabels[blockIndex] && block.caption) {
+      pushBlankLine(out);
+      out.push(block.caption);
     }
-    lines.push('');
 
-    for (const paragraph of block.paragraphs) {
+    const blockIds = blockIdsByAnchor(block);
+    let previousIsItem = false;
+    for (const [index, paragraph] of block.paragraphs.entries()) {
+      // 第1項は番号を持たないため、条の先頭の文と算用数字の番号を持つ文を項の始まりとみなす。
+      const startsParagraph = index === 0 || ARABIC_NUMBER_PATTERN.test(paragraph.number ?? '');
       const renderedSegments: string[] = [];
+      const recordUnresolved = (
+        reason: UnresolvedRefRecord['reason'],
+        rawText: string,
+        href: string,
+        status: UnresolvedRefStatus = 'pending',
+      ): void => {
+        if (href && context.ignoredRefs?.has(href)) {
+          return;
+        }
+        const unresolved: UnresolvedRefRecord = {
+          timestamp: new Date().toISOString(),
+          root_law_id: context.rootLawId,
+          root_law_title: context.rootLawTitle,
+          source_law_id: doc.lawId,
+          from_anchor: paragraph.anchor,
+          raw_text: rawText,
+          href,
+          reason,
+          status,
+        };
+        const key = unresolvedKey(unresolved);
+        if (!context.unresolvedSeen.has(key)) {
+          context.unresolvedSeen.add(key);
+          context.unresolved.push(unresolved);
+        }
+      };
+      // 同一法令内のリンク。分割出力ではアンカーが別のセクションノートにあり得る。
+      const internalLink = (anchor: string, text: string): string => {
+        const targetNote = resolveSectionNote(split?.index, anchor) ?? currentFileName;
+        const toNotePath = targetNote === blockNotePath ? undefined : targetNote;
+        const blockRef = blockRefs.get(anchorProvisionKey(anchor) ?? '');
+        return noteLink(
+          options,
+          blockNotePath,
+          toNotePath,
+          blockRef ? `^${blockRef}` : linkAnchor(anchor, options.anchorStyle),
+          text,
+        );
+      };
+      // 部分ノートに含まれない条へのリンクはアンカー先が存在しないため、未解決参照として残す。
+      const isOutsidePartialRange = (lawId: string, anchor: string | undefined): boolean => {
+        const articleKeys = context.partialScopes?.get(lawId);
+        return anchor !== undefined && articleKeys !== undefined && isAnchorOutsidePartialRange(anchor, articleKeys);
+      };
+
+      // 「同法附則第二条」のように法令名がリンク文言で直前にあることもあるため、直前の断片の文言を覚えておく。
+      let precedingText = '';
+      // `--link-text-refs` の「同条」「同項」の受け先。項・号の文をまたいで受けることは確かめられないため、文ごとに空から始める。
+      let recent: RecentProvision = {};
+      // `--link-text-refs` の「同法」の受け先。同じ文の中で直前にノートへリンクした他の法令で、法令を特定できない参照の後は空に戻す。
+      let recentLawId: string | undefined;
+      const sameLawLink = (text: string, anchor: string | undefined): string => {
+        const entry = recentLawId ? dictionary[recentLawId] : undefined;
+        if (!recentLawId || !entry) {
+          return text;
+        }
+        const linkedAnchor = anchor && !isOutsidePartialRange(recentLawId, anchor) ? anchor : undefined;
+        context.references.push({ source_law_id: doc.lawId, target_law_id: recentLawId, anchor: linkedAnchor, text });
+        const sectionNote = linkedAnchor
+          ? resolveSectionNote(context.sectionNotes?.get(recentLawId), linkedAnchor)
+          : undefined;
+        return noteLink(
+          options,
+          blockNotePath,
+          sectionNote ?? resolveNoteRelativePath(recentLawId, entry, options),
+          linkedAnchor ? linkAnchor(linkedAnchor, options.anchorStyle) : undefined,
+          text,
+        );
+      };
+      // `--short-ref-labels` で、同じ法令への連続参照の2つ目以降を短くするための、直前の他法令への参照とその後の文言。
+      let previousLawRef: { lawId: string; name: string } | undefined;
+      let textSinceLawRef = '';
+      // 読み替え規定・みなし規定の鉤括弧の中は、読み替える前後の文言そのものであって参照ではない。
+      // 「第十条中『三十日』とあるのは『六十日』と読み替える」の「第十条」のような括弧の外の参照は、従来どおりリンクにする。
+      const quotesAreLiteral = READING_REPLACEMENT_PATTERN.test(
+        paragraph.segments.map((segment) => segment.text).join(''),
+      );
+      let quoteDepth = 0;
+      // 条文中の表は、セルごとに描画した文言を行にまとめる。
+      const tableRows: Array<{ cells: string[]; header: boolean }> = [];
+      const tableCells: string[] = [];
       for (const segment of paragraph.segments) {
+        if (segment.type === 'table_break') {
+          if (segment.boundary === 'cell') {
+            tableCells.push(renderedSegments.splice(0).join(''));
+          } else {
+            tableRows.push({ cells: tableCells.splice(0), header: segment.boundary === 'header_row' });
+          }
+          precedingText = '';
+          previousLawRef = undefined;
+          continue;
+        }
         if (segment.type === 'text') {
-          renderedSegments.push(segment.text);
+          // `a[href]` の無い文言からのリンク化は推測を含むため、`--link-text-refs` を指定したときだけ行う。
+          const supplParts: Array<SupplRefPart | SameLawPart> = options.linkTextRefs
+            ? splitSupplProvisionRefs(segment.text, supplIndex, precedingText).flatMap((part) =>
+                part.type === 'text' ? splitSameLawRefs(part.text) : [part],
+              )
+            : [{ type: 'text', text: segment.text }];
+          for (const supplPart of supplParts) {
+            // 「同条」「同項」の受け先は、附則の参照を含めてそこまでに出た参照で決まるため、断片ごとに分ける。
+            const parts: Array<SupplRefPart | SameLawPart | SameProvisionPart> =
+              supplPart.type === 'text' && options.linkTextRefs
+                ? splitSameProvisionRefs(supplPart.text, recent)
+                : [supplPart];
+            for (const part of parts) {
+              const kind = part.type !== 'text' && part.text.endsWith('項') ? 'paragraph' : 'article';
+              const quoted = quotesAreLiteral && quoteDepth > 0;
+              quoteDepth = quoteDepthAfter(part.text, quoteDepth);
+              if (part.type === 'same_law') {
+                // 他の法令の条を指すため、続く「同条」「同項」はこのノートのアンカーでは受けられない。
+                renderedSegments.push(quoted ? part.text : sameLawLink(part.text, part.anchor));
+                recent = {};
+                continue;
+              }
+              if (part.type === 'text' || !linkify.has(kind) || quoted) {
+                renderedSegments.push(part.text);
+              } else if (part.anchor) {
+                renderedSegments.push(internalLink(part.anchor, part.text));
+                if (part.type === 'suppl_ref') {
+                  recent = recentProvisionOf(part.anchor);
+                }
+              } else if (part.type === 'suppl_ref') {
+                // どの附則か特定できない参照は、推測でリンクせず未解決参照として残す。
+                recordUnresolved('ambiguous_suppl_provision', part.text, '');
+                renderedSegments.push(part.text);
+              } else {
+                // 受け先の分からない「同条」「同項」は文言のまま残す。ノート内の条・項の言い換えで、取得し直す参照先も無いため記録しない。
+                renderedSegments.push(part.text);
+              }
+            }
+          }
+          precedingText = segment.text;
+          textSinceLawRef += segment.text;
+          continue;
+        }
+        if (segment.type === 'figure') {
+          renderedSegments.push(figureEmbed(segment.src, options.outputFormat));
+          precedingText = '';
+          previousLawRef = undefined;
           continue;
         }
 
         const href = segment.href.trim();
         const linkText = segment.text || href;
+        precedingText = linkText;
+        const previousRef = CONSECUTIVE_REF_SEPARATOR_PATTERN.test(textSinceLawRef) ? previousLawRef : undefined;
+        previousLawRef = undefined;
+        textSinceLawRef = '';
 
-        if (!href) {
+        if (!href || (quotesAreLiteral && quoteDepth > 0)) {
           renderedSegments.push(linkText);
           continue;
         }
+
         if (href.startsWith('#')) {
           const anchor = href.replace(/^#/, '').trim();
-          renderedSegments.push(`[[#${anchor}|${linkText}]]`);
+          recent = isOutsidePartialRange(doc.lawId, anchor) ? {} : recentProvisionOf(anchor);
+          if (!linkify.has(linkifyKindForAnchor(anchor))) {
+            renderedSegments.push(linkText);
+            continue;
+          }
+          if (isOutsidePartialRange(doc.lawId, anchor)) {
+            recordUnresolved('outside_partial_range', linkText, href);
+            renderedSegments.push(linkText);
+            continue;
+          }
+          renderedSegments.push(internalLink(anchor, linkText));
           continue;
         }
 
-        const parsed = parseLawIdFromHref(href);
+        const titleResolvedLawId = /^https?:\/\//.test(href) ? undefined : context.refTitleResolutions?.get(linkText);
+        const parsed = parseLawIdFromHref(href) ?? (titleResolvedLawId ? { lawId: titleResolvedLawId } : undefined);
+        if (parsed?.lawId !== doc.lawId) {
+          recentLawId = undefined;
+        }
+        // 他の法令の条を受ける「同条」は、このノートのアンカーでは指せない。
+        recent =
+          parsed?.lawId === doc.lawId && parsed.anchor && !isOutsidePartialRange(parsed.lawId, parsed.anchor)
+            ? recentProvisionOf(parsed.anchor)
+            : {};
+        // 連続参照の2つ目以降は、直前の参照と同じ法令名を除いた条名だけを表示する（記録する文言は原文のまま）。
+        const sameLawName = parsed && previousRef?.lawId === parsed.lawId ? previousRef.name : '';
+        const label =
+          options.shortRefLabels && sameLawName && linkText.startsWith(sameLawName) && linkText !== sameLawName
+            ? linkText.slice(sameLawName.length)
+            : linkText;
+        if (parsed && parsed.lawId !== doc.lawId) {
+          // 「第三条」のように法令名を持たない参照は、直前の法令名を引き継ぐ。
+          previousLawRef = { lawId: parsed.lawId, name: refLawName(linkText) || sameLawName };
+        }
+        // リンク化しない種別は文言だけを残す。意図して外したリンクなので未解決参照にも記録しない。
+        if (!linkify.has(parsed?.lawId === doc.lawId ? linkifyKindForAnchor(parsed.anchor) : 'external')) {
+          renderedSegments.push(linkText);
+          continue;
+        }
+        const outsidePartialRange = parsed !== undefined && isOutsidePartialRange(parsed.lawId, parsed.anchor);
+        if (outsidePartialRange) {
+          recordUnresolved('outside_partial_range', linkText, href);
+        }
+        if (parsed && parsed.lawId === doc.lawId && options.selfLinks === 'internal') {
+          // 同一ノート内への参照はファイル名を含めないことで、ノートをリネームしてもリンクが切れない。
+          renderedSegments.push(
+            parsed.anchor && !outsidePartialRange ? internalLink(parsed.anchor, linkText) : linkText,
+          );
+          continue;
+        }
         if (parsed) {
+          const policy = refPolicyFor(
+            options.refPolicies,
+            classifyLawType(parsed.lawId, dictionary[parsed.lawId]) ?? classifyRefKind(linkText),
+          );
+          if (policy === 'ignore') {
+            renderedSegments.push(linkText);
+            continue;
+          }
+          if (policy === 'external') {
+            // 取得しない種別はノートを作らないため、e-Govの法令ページへ直接リンクする。
+            externalRefs.add(linkText);
+            const pageUrl = getLawPageUrl(options, parsed.lawId);
+            renderedSegments.push(`[${label}](${parsed.anchor ? `${pageUrl}#${parsed.anchor}` : pageUrl})`);
+            continue;
+          }
           let entry = dictionary[parsed.lawId];
           if (!entry) {
             entry = {
@@ -113,105 +787,425 @@ export function renderMarkdown(
               safe_title: `law_${parsed.lawId}`,
               file_name: `law_${parsed.lawId}.md`,
               updated_at: new Date().toISOString(),
+              ...dictionaryProvenance('fallback'),
             };
             dictionary[parsed.lawId] = entry;
             dictionaryDirty = true;
 
-            const unresolved: UnresolvedRefRecord = {
-              timestamp: new Date().toISOString(),
-              root_law_id: context.rootLawId,
-              root_law_title: context.rootLawTitle,
-              from_anchor: paragraph.anchor,
-              raw_text: linkText,
-              href,
-              reason: 'target_not_built',
-            };
-            const key = unresolvedKey(unresolved);
-            if (!context.unresolvedSeen.has(key)) {
-              context.unresolvedSeen.add(key);
-              context.unresolved.push(unresolved);
-            }
+            recordUnresolved('target_not_built', linkText, href);
           }
           if (isFallbackDictionaryEntry(parsed.lawId, entry)) {
-            const unresolved: UnresolvedRefRecord = {
-              timestamp: new Date().toISOString(),
-              root_law_id: context.rootLawId,
-              root_law_title: context.rootLawTitle,
-              from_anchor: paragraph.anchor,
-              raw_text: linkText,
-              href,
-              reason: 'target_not_built',
-            };
-            const key = unresolvedKey(unresolved);
-            if (!context.unresolvedSeen.has(key)) {
-              context.unresolvedSeen.add(key);
-              context.unresolved.push(unresolved);
-            }
+            recordUnresolved('target_not_built', linkText, href);
           }
 
           if (currentDepth + 1 > options.maxDepth) {
-            const unresolved: UnresolvedRefRecord = {
-              timestamp: new Date().toISOString(),
-              root_law_id: context.rootLawId,
-              root_law_title: context.rootLawTitle,
-              from_anchor: paragraph.anchor,
-              raw_text: linkText,
-              href,
-              reason: 'depth_limit',
-            };
-            const key = unresolvedKey(unresolved);
-            if (!context.unresolvedSeen.has(key)) {
-              context.unresolvedSeen.add(key);
-              context.unresolved.push(unresolved);
-            }
+            recordUnresolved('depth_limit', linkText, href);
+          } else if (!isBlockInRefScope(block.id, options.refScope)) {
+            // リンクは出すが参照先は取得しないため、ノートが無ければ行き先の無いリンクになる。
+            recordUnresolved('outside_ref_scope', linkText, href);
           } else if (!referencedLawIdSet.has(parsed.lawId)) {
             referencedLawIdSet.add(parsed.lawId);
             referencedLawIds.push(parsed.lawId);
           }
 
-          const target = parsed.anchor ? `laws/${entry.file_name}#${parsed.anchor}` : `laws/${entry.file_name}`;
-          renderedSegments.push(`[[${target}|${linkText}]]`);
+          context.references.push({
+            source_law_id: doc.lawId,
+            target_law_id: parsed.lawId,
+            anchor: parsed.anchor,
+            text: linkText,
+          });
+
+          const sectionNote = parsed.anchor
+            ? resolveSectionNote(context.sectionNotes?.get(parsed.lawId), parsed.anchor)
+            : undefined;
+          // このノート自身の法令への参照は「同法」の受け先を変えない（`--self-links internal` と同じ扱い）。
+          if (parsed.lawId !== doc.lawId) {
+            recentLawId = parsed.lawId;
+          }
+          renderedSegments.push(
+            noteLink(
+              options,
+              blockNotePath,
+              sectionNote ?? resolveNoteRelativePath(parsed.lawId, entry, options),
+              parsed.anchor && !outsidePartialRange ? linkAnchor(parsed.anchor, options.anchorStyle) : undefined,
+              label,
+            ),
+          );
           continue;
         }
 
-        if (href.startsWith('http://') || href.startsWith('https://')) {
+        // e-Gov外の参照（条例など）は取得できないため、種別のポリシーに従って未解決参照に溜めずに扱う。
+        const textPolicy = refPolicyFor(options.refPolicies, classifyRefKind(linkText));
+        if (textPolicy === 'external') {
+          externalRefs.add(linkText);
+        }
+        if ((href.startsWith('http://') || href.startsWith('https://')) && textPolicy !== 'ignore') {
           renderedSegments.push(`[${linkText}](${href})`);
           continue;
         }
 
-        const unresolved: UnresolvedRefRecord = {
-          timestamp: new Date().toISOString(),
-          root_law_id: context.rootLawId,
-          root_law_title: context.rootLawTitle,
-          from_anchor: paragraph.anchor,
-          raw_text: linkText,
-          href,
-          reason: 'unknown_format',
-        };
-        const key = unresolvedKey(unresolved);
-        if (!context.unresolvedSeen.has(key)) {
-          context.unresolvedSeen.add(key);
-          context.unresolved.push(unresolved);
+        if (textPolicy === 'search') {
+          const name = refLawName(linkText);
+          const invalid = !isPlausibleLawName(name) || context.invalidRefNames?.has(name);
+          recordUnresolved('unknown_format', linkText, href, invalid ? 'invalid' : 'pending');
         }
         renderedSegments.push(linkText);
       }
 
-      const paragraphText = renderedSegments.join('').replace(/\s+/g, ' ').trim();
+      if (tableRows.length > 0) {
+        pushBlankLine(out);
+        // 表は前後を空行で区切らないと、直前のアンカーや直後の段落と続けて解釈される。
+        out.push(`<a id="${paragraph.anchor}"></a>`, '', ...renderMarkdownTable(tableRows), '');
+        previousIsItem = false;
+        continue;
+      }
+
+      // 空白を詰める前でないと、条名の後の全角空白と本文中の空白を区別できない。
+      const rawText = renderedSegments.join('');
+      const paragraphText = (index === 0 ? stripLeadingArticleTitle(rawText, block.heading) : rawText)
+        .replace(WHITESPACE_RUN_PATTERN, ' ')
+        .trim();
       if (paragraphText) {
-        lines.push(`<a id="${paragraph.anchor}"></a>`);
-        lines.push(paragraphText);
-        lines.push('');
+        const isItem = Boolean(paragraph.number) && !startsParagraph;
+        // 字下げは空白を詰めた後に付けるため、本文中の空白の正規化で潰れない。項の文は字下げしない。
+        const level = startsParagraph ? 0 : (paragraph.level ?? 0);
+        const asListItem = options.itemStyle === 'list' && level > 0;
+        // 項と項の間は空行で区切り、同じ項に続く号の並びは1つのリストとして詰める。
+        // ただしブロックIDは段落の末尾にしか付けられないため、リストにしない号・細分はブロックID付きなら1つずつ区切る。
+        const packsItem = isItem && previousIsItem && (asListItem || !options.paragraphBlockIds);
+        if (!packsItem) {
+          pushBlankLine(out);
+        }
+        previousIsItem = isItem;
+        if (!asListItem) {
+          out.push(`<a id="${paragraph.anchor}"></a>`);
+        }
+        let line = paragraphText;
+        if (paragraph.number && startsParagraph) {
+          // 項の境目が本文に埋もれないよう、項番号は太字にして区別する。
+          line = `**${paragraph.number}** ${paragraphText}`;
+        } else if (paragraph.number) {
+          // 号番号は原文の体裁に合わせ、全角空白区切りで行頭に置く。
+          line = `${paragraph.number}　${paragraphText}`;
+        }
+        const blockId = options.paragraphBlockIds ? blockIds.get(paragraph.anchor) : undefined;
+        if (blockId) {
+          line = `${line} ^${blockId}`;
+        }
+        if (asListItem) {
+          // アンカーだけの行を挟むとリストが途切れるため、リストではアンカーを項目の先頭に置く。
+          // 番号の無い文（細分の表の右欄など）は、直前の項目の続きとして同じ深さに字下げする。
+          const marker = paragraph.number ? '- ' : '  ';
+          line = `${'  '.repeat(level - 1)}${marker}<a id="${paragraph.anchor}"></a>${line}`;
+        } else if (options.itemStyle === 'indent') {
+          line = `${'  '.repeat(level)}${line}`;
+        }
+        out.push(line);
       }
     }
   }
 
+  let markdown = `${lines.join('\n').trimEnd()}\n`;
+  if (externalRefs.size > 0) {
+    markdown = setFrontmatterField(markdown, 'external_refs', renderFrontmatterField('external_refs', [...externalRefs]));
+  }
+  if (options.summaryCallout) {
+    markdown = replaceSummaryCallout(markdown, renderSummaryCallout(doc, currentEntry, stats, supersededByLink));
+  }
+
   return {
-    markdown: `${lines.join('\n').trimEnd()}\n`,
+    markdown,
+    sectionNotes: [...sectionLines].map(([relativePath, noteLines]) => ({
+      relativePath,
+      markdown: `${noteLines.join('\n').trimEnd()}\n`,
+    })),
     referencedLawIds,
     dictionaryDirty,
   };
 }
 
+/**
+ * 実行中に収集した参照関係から、参照先ノート末尾に置く「被参照」セクションを生成する。
+ * Obsidianのバックリンクは全リンクを拾うため、法令間で条を参照している関係だけを明示する。
+ */
+export function renderBacklinksSection(
+  targetLawId: string,
+  references: LawReferenceEdge[],
+  dictionary: LawDictionary,
+  options: CliOptions,
+  sectionIndex?: SectionNoteIndex,
+): string[] {
+  const targetEntry = dictionary[targetLawId];
+  const targetFileName = targetEntry
+    ? resolveNoteRelativePath(targetLawId, targetEntry, options)
+    : `law_${targetLawId}.md`;
+  const lines: string[] = [];
+  const seen = new Set<string>();
+  for (const ref of references) {
+    if (ref.target_law_id !== targetLawId || ref.source_law_id === targetLawId) {
+      continue;
+    }
+    const key = `${ref.source_law_id}\t${ref.anchor ?? ''}\t${ref.text}`;
+    if (seen.has(key)) {
+      continue;
+    }
+    seen.add(key);
+    const source = dictionary[ref.source_law_id];
+    const sourceTitle = source?.title ?? `law_${ref.source_law_id}`;
+    let sourceLink = sourceTitle;
+    if (source) {
+      const sourceFileName = resolveNoteRelativePath(ref.source_law_id, source, options);
+      sourceLink = noteLink(options, targetFileName, sourceFileName, undefined, displayTitle(sourceTitle));
+    }
+    // 分割出力した法令では、参照された条はセクションノート側にあるため、そのノートの見出しへリンクする。
+    const sectionNote = ref.anchor ? resolveSectionNote(sectionIndex, ref.anchor) : undefined;
+    const targetText = ref.anchor
+      ? noteLink(options, targetFileName, sectionNote, linkAnchor(ref.anchor, options.anchorStyle), ref.text)
+      : ref.text;
+    lines.push(`- ${sourceLink} から ${targetText} が参照されています`);
+  }
+  if (lines.length === 0) {
+    return [];
+  }
+  return [heading(options.headingBaseLevel, BACKLINKS_TITLE), '', ...lines];
+}
+
+/**
+ * 参照先ノートのfrontmatterへ書く `referenced_articles` / `referenced_from` の行を生成する。
+ * 同じ法令が複数経路から参照された場合も、条リストは重複を除いてマージする。
+ */
+export function renderReferencedArticleFields(
+  targetLawId: string,
+  references: LawReferenceEdge[],
+  dictionary: LawDictionary,
+): { referencedArticles: string[]; referencedFrom: string[] } {
+  const articles: string[] = [];
+  const bySource = new Map<string, string[]>();
+  for (const ref of references) {
+    // 条を特定しない法令全体への参照は「どの条か」の情報を持たないため対象外とする。
+    if (ref.target_law_id !== targetLawId || ref.source_law_id === targetLawId || !ref.anchor) {
+      continue;
+    }
+    if (!articles.includes(ref.text)) {
+      articles.push(ref.text);
+    }
+    const sourceTitle = dictionary[ref.source_law_id]?.title ?? `law_${ref.source_law_id}`;
+    const sourceArticles = bySource.get(sourceTitle) ?? [];
+    if (!sourceArticles.includes(ref.text)) {
+      sourceArticles.push(ref.text);
+    }
+    bySource.set(sourceTitle, sourceArticles);
+  }
+  if (articles.length === 0) {
+    return { referencedArticles: [], referencedFrom: [] };
+  }
+  return {
+    referencedArticles: renderFrontmatterField('referenced_articles', articles),
+    referencedFrom: renderFrontmatterField('referenced_from', Object.fromEntries(bySource)),
+  };
+}
+
+/**
+ * 参照先ノートのfrontmatterへ書く `referenced_by`（この法令を取得対象に積んだ参照元ノートへのリンク）の行を生成する。
+ * 起点法令（`rootLawIds`）は辿る参照元が無いため、`referenced_by` の代わりに `root: true` を付ける。
+ */
+export function renderReferencedByFields(
+  lawId: string,
+  sourceLawIds: Iterable<string>,
+  rootLawIds: ReadonlySet<string>,
+  dictionary: LawDictionary,
+  options: CliOptions,
+): { root: string[]; referencedBy: string[] } {
+  if (rootLawIds.has(lawId)) {
+    return { root: renderFrontmatterField('root', true), referencedBy: [] };
+  }
+  const entry = dictionary[lawId];
+  const fileName = entry ? resolveNoteRelativePath(lawId, entry, options) : `law_${lawId}.md`;
+  const links = [...sourceLawIds].map((sourceLawId) => {
+    const source = dictionary[sourceLawId];
+    return source
+      ? noteLink(
+          options,
+          fileName,
+          resolveNoteRelativePath(sourceLawId, source, options),
+          undefined,
+          displayTitle(source.title),
+        )
+      : `law_${sourceLawId}`;
+  });
+  return { root: [], referencedBy: renderFrontmatterField('referenced_by', links) };
+}
+
+/**
+ * 新法のノートのfrontmatterへ書く `supersedes`（この法令により廃止された旧法ノートへのリンク）の行を生成する。
+ * `successors` は同じ実行で取得した旧法の law_id から新法の law_id への対応。
+ */
+export function renderSupersedesField(
+  lawId: string,
+  successors: Map<string, string>,
+  dictionary: LawDictionary,
+  options: CliOptions,
+): string[] {
+  const entry = dictionary[lawId];
+  const fileName = entry ? resolveNoteRelativePath(lawId, entry, options) : `law_${lawId}.md`;
+  const links = [...successors]
+    .filter(([, successorLawId]) => successorLawId === lawId)
+    .map(([predecessorLawId]) => {
+      const predecessor = dictionary[predecessorLawId];
+      return predecessor
+        ? noteLink(
+            options,
+            fileName,
+            resolveNoteRelativePath(predecessorLawId, predecessor, options),
+            undefined,
+            displayTitle(predecessor.title),
+          )
+        : `law_${predecessorLawId}`;
+    });
+  return renderFrontmatterField('supersedes', links);
+}
+
+/**
+ * 既存ノートの「被参照」セクション（`headingBaseLevel` の見出し）の項目行（`- ` で始まる行）を返す。
+ * セクションが無ければ空配列を返す。
+ */
+export function extractBacklinkLines(markdown: string, headingBaseLevel: number): string[] {
+  const marker = `\n${heading(headingBaseLevel, BACKLINKS_TITLE)}\n`;
+  const index = markdown.indexOf(marker);
+  if (index < 0) {
+    return [];
+  }
+  const lines: string[] = [];
+  for (const line of markdown.slice(index + marker.length).split('\n')) {
+    if (/^#+ /.test(line)) {
+      break;
+    }
+    if (line.startsWith('- ')) {
+      lines.push(line);
+    }
+  }
+  return lines;
+}
+
+/**
+ * 被参照セクションの項目行（`- <参照元> から <参照した条> が参照されています`）から、参照元の law_id を読み取る。
+ * 読み取れなければ undefined を返す。
+ */
+function backlinkSourceLawId(line: string, lawNumIndex?: ReadonlyMap<string, string>): string | undefined {
+  const source = line.slice(2, line.indexOf(' から '));
+  // 辞書に無い参照元はリンクにせず `law_<law_id>` と書いている。
+  return (
+    scanReferencedLawIdsFromMarkdown(source, 'all', lawNumIndex).referencedLawIds[0] ??
+    source.match(/^law_([A-Za-z0-9]+)$/)?.[1]
+  );
+}
+
+/**
+ * `renderBacklinksSection` の結果に、既存ノートの被参照セクションにあった項目行 `previousLines` を重複なく加える。
+ * 今回取得していない参照元の項目を失わないよう、既存の項目は残したまま今回の項目を合わせる。
+ * ただし `refreshedSourceLawIds`（この実行で本文を描画し直した法令）からの既存の項目は今回の項目で置き換え、
+ * 改正などで参照しなくなった条の項目を残さない。見出しは `headingBaseLevel` のレベルにする。
+ */
+export function mergeBacklinksSection(
+  sectionLines: string[],
+  previousLines: string[],
+  headingBaseLevel: number,
+  refreshedSourceLawIds: ReadonlySet<string> = new Set(),
+  lawNumIndex?: ReadonlyMap<string, string>,
+): string[] {
+  const kept = previousLines.filter((line) => {
+    const sourceLawId = backlinkSourceLawId(line, lawNumIndex);
+    return sourceLawId === undefined || !refreshedSourceLawIds.has(sourceLawId);
+  });
+  const lines = [...new Set([...sectionLines.slice(2), ...kept])];
+  return lines.length > 0 ? [heading(headingBaseLevel, BACKLINKS_TITLE), '', ...lines] : [];
+}
+
+/**
+ * 既存の「被参照」セクションを取り除いてから差し替える。
+ * 再実行やskip済みノートへの再適用でも重複追記しないようにするため。既存のセクションは `headingBaseLevel` の見出しで探す。
+ */
+export function replaceBacklinksSection(markdown: string, sectionLines: string[], headingBaseLevel: number): string {
+  const marker = `\n${heading(headingBaseLevel, BACKLINKS_TITLE)}\n`;
+  const index = markdown.indexOf(marker);
+  const body = (index >= 0 ? markdown.slice(0, index) : markdown).trimEnd();
+  if (sectionLines.length === 0) {
+    return `${body}\n`;
+  }
+  return `${body}\n\n${sectionLines.join('\n')}\n`;
+}
+
+/**
+ * 参照した法令1件。`revisionId` は参照した時点のその法令のリビジョンID（`revision_info.law_revision_id`）で、分からなければ undefined。
+ */
+export interface CitedLaw {
+  lawId: string;
+  title: string;
+  revisionId?: string;
+}
+
+/**
+ * 実行中に収集した参照関係から、`sourceLawId` の本文が参照した他の法令を最初に参照した順に並べ、リビジョンIDを添える。
+ * `revisionIds` は同じ実行で取得した（またはアーカイブから読んだ）法令の law_id からリビジョンIDへの対応。
+ */
+export function collectCitedLaws(
+  sourceLawId: string,
+  references: LawReferenceEdge[],
+  dictionary: LawDictionary,
+  revisionIds: Map<string, string>,
+): CitedLaw[] {
+  const cited = new Map<string, CitedLaw>();
+  for (const ref of references) {
+    if (ref.source_law_id !== sourceLawId || ref.target_law_id === sourceLawId || cited.has(ref.target_law_id)) {
+      continue;
+    }
+    cited.set(ref.target_law_id, {
+      lawId: ref.target_law_id,
+      title: dictionary[ref.target_law_id]?.title ?? `law_${ref.target_law_id}`,
+      revisionId: revisionIds.get(ref.target_law_id),
+    });
+  }
+  return [...cited.values()];
+}
+
+/**
+ * `--citation-style section` で参照元ノートの末尾（「被参照」セクションの前）に置く、参照した法令とリビジョンIDの一覧。
+ * 本文のリンクと重ならないよう、法令名はリンクにしない。見出しは条見出しと同じ `headingBaseLevel` のレベルにする。
+ */
+export function renderCitationSection(cited: CitedLaw[], headingBaseLevel: number): string[] {
+  if (cited.length === 0) {
+    return [];
+  }
+  const lines = cited.map(
+    (law) => `- ${displayTitle(law.title)}（${law.lawId}）: ${law.revisionId ?? 'リビジョン不明'}`,
+  );
+  return [heading(headingBaseLevel, CITATIONS_TITLE), '', ...lines];
+}
+
+/**
+ * `--citation-style frontmatter` で書く `cited_revisions`（法令名ごとのリビジョンID）の行を生成する。
+ */
+export function renderCitedRevisionsField(cited: CitedLaw[]): string[] {
+  return renderFrontmatterField(
+    'cited_revisions',
+    Object.fromEntries(cited.map((law) => [displayTitle(law.title), [law.revisionId ?? '不明']])),
+  );
+}
+
+/**
+ * 既存の「参照した法令とリビジョン」セクションを取り除いてから差し替える。セクションは「被参照」セクションの前に置き、
+ * 「被参照」セクションはそのまま残す。どちらのセクションも `headingBaseLevel` の見出しで探す。
+ */
+export function replaceCitationSection(markdown: string, sectionLines: string[], headingBaseLevel: number): string {
+  const backlinksIndex = markdown.indexOf(`\n${heading(headingBaseLevel, BACKLINKS_TITLE)}\n`);
+  const head = backlinksIndex >= 0 ? markdown.slice(0, backlinksIndex) : markdown;
+  const backlinks = backlinksIndex >= 0 ? markdown.slice(backlinksIndex) : '';
+  const citationIndex = head.indexOf(`\n${heading(headingBaseLevel, CITATIONS_TITLE)}\n`);
+  const body = (citationIndex >= 0 ? head.slice(0, citationIndex) : head).trimEnd();
+  const section = sectionLines.length > 0 ? `\n\n${sectionLines.join('\n')}` : '';
+  return `${body}${section}\n${backlinks}`;
+}
+
 /**
  * フィクスチャテスト用に最小コンテキストでMarkdownを生成する。
  */
@@ -219,23 +1213,14 @@ export function renderMarkdownForTest(doc: ScrapedLawDocument): string {
   const result = renderMarkdown(
     doc,
     {},
-    {
-      buildDictionary: false,
-      maxDepth: 1,
-      ifExists: 'overwrite',
-      retry: 3,
-      timeoutMs: 30_000,
-      dictionaryPath: DEFAULT_DICTIONARY_PATH,
-      dictionaryAutoupdate: false,
-      unresolvedPath: DEFAULT_UNRESOLVED_PATH,
-      outputDir: DEFAULT_OUTPUT_DIR,
-      apiBaseUrl: DEFAULT_API_BASE,
-    },
+    defaultCliOptions(),
     {
       rootLawId: doc.lawId,
       rootLawTitle: doc.title,
       unresolved: [],
       unresolvedSeen: new Set(),
+      failures: [],
+      references: [],
     },
     0,
   );
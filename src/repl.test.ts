@@ -0,0 +1,18 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { parseReplCommand } from './index.js';
+
+test('parseReplCommand: コロンで始まる行はコマンド、それ以外は法令名として扱う', () => {
+  assert.deepEqual(parseReplCommand('  民法  '), { type: 'title', title: '民法' });
+  assert.deepEqual(parseReplCommand('平成十一年法律第二百二十五号'), { type: 'title', title: '平成十一年法律第二百二十五号' });
+  assert.deepEqual(parseReplCommand(''), { type: 'empty' });
+  assert.deepEqual(parseReplCommand('# メモ'), { type: 'empty' });
+  assert.deepEqual(parseReplCommand(':quit'), { type: 'quit' });
+  assert.deepEqual(parseReplCommand(':q'), { type: 'quit' });
+  assert.deepEqual(parseReplCommand(':help'), { type: 'help' });
+  assert.deepEqual(parseReplCommand(':dict'), { type: 'dict' });
+  assert.deepEqual(parseReplCommand(':id 129AC0000000089'), { type: 'law_id', lawId: '129AC0000000089' });
+  assert.deepEqual(parseReplCommand(':id 民法'), { type: 'unknown', text: ':id 民法' });
+  assert.deepEqual(parseReplCommand(':save'), { type: 'unknown', text: ':save' });
+});
@@ -0,0 +1,163 @@
+import process from 'node:process';
+import readline from 'node:readline/promises';
+import { recordCandidateChoice } from './choices.js';
+import { addDictionaryAlias } from './dictionary.js';
+import { flushDeferredSaves, processLawGraph, resolveTitleNonInteractive } from './process.js';
+import type { CliOptions, DeferredSaves, LawCandidate, LawDictionary } from './types.js';
+
+const REPL_PROMPT = 'law> ';
+const LAW_ID_PATTERN = /^[0-9A-Za-z]{15}$/;
+const REPL_HELP = [
+  '法令名（または法令番号）を入力すると、その法令を起点に取得します。',
+  '  :id <law_id>  law_id を指定して取得する（候補が複数ある法令名の確定に使い、選んだ法令は次から聞き直さない）',
+  '  :dict         辞書の件数を表示する',
+  '  :help         このヘルプを表示する',
+  '  :quit         辞書・未解決参照を保存して終了する（EOFでも終了）',
+].join('\n');
+
+/**
+ * `--repl` の1行分の入力を解釈した結果。
+ */
+export type ReplCommand =
+  | { type: 'empty' }
+  | { type: 'quit' }
+  | { type: 'help' }
+  | { type: 'dict' }
+  | { type: 'law_id'; lawId: string }
+  | { type: 'title'; title: string }
+  | { type: 'unknown'; text: string };
+
+/**
+ * `--repl` の入力行を解釈する。`:` で始まる行は簡易コマンド、空行と `#` で始まる行は無視、それ以外は法令名とみなす。
+ */
+export function parseReplCommand(line: string): ReplCommand {
+  const text = line.trim();
+  if (!text || text.startsWith('#')) {
+    return { type: 'empty' };
+  }
+  if (!text.startsWith(':')) {
+    return { type: 'title', title: text };
+  }
+  const [name, ...args] = text.split(/\s+/);
+  if (name === ':quit' || name === ':q' || name === ':exit') {
+    return { type: 'quit' };
+  }
+  if (name === ':help' || name === ':h') {
+    return { type: 'help' };
+  }
+  if (name === ':dict') {
+    return { type: 'dict' };
+  }
+  if (name === ':id' && args.length === 1 && LAW_ID_PATTERN.test(args[0])) {
+    return { type: 'law_id', lawId: args[0] };
+  }
+  return { type: 'unknown', text };
+}
+
+async function processReplLaw(
+  options: CliOptions,
+  dictionary: LawDictionary,
+  deferred: DeferredSaves,
+  lawId: string,
+  lawTitle: string,
+): Promise<boolean> {
+  try {
+    const result = await processLawGraph(options, lawId, lawTitle, dictionary, undefined, deferred);
+    const status = result.failed > 0 ? '一部失敗' : '成功';
+    process.stdout.write(
+      `${status}: ${lawTitle} (${lawId}) 生成 ${result.written}件 / スキップ ${result.skipped}件 / 失敗 ${result.failed}件\n`,
+    );
+    return result.failed === 0;
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    process.stdout.write(`失敗: ${lawTitle} (${lawId}): ${message}\n`);
+    return false;
+  }
+}
+
+/**
+ * 標準入力から法令名を1行ずつ受け取り、それぞれを起点法令として取得し続ける。
+ * プロセスを起動し直さずに済むよう辞書は同じものを使い続け、辞書・未解決参照・取得失敗の保存は終了時に一度だけ行う。
+ * 法令を1件に確定できなかった直後の `:id` はその法令名への選択として選択履歴に記録し、同じ法令名では聞き直さない。
+ * 法令名を短くして検索し直した候補から選んだ場合は、入力した法令名を辞書の別名にも登録する。
+ */
+export async function runRepl(options: CliOptions, dictionary: LawDictionary): Promise<void> {
+  const deferred: DeferredSaves = { unresolved: [], failures: [] };
+  let succeeded = 0;
+  let failed = 0;
+  // 直前に1件に確定できなかった法令名と候補。続く `:id` をこの法令名への選択とみなす。
+  let ambiguous: { title: string; candidates: LawCandidate[]; fallbackQuery?: string } | undefined;
+  const rl = readline.createInterface({ input: process.stdin, output: process.stdout, prompt: REPL_PROMPT });
+  process.stdout.write('法令名を入力してください（:help でコマンド一覧、:quit で終了）\n');
+  rl.prompt();
+  try {
+    for await (const line of rl) {
+      const command = parseReplCommand(line);
+      if (command.type === 'quit') {
+        break;
+      }
+      if (command.type === 'help') {
+        process.stdout.write(`${REPL_HELP}\n`);
+      } else if (command.type === 'dict') {
+        process.stdout.write(`辞書: ${Object.keys(dictionary).length}件\n`);
+      } else if (command.type === 'unknown') {
+        process.stdout.write(`未対応のコマンドです: ${command.text}（:help でコマンド一覧）\n`);
+      } else if (command.type === 'law_id' || command.type === 'title') {
+        let lawId = command.type === 'law_id' ? command.lawId : undefined;
+        let lawTitle = lawId ? (dictionary[lawId]?.title ?? `law_${lawId}`) : '';
+        // 検索し直した候補から選んだときの元の入力。取得後に辞書の別名として登録する。
+        let alias: string | undefined;
+        if (command.type === 'law_id' && ambiguous) {
+          const candidate = ambiguous.candidates.find((item) => item.law_id === command.lawId);
+          lawTitle = candidate?.law_title ?? lawTitle;
+          await recordCandidateChoice(options, ambiguous.title, { law_id: command.lawId, law_title: lawTitle });
+          alias = ambiguous.fallbackQuery ? ambiguous.title : undefined;
+        }
+        ambiguous = undefined;
+        if (command.type === 'title') {
+          try {
+            const resolved = await resolveTitleNonInteractive(options, dictionary, command.title);
+            lawId = resolved.selected?.law_id;
+            lawTitle = resolved.selected?.law_title ?? command.title;
+            if (!lawId) {
+              // 対話を中断させないよう、候補を示して `:id` で選び直してもらう。
+              // 検索し直して得た候補から選んだ場合も、選択履歴は入力した法令名で記録し、次からは同じ入力で確定させる。
+              ambiguous = {
+                title: command.title,
+                candidates: resolved.candidates,
+                fallbackQuery: resolved.fallbackQuery,
+              };
+              const fallback = resolved.fallbackQuery ? `「${resolved.fallbackQuery}」で検索し直した` : '';
+              process.stdout.write(
+                `法令を1件に確定できません: ${command.title} (${fallback}候補 ${resolved.candidates.length}件)\n`,
+              );
+              for (const candidate of resolved.candidates) {
+                process.stdout.write(`  - ${candidate.law_title} (${candidate.law_id ?? 'law_idなし'})\n`);
+              }
+            }
+          } catch (error) {
+            const message = error instanceof Error ? error.message : String(error);
+            process.stdout.write(`失敗: ${command.title}: ${message}\n`);
+            failed += 1;
+          }
+        }
+        if (lawId) {
+          if (await processReplLaw(options, dictionary, deferred, lawId, lawTitle)) {
+            succeeded += 1;
+          } else {
+            failed += 1;
+          }
+          // 辞書は終了時にまとめて保存されるため、別名も次の実行から法令名の辞書引きに使われる。
+          if (alias && addDictionaryAlias(dictionary, lawId, alias)) {
+            process.stdout.write(`辞書に別名を登録しました: ${alias} → ${dictionary[lawId].title} (${lawId})\n`);
+          }
+        }
+      }
+      rl.prompt();
+    }
+  } finally {
+    rl.close();
+    await flushDeferredSaves(options, dictionary, deferred);
+    process.stderr.write(`REPLの処理結果: 成功 ${succeeded}件 / 失敗 ${failed}件\n`);
+  }
+}
@@ -1,5 +1,8 @@
 import { chromium, type Page } from 'playwright';
-import { getLawSiteBaseUrl, wait } from './utils.js';
+import { serializeCollectors, type CollectorNode, type CollectorState } from './collectors.js';
+import { articleHeading, provisionNumber } from './provision.js';
+import { loadLawPageArchive, saveLawPageArchive } from './storage.js';
+import { getLawPageUrl, getLawSiteBaseUrl, onShutdown, throttleRequest, wait } from './utils.js';
 import type { CliOptions, ParagraphSegment, ScrapedLawDocument } from './types.js';
 
 /**
@@ -21,131 +24,264 @@ async function waitForProvisionRoot(page: Page, timeoutMs: number): Promise<void
 }
 
 /**
- * 既に読み込まれたページDOMから本文構造を抽出する。
+ * 法令ページの本文DOMから抽出した生の内容。条番号・項番号の整形はNode側で行う。
  */
-export async function extractLawDocumentFromPage(
-  page: Page,
-  lawId: string,
-  sourceUrl: string,
-): Promise<ScrapedLawDocument> {
-  const result = await page.evaluate(() => {
-    const provisionRoot =
-      document.querySelector('#MainProvision') ??
-      document.querySelector('#provisionview') ??
-      document.querySelector('main.main-content');
-    if (!provisionRoot) {
-      throw new Error('本文セレクタ未検出');
-    }
+interface PageContent {
+  title: string;
+  lawNum: string;
+  revisionTitle: string;
+  amendingLawTitle: string;
+  blocks: Array<{
+    id: string;
+    caption: string;
+    titleText: string;
+    paragraphs: Array<{
+      anchor: string;
+      numbered: boolean;
+      elementId: string | undefined;
+      numberText: string;
+      segments: ParagraphSegment[];
+      level: number;
+    }>;
+  }>;
+  unsupportedElements: Record<string, number>;
+}
 
-    const titleElement =
-      document.querySelector<HTMLElement>('#titlebar .title-law .lawlabel') ??
-      document.querySelector<HTMLElement>('.lawdetaillawtitle') ??
-      document.querySelector<HTMLElement>('main.main-content h1');
-
-    let title = '';
-    if (titleElement) {
-      const clone = titleElement.cloneNode(true) as HTMLElement;
-      // 法令番号などを除いて純粋な法令名を優先取得する。
-      clone.querySelectorAll('.lawnumber').forEach((node) => node.remove());
-      title = clone.textContent?.trim() ?? '';
-    }
-    if (!title) {
-      title = document.title.replace(' | e-Gov 法令検索', '').trim();
-    }
-    if (!title) {
-      title = '無題法令';
-    }
+/**
+ * ブラウザ内で本文DOMから内容を抽出する。`page.evaluate` へソースを文字列にして送るため、
+ * モジュールの他の値は参照せず、文の取り込みは引数の `collect`（`serializeCollectors` の式）に任せる。
+ */
+function extractPageContent(
+  inlineTables: boolean,
+  collect: (node: CollectorNode, state: CollectorState) => void,
+): PageContent {
+  const provisionRoot =
+    document.querySelector('#MainProvision') ??
+    document.querySelector('#provisionview') ??
+    document.querySelector('main.main-content');
+  if (!provisionRoot) {
+    throw new Error('本文セレクタ未検出');
+  }
 
-    const articleNodes = Array.from(provisionRoot.querySelectorAll<HTMLElement>('article.article[id]'));
-    const fallbackArticleNodes =
-      articleNodes.length > 0
-        ? articleNodes
-        : Array.from(
-            provisionRoot.querySelectorAll<HTMLElement>(
-              '[id^="Mp-"], [id^="Sup-"], [id^="App-"], [id^="Ap-"], [id^="Enf-"]',
-            ),
-          );
-
-    const blocks = fallbackArticleNodes.map((article) => {
-      const heading =
-        article.querySelector<HTMLElement>('.articleheading, .paragraphtitle, .istitle')?.innerText.trim() ??
-        article.getAttribute('id') ??
-        '条文';
-
-      const paragraphNodes = Array.from(article.querySelectorAll<HTMLElement>('p.sentence'));
-      const paragraphs = paragraphNodes.map((p, index) => {
-        const anchor = p.getAttribute('id') ?? `${article.id}-p${index + 1}`;
-        const segments: ParagraphSegment[] = [];
+  const titleElement =
+    document.querySelector<HTMLElement>('#titlebar .title-law .lawlabel') ??
+    document.querySelector<HTMLElement>('.lawdetaillawtitle') ??
+    document.querySelector<HTMLElement>('main.main-content h1');
 
-        // a[href]以外の参照文言は推測リンク化せず、テキストのまま保持する。
-        const collect = (node: Node): void => {
-          if (node.nodeType === Node.TEXT_NODE) {
-            const text = node.textContent ?? '';
-            if (text) {
-              segments.push({ type: 'text', text });
+  let title = '';
+  let lawNum = '';
+  if (titleElement) {
+    const clone = titleElement.cloneNode(true) as HTMLElement;
+    // 法令番号は「（昭和三十四年法律第百二十一号）」の形で併記されるため、括弧を外して別に保持する。
+    lawNum = clone.querySelector('.lawnumber')?.textContent?.trim().replace(/^（|）$/g, '') ?? '';
+    // 法令番号などを除いて純粋な法令名を優先取得する。
+    clone.querySelectorAll('.lawnumber').forEach((node) => node.remove());
+    title = clone.textContent?.trim() ?? '';
+  }
+  if (!title) {
+    title = document.title.replace(' | e-Gov 法令検索', '').trim();
+  }
+  if (!title) {
+    title = '無題法令';
+  }
+
+  // 改正履歴パネルの見出し部分が、表示中（現在施行）の版の施行日と改正法令を示す。
+  const revisionMeta = document.querySelector<HTMLElement>('.revisionmeta');
+  const revisionTitleElement = revisionMeta?.querySelector<HTMLElement>('.revisiontitle')?.cloneNode(true) as
+    | HTMLElement
+    | undefined;
+  revisionTitleElement?.querySelectorAll('.currentmark').forEach((node) => node.remove());
+  const revisionTitle = revisionTitleElement?.textContent?.replace(/\s+/g, ' ').trim() ?? '';
+  const amendingLawTitle =
+    revisionMeta?.querySelector<HTMLElement>('.revisionamendinglawtitle')?.textContent?.replace(/\s+/g, '').trim() ??
+    '';
+
+  const unsupportedElements: Record<string, number> = {};
+  const articleNodes = Array.from(provisionRoot.querySelectorAll<HTMLElement>('article.article[id]'));
+  const fallbackArticleNodes =
+    articleNodes.length > 0
+      ? articleNodes
+      : Array.from(
+          provisionRoot.querySelectorAll<HTMLElement>(
+            '[id^="Mp-"], [id^="Sup-"], [id^="App-"], [id^="Ap-"], [id^="Enf-"]',
+          ),
+        );
+
+  // セルの結合や入れ子の表があると行と列を組み直せないため、全行が同じ列数の単純な表だけを表に整形する。
+  const isPlainTable = (table: HTMLTableElement): boolean => {
+    const rows = Array.from(table.rows);
+    const width = rows[0]?.cells.length ?? 0;
+    return (
+      width >= 2 &&
+      !table.querySelector('table') &&
+      rows.every(
+        (row) =>
+          row.cells.length === width &&
+          Array.from(row.cells).every((cell) => cell.colSpan === 1 && cell.rowSpan === 1),
+      )
+    );
+  };
+
+  const blocks = fallbackArticleNodes.map((article) => {
+    // 条番号はNode側でIDから作るため、ここでは見出し（括弧書き）と条名テキストを生のまま渡す。
+    const caption = article.querySelector<HTMLElement>('.articleheading')?.innerText.trim() ?? '';
+    const titleText =
+      Array.from(article.querySelectorAll<HTMLElement>('.paragraphtitle, .supplprovision-label'))
+        .map((node) => node.innerText.trim())
+        .find((text) => text.length > 0) ?? '';
+
+    // 別表は別に扱うため、条文中の表だけを対象にする。表に整形できないものは従来どおり文ごとに出す。
+    const tables = inlineTables
+      ? Array.from(article.querySelectorAll<HTMLTableElement>('table.tablestruct')).filter(
+          (table) => !table.closest('.appdxtable') && isPlainTable(table),
+        )
+      : [];
+    const emittedTables = new Set<HTMLTableElement>();
+    const paragraphNodes = Array.from(article.querySelectorAll<HTMLElement>('p.sentence'));
+    const paragraphs = paragraphNodes.flatMap((p, index) => {
+      const table = p.closest<HTMLTableElement>('table.tablestruct');
+      if (table && tables.includes(table)) {
+        // 表の中の文は、表の最初の文の位置で表全体を1つの段落として出す。
+        if (emittedTables.has(table)) {
+          return [];
+        }
+        emittedTables.add(table);
+        const segments: ParagraphSegment[] = [];
+        for (const row of Array.from(table.rows)) {
+          const cells = Array.from(row.cells);
+          for (const cell of cells) {
+            for (const child of Array.from(cell.childNodes)) {
+              collect(child, { segments, unsupportedElements });
             }
-            return;
-          }
-          if (node.nodeType !== Node.ELEMENT_NODE) {
-            return;
+            segments.push({ type: 'table_break', text: '', boundary: 'cell' });
           }
-          const element = node as HTMLElement;
-          if (element.tagName.toLowerCase() === 'a' && element.hasAttribute('href')) {
-            segments.push({
-              type: 'link',
-              text: element.textContent?.trim() ?? '',
-              href: element.getAttribute('href') ?? '',
-            });
-            return;
-          }
-          for (const child of Array.from(element.childNodes)) {
-            collect(child);
-          }
-        };
-        for (const child of Array.from(p.childNodes)) {
-          collect(child);
+          const header = cells.every((cell) => cell.tagName.toLowerCase() === 'th');
+          segments.push({ type: 'table_break', text: '', boundary: header ? 'header_row' : 'row' });
         }
+        const anchor = table.id || `${article.id}-table${emittedTables.size}`;
+        return [{ anchor, numbered: false, elementId: undefined, numberText: '', segments, level: 0 }];
+      }
 
-        return { anchor, segments };
-      });
+      // 項・号の要素IDは本文中の `#Mp-...-Pr_2` 形式のリンク先と一致するため、先頭の文のアンカーに使う。
+      const element = p.parentElement?.closest<HTMLElement>('[id]');
+      const elementId =
+        element && element !== article && element.querySelector('p.sentence') === p ? element.id : undefined;
+      const anchor = p.getAttribute('id') ?? elementId ?? `${article.id}-p${index + 1}`;
+      const segments: ParagraphSegment[] = [];
+      for (const child of Array.from(p.childNodes)) {
+        collect(child, { segments, unsupportedElements });
+      }
+
+      // 項番号・号番号は文の外側の見出しspanにあるため、コンテナ内の最初の文にだけ付与する。
+      let numbered = false;
+      let numberText = '';
+      const container = p.closest('.istitle');
+      if (container && container.querySelector('p.sentence') === p) {
+        numbered = true;
+        numberText =
+          container
+            .querySelector(':scope > .paragraphtitle, :scope > .itemtitle, :scope > .portiontitle')
+            ?.textContent?.trim() ?? '';
+      }
+
+      // 号・細分は親の号の中に入れ子で置かれるため、条までの祖先の号・細分の数を階層の深さにする。
+      let level = 0;
+      for (let node = p.parentElement; node && node !== article; node = node.parentElement) {
+        if (node.classList.contains('item') || node.classList.contains('subitem')) {
+          level += 1;
+        }
+      }
 
-      return {
-        id: article.getAttribute('id') ?? '',
-        heading,
-        paragraphs,
-      };
+      return [{ anchor, numbered, elementId, numberText, segments, level }];
     });
 
-    return { title, blocks };
+    return {
+      id: article.getAttribute('id') ?? '',
+      caption,
+      titleText,
+      paragraphs,
+    };
   });
 
+  return { title, lawNum, revisionTitle, amendingLawTitle, blocks, unsupportedElements };
+}
+
+/**
+ * 既に読み込まれたページDOMから本文構造を抽出する。
+ * `inlineTables` を指定すると、条文中の表（`TableStruct`）を表の区切りを持つ1つの段落にまとめる。
+ */
+export async function extractLawDocumentFromPage(
+  page: Page,
+  lawId: string,
+  sourceUrl: string,
+  inlineTables = false,
+): Promise<ScrapedLawDocument> {
+  const script = `(${extractPageContent.toString()})(${JSON.stringify(inlineTables)}, ${serializeCollectors()})`;
+  const result = (await page.evaluate(script)) as PageContent;
+
   return {
     lawId,
     title: result.title,
+    lawNum: result.lawNum || undefined,
+    revisionTitle: result.revisionTitle || undefined,
+    amendingLawTitle: result.amendingLawTitle || undefined,
     sourceUrl,
-    blocks: result.blocks,
+    blocks: result.blocks.map((block) => ({
+      id: block.id,
+      heading: articleHeading(block.id, block.caption, block.titleText),
+      caption: block.caption || undefined,
+      paragraphs: block.paragraphs.map((paragraph) => ({
+        anchor: paragraph.anchor,
+        number: paragraph.numbered ? provisionNumber(paragraph.elementId, paragraph.numberText) : undefined,
+        level: paragraph.level || undefined,
+        segments: paragraph.segments,
+      })),
+    })),
+    unsupportedElements: Object.keys(result.unsupportedElements).length > 0 ? result.unsupportedElements : undefined,
   };
 }
 
+/**
+ * 未対応要素の集計を、多い順に「svg×2, object×1」の形で一覧にする。
+ */
+export function formatUnsupportedElements(counts: Record<string, number>): string {
+  return Object.entries(counts)
+    .sort(([a, countA], [b, countB]) => countB - countA || a.localeCompare(b))
+    .map(([key, count]) => `${key}×${count}`)
+    .join(', ');
+}
+
 async function scrapeLawDocument(lawId: string, options: CliOptions): Promise<ScrapedLawDocument> {
   const isInDocker = process.env.IN_DOCKER === '1';
   const browser = await chromium.launch({
     headless: true,
+    // 既定ではPlaywrightがシグナルを受けてブラウザを閉じ、Ctrl+C で処理中の法令を書き終える前に取得が失敗する。
+    // シグナルは `installCancelHandler` に任せ、待たずに終了するときだけ `onShutdown` から閉じる。
+    handleSIGINT: false,
+    handleSIGTERM: false,
+    handleSIGHUP: false,
     // Docker環境では sandbox / shared memory 制約でブラウザが早期終了しやすいため、
     // コンテナ実行時のみ安全側オプションを有効化する。
     args: isInDocker ? ['--no-sandbox', '--disable-setuid-sandbox', '--disable-dev-shm-usage'] : [],
   });
-  const page = await browser.newPage();
-  const sourceUrl = `${getLawSiteBaseUrl(options.apiBaseUrl)}/law/${lawId}`;
+  const removeShutdownCleanup = onShutdown(() => browser.close());
+  const pageUrl = `${getLawSiteBaseUrl(options.apiBaseUrl)}/law/${lawId}`;
 
   try {
-    await page.goto(sourceUrl, { waitUntil: 'domcontentloaded', timeout: options.timeoutMs });
+    const page = await browser.newPage();
+    const waitedMs = await throttleRequest(options.requestIntervalMs);
+    if (options.verbose && waitedMs > 0) {
+      process.stderr.write(`リクエスト間隔待機: ${waitedMs}ms (${pageUrl})\n`);
+    }
+    await page.goto(pageUrl, { waitUntil: 'domcontentloaded', timeout: options.timeoutMs });
     await page.waitForLoadState('networkidle', { timeout: options.timeoutMs }).catch(() => undefined);
     await waitForProvisionRoot(page, options.timeoutMs);
     // finallyでbrowser.close()する前に抽出完了まで待たないと、
     // page.evaluate中にページが閉じられて失敗する。
-    return await extractLawDocumentFromPage(page, lawId, sourceUrl);
+    // 取得元と、ノートから人が開くURL（--web-base-url）は別に扱う。
+    return await extractLawDocumentFromPage(page, lawId, getLawPageUrl(options, lawId), options.inlineTables);
   } finally {
+    removeShutdownCleanup();
     await browser.close();
   }
 }
@@ -167,3 +303,23 @@ export async function scrapeLawDocumentWithRetry(lawId: string, options: CliOpti
   }
   throw lastError;
 }
+
+/**
+ * 法令ページの本文を取得する。`--archive-dir` 指定時は抽出した本文をアーカイブへ保存し、
+ * `--from-archive` 指定時はページを開かずにアーカイブから読み込む。アーカイブで作り直す実行の結果が
+ * その時点のページに左右されないよう、アーカイブに無い法令は取得し直さずにエラーにする。
+ */
+export async function loadLawDocument(lawId: string, options: CliOptions): Promise<ScrapedLawDocument> {
+  if (options.fromArchive && options.archiveDir) {
+    const archived = await loadLawPageArchive(options.archiveDir, lawId);
+    if (!archived) {
+      throw new Error(`--from-archive: 法令ページのアーカイブがありません: ${lawId}（${options.archiveDir}）`);
+    }
+    return archived.document;
+  }
+  const doc = await scrapeLawDocumentWithRetry(lawId, options);
+  if (options.archiveDir) {
+    await saveLawPageArchive(options.archiveDir, doc);
+  }
+  return doc;
+}
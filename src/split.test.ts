@@ -0,0 +1,100 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  defaultCliOptions,
+  renderMarkdown,
+  resolveSectionNote,
+  sectionNotePath,
+  splitLawSections,
+  topLevelDivision,
+} from './index.js';
+
+const block = (id: string, links: Array<{ text: string; href: string }> = []) => ({
+  id,
+  heading: id,
+  paragraphs: [{ anchor: `${id}-Pr_1`, segments: links.map((link) => ({ type: 'link' as const, ...link })) }],
+});
+
+test('topLevelDivision: 編があれば編、なければ章を分割単位にする', () => {
+  assert.deepEqual(topLevelDivision('Mp-Pa_1-Ch_2-At_3'), { key: 'Pa_1', label: '第一編' });
+  assert.deepEqual(topLevelDivision('Mp-Ch_12-At_30'), { key: 'Ch_12', label: '第十二章' });
+  assert.equal(topLevelDivision('Mp-At_1'), undefined);
+  assert.equal(topLevelDivision('129AC0000000089-Sp-At_1'), undefined);
+});
+
+test('sectionNotePath: 親ノートと同じフォルダに「親ノート名 第一編.md」を置く', () => {
+  assert.equal(sectionNotePath('民法_129AC0000000089.md', '第一編'), '民法_129AC0000000089 第一編.md');
+  assert.equal(sectionNotePath('法律/民法.md', '第二編'), '法律/民法 第二編.md');
+});
+
+test('splitLawSections: 条を最上位の階層ごとに振り分け、アンカーからノートを引ける', () => {
+  const doc = {
+    lawId: '129AC0000000089',
+    title: '民法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/129AC0000000089',
+    blocks: [
+      block('Mp-Pa_1-Ch_1-At_1'),
+      block('Mp-Pa_1-Ch_2-At_3_2'),
+      block('Mp-Pa_2-At_175'),
+      block('129AC0000000089-Sp'),
+    ],
+  };
+  const { sections, index } = splitLawSections(doc, '民法.md');
+  assert.deepEqual(
+    sections.map((section) => [section.key, section.relativePath, section.blocks.map((b) => b.id)]),
+    [
+      ['Pa_1', '民法 第一編.md', ['Mp-Pa_1-Ch_1-At_1', 'Mp-Pa_1-Ch_2-At_3_2']],
+      ['Pa_2', '民法 第二編.md', ['Mp-Pa_2-At_175']],
+    ],
+  );
+  assert.equal(resolveSectionNote(index, 'Mp-Pa_1-Ch_2-At_3_2-Pr_1'), '民法 第一編.md');
+  // 他法令からのリンクは編・章を省略することがあるため、条番号で引く。
+  assert.equal(resolveSectionNote(index, 'Mp-At_175'), '民法 第二編.md');
+  assert.equal(resolveSectionNote(index, 'Mp-At_999'), undefined);
+  assert.equal(resolveSectionNote(index, '129AC0000000089-Sp'), undefined);
+  assert.equal(resolveSectionNote(undefined, 'Mp-At_175'), undefined);
+});
+
+test('renderMarkdown: --split-by chapter で章ごとのノートと目次を出力し、条リンクを分割先へ向ける', () => {
+  const doc = {
+    lawId: '334AC0000000121',
+    title: '特許法',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/334AC0000000121',
+    blocks: [
+      block('Mp-Ch_1-At_1', [
+        { text: '次条', href: '#Mp-Ch_1-At_2' },
+        { text: '第三条', href: '#Mp-Ch_2-At_3' },
+      ]),
+      block('Mp-Ch_1-At_2'),
+      block('Mp-Ch_2-At_3'),
+      block('334AC0000000121-Sp', [{ text: '第一条', href: '#Mp-Ch_1-At_1' }]),
+    ],
+  };
+  const context = {
+    rootLawId: doc.lawId,
+    rootLawTitle: doc.title,
+    unresolved: [],
+    unresolvedSeen: new Set<string>(),
+    failures: [],
+    references: [],
+    sectionNotes: new Map(),
+  };
+  const options = { ...defaultCliOptions(), splitBy: 'chapter' as const, linkStyle: 'shortest' as const };
+  const rendered = renderMarkdown(doc, {}, options, context, 0);
+
+  assert.match(rendered.markdown, /^- \[\[law_334AC0000000121 第一章\.md\|第一章\]\]$/m);
+  assert.match(rendered.markdown, /\[\[law_334AC0000000121 第一章\.md#Mp-Ch_1-At_1\|第一条\]\]/);
+  assert.doesNotMatch(rendered.markdown, /<a id="Mp-Ch_1-At_1"><\/a>/);
+  assert.deepEqual(
+    rendered.sectionNotes.map((note) => note.relativePath),
+    ['law_334AC0000000121 第一章.md', 'law_334AC0000000121 第二章.md'],
+  );
+  const [chapter1, chapter2] = rendered.sectionNotes.map((note) => note.markdown);
+  assert.match(chapter1, /^parent_law_id: 334AC0000000121$/m);
+  assert.doesNotMatch(chapter1, /^law_id:/m);
+  assert.match(chapter1, /\[\[#Mp-Ch_1-At_2\|次条\]\]/);
+  assert.match(chapter1, /\[\[law_334AC0000000121 第二章\.md#Mp-Ch_2-At_3\|第三条\]\]/);
+  assert.match(chapter2, /<a id="Mp-Ch_2-At_3"><\/a>/);
+  assert.equal(context.sectionNotes.get(doc.lawId)?.byArticle.get('3'), 'law_334AC0000000121 第二章.md');
+});
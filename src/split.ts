@@ -0,0 +1,85 @@
+import path from 'node:path';
+import { toKanjiNumber } from './lawnum.js';
+import { anchorArticleKey, parseProvisionId } from './provision.js';
+import type { ArticleBlock, ScrapedLawDocument, SectionNoteIndex } from './types.js';
+
+const TOP_DIVISION_PATTERN = /^Mp-((Pa|Ch)_(\d+))(?:-|$)/;
+const DIVISION_SUFFIXES: Record<string, string> = { Pa: '編', Ch: '章' };
+
+/**
+ * 分割出力する1つのセクションノート（編があれば編、なければ章）。
+ */
+export interface LawSection {
+  /** 要素IDの最上位の階層セグメント（`Pa_1` / `Ch_2`）。 */
+  key: string;
+  label: string;
+  relativePath: string;
+  blocks: ArticleBlock[];
+}
+
+/**
+ * 本則の要素IDから、分割単位となる最上位の階層（編があれば編、なければ章）を取り出す。
+ */
+export function topLevelDivision(elementId: string): { key: string; label: string } | undefined {
+  const matched = elementId.match(TOP_DIVISION_PATTERN);
+  if (!matched) {
+    return undefined;
+  }
+  return { key: matched[1], label: `第${toKanjiNumber(Number(matched[3]))}${DIVISION_SUFFIXES[matched[2]]}` };
+}
+
+/**
+ * 親ノートのパス（出力ディレクトリからの相対パス）から、セクションノートのパスを作る。
+ * 親ノートと同じフォルダに並べ、ファイル名は「親ノート名 第一編.md」とする。
+ */
+export function sectionNotePath(parentRelativePath: string, label: string): string {
+  const parsed = path.parse(parentRelativePath);
+  return path.join(parsed.dir, `${parsed.name} ${label}${parsed.ext}`);
+}
+
+/**
+ * `--split-by chapter` 用に、本則の条を最上位の階層ごとのセクションへ振り分ける。
+ * 階層に属さないブロック（附則・別表、章の無い法令の条）は親ノートに残す。
+ * あわせて、リンク先のアンカーからセクションノートを引く対応表を作る。
+ */
+export function splitLawSections(
+  doc: ScrapedLawDocument,
+  parentRelativePath: string,
+): { sections: LawSection[]; index: SectionNoteIndex } {
+  const sections = new Map<string, LawSection>();
+  const index: SectionNoteIndex = { byDivision: new Map(), byArticle: new Map() };
+  for (const block of doc.blocks) {
+    const division = topLevelDivision(block.id);
+    if (!division) {
+      continue;
+    }
+    let section = sections.get(division.key);
+    if (!section) {
+      section = { ...division, relativePath: sectionNotePath(parentRelativePath, division.label), blocks: [] };
+      sections.set(division.key, section);
+      index.byDivision.set(division.key, section.relativePath);
+    }
+    section.blocks.push(block);
+    const parsed = parseProvisionId(block.id);
+    if (parsed?.kind === 'article') {
+      index.byArticle.set(parsed.from.join('_'), section.relativePath);
+    }
+  }
+  return { sections: [...sections.values()], index };
+}
+
+/**
+ * アンカーが指す条・階層を含むセクションノートのパスを返す。親ノートにある場合は undefined を返す。
+ * リンク元によって章などの階層が省略される（`Mp-At_195`）ため、階層が無ければ条番号で引く。
+ */
+export function resolveSectionNote(index: SectionNoteIndex | undefined, anchor: string): string | undefined {
+  if (!index) {
+    return undefined;
+  }
+  const division = topLevelDivision(anchor);
+  if (division) {
+    return index.byDivision.get(division.key);
+  }
+  const articleKey = anchorArticleKey(anchor);
+  return articleKey ? index.byArticle.get(articleKey) : undefined;
+}
@@ -0,0 +1,116 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import {
+  apiEndpointName,
+  buildRunSummary,
+  countRunStat,
+  formatApiStats,
+  formatSlowestLaws,
+  getApiStats,
+  getRunStats,
+  recordApiRequest,
+  recordCacheLookup,
+  recordLawTiming,
+  recordRunFailure,
+  resetApiStats,
+  resetRunStats,
+} from './index.js';
+
+test('apiEndpointName: 法令ごとのパスもエンドポイント単位にまとめる', () => {
+  assert.equal(apiEndpointName('https://laws.e-gov.go.jp/api/2/laws?law_title=特許法'), 'laws');
+  assert.equal(
+    apiEndpointName('https://laws.e-gov.go.jp/api/2/law_data/334AC0000000121?response_format=json'),
+    'law_data',
+  );
+});
+
+test('formatApiStats: エンドポイント別の回数・リトライ・転送量・所要時間を1行にまとめる', () => {
+  resetApiStats();
+  assert.equal(formatApiStats(getApiStats(), 1000), undefined);
+
+  recordApiRequest('https://laws.e-gov.go.jp/api/2/laws?law_title=特許法', false, 2048);
+  recordApiRequest('https://laws.e-gov.go.jp/api/2/law_data/334AC0000000121', false, 0);
+  recordApiRequest('https://laws.e-gov.go.jp/api/2/law_data/334AC0000000121', true, 4_400_000);
+  recordCacheLookup('search', true);
+  recordCacheLookup('search', false);
+
+  const stats = getApiStats();
+  assert.deepEqual(stats.endpoints.law_data, { requests: 2, retries: 1, bytes: 4_400_000 });
+  assert.equal(
+    formatApiStats(stats, 38_200),
+    'API統計: laws 1回 / law_data 2回 / リトライ1回 / 合計4.2MB / 38秒 / searchキャッシュ ヒット1 / ミス1',
+  );
+  resetApiStats();
+});
+
+test('formatSlowestLaws: 所要時間の長い法令の上位とlaw_dataの合計受信量を示す', () => {
+  resetApiStats();
+  recordApiRequest('https://laws.e-gov.go.jp/api/2/law_data/129AC0000000089?response_format=json', false, 3_000_000);
+  recordApiRequest('https://laws.e-gov.go.jp/api/2/law_data/334AC0000000121', false, 1_500_000);
+  recordLawTiming('334AC0000000121', '特許法', 4_200);
+  recordLawTiming('129AC0000000089', '民法', 12_300);
+  recordLawTiming('322AC0000000049', '労働基準法', 800);
+
+  const stats = getApiStats();
+  assert.equal(stats.laws[1].bytes, 3_000_000);
+  assert.equal(
+    formatSlowestLaws(stats, 2),
+    [
+      '処理時間の長い法令（3件中、law_data 合計4.3MB）:',
+      '  1. 民法 (129AC0000000089) 12.3秒 / law_data 2.9MB',
+      '  2. 特許法 (334AC0000000121) 4.2秒 / law_data 1.4MB',
+    ].join('\n'),
+  );
+  assert.equal(formatSlowestLaws(stats, 0), undefined);
+  resetApiStats();
+  assert.equal(formatSlowestLaws(getApiStats(), 5), undefined);
+});
+
+test('buildRunSummary: 実行件数と失敗法令をまとめ、終了コードから成否を決める', () => {
+  resetRunStats();
+  countRunStat('fetched', 3);
+  countRunStat('skipped');
+  countRunStat('unresolved', 2);
+  countRunStat('dictionaryAdded', 4);
+  recordRunFailure({ law_id: '129AC0000000089', title: '民法', error: 'timeout' });
+  resetApiStats();
+  recordApiRequest('https://laws.e-gov.go.jp/api/2/laws?law_title=特許法', false, 2048);
+  recordApiRequest('https://laws.e-gov.go.jp/api/2/law_data/334AC0000000121', false, 0);
+  recordApiRequest('https://laws.e-gov.go.jp/api/2/law_data/334AC0000000121', true, 4096);
+  recordCacheLookup('search', true);
+
+  const startedAt = Date.UTC(2026, 9, 16, 0, 0, 0);
+  const summary = buildRunSummary(getRunStats(), getApiStats(), 2000, startedAt, startedAt + 1500, 3);
+  assert.deepEqual(summary, {
+    status: 'partial',
+    exit_code: 3,
+    started_at: '2026-10-16T00:00:00.000Z',
+    finished_at: '2026-10-16T00:00:01.500Z',
+    elapsed_ms: 1500,
+    throttle_wait_ms: 2000,
+    fetched: 3,
+    skipped: 1,
+    unresolved: 2,
+    dictionary_added: 4,
+    failed_laws: [{ law_id: '129AC0000000089', title: '民法', error: 'timeout' }],
+    api: {
+      requests: 3,
+      retries: 1,
+      bytes: 6144,
+      endpoints: {
+        laws: { requests: 1, retries: 0, bytes: 2048 },
+        law_data: { requests: 2, retries: 1, bytes: 4096 },
+      },
+      cache: { search: { hits: 1, misses: 0 } },
+    },
+  });
+  // `--diff` の差分あり（4）は処理としては成功。
+  assert.equal(buildRunSummary(getRunStats(), getApiStats(), 0, startedAt, startedAt, 4).status, 'success');
+  assert.equal(buildRunSummary(getRunStats(), getApiStats(), 0, startedAt, startedAt, 130).status, 'cancelled');
+  const failed = buildRunSummary(getRunStats(), getApiStats(), 0, startedAt, startedAt, 1, new Error('law_id がありません'));
+  assert.equal(failed.status, 'failed');
+  assert.equal(failed.error, 'law_id がありません');
+  resetRunStats();
+  resetApiStats();
+});
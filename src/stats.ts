@@ -0,0 +1,197 @@
+import type { ApiStats, LawFetchStats, RunFailedLaw, RunStats, RunSummary } from './types.js';
+
+const API_ENDPOINT_PATTERN = /^\/api\/[^/]+\/([^/]+)/;
+const LAW_DATA_PATH_PATTERN = /^\/api\/[^/]+\/law_data\/([^/]+)/;
+
+let apiStats: ApiStats = { endpoints: {}, cache: {}, lawDataBytes: {}, laws: [] };
+let runStats: RunStats = { fetched: 0, skipped: 0, unresolved: 0, dictionaryAdded: 0, failures: [] };
+
+/**
+ * APIのURLから統計上のエンドポイント名（`laws` / `law_data` など）を取り出す。
+ * `law_data/{law_id}` のような法令ごとのパスも1つのエンドポイントとして数える。
+ */
+export function apiEndpointName(url: string): string {
+  const pathname = new URL(url).pathname;
+  return pathname.match(API_ENDPOINT_PATTERN)?.[1] ?? pathname;
+}
+
+function endpointStats(endpoint: string) {
+  apiStats.endpoints[endpoint] ??= { requests: 0, retries: 0, bytes: 0 };
+  return apiStats.endpoints[endpoint];
+}
+
+/**
+ * API呼び出し1回分（再試行を含む）を記録する。`bytes` は受信できたレスポンス本文の大きさ。
+ */
+export function recordApiRequest(url: string, isRetry: boolean, bytes: number): void {
+  const stats = endpointStats(apiEndpointName(url));
+  stats.requests += 1;
+  stats.retries += isRetry ? 1 : 0;
+  stats.bytes += bytes;
+  // どの法令が重いかを後で示せるよう、law_data は法令ごとにも積算する。
+  const lawId = new URL(url).pathname.match(LAW_DATA_PATH_PATTERN)?.[1];
+  if (lawId) {
+    apiStats.lawDataBytes[lawId] = (apiStats.lawDataBytes[lawId] ?? 0) + bytes;
+  }
+}
+
+/**
+ * 法令1件の取得開始からMarkdown変換までの所要時間を、その法令の `law_data` の受信バイト数と合わせて記録する。
+ */
+export function recordLawTiming(lawId: string, title: string, elapsedMs: number): LawFetchStats {
+  const stats = { lawId, title, elapsedMs, bytes: apiStats.lawDataBytes[lawId] ?? 0 };
+  apiStats.laws.push(stats);
+  return stats;
+}
+
+/**
+ * キャッシュの参照結果を記録する。ヒット率からAPI呼び出しをどれだけ省けたかを把握するため。
+ */
+export function recordCacheLookup(cacheName: string, hit: boolean): void {
+  const stats = (apiStats.cache[cacheName] ??= { hits: 0, misses: 0 });
+  stats[hit ? 'hits' : 'misses'] += 1;
+}
+
+/**
+ * ここまでのAPI統計の写しを返す。
+ */
+export function getApiStats(): ApiStats {
+  return structuredClone(apiStats);
+}
+
+/**
+ * API統計を初期化する。同じプロセスで複数回実行するテストのために使う。
+ */
+export function resetApiStats(): void {
+  apiStats = { endpoints: {}, cache: {}, lawDataBytes: {}, laws: [] };
+}
+
+/**
+ * 法令1件分の計測結果を1行（例: 「民法 (129AC0000000089) 12.3秒 / law_data 1.2MB」）にする。
+ */
+export function formatLawTiming(stats: LawFetchStats): string {
+  const seconds = (stats.elapsedMs / 1000).toFixed(1);
+  return `${stats.title} (${stats.lawId}) ${seconds}秒 / law_data ${formatBytes(stats.bytes)}`;
+}
+
+/**
+ * 所要時間の長い法令の上位 `limit` 件と、law_data の合計受信バイト数を出力用の行にする。
+ * 並列度やタイムアウトを調整する手がかりにするため。法令を1件も取得していなければ undefined を返す。
+ */
+export function formatSlowestLaws(stats: ApiStats, limit: number): string | undefined {
+  if (limit <= 0 || stats.laws.length === 0) {
+    return undefined;
+  }
+  const totalBytes = Object.values(stats.lawDataBytes).reduce((sum, bytes) => sum + bytes, 0);
+  const slowest = [...stats.laws].sort((a, b) => b.elapsedMs - a.elapsedMs).slice(0, limit);
+  return [
+    `処理時間の長い法令（${stats.laws.length}件中、law_data 合計${formatBytes(totalBytes)}）:`,
+    ...slowest.map((law, index) => `  ${index + 1}. ${formatLawTiming(law)}`),
+  ].join('\n');
+}
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024) {
+    return `${bytes}B`;
+  }
+  if (bytes < 1024 * 1024) {
+    return `${(bytes / 1024).toFixed(1)}KB`;
+  }
+  return `${(bytes / 1024 / 1024).toFixed(1)}MB`;
+}
+
+/**
+ * 実行終了時に出すAPI統計の1行サマリ（例: 「API統計: laws 3回 / law_data 12回 / リトライ2回 / 合計4.2MB / 38秒」）を作る。
+ * APIを一度も呼ばず、キャッシュも参照しなかった場合は undefined を返す。
+ */
+export function formatApiStats(stats: ApiStats, elapsedMs: number): string | undefined {
+  const endpoints = Object.entries(stats.endpoints);
+  const caches = Object.entries(stats.cache);
+  if (endpoints.length === 0 && caches.length === 0) {
+    return undefined;
+  }
+  const retries = endpoints.reduce((sum, [, endpoint]) => sum + endpoint.retries, 0);
+  const bytes = endpoints.reduce((sum, [, endpoint]) => sum + endpoint.bytes, 0);
+  const parts = [
+    ...endpoints.map(([name, endpoint]) => `${name} ${endpoint.requests}回`),
+    `リトライ${retries}回`,
+    `合計${formatBytes(bytes)}`,
+    `${Math.round(elapsedMs / 1000)}秒`,
+    ...caches.map(([name, cache]) => `${name}キャッシュ ヒット${cache.hits} / ミス${cache.misses}`),
+  ];
+  return `API統計: ${parts.join(' / ')}`;
+}
+
+/**
+ * 実行サマリ（`--summary-json`）の件数を加算する。
+ */
+export function countRunStat(key: Exclude<keyof RunStats, 'failures'>, count = 1): void {
+  runStats[key] += count;
+}
+
+/**
+ * 実行サマリに、取得に失敗した法令を記録する。
+ */
+export function recordRunFailure(failure: RunFailedLaw): void {
+  runStats.failures.push(failure);
+}
+
+/**
+ * ここまでの実行件数の写しを返す。
+ */
+export function getRunStats(): RunStats {
+  return structuredClone(runStats);
+}
+
+/**
+ * 実行件数を初期化する。同じプロセスで複数回実行するテストのために使う。
+ */
+export function resetRunStats(): void {
+  runStats = { fetched: 0, skipped: 0, unresolved: 0, dictionaryAdded: 0, failures: [] };
+}
+
+/**
+ * `--summary-json` に書き出す実行サマリを作る。CIなどが終了コードを見なくても成否を判定できるよう、
+ * 終了コードから `status` を決める。`--diff` の差分あり（4）は処理としては成功なので `success` にし、
+ * Ctrl+C による中断（130）は `cancelled` とする。e-Govへの負荷をCIでも追えるよう、API統計の回数とリクエスト間隔の待機時間も載せる。
+ */
+export function buildRunSummary(
+  stats: RunStats,
+  apiStats: ApiStats,
+  throttleWaitMs: number,
+  startedAt: number,
+  finishedAt: number,
+  exitCode: number,
+  error?: unknown,
+): RunSummary {
+  const status =
+    exitCode === 0 || exitCode === 4
+      ? 'success'
+      : exitCode === 3
+        ? 'partial'
+        : exitCode === 130
+          ? 'cancelled'
+          : 'failed';
+  const endpoints = Object.values(apiStats.endpoints);
+  return {
+    status,
+    exit_code: exitCode,
+    started_at: new Date(startedAt).toISOString(),
+    finished_at: new Date(finishedAt).toISOString(),
+    elapsed_ms: finishedAt - startedAt,
+    throttle_wait_ms: throttleWaitMs,
+    fetched: stats.fetched,
+    skipped: stats.skipped,
+    unresolved: stats.unresolved,
+    dictionary_added: stats.dictionaryAdded,
+    failed_laws: stats.failures,
+    api: {
+      requests: endpoints.reduce((sum, endpoint) => sum + endpoint.requests, 0),
+      retries: endpoints.reduce((sum, endpoint) => sum + endpoint.retries, 0),
+      bytes: endpoints.reduce((sum, endpoint) => sum + endpoint.bytes, 0),
+      endpoints: apiStats.endpoints,
+      cache: apiStats.cache,
+    },
+    ...(error !== undefined ? { error: error instanceof Error ? error.message : String(error) } : {}),
+  };
+}
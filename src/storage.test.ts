@@ -1,7 +1,45 @@
 import test from 'node:test';
 import assert from 'node:assert/strict';
+import { spawnSync } from 'node:child_process';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { setTimeout as delay } from 'node:timers/promises';
 
-import { mergeUnresolvedRecords } from './index.js';
+import {
+  appendFailedLaws,
+  appendUnresolved,
+  appendUnresolvedLog,
+  clearUnresolvedLog,
+  decodeMessagePack,
+  decodeNoteText,
+  defaultCliOptions,
+  encodeNoteText,
+  FileLockTimeoutError,
+  findDictionaryKeyConflicts,
+  findMissingLinks,
+  isSearchCacheFresh,
+  loadDictionaries,
+  loadDictionary,
+  loadFailedLaws,
+  mergeDictionaryForSave,
+  mergeUnresolvedRecords,
+  parseDictionaryFile,
+  readNoteText,
+  recoverUnresolvedLog,
+  relinkRefText,
+  reportDictionaryKeyConflicts,
+  resolveDictFormat,
+  resolvePendingUnresolvedRefs,
+  retryPendingRefs,
+  saveDictionary,
+  scanReferencedLawIdsFromMarkdown,
+  splitFrontmatter,
+  unresolvedLogPath,
+  updateUnresolvedStatus,
+  withFileLock,
+  writeFileAtomic,
+} from './index.js';
 
 test('mergeUnresolvedRecords: 同一キーを重複追加しない', () => {
   const existing = [
@@ -38,3 +76,521 @@ test('mergeUnresolvedRecords: 同一キーを重複追加しない', () => {
   const merged = mergeUnresolvedRecords(existing, incoming);
   assert.equal(merged.length, 2);
 });
+
+test('isSearchCacheFresh: TTLを超えたキャッシュは期限切れとする', () => {
+  const entry = { cached_at: '2026-02-20T00:00:00Z', candidates: [] };
+  assert.equal(isSearchCacheFresh(entry, 24, new Date('2026-02-20T23:00:00Z')), true);
+  assert.equal(isSearchCacheFresh(entry, 24, new Date('2026-02-21T01:00:00Z')), false);
+  assert.equal(isSearchCacheFresh({ cached_at: 'invalid', candidates: [] }, 24), false);
+});
+
+test('appendFailedLaws: 同じ法令の失敗は最も浅い記録にまとめる', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-failed-'));
+  const filePath = path.join(tmp, 'failed_laws.json');
+  const record = {
+    timestamp: '2026-02-20T00:00:00Z',
+    law_id: '129AC0000000089',
+    title: '民法',
+    depth: 2,
+    error: 'timeout',
+  };
+  await appendFailedLaws(filePath, [record]);
+  await appendFailedLaws(filePath, [{ ...record, depth: 1 }, { ...record, law_id: '140AC0000000045', title: '刑法' }]);
+  await appendFailedLaws(filePath, [{ ...record, depth: 3 }]);
+  const loaded = await loadFailedLaws(filePath);
+  assert.deepEqual(
+    loaded.map((item) => [item.law_id, item.depth]),
+    [
+      ['129AC0000000089', 1],
+      ['140AC0000000045', 2],
+    ],
+  );
+});
+
+test('findDictionaryKeyConflicts / reportDictionaryKeyConflicts: 照合キーの重なりを見つけてログに追記する', async () => {
+  const entry = (title: string, abbrev?: string) => ({
+    title,
+    safe_title: title,
+    file_name: '',
+    updated_at: '',
+    abbrev,
+  });
+  const dictionary = {
+    '322AC0000000120': entry('国家公務員法'),
+    '322AC0000000999': entry('國家公務員法'),
+    '334AC0000000121': entry('特許法'),
+    '411AC0000000087': entry('民事再生法', '再生法'),
+    '999AC0000000001': entry('再生法'),
+    '100AC0000000001': entry('law_100AC0000000001'),
+  };
+  const conflicts = findDictionaryKeyConflicts(dictionary, 'api_scan');
+  assert.deepEqual(
+    conflicts.map((conflict) => [conflict.key, conflict.law_ids, conflict.source]),
+    [
+      ['国家公務員法', ['322AC0000000120', '322AC0000000999'], 'api_scan'],
+      ['再生法', ['411AC0000000087', '999AC0000000001'], 'api_scan'],
+    ],
+  );
+  // 登録した法令を指定すると、その法令を含む重なりだけを返す。
+  assert.deepEqual(
+    findDictionaryKeyConflicts(dictionary, 'fuzzy', ['999AC0000000001']).map((conflict) => conflict.titles),
+    [['民事再生法', '再生法']],
+  );
+  assert.deepEqual(findDictionaryKeyConflicts(dictionary, 'fuzzy', ['334AC0000000121']), []);
+
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-dict-conflict-'));
+  const dictConflictLogPath = path.join(tmp, 'logs', 'dict_conflicts.jsonl');
+  await reportDictionaryKeyConflicts({ dictConflictLogPath }, conflicts.slice(0, 1));
+  await reportDictionaryKeyConflicts({ dictConflictLogPath }, conflicts.slice(1));
+  const lines = (await fs.readFile(dictConflictLogPath, 'utf8')).trimEnd().split('\n');
+  assert.deepEqual(lines.map((line) => JSON.parse(line).key), ['国家公務員法', '再生法']);
+});
+
+test('updateUnresolvedStatus: 別名と参照元に一致する記録だけを更新する', () => {
+  const record = (rootLawId: string, rawText: string, href: string) => ({
+    timestamp: '2026-02-20T00:00:00Z',
+    root_law_id: rootLawId,
+    root_law_title: rootLawId,
+    from_anchor: 'Mp-At_1-Pr_1',
+    raw_text: rawText,
+    href,
+    reason: 'target_not_built' as const,
+    status: 'pending' as const,
+  });
+  const records = [
+    record('334AC0000000121', '民法', '/law/129AC0000000089'),
+    record('335AC0000000123', '民法', '/law/129AC0000000089'),
+    record('334AC0000000121', '商法', '/law/132AC0000000048'),
+  ];
+
+  const resolved = updateUnresolvedStatus(records, '民法', 'resolved', '334AC0000000121', '129AC0000000089');
+  assert.equal(resolved.matched.length, 1);
+  assert.deepEqual(
+    resolved.records.map((item) => [item.status, item.resolved_law_id]),
+    [
+      ['resolved', '129AC0000000089'],
+      ['pending', undefined],
+      ['pending', undefined],
+    ],
+  );
+
+  const ignored = updateUnresolvedStatus(records, '/law/129AC0000000089', 'ignored');
+  assert.deepEqual(
+    ignored.records.map((item) => item.status),
+    ['ignored', 'ignored', 'pending'],
+  );
+});
+
+test('retryPendingRefs: 法令名で解決できなかった pending の記録だけを解決し直す', () => {
+  const record = (rawText: string, reason: 'unknown_format' | 'depth_limit', status: 'pending' | 'ignored') => ({
+    timestamp: '2026-02-20T00:00:00Z',
+    root_law_id: '334AC0000000121',
+    root_law_title: '特許法',
+    from_anchor: 'Mp-At_1-Pr_1',
+    raw_text: rawText,
+    href: '',
+    reason,
+    status,
+  });
+  const records = [
+    record('民法第三条', 'unknown_format', 'pending'),
+    record('民法', 'depth_limit', 'pending'),
+    record('民法', 'unknown_format', 'ignored'),
+    record('旧商法', 'unknown_format', 'pending'),
+  ];
+
+  const retried = retryPendingRefs(records, new Map([['民法', '129AC0000000089']]));
+  assert.equal(retried.resolved.length, 1);
+  assert.deepEqual(
+    retried.records.map((item) => [item.status, item.resolved_law_id]),
+    [
+      ['resolved', '129AC0000000089'],
+      ['pending', undefined],
+      ['ignored', undefined],
+      ['pending', undefined],
+    ],
+  );
+});
+
+test('relinkRefText: アンカーの段落でリンクになっていない文言だけをリンクにする', () => {
+  const markdown = [
+    '<a id="Mp-At_1"></a>',
+    '民法の規定',
+    '<a id="Mp-At_2"></a>',
+    '[[law_129AC0000000089|民法]]及び民法第三条',
+    '<a id="Mp-At_3"></a>',
+  ].join('\n');
+
+  const relinked = relinkRefText(markdown, 'Mp-At_2', '民法', '[[law_129AC0000000089|民法]]');
+  assert.equal(relinked.split('\n')[3], '[[law_129AC0000000089|民法]]及び[[law_129AC0000000089|民法]]第三条');
+  assert.equal(relinked.split('\n')[1], '民法の規定');
+  assert.equal(relinkRefText(markdown, 'Mp-At_3', '民法', '[[x|民法]]'), markdown);
+});
+
+const dictEntry = (title: string, fileName: string, updatedAt: string) => ({
+  title,
+  safe_title: title,
+  file_name: fileName,
+  updated_at: updatedAt,
+});
+
+test('resolvePendingUnresolvedRefs: 再帰取得した参照先の法令で記録した参照は、その法令のノートでリンクにする', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-resolve-pending-'));
+  const outputDir = path.join(tmp, 'laws');
+  await fs.mkdir(outputDir);
+  const dictionary = {
+    '334AC0000000121': dictEntry('特許法', '特許法_334AC0000000121.md', '2026-02-20T00:00:00Z'),
+    '408AC0000000109': dictEntry('民事訴訟法', '民事訴訟法_408AC0000000109.md', '2026-02-20T00:00:00Z'),
+    '129AC0000000089': dictEntry('民法', '民法_129AC0000000089.md', '2026-02-20T00:00:00Z'),
+  };
+  const note = ['<a id="Mp-At_1"></a>', '民法の規定'].join('\n');
+  await fs.writeFile(path.join(outputDir, '特許法_334AC0000000121.md'), note);
+  await fs.writeFile(path.join(outputDir, '民事訴訟法_408AC0000000109.md'), note);
+  const unresolvedPath = path.join(tmp, 'unresolved_refs.json');
+  // 特許法を起点に depth 1 で取得した民事訴訟法の本文にあった参照。
+  const record = {
+    timestamp: '2026-02-20T00:00:00Z',
+    root_law_id: '334AC0000000121',
+    root_law_title: '特許法',
+    source_law_id: '408AC0000000109',
+    from_anchor: 'Mp-At_1',
+    raw_text: '民法',
+    href: '',
+    reason: 'unknown_format' as const,
+    status: 'pending' as const,
+  };
+  await fs.writeFile(unresolvedPath, JSON.stringify([record]));
+  const options = {
+    ...defaultCliOptions(),
+    outputDir,
+    unresolvedPath,
+    choicesPath: path.join(tmp, 'choices.json'),
+  };
+
+  await resolvePendingUnresolvedRefs(options, dictionary);
+  const source = await fs.readFile(path.join(outputDir, '民事訴訟法_408AC0000000109.md'), 'utf8');
+  assert.match(source.split('\n')[1], /^\[\[[^\]]*129AC0000000089[^\]]*\|民法\]\]の規定$/);
+  assert.equal(await fs.readFile(path.join(outputDir, '特許法_334AC0000000121.md'), 'utf8'), note);
+  const [updated] = JSON.parse(await fs.readFile(unresolvedPath, 'utf8'));
+  assert.equal(updated.status, 'resolved');
+  assert.equal(updated.resolved_law_id, '129AC0000000089');
+});
+
+test('mergeDictionaryForSave: 他プロセスの登録を残し、仮登録でないもの・新しいものを採用する', () => {
+  const latest = {
+    '129AC0000000089': dictEntry('民法', '民法.md', '2026-02-20T00:00:00Z'),
+    '140AC0000000045': dictEntry('刑法', '刑法.md', '2026-02-20T02:00:00Z'),
+    '132AC0000000048': dictEntry('商法', '商法.md', '2026-02-20T00:00:00Z'),
+  };
+  const own = {
+    '129AC0000000089': dictEntry('law_129AC0000000089', 'law_129AC0000000089.md', '2026-02-20T03:00:00Z'),
+    '140AC0000000045': dictEntry('刑法（旧）', '刑法.md', '2026-02-20T01:00:00Z'),
+    '132AC0000000048': dictEntry('商法', '商法_132AC0000000048.md', '2026-02-20T01:00:00Z'),
+    '334AC0000000121': dictEntry('特許法', '特許法.md', '2026-02-20T01:00:00Z'),
+  };
+  const merged = mergeDictionaryForSave(latest, own);
+  assert.deepEqual(
+    Object.fromEntries(Object.entries(merged).map(([lawId, entry]) => [lawId, entry.file_name])),
+    {
+      '129AC0000000089': '民法.md',
+      '140AC0000000045': '刑法.md',
+      '132AC0000000048': '商法_132AC0000000048.md',
+      '334AC0000000121': '特許法.md',
+    },
+  );
+  assert.equal(merged['140AC0000000045'].title, '刑法');
+});
+
+test('loadDictionaries / saveDictionary: 先の辞書を優先して読み込み、新規登録は書き込み先にだけ保存する', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-dicts-'));
+  const sharedPath = path.join(tmp, 'shared.json');
+  const personalPath = path.join(tmp, 'personal.json');
+  await fs.writeFile(
+    sharedPath,
+    JSON.stringify({
+      '129AC0000000089': dictEntry('民法', '民法.md', '2026-02-20T00:00:00Z'),
+      '334AC0000000121': dictEntry('特許法', '特許法.md', '2026-02-20T00:00:00Z'),
+    }),
+  );
+  await fs.writeFile(
+    personalPath,
+    JSON.stringify({
+      '129AC0000000089': dictEntry('民法（個人）', '民法（個人）.md', '2026-02-21T00:00:00Z'),
+      // 先の辞書の「特許法」と別の法令を指す法令名は先勝ちで読み込まない。
+      '999AC0000000001': dictEntry('特許法', '特許法_999AC0000000001.md', '2026-02-21T00:00:00Z'),
+      '140AC0000000045': dictEntry('刑法', '刑法.md', '2026-02-21T00:00:00Z'),
+    }),
+  );
+  const dictionary = await loadDictionaries([sharedPath, personalPath]);
+  assert.deepEqual(Object.keys(dictionary).sort(), ['129AC0000000089', '140AC0000000045', '334AC0000000121']);
+  assert.equal(dictionary['129AC0000000089'].title, '民法');
+  assert.deepEqual(await loadDictionaries([sharedPath]), await loadDictionary(sharedPath));
+
+  dictionary['132AC0000000048'] = dictEntry('商法', '商法.md', '2026-02-22T00:00:00Z');
+  const options = {
+    dictionaryPath: personalPath,
+    dictionaryPaths: [sharedPath, personalPath],
+    dictCompact: false,
+    verbose: false,
+    lockTimeoutMs: 5_000,
+  };
+  await saveDictionary(options, dictionary);
+  assert.deepEqual(Object.keys(await loadDictionary(sharedPath)).sort(), ['129AC0000000089', '334AC0000000121']);
+  const personal = await loadDictionary(personalPath);
+  assert.deepEqual(Object.keys(personal).sort(), [
+    '129AC0000000089',
+    '132AC0000000048',
+    '140AC0000000045',
+    '999AC0000000001',
+  ]);
+  // 共通辞書から読み込んだだけのエントリは個人辞書へ書き写さない。
+  assert.equal(personal['129AC0000000089'].title, '民法（個人）');
+  assert.equal(personal['334AC0000000121'], undefined);
+  assert.equal(dictionary['129AC0000000089'].title, '民法');
+});
+
+test('loadDictionary / saveDictionary: 旧形式の辞書を読み、版付きの形式で保存して直前の内容を .bak に残す', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-dict-schema-'));
+  const dictionaryPath = path.join(tmp, 'law_dictionary.json');
+  const legacy = { '129AC0000000089': dictEntry('民法', '民法.md', '2026-02-20T00:00:00Z') };
+  await fs.writeFile(dictionaryPath, JSON.stringify(legacy));
+  const dictionary = await loadDictionary(dictionaryPath);
+  assert.deepEqual(dictionary, legacy);
+
+  dictionary['140AC0000000045'] = dictEntry('刑法', '刑法.md', '2026-02-21T00:00:00Z');
+  await saveDictionary({ dictionaryPath, dictCompact: false, verbose: false, lockTimeoutMs: 5_000 }, dictionary);
+  const saved = JSON.parse(await fs.readFile(dictionaryPath, 'utf8'));
+  assert.equal(saved.schema_version, 1);
+  assert.deepEqual(Object.keys(saved.laws).sort(), ['129AC0000000089', '140AC0000000045']);
+  assert.deepEqual(JSON.parse(await fs.readFile(`${dictionaryPath}.bak`, 'utf8')), legacy);
+  assert.deepEqual(await loadDictionary(dictionaryPath), saved.laws);
+
+  assert.deepEqual(parseDictionaryFile({ schema_version: 1 }, dictionaryPath), {});
+  assert.throws(() => parseDictionaryFile({ schema_version: 2, laws: {} }, dictionaryPath), /schema_version 2/);
+  assert.throws(() => parseDictionaryFile([], dictionaryPath), /オブジェクト/);
+});
+
+test('saveDictionary / loadDictionary: MessagePackで保存した辞書を形式を指定せずに読み込める', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-dict-msgpack-'));
+  const options = { dictCompact: false, verbose: false, lockTimeoutMs: 5_000 };
+  const dictionary = { '129AC0000000089': dictEntry('民法', '民法.md', '2026-02-20T00:00:00Z') };
+
+  // 拡張子が .msgpack なら指定が無くてもMessagePackで保存する。
+  const msgpackPath = path.join(tmp, 'law_dictionary.msgpack');
+  await saveDictionary({ ...options, dictionaryPath: msgpackPath }, { ...dictionary });
+  const encoded = await fs.readFile(msgpackPath);
+  assert.deepEqual(decodeMessagePack(encoded), { schema_version: 1, laws: dictionary });
+  assert.deepEqual(await loadDictionary(msgpackPath), dictionary);
+
+  // `--dict-format` の指定は拡張子より優先し、読み込みは中身から形式を判別する。
+  const jsonNamedPath = path.join(tmp, 'law_dictionary.json');
+  await saveDictionary({ ...options, dictionaryPath: jsonNamedPath, dictFormat: 'messagepack' }, { ...dictionary });
+  assert.deepEqual(await loadDictionary(jsonNamedPath), dictionary);
+  await saveDictionary({ ...options, dictionaryPath: jsonNamedPath, dictFormat: 'json' }, { ...dictionary });
+  assert.equal(JSON.parse(await fs.readFile(jsonNamedPath, 'utf8')).schema_version, 1);
+  assert.deepEqual(await loadDictionary(jsonNamedPath), dictionary);
+
+  assert.equal(resolveDictFormat('dict.MSGPACK'), 'messagepack');
+  assert.equal(resolveDictFormat('dict.json'), 'json');
+  assert.equal(resolveDictFormat('dict.msgpack', 'json'), 'json');
+});
+
+test('saveDictionary / appendUnresolved: 並行して保存しても両方の変更が残る', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-lock-'));
+  const dictionaryPath = path.join(tmp, 'law_name_dict.json');
+  const options = { dictionaryPath, dictCompact: false, verbose: false, lockTimeoutMs: 5_000 };
+  const first = { '129AC0000000089': dictEntry('民法', '民法.md', '2026-02-20T00:00:00Z') };
+  const second = { '140AC0000000045': dictEntry('刑法', '刑法.md', '2026-02-20T00:00:00Z') };
+  await Promise.all([saveDictionary(options, first), saveDictionary(options, second)]);
+  assert.deepEqual(Object.keys(await loadDictionary(dictionaryPath)).sort(), ['129AC0000000089', '140AC0000000045']);
+
+  const unresolvedPath = path.join(tmp, 'unresolved_refs.json');
+  const record = (rawText: string) => ({
+    timestamp: '2026-02-20T00:00:00Z',
+    root_law_id: '334AC0000000121',
+    root_law_title: '特許法',
+    from_anchor: 'Mp-At_1',
+    raw_text: rawText,
+    href: '#Mp-At_0',
+    reason: 'unknown_format' as const,
+  });
+  await Promise.all([
+    appendUnresolved(unresolvedPath, [record('前条')], 5_000),
+    appendUnresolved(unresolvedPath, [record('次条')], 5_000),
+  ]);
+  const saved = JSON.parse(await fs.readFile(unresolvedPath, 'utf8')) as Array<{ raw_text: string }>;
+  assert.deepEqual(saved.map((item) => item.raw_text).sort(), ['前条', '次条']);
+  await assert.rejects(fs.access(`${unresolvedPath}.lock`));
+});
+
+test('appendUnresolvedLog / recoverUnresolvedLog: 途中で終わった実行のログを未解決参照へ取り込んで消し、実行中のログは残す', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-unresolved-log-'));
+  const unresolvedPath = path.join(tmp, 'unresolved_refs.json');
+  const record = (rawText: string) => ({
+    timestamp: '2026-10-16T00:00:00Z',
+    root_law_id: '334AC0000000121',
+    root_law_title: '特許法',
+    from_anchor: 'Mp-At_1',
+    raw_text: rawText,
+    href: '#Mp-At_0',
+    reason: 'unknown_format' as const,
+  });
+  await appendUnresolved(unresolvedPath, [record('前条')], 5_000);
+  // 実行中のプロセス（このテスト自身）のログ。
+  await appendUnresolvedLog(unresolvedPath, [record('同条')]);
+  const ownLogPath = unresolvedLogPath(unresolvedPath);
+  assert.equal(ownLogPath, path.join(tmp, `unresolved_refs.pending.${process.pid}.jsonl`));
+  // 終了したプロセスのログと、プロセスごとに分ける前の形式のログ。
+  const deadLogPath = unresolvedLogPath(unresolvedPath, 0x7fffffff);
+  await fs.writeFile(deadLogPath, `${JSON.stringify(record('次条'))}\n${JSON.stringify(record('前項'))}\n`);
+  // 追記の途中でプロセスが終わった最終行。
+  await fs.appendFile(deadLogPath, '{"timestamp":');
+  const legacyLogPath = path.join(tmp, 'unresolved_refs.pending.jsonl');
+  await fs.writeFile(legacyLogPath, `${JSON.stringify(record('後条'))}\n`);
+
+  assert.equal(await recoverUnresolvedLog(unresolvedPath, 5_000), 3);
+  const saved = JSON.parse(await fs.readFile(unresolvedPath, 'utf8')) as Array<{ raw_text: string }>;
+  assert.deepEqual(saved.map((item) => item.raw_text).sort(), ['前条', '前項', '次条', '後条'].sort());
+  await assert.rejects(fs.access(deadLogPath));
+  await assert.rejects(fs.access(legacyLogPath));
+  await fs.access(ownLogPath);
+  assert.equal(await recoverUnresolvedLog(unresolvedPath, 5_000), 0);
+
+  await clearUnresolvedLog(unresolvedPath);
+  await assert.rejects(fs.access(ownLogPath));
+});
+
+test('saveDictionary: ロックを取得できなければ変更をセッション固有のファイルへ退避する', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-lock-'));
+  const dictionaryPath = path.join(tmp, 'law_name_dict.json');
+  const options = { dictionaryPath, dictCompact: false, verbose: false, lockTimeoutMs: 0 };
+  const own = { '129AC0000000089': dictEntry('民法', '民法.md', '2026-02-20T00:00:00Z') };
+  await withFileLock(dictionaryPath, 0, async () => {
+    await saveDictionary(options, own);
+    await assert.rejects(withFileLock(dictionaryPath, 0, async () => undefined), FileLockTimeoutError);
+  });
+  const files = await fs.readdir(tmp);
+  assert.ok(!files.includes('law_name_dict.json'));
+  const evacuated = files.find((file) => /^law_name_dict\.\d+-\d+\.json$/.test(file));
+  assert.ok(evacuated);
+  assert.deepEqual(JSON.parse(await fs.readFile(path.join(tmp, evacuated), 'utf8')), own);
+});
+
+test('saveDictionary: 終了したプロセスのロックは新しくてもすぐ取り直し、退避せずに辞書へ書き込む', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-lock-'));
+  const dictionaryPath = path.join(tmp, 'law_name_dict.json');
+  const options = { dictionaryPath, dictCompact: false, verbose: false, lockTimeoutMs: 0 };
+  const own = { '129AC0000000089': dictEntry('民法', '民法.md', '2026-02-20T00:00:00Z') };
+  // 異常終了したプロセスが残したロックを模す。更新時刻は新しく、古さでは放棄とみなされない。
+  const { pid } = spawnSync(process.execPath, ['-e', '']);
+  await fs.writeFile(`${dictionaryPath}.lock`, `${pid}\n`, 'utf8');
+
+  await saveDictionary(options, own);
+  assert.deepEqual(await loadDictionary(dictionaryPath), own);
+  assert.deepEqual(await fs.readdir(tmp), ['law_name_dict.json']);
+});
+
+test('withFileLock: 終了したプロセスのロックを複数の待機側が同時に取り直しても、ロックを持つのは常に1つだけ', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-lock-'));
+  const filePath = path.join(tmp, 'law_name_dict.json');
+  const { pid } = spawnSync(process.execPath, ['-e', '']);
+  await fs.writeFile(`${filePath}.lock`, `${pid}\n`, 'utf8');
+
+  // 待機側がそろって放棄と判断し、先に取り直した側のロックを後の側が消すと、同時に2つが中へ入る。
+  let active = 0;
+  let maxActive = 0;
+  await Promise.all(
+    Array.from({ length: 5 }, () =>
+      withFileLock(filePath, 5_000, async () => {
+        active += 1;
+        maxActive = Math.max(maxActive, active);
+        await delay(20);
+        active -= 1;
+      }),
+    ),
+  );
+  assert.equal(maxActive, 1);
+  assert.deepEqual(await fs.readdir(tmp), []);
+});
+
+test('withFileLock: 放棄と判断した後に他のプロセスが取り直したロックは消さずに元へ戻す', async (t) => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-lock-'));
+  const filePath = path.join(tmp, 'law_name_dict.json');
+  const lockPath = `${filePath}.lock`;
+  const { pid } = spawnSync(process.execPath, ['-e', '']);
+  await fs.writeFile(lockPath, `${pid}\n`, 'utf8');
+
+  // 終了したプロセスのロックを放棄と判断してから移すまでの間に、別の待機側が先に取り直した状況を模す。
+  const othersLock = `${process.ppid}\n1\n`;
+  const rename = fs.rename;
+  t.mock.method(fs, 'rename', async (from: string, to: string) => {
+    if (from === lockPath) {
+      await fs.rm(lockPath);
+      await fs.writeFile(lockPath, othersLock, { encoding: 'utf8', flag: 'wx' });
+    }
+    return rename(from, to);
+  });
+  await assert.rejects(withFileLock(filePath, 0, async () => undefined), FileLockTimeoutError);
+  assert.equal(await fs.readFile(lockPath, 'utf8'), othersLock);
+  assert.deepEqual(await fs.readdir(tmp), ['law_name_dict.json.lock']);
+});
+
+test('withFileLock: 放棄とみなされて他のプロセスが取り直したロックは、終了時に消さない', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-lock-'));
+  const filePath = path.join(tmp, 'law_name_dict.json');
+  const othersLock = `${process.ppid}\n1\n`;
+  await withFileLock(filePath, 0, async () => {
+    await fs.writeFile(`${filePath}.lock`, othersLock, 'utf8');
+  });
+  assert.equal(await fs.readFile(`${filePath}.lock`, 'utf8'), othersLock);
+});
+
+test('writeFileAtomic: 書き込みが途中で失敗しても既存ファイルを壊さず、一時ファイルも残さない', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-atomic-'));
+  const filePath = path.join(tmp, 'law_name_dict.json');
+  await writeFileAtomic(filePath, '{"old":true}');
+
+  // 辞書の前半だけを書いたところでプロセスが落ちた状況を模す。
+  async function* interrupted() {
+    yield '{"new":';
+    throw new Error('中断');
+  }
+  await assert.rejects(writeFileAtomic(filePath, interrupted()), /中断/);
+  assert.equal(await fs.readFile(filePath, 'utf8'), '{"old":true}');
+  assert.deepEqual(await fs.readdir(tmp), ['law_name_dict.json']);
+
+  await writeFileAtomic(filePath, Buffer.from('{"new":true}'));
+  assert.equal(await fs.readFile(filePath, 'utf8'), '{"new":true}');
+});
+
+test('encodeNoteText / readNoteText: CRLF・BOM付きで書いたノートもLFに戻して読み、リンクと見出しの扱いは変わらない', async () => {
+  const tmp = await fs.mkdtemp(path.join(os.tmpdir(), 'laws-line-ending-'));
+  const notePath = path.join(tmp, '特許法_334AC0000000121.md');
+  const markdown = [
+    '---',
+    'law_id: "334AC0000000121"',
+    '---',
+    '# 特許法',
+    '',
+    '## 第一条',
+    '[[民法_129AC0000000089.md#Mp-At_1|民法第一条]]',
+    '',
+  ].join('\n');
+  const encoded = encodeNoteText(markdown, { lineEnding: 'crlf', bom: true });
+  assert.ok(encoded.startsWith('\ufeff---\r\n'));
+  assert.equal(encoded.split('\r\n').length, markdown.split('\n').length);
+  assert.equal(encodeNoteText(markdown, { lineEnding: 'lf', bom: false }), markdown);
+  // 変換を重ねてもCRが重ならない。
+  assert.equal(encodeNoteText(encoded, { lineEnding: 'crlf', bom: false }), encoded);
+
+  await writeFileAtomic(notePath, encoded);
+  const read = await readNoteText(notePath);
+  assert.equal(read, markdown);
+  assert.equal(decodeNoteText(encoded), markdown);
+  assert.equal(splitFrontmatter(read).frontmatter?.law_id, '334AC0000000121');
+  assert.deepEqual(scanReferencedLawIdsFromMarkdown(read).referencedLawIds, ['129AC0000000089']);
+  assert.deepEqual(
+    findMissingLinks([{ path: '特許法_334AC0000000121.md', markdown: read }]).map((record) => record.target),
+    ['民法_129AC0000000089.md'],
+  );
+});
@@ -1,9 +1,85 @@
 import fs from 'node:fs/promises';
 import path from 'node:path';
-import type { LawDictionary, UnresolvedRefRecord } from './types.js';
+import process from 'node:process';
+import { DICTIONARY_SCHEMA_VERSION } from './config.js';
+import type {
+  CandidateChoices,
+  CliOptions,
+  DictEntrySource,
+  DictFormat,
+  DictKeyConflictRecord,
+  FailedLawRecord,
+  LawDataArchive,
+  LawDataResponse,
+  LawDictionary,
+  LawDictionaryEntry,
+  LawDictionaryFile,
+  LawFullTextFormat,
+  LawPageArchive,
+  ScrapedLawDocument,
+  SearchCache,
+  SearchCacheEntry,
+  UnresolvedRefRecord,
+} from './types.js';
+import { decodeMessagePack, encodeMessagePack, startsWithMessagePackMap } from './msgpack.js';
+import { canonicalizeKanji } from './kanji.js';
+import { dictionaryEntryNames } from './notes.js';
+import { wait } from './utils.js';
+
+const LOCK_RETRY_INTERVAL_MS = 100;
+// 持ち主のPIDを読めないロックファイルで後続の実行が止まり続けないよう、十分古いロックは放棄されたものとみなす。
+const STALE_LOCK_MS = 10 * 60 * 1000;
+const MAX_CONFLICT_WARNINGS = 10;
+// Windowsでは置き換え先を他のプロセス（Obsidianやウイルス対策ソフト）が開いていると rename が一時的に失敗するため、少し待って取り直す。
+const RENAME_RETRY_CODES = new Set(['EPERM', 'EACCES', 'EBUSY']);
+const RENAME_RETRIES = 5;
+const RENAME_RETRY_INTERVAL_MS = 100;
+let atomicWriteSeq = 0;
+let lockSeq = 0;
+
+/**
+ * 辞書未登録時に仮登録した `law_<law_id>.md` 形式のエントリかを判定する。
+ */
+export function isFallbackDictionaryEntry(lawId: string, entry: LawDictionaryEntry): boolean {
+  return entry.file_name === `law_${lawId}.md`;
+}
+
+const SOURCE_CONFIDENCE: Record<DictEntrySource, number> = {
+  api_scan: 1,
+  api_lookup: 1,
+  fetched: 1,
+  manual: 1,
+  candidate: 0.9,
+  import: 0.8,
+  fuzzy: 0.6,
+  fallback: 0,
+};
+
+/**
+ * 登録経路に応じて辞書エントリへ付ける `source` / `confidence` を返す。
+ */
+export function dictionaryProvenance(source: DictEntrySource): Pick<LawDictionaryEntry, 'source' | 'confidence'> {
+  return { source, confidence: SOURCE_CONFIDENCE[source] };
+}
+
+/**
+ * 辞書エントリの確信度を返す。`source` / `confidence` を持たない旧形式のエントリは、
+ * 仮登録なら0、それ以外は従来どおり確定済みの対応として1とみなす。
+ */
+export function dictionaryEntryConfidence(lawId: string, entry: LawDictionaryEntry): number {
+  if (typeof entry.confidence === 'number') {
+    return entry.confidence;
+  }
+  if (entry.source) {
+    return SOURCE_CONFIDENCE[entry.source] ?? 1;
+  }
+  return isFallbackDictionaryEntry(lawId, entry) ? 0 : 1;
+}
 
 export function unresolvedKey(item: UnresolvedRefRecord): string {
-  return `${item.root_law_id}\t${item.from_anchor}\t${item.raw_text}\t${item.href}`;
+  // 起点法令が同じでも、参照先の法令どうしで同じアンカー・文言の参照がありうるため、参照元の法令も含める。
+  const sourceLawId = item.source_law_id ?? item.root_law_id;
+  return [item.root_law_id, sourceLawId, item.from_anchor, item.raw_text, item.href].join('\t');
 }
 
 /**
@@ -25,18 +101,381 @@ export function mergeUnresolvedRecords(
   return merged;
 }
 
+/**
+ * ロックファイルを取得できないまま待機時間を過ぎたことを表すエラー。
+ */
+export class FileLockTimeoutError extends Error {
+  constructor(readonly lockPath: string) {
+    super(`ロックを取得できませんでした: ${lockPath}`);
+    this.name = 'FileLockTimeoutError';
+  }
+}
+
+function isProcessAlive(pid: number): boolean {
+  try {
+    process.kill(pid, 0);
+    return true;
+  } catch (error) {
+    // EPERM は他のユーザーのプロセスとして存在している。
+    return (error as NodeJS.ErrnoException).code === 'EPERM';
+  }
+}
+
+/**
+ * ロックファイルが放棄されたものなら、警告に使う理由を返す。
+ * ロックファイルの1行目にはロックを取ったプロセスのPIDを書いているため、異常終了したプロセスのロックはすぐ放棄とみなせる。
+ */
+async function staleLockReason(lockPath: string): Promise<string | undefined> {
+  const pid = Number((await fs.readFile(lockPath, 'utf8')).split('\n')[0].trim());
+  if (Number.isInteger(pid) && pid > 0) {
+    return isProcessAlive(pid) ? undefined : `終了したプロセス（PID ${pid}）の`;
+  }
+  // 作成直後でPIDをまだ書いていないロックや、PIDの無い古い形式のロックは、更新時刻で判断する。
+  return Date.now() - (await fs.stat(lockPath)).mtimeMs < STALE_LOCK_MS ? undefined : '古い';
+}
+
+/**
+ * 放棄されたロックを削除する。削除したか、確認中にロックが解放されていれば `true` を返す。
+ */
+async function removeStaleLock(lockPath: string): Promise<boolean> {
+  try {
+    if (!(await staleLockReason(lockPath))) {
+      return false;
+    }
+    // 複数のプロセスが同じロックを同時に放棄と判断すると、先に取り直したプロセスのロックを後のプロセスが消してしまう。
+    // 一意な名前へ移してから中身を確かめ直し、移したのが取り直されたロックなら元に戻す。
+    lockSeq += 1;
+    const stalePath = `${lockPath}.${process.pid}-${lockSeq}.stale`;
+    await fs.rename(lockPath, stalePath);
+    const reason = await staleLockReason(stalePath);
+    if (!reason) {
+      await fs.link(stalePath, lockPath).catch((error: NodeJS.ErrnoException) => {
+        // 戻すまでの間にさらに別のプロセスがロックを取った場合は、そちらを残す。
+        if (error.code !== 'EEXIST') {
+          throw error;
+        }
+      });
+      await fs.rm(stalePath, { force: true });
+      return false;
+    }
+    await fs.rm(stalePath, { force: true });
+    process.stderr.write(`警告: ${reason}ロックファイルを削除しました: ${lockPath}\n`);
+    return true;
+  } catch (error) {
+    // 確認中に他のプロセスがロックを解放した場合は、すぐに取り直せばよい。
+    if ((error as NodeJS.ErrnoException).code === 'ENOENT') {
+      return true;
+    }
+    throw error;
+  }
+}
+
+/**
+ * `<filePath>.lock` を排他作成してロックを取り、`fn` を実行する。
+ * 別ターミナルで並行実行したプロセス同士が、同じJSONを読み込み〜書き戻しする間に割り込まないようにする。
+ * `timeoutMs` 以内に取得できなければ `FileLockTimeoutError` を投げる。
+ */
+export async function withFileLock<T>(filePath: string, timeoutMs: number, fn: () => Promise<T>): Promise<T> {
+  const lockPath = `${filePath}.lock`;
+  await fs.mkdir(path.dirname(filePath), { recursive: true });
+  const deadline = Date.now() + timeoutMs;
+  // 同じプロセス内の別のロックとも区別できるよう、PIDの後に連番を書く。
+  lockSeq += 1;
+  const owner = `${process.pid}\n${lockSeq}\n`;
+  for (;;) {
+    try {
+      await fs.writeFile(lockPath, owner, { encoding: 'utf8', flag: 'wx' });
+      break;
+    } catch (error) {
+      if ((error as NodeJS.ErrnoException).code !== 'EEXIST') {
+        throw error;
+      }
+    }
+    if (await removeStaleLock(lockPath)) {
+      continue;
+    }
+    if (Date.now() >= deadline) {
+      throw new FileLockTimeoutError(lockPath);
+    }
+    await wait(LOCK_RETRY_INTERVAL_MS);
+  }
+  try {
+    return await fn();
+  } finally {
+    // 放棄とみなされて他のプロセスが取り直したロックは消さない。
+    if ((await fs.readFile(lockPath, 'utf8').catch(() => undefined)) === owner) {
+      await fs.rm(lockPath, { force: true });
+    }
+  }
+}
+
+/**
+ * ロックを取得できなかった変更を、他のプロセスと衝突しないセッション固有のファイルへ退避する。
+ * 変更を捨てずに残し、後から手動で取り込めるようにする。
+ */
+async function evacuateChanges(
+  filePath: string,
+  data: unknown,
+  error: FileLockTimeoutError,
+  hint: string,
+): Promise<void> {
+  const parsed = path.parse(filePath);
+  const sessionPath = path.join(parsed.dir, `${parsed.name}.${process.pid}-${Date.now()}${parsed.ext}`);
+  await writeJson(sessionPath, data);
+  process.stderr.write(`警告: ${error.message}。変更を ${sessionPath} へ退避しました（${hint}）\n`);
+}
+
+/**
+ * ロックを取って最新の内容を読み直し、`merge` で自分の変更と合わせて書き戻す。
+ * ロックを取得できなければ、`changes` をセッション固有のファイルへ退避する。
+ */
+async function saveWithLock(
+  filePath: string,
+  timeoutMs: number,
+  changes: unknown,
+  merge: () => Promise<void>,
+  hint: string,
+): Promise<void> {
+  try {
+    await withFileLock(filePath, timeoutMs, merge);
+  } catch (error) {
+    if (!(error instanceof FileLockTimeoutError)) {
+      throw error;
+    }
+    await evacuateChanges(filePath, changes, error, hint);
+  }
+}
+
+/**
+ * 保存直前に読み直した辞書へ、このプロセスの辞書をマージする。
+ * 他のプロセスが登録した法令を残し、同じ法令は仮登録でないもの、次に `updated_at` が新しいものを採用する。
+ */
+export function mergeDictionaryForSave(latest: LawDictionary, own: LawDictionary): LawDictionary {
+  const merged: LawDictionary = { ...latest };
+  for (const [lawId, entry] of Object.entries(own)) {
+    const current = merged[lawId];
+    if (!current) {
+      merged[lawId] = entry;
+      continue;
+    }
+    const currentIsFallback = isFallbackDictionaryEntry(lawId, current);
+    const entryIsFallback = isFallbackDictionaryEntry(lawId, entry);
+    if (currentIsFallback !== entryIsFallback) {
+      merged[lawId] = currentIsFallback ? entry : current;
+      continue;
+    }
+    if (Date.parse(entry.updated_at) >= Date.parse(current.updated_at)) {
+      merged[lawId] = entry;
+    }
+  }
+  return merged;
+}
+
 /**
  * 指定パスにJSONを保存する。
+ * `compact` 指定時はインデントを省き、数万件規模の辞書でも書き込み量を抑える。
  */
-export async function writeJson(filePath: string, data: unknown): Promise<void> {
+export async function writeJson(filePath: string, data: unknown, compact = false): Promise<void> {
   await fs.mkdir(path.dirname(filePath), { recursive: true });
-  await fs.writeFile(filePath, JSON.stringify(data, null, 2), 'utf8');
+  await writeFileAtomic(filePath, compact ? JSON.stringify(data) : JSON.stringify(data, null, 2));
+}
+
+/**
+ * 内部ではLFで扱っているノートの文字列を、`--line-ending` / `--bom` の指定に合わせる。ノートを書き込む直前に一度だけ通す。
+ */
+export function encodeNoteText(text: string, options: Pick<CliOptions, 'lineEnding' | 'bom'>): string {
+  const converted = options.lineEnding === 'crlf' ? text.replace(/\r?\n/g, '\r\n') : text;
+  return options.bom ? `\ufeff${converted}` : converted;
+}
+
+/**
+ * 書き出したノートの文字列を、内部で扱う形（BOMなし・改行はLF）に戻す。
+ */
+export function decodeNoteText(text: string): string {
+  return text.replace(/^\ufeff/, '').replace(/\r\n/g, '\n');
+}
+
+/**
+ * ノートを読み込む。`--line-ending crlf` や `--bom` で書いたノートも、BOMを外して改行をLFにそろえて返すため、
+ * frontmatter・Wikiリンク・見出しの処理は書き出し時の設定を気にしなくてよい。
+ */
+export async function readNoteText(filePath: string): Promise<string> {
+  return decodeNoteText(await fs.readFile(filePath, 'utf8'));
 }
 
-export async function loadDictionary(filePath: string): Promise<LawDictionary> {
+/**
+ * 同じディレクトリの一時ファイルへ書いてから rename で置き換える。書き込みの途中でプロセスが落ちても、
+ * 元のファイルが途中までの中身で残らないようにする。一時ファイルは rename の前に fsync する。
+ * ノート・辞書・未解決参照などの保存はすべてこれを通す。
+ * `data` に非同期イテラブルを渡すと、分けて受け取った内容を順に書き込む。
+ */
+export async function writeFileAtomic(
+  filePath: string,
+  data: string | Uint8Array | AsyncIterable<string | Uint8Array>,
+): Promise<void> {
+  // 同じプロセス内で同じファイルへの書き込みが重なっても一時ファイルを取り合わないよう、連番も付ける。
+  atomicWriteSeq += 1;
+  const tempPath = `${filePath}.${process.pid}-${atomicWriteSeq}.tmp`;
   try {
-    const content = await fs.readFile(filePath, 'utf8');
-    return JSON.parse(content) as LawDictionary;
+    const handle = await fs.open(tempPath, 'w');
+    try {
+      await handle.writeFile(data, 'utf8');
+      // rename だけが先にディスクへ反映されると、OSごと落ちたときに中身の無いファイルへ置き換わるため、先に同期する。
+      await handle.sync();
+    } finally {
+      await handle.close();
+    }
+    for (let attempt = 0; ; attempt += 1) {
+      try {
+        await fs.rename(tempPath, filePath);
+        break;
+      } catch (error) {
+        if (attempt >= RENAME_RETRIES || !RENAME_RETRY_CODES.has((error as NodeJS.ErrnoException).code ?? '')) {
+          throw error;
+        }
+        await wait(RENAME_RETRY_INTERVAL_MS);
+      }
+    }
+  } catch (error) {
+    await fs.rm(tempPath, { force: true });
+    throw error;
+  }
+}
+
+/**
+ * 他の辞書ファイルから読み込んだままのエントリを除き、この実行で登録・更新したエントリだけを返す。
+ */
+export function excludeInheritedEntries(dictionary: LawDictionary, inherited: LawDictionary): LawDictionary {
+  return Object.fromEntries(
+    Object.entries(dictionary).filter(
+      ([lawId, entry]) => !inherited[lawId] || JSON.stringify(inherited[lawId]) !== JSON.stringify(entry),
+    ),
+  );
+}
+
+/**
+ * 辞書を保存する。`--verbose` 時は件数と所要時間を表示する。
+ * 並行実行した他のプロセスの登録を消さないよう、ロック中に最新の辞書を読み直してマージし、
+ * マージ結果を `dictionary` にも反映する。削除したエントリは `removedLawIds` で渡す（マージで復活させないため）。
+ * 複数の辞書を読み込んでいる場合は、書き込み先以外の辞書から読み込んだままのエントリは書き込まない。
+ */
+export async function saveDictionary(
+  options: Pick<CliOptions, 'dictionaryPath' | 'dictCompact' | 'verbose' | 'lockTimeoutMs'> &
+    Partial<Pick<CliOptions, 'dictionaryPaths' | 'dictFormat'>>,
+  dictionary: LawDictionary,
+  removedLawIds: string[] = [],
+): Promise<void> {
+  const started = Date.now();
+  const inheritedPaths = (options.dictionaryPaths ?? []).filter((filePath) => filePath !== options.dictionaryPath);
+  const own =
+    inheritedPaths.length > 0
+      ? excludeInheritedEntries(dictionary, await loadDictionaries(inheritedPaths))
+      : dictionary;
+  await saveWithLock(
+    options.dictionaryPath,
+    options.lockTimeoutMs,
+    own,
+    async () => {
+      const merged = mergeDictionaryForSave(await loadDictionary(options.dictionaryPath), own);
+      for (const lawId of removedLawIds) {
+        delete merged[lawId];
+      }
+      await backupFile(options.dictionaryPath);
+      const file: LawDictionaryFile = { schema_version: DICTIONARY_SCHEMA_VERSION, laws: merged };
+      if (resolveDictFormat(options.dictionaryPath, options.dictFormat) === 'messagepack') {
+        await fs.mkdir(path.dirname(options.dictionaryPath), { recursive: true });
+        await writeFileAtomic(options.dictionaryPath, encodeMessagePack(file));
+      } else {
+        await writeJson(options.dictionaryPath, file, options.dictCompact);
+      }
+      // 書き込み先の辞書にしか無い古いエントリで、優先度の高い辞書から読み込んだエントリを置き換えない。
+      Object.assign(
+        dictionary,
+        own === dictionary
+          ? merged
+          : Object.fromEntries(Object.entries(merged).filter(([lawId]) => own[lawId] || !dictionary[lawId])),
+      );
+    },
+    '他の実行の終了後に --import-dictionary で取り込んでください',
+  );
+  if (options.verbose) {
+    process.stderr.write(
+      `辞書保存: ${Object.keys(dictionary).length}件 ${Date.now() - started}ms (${options.dictionaryPath})\n`,
+    );
+  }
+}
+
+/**
+ * 辞書を保存する形式を決める。`--dict-format` の指定が無ければ、拡張子が `.msgpack` ならMessagePack、それ以外はJSONにする。
+ */
+export function resolveDictFormat(filePath: string, format?: DictFormat): DictFormat {
+  return format ?? (path.extname(filePath).toLowerCase() === '.msgpack' ? 'messagepack' : 'json');
+}
+
+/**
+ * 辞書ファイルの中身を読む。先頭のバイトでMessagePackかJSONかを判別するため、保存形式の指定や拡張子に関わらず読める。
+ */
+export function decodeDictionaryContent(content: Buffer): unknown {
+  return startsWithMessagePackMap(content) ? decodeMessagePack(content) : JSON.parse(content.toString('utf8'));
+}
+
+/**
+ * 上書きする前のファイルを `<ファイル名>.bak` へ写しておく。保存した内容が壊れていても、直前の状態へ手で戻せるようにする。
+ * 元のファイルが無ければ何もしない。
+ */
+async function backupFile(filePath: string): Promise<void> {
+  try {
+    await fs.copyFile(filePath, `${filePath}.bak`);
+  } catch (error) {
+    if ((error as NodeJS.ErrnoException).code !== 'ENOENT') {
+      throw error;
+    }
+  }
+}
+
+/**
+ * 辞書ファイルの内容から辞書を取り出す。`schema_version` を持たない旧形式（law_id をキーとするオブジェクトだけ）は
+ * そのまま辞書として扱い、次の保存で現在の形式に書き直す。このツールより新しい形式のファイルは読み違えないよう拒否する。
+ */
+export function parseDictionaryFile(parsed: unknown, filePath: string): Record<string, unknown> {
+  if (typeof parsed !== 'object' || parsed === null || Array.isArray(parsed)) {
+    throw new Error(`辞書JSONは law_id をキーとするオブジェクトである必要があります: ${filePath}`);
+  }
+  const file = parsed as Partial<Record<keyof LawDictionaryFile, unknown>>;
+  if (typeof file.schema_version !== 'number') {
+    return parsed as Record<string, unknown>;
+  }
+  if (file.schema_version > DICTIONARY_SCHEMA_VERSION) {
+    throw new Error(
+      `辞書の形式（schema_version ${file.schema_version}）にこのバージョンは対応していません` +
+        `（対応: ${DICTIONARY_SCHEMA_VERSION}まで）: ${filePath}`,
+    );
+  }
+  if (file.laws === undefined) {
+    return {};
+  }
+  if (typeof file.laws !== 'object' || file.laws === null || Array.isArray(file.laws)) {
+    throw new Error(`辞書JSONの laws は law_id をキーとするオブジェクトである必要があります: ${filePath}`);
+  }
+  return file.laws as Record<string, unknown>;
+}
+
+/**
+ * 辞書を読み込む。ファイルが無ければ空辞書を返す。旧形式の辞書もそのまま読める。
+ * 整形済み・compactのどちらのJSONでも、MessagePackでも読み込める。
+ */
+export async function loadDictionary(
+  filePath: string,
+  options?: Pick<CliOptions, 'verbose'>,
+): Promise<LawDictionary> {
+  try {
+    const started = Date.now();
+    const content = decodeDictionaryContent(await fs.readFile(filePath));
+    const dictionary = parseDictionaryFile(content, filePath) as LawDictionary;
+    if (options?.verbose) {
+      process.stderr.write(`辞書読込: ${Object.keys(dictionary).length}件 ${Date.now() - started}ms (${filePath})\n`);
+    }
+    return dictionary;
   } catch (error) {
     const maybeNodeError = error as NodeJS.ErrnoException;
     if (maybeNodeError.code === 'ENOENT') {
@@ -46,7 +485,190 @@ export async function loadDictionary(filePath: string): Promise<LawDictionary> {
   }
 }
 
-async function loadExistingUnresolved(filePath: string): Promise<UnresolvedRefRecord[]> {
+function lawDataArchivePath(archiveDir: string, lawId: string): string {
+  return path.join(archiveDir, `${lawId}.json`);
+}
+
+/**
+ * `law_data` の生レスポンスを `<archiveDir>/<law_id>.json` へ保存する。変換ロジックを直したときに
+ * APIを呼び直さずに済むよう、レスポンスは加工せず、取得日時とリビジョンIDを添える。
+ */
+export async function saveLawDataArchive(
+  archiveDir: string,
+  lawId: string,
+  fullTextFormat: LawFullTextFormat,
+  response: LawDataResponse,
+): Promise<LawDataArchive> {
+  const revisionId = response.revision_info?.law_revision_id;
+  const archive: LawDataArchive = {
+    law_id: lawId,
+    fetched_at: new Date().toISOString(),
+    revision_id: typeof revisionId === 'string' ? revisionId : undefined,
+    full_text_format: fullTextFormat,
+    response,
+  };
+  await writeJson(lawDataArchivePath(archiveDir, lawId), archive);
+  return archive;
+}
+
+/**
+ * アーカイブ済みの `law_data` を読み込む。アーカイブに無い・読めない法令は undefined を返す。
+ */
+export async function loadLawDataArchive(archiveDir: string, lawId: string): Promise<LawDataArchive | undefined> {
+  try {
+    const archive = JSON.parse(await fs.readFile(lawDataArchivePath(archiveDir, lawId), 'utf8')) as LawDataArchive;
+    return archive.law_id === lawId && archive.response ? archive : undefined;
+  } catch (error) {
+    if ((error as NodeJS.ErrnoException).code === 'ENOENT' || error instanceof SyntaxError) {
+      return undefined;
+    }
+    throw error;
+  }
+}
+
+function lawPageArchivePath(archiveDir: string, lawId: string): string {
+  return path.join(archiveDir, `${lawId}.page.json`);
+}
+
+/**
+ * 法令ページから抽出した本文を `<archiveDir>/<law_id>.page.json` へ保存する。ノートの本文は `law_data` ではなく
+ * 法令ページから作るため、`--from-archive` でページを開かずに同じノートを作り直せるよう、抽出結果をそのまま残す。
+ */
+export async function saveLawPageArchive(archiveDir: string, doc: ScrapedLawDocument): Promise<LawPageArchive> {
+  const archive: LawPageArchive = { law_id: doc.lawId, fetched_at: new Date().toISOString(), document: doc };
+  await writeJson(lawPageArchivePath(archiveDir, doc.lawId), archive);
+  return archive;
+}
+
+/**
+ * アーカイブ済みの法令ページの本文を読み込む。アーカイブに無い・読めない法令は undefined を返す。
+ */
+export async function loadLawPageArchive(archiveDir: string, lawId: string): Promise<LawPageArchive | undefined> {
+  try {
+    const archive = JSON.parse(await fs.readFile(lawPageArchivePath(archiveDir, lawId), 'utf8')) as LawPageArchive;
+    return archive.law_id === lawId && archive.document ? archive : undefined;
+  } catch (error) {
+    if ((error as NodeJS.ErrnoException).code === 'ENOENT' || error instanceof SyntaxError) {
+      return undefined;
+    }
+    throw error;
+  }
+}
+
+function dictionaryMatchKeys(lawId: string, entry: LawDictionaryEntry): string[] {
+  // 仮登録の `law_<law_id>` は法令名ではないため、照合にも使わない。
+  return isFallbackDictionaryEntry(lawId, entry)
+    ? []
+    : [...new Set(dictionaryEntryNames(entry).map(canonicalizeKanji))];
+}
+
+/**
+ * 辞書の照合キー（正規化した法令名・略称）が別の law_id のエントリと重なっているものを返す。
+ * 重なったキーでは法令名から法令を一意に引けないため、正規化の穴や誤ったエントリを見つける手がかりにする。
+ * `lawIds` を指定すると、それらの法令を含む重なりだけを返す。
+ */
+export function findDictionaryKeyConflicts(
+  dictionary: LawDictionary,
+  source: DictEntrySource,
+  lawIds?: string[],
+): DictKeyConflictRecord[] {
+  const owners = new Map<string, string[]>();
+  for (const [lawId, entry] of Object.entries(dictionary)) {
+    for (const key of dictionaryMatchKeys(lawId, entry)) {
+      const ids = owners.get(key);
+      if (ids) {
+        ids.push(lawId);
+      } else {
+        owners.set(key, [lawId]);
+      }
+    }
+  }
+  const targets = lawIds ? new Set(lawIds) : undefined;
+  const timestamp = new Date().toISOString();
+  return [...owners]
+    .filter(([, ids]) => ids.length > 1 && (!targets || ids.some((lawId) => targets.has(lawId))))
+    .map(([key, ids]) => ({
+      timestamp,
+      key,
+      law_ids: ids,
+      titles: ids.map((lawId) => dictionary[lawId].title),
+      source,
+    }));
+}
+
+/**
+ * 辞書の照合キーの重なりを警告し、`--dict-conflict-log` があれば1行1件のJSONで追記する。
+ * 全件走査では重なりが多くなり得るため、警告は先頭の数件と件数にとどめ、全件はログで確認させる。
+ */
+export async function reportDictionaryKeyConflicts(
+  options: Pick<CliOptions, 'dictConflictLogPath'>,
+  conflicts: DictKeyConflictRecord[],
+): Promise<void> {
+  if (conflicts.length === 0) {
+    return;
+  }
+  for (const conflict of conflicts.slice(0, MAX_CONFLICT_WARNINGS)) {
+    const laws = conflict.law_ids.map((lawId, index) => `${conflict.titles[index]} (${lawId})`).join(' / ');
+    process.stderr.write(`警告: 辞書の照合キーが複数の法令で重なっています: 「${conflict.key}」 ${laws}\n`);
+  }
+  if (conflicts.length > MAX_CONFLICT_WARNINGS) {
+    process.stderr.write(`警告: ほかに照合キーの重なりが ${conflicts.length - MAX_CONFLICT_WARNINGS}件あります\n`);
+  }
+  if (options.dictConflictLogPath) {
+    await fs.mkdir(path.dirname(options.dictConflictLogPath), { recursive: true });
+    await fs.appendFile(
+      options.dictConflictLogPath,
+      conflicts.map((conflict) => `${JSON.stringify(conflict)}\n`).join(''),
+      'utf8',
+    );
+  }
+}
+
+/**
+ * 複数の辞書ファイルを、先に指定したものを優先してマージして読み込む。1件だけなら `loadDictionary` と同じ。
+ * 同じ law_id は先の辞書のエントリを使う。後の辞書で法令名・略称が先の辞書の別の法令と重なるエントリは、
+ * 法令名で引いたときに当たる法令が読み込み順で変わらないよう、警告して読み込まない（先勝ち）。
+ */
+export async function loadDictionaries(
+  filePaths: string[],
+  options?: Pick<CliOptions, 'verbose'>,
+): Promise<LawDictionary> {
+  const merged: LawDictionary = {};
+  const aliasOwners = new Map<string, string>();
+  for (const filePath of filePaths) {
+    const loaded: Array<[string, string[]]> = [];
+    for (const [lawId, entry] of Object.entries(await loadDictionary(filePath, options))) {
+      if (merged[lawId]) {
+        continue;
+      }
+      const aliases = dictionaryMatchKeys(lawId, entry);
+      const owner = aliases.map((alias) => aliasOwners.get(alias)).find((other) => other && other !== lawId);
+      if (owner) {
+        process.stderr.write(
+          `警告: 辞書間で法令名が別の法令を指しています。先の辞書を優先します: ` +
+            `${entry.title} (${lawId}, ${filePath}) / ${merged[owner].title} (${owner})\n`,
+        );
+        continue;
+      }
+      merged[lawId] = entry;
+      loaded.push([lawId, aliases]);
+    }
+    // 同じ辞書の中で法令名が重なるのは従来どおり許し、辞書をまたぐ重なりだけを見る。
+    for (const [lawId, aliases] of loaded) {
+      for (const alias of aliases) {
+        if (!aliasOwners.has(alias)) {
+          aliasOwners.set(alias, lawId);
+        }
+      }
+    }
+  }
+  return merged;
+}
+
+/**
+ * 未解決参照の記録を読み込む。ファイルが無ければ空配列を返す。
+ */
+export async function loadExistingUnresolved(filePath: string): Promise<UnresolvedRefRecord[]> {
   try {
     const content = await fs.readFile(filePath, 'utf8');
     const parsed = JSON.parse(content) as unknown;
@@ -71,8 +693,209 @@ async function loadExistingUnresolved(filePath: string): Promise<UnresolvedRefRe
   }
 }
 
-export async function appendUnresolved(filePath: string, items: UnresolvedRefRecord[]): Promise<void> {
-  const existing = await loadExistingUnresolved(filePath);
-  const merged = mergeUnresolvedRecords(existing, items);
-  await writeJson(filePath, merged);
+/**
+ * 未解決参照を追記する。ロック中に最新の記録を読み直し、並行実行した他のプロセスの追記を残す。
+ */
+export async function appendUnresolved(
+  filePath: string,
+  items: UnresolvedRefRecord[],
+  lockTimeoutMs: number,
+): Promise<void> {
+  await updateUnresolved(filePath, lockTimeoutMs, items, (existing) => mergeUnresolvedRecords(existing, items));
+}
+
+/**
+ * `--flush-each` で未解決参照を法令ごとに追記するログのパス。未解決参照のJSONと同じディレクトリに置く。
+ * 同じ出力先へ並行実行した他のプロセスのログを取り込んだり消したりしないよう、プロセスごとに別のファイルにする。
+ */
+export function unresolvedLogPath(unresolvedPath: string, pid = process.pid): string {
+  return `${unresolvedPath.replace(/\.json$/, '')}.pending.${pid}.jsonl`;
+}
+
+/**
+ * 終了したプロセスが残した未解決参照のログを返す。実行中のプロセス（自分を含む）のログは、そのプロセスが保存して消すため含めない。
+ * プロセスごとに分ける前の形式のログ（`.pending.jsonl`）は、書いたプロセスが分からないため終了したものとみなす。
+ */
+async function listAbandonedUnresolvedLogs(unresolvedPath: string): Promise<string[]> {
+  const prefix = `${path.basename(unresolvedPath).replace(/\.json$/, '')}.pending.`;
+  const dir = path.dirname(unresolvedPath);
+  let names: string[];
+  try {
+    names = await fs.readdir(dir);
+  } catch (error) {
+    if ((error as NodeJS.ErrnoException).code === 'ENOENT') {
+      return [];
+    }
+    throw error;
+  }
+  return names
+    .filter((name) => {
+      if (!name.startsWith(prefix)) {
+        return false;
+      }
+      const rest = name.slice(prefix.length);
+      const pid = rest.match(/^(\d+)\.jsonl$/)?.[1];
+      return rest === 'jsonl' || (pid !== undefined && !isProcessAlive(Number(pid)));
+    })
+    .sort()
+    .map((name) => path.join(dir, name));
+}
+
+/**
+ * 未解決参照を追記専用ログ（JSON Lines）に書き足す。JSON全体を読み書きし直さないため、法令ごとに呼んでも軽い。
+ */
+export async function appendUnresolvedLog(unresolvedPath: string, items: UnresolvedRefRecord[]): Promise<void> {
+  if (items.length === 0) {
+    return;
+  }
+  const logPath = unresolvedLogPath(unresolvedPath);
+  await fs.mkdir(path.dirname(logPath), { recursive: true });
+  await fs.appendFile(logPath, items.map((item) => `${JSON.stringify(item)}\n`).join(''), 'utf8');
+}
+
+/**
+ * 途中で終わった実行が残した未解決参照のログを、未解決参照のJSONへマージして消す。マージした件数を返す。
+ * 実行中の他のプロセスのログには触れない。書き込み途中で終わった最終行は読めないため捨てる。
+ */
+export async function recoverUnresolvedLog(unresolvedPath: string, lockTimeoutMs: number): Promise<number> {
+  let recovered = 0;
+  for (const logPath of await listAbandonedUnresolvedLogs(unresolvedPath)) {
+    let content: string;
+    try {
+      content = await fs.readFile(logPath, 'utf8');
+    } catch (error) {
+      // 同時に起動した他のプロセスが先に取り込んで消した。
+      if ((error as NodeJS.ErrnoException).code === 'ENOENT') {
+        continue;
+      }
+      throw error;
+    }
+    const records: UnresolvedRefRecord[] = [];
+    for (const line of content.split('\n')) {
+      try {
+        if (line.trim()) {
+          records.push(JSON.parse(line) as UnresolvedRefRecord);
+        }
+      } catch {
+        // 追記途中で中断された行。
+      }
+    }
+    // 同じログを2つのプロセスが取り込んでも、マージで重複は除かれる。
+    await appendUnresolved(unresolvedPath, records, lockTimeoutMs);
+    await fs.rm(logPath, { force: true });
+    recovered += records.length;
+  }
+  return recovered;
+}
+
+/**
+ * このプロセスの未解決参照のログを消す。ログの内容を未解決参照のJSONへ保存し終えてから呼ぶ。
+ */
+export async function clearUnresolvedLog(unresolvedPath: string): Promise<void> {
+  await fs.rm(unresolvedLogPath(unresolvedPath), { force: true });
+}
+
+/**
+ * ロック中に最新の未解決参照を読み直し、`update` を適用して書き戻す。
+ * ロックを取得できなければ `changes`（このプロセスの変更分）を退避する。
+ */
+export async function updateUnresolved(
+  filePath: string,
+  lockTimeoutMs: number,
+  changes: UnresolvedRefRecord[],
+  update: (existing: UnresolvedRefRecord[]) => UnresolvedRefRecord[],
+): Promise<void> {
+  await saveWithLock(
+    filePath,
+    lockTimeoutMs,
+    changes,
+    async () => {
+      await writeJson(filePath, update(await loadExistingUnresolved(filePath)));
+    },
+    '他の実行の終了後に元のファイルへマージしてください',
+  );
+}
+
+/**
+ * 取得失敗した法令の記録を読み込む。ファイルが無ければ空配列を返す。
+ */
+export async function loadFailedLaws(filePath: string): Promise<FailedLawRecord[]> {
+  try {
+    const content = await fs.readFile(filePath, 'utf8');
+    const parsed = JSON.parse(content) as unknown;
+    if (!Array.isArray(parsed)) {
+      return [];
+    }
+    return parsed.filter((item): item is FailedLawRecord => {
+      const value = item as Partial<FailedLawRecord>;
+      return typeof value.law_id === 'string' && typeof value.depth === 'number';
+    });
+  } catch (error) {
+    const maybeNodeError = error as NodeJS.ErrnoException;
+    if (maybeNodeError.code === 'ENOENT') {
+      return [];
+    }
+    throw error;
+  }
+}
+
+/**
+ * 取得失敗した法令を追記する。
+ * 同じ法令が複数回失敗した場合は、再試行時の深さ判定に使うため最も浅い記録を残す。
+ */
+export async function appendFailedLaws(filePath: string, items: FailedLawRecord[]): Promise<void> {
+  if (items.length === 0) {
+    return;
+  }
+  const merged = new Map<string, FailedLawRecord>();
+  for (const item of [...(await loadFailedLaws(filePath)), ...items]) {
+    const current = merged.get(item.law_id);
+    if (!current || item.depth <= current.depth) {
+      merged.set(item.law_id, item);
+    }
+  }
+  await writeJson(filePath, [...merged.values()]);
+}
+
+/**
+ * 検索候補キャッシュを読み込む。
+ * キャッシュは再生成可能なため、壊れていても空として扱い処理を止めない。
+ */
+export async function loadSearchCache(filePath: string): Promise<SearchCache> {
+  try {
+    const content = await fs.readFile(filePath, 'utf8');
+    const parsed = JSON.parse(content) as unknown;
+    if (typeof parsed !== 'object' || parsed === null || Array.isArray(parsed)) {
+      return {};
+    }
+    return parsed as SearchCache;
+  } catch {
+    return {};
+  }
+}
+
+/**
+ * 候補の選択履歴を読み込む。ファイルが無い・壊れている場合は空として扱い、法令名検索からやり直させる。
+ */
+export async function loadCandidateChoices(filePath: string): Promise<CandidateChoices> {
+  try {
+    const parsed = JSON.parse(await fs.readFile(filePath, 'utf8')) as unknown;
+    if (typeof parsed !== 'object' || parsed === null || Array.isArray(parsed)) {
+      return {};
+    }
+    return parsed as CandidateChoices;
+  } catch {
+    return {};
+  }
+}
+
+/**
+ * キャッシュエントリがTTL内かを判定する。
+ */
+export function isSearchCacheFresh(entry: SearchCacheEntry, ttlHours: number, now: Date = new Date()): boolean {
+  const cachedAt = Date.parse(entry.cached_at);
+  if (Number.isNaN(cachedAt)) {
+    return false;
+  }
+  return now.getTime() - cachedAt <= ttlHours * 60 * 60 * 1000;
 }
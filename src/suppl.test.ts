@@ -0,0 +1,100 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { buildSupplProvisionIndex, defaultCliOptions, lawIdFromLawNum, renderMarkdown } from './index.js';
+
+const block = (id: string, paragraphIds: string[] = [], text = '') => ({
+  id,
+  heading: id,
+  paragraphs: paragraphIds.map((anchor) => ({ anchor, segments: text ? [{ type: 'text' as const, text }] : [] })),
+});
+
+const renderWith = (blocks: ReturnType<typeof block>[], linkTextRefs = true) => {
+  const doc = {
+    lawId: '411AC0000000087',
+    title: '民事再生法',
+    lawNum: '平成十一年法律第二百二十五号',
+    sourceUrl: 'https://laws.e-gov.go.jp/law/411AC0000000087',
+    blocks,
+  };
+  const context = {
+    rootLawId: doc.lawId,
+    rootLawTitle: doc.title,
+    unresolved: [],
+    unresolvedSeen: new Set<string>(),
+    failures: [],
+    references: [],
+  };
+  const rendered = renderMarkdown(doc, {}, { ...defaultCliOptions(), linkTextRefs }, context, 0);
+  return { markdown: rendered.markdown, unresolved: context.unresolved };
+};
+
+test('lawIdFromLawNum: 法律・政令の法令番号から law_id を作る', () => {
+  assert.equal(lawIdFromLawNum('平成十一年法律第百六十号'), '411AC0000000160');
+  assert.equal(lawIdFromLawNum('昭和三十四年政令第三十二号'), '334CO0000000032');
+  assert.equal(lawIdFromLawNum('令和元年法律第二号'), '501AC0000000002');
+  assert.equal(lawIdFromLawNum('平成十二年厚生省令第百二十七号'), undefined);
+  assert.equal(lawIdFromLawNum('附則'), undefined);
+});
+
+test('buildSupplProvisionIndex: 制定附則と改正附則の接頭辞とアンカーを集める', () => {
+  const index = buildSupplProvisionIndex({
+    lawId: '411AC0000000087',
+    title: '民事再生法',
+    sourceUrl: '',
+    blocks: [
+      block('Mp-At_1', ['Mp-At_1-Pr_1']),
+      block('411AC0000000087-Sp', ['411AC0000000087-Sp-Pr_1']),
+      block('416AC0000000076-Sp-At_2', ['416AC0000000076-Sp-At_2-Pr_1']),
+    ],
+  });
+  assert.deepEqual([...index.prefixes], ['411AC0000000087', '416AC0000000076']);
+  assert.ok(index.anchors.has('416AC0000000076-Sp-At_2'));
+  assert.ok(!index.anchors.has('Mp-At_1'));
+  assert.equal(index.lawNumKey, undefined);
+});
+
+test('renderMarkdown: 附則が1つだけなら「附則第三項」を附則の項へリンクする', () => {
+  const { markdown, unresolved } = renderWith([
+    block('Mp-At_3', ['Mp-At_3-Pr_1'], '附則第三項の規定により、同法附則第三項の例による。'),
+    block('411AC0000000087-Sp', ['411AC0000000087-Sp-Pr_1', '411AC0000000087-Sp-Pr_3']),
+  ]);
+  assert.match(markdown, /\[\[#411AC0000000087-Sp-Pr_3\|附則第三項\]\]の規定により/);
+  // 「同法附則」は他の法令の附則なので、自法令の附則や本則の第三条へはリンクしない。
+  assert.match(markdown, /同法附則第三項の例による/);
+  assert.doesNotMatch(markdown, /\[\[#Mp-At_3/);
+  assert.deepEqual(unresolved, []);
+});
+
+test('renderMarkdown: 改正附則があると法令番号の無い附則参照は未解決参照にする', () => {
+  const { markdown, unresolved } = renderWith([
+    block(
+      'Mp-At_3',
+      ['Mp-At_3-Pr_1'],
+      '附則第二条及び平成十六年法律第七十六号附則第二条並びに平成十一年法律第二百二十五号附則第一条',
+    ),
+    block('411AC0000000087-Sp-At_1', ['411AC0000000087-Sp-At_1-Pr_1']),
+    block('416AC0000000076-Sp-At_2', ['416AC0000000076-Sp-At_2-Pr_1']),
+  ]);
+  assert.match(markdown, /\[\[#416AC0000000076-Sp-At_2\|平成十六年法律第七十六号附則第二条\]\]/);
+  // 自法令の法令番号で特定された参照は制定附則へリンクする。
+  assert.match(markdown, /\[\[#411AC0000000087-Sp-At_1\|平成十一年法律第二百二十五号附則第一条\]\]/);
+  assert.match(markdown, /^附則第二条及び/m);
+  assert.deepEqual(
+    unresolved.map((record) => [record.raw_text, record.href, record.reason]),
+    [['附則第二条', '', 'ambiguous_suppl_provision']],
+  );
+});
+
+test('renderMarkdown: --link-text-refs を指定しなければ附則の参照文言をリンクにしない', () => {
+  const { markdown, unresolved } = renderWith(
+    [
+      block('Mp-At_3', ['Mp-At_3-Pr_1'], '附則第三項の規定による。'),
+      block('411AC0000000087-Sp', ['411AC0000000087-Sp-Pr_1', '411AC0000000087-Sp-Pr_3']),
+    ],
+    false,
+  );
+  assert.match(markdown, /^附則第三項の規定による。/m);
+  assert.doesNotMatch(markdown, /\[\[#411AC0000000087-Sp-Pr_3/);
+  assert.deepEqual(unresolved, []);
+});
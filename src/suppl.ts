@@ -0,0 +1,112 @@
+import { normalizeLawNum, parseJapaneseNumber } from './lawnum.js';
+import type { ScrapedLawDocument } from './types.js';
+
+const NUMBER_CHARS = '0-9０-９〇一二三四五六七八九十百千';
+// 改正附則は「平成十一年法律第百六十号附則第二条」のように法令番号で特定されるため、直前の法令番号も一緒に読む。
+const SUPPL_REF_PATTERN = new RegExp(
+  `((?:明治|大正|昭和|平成|令和)(?:元|[${NUMBER_CHARS}]+)年[^${NUMBER_CHARS}、。（）\\s]{1,4}第[${NUMBER_CHARS}]+号)?` +
+    `附則第([${NUMBER_CHARS}]+)(項|条)`,
+  'g',
+);
+const SUPPL_BLOCK_PATTERN = /^(.+)-Sp(?:-|$)/;
+// 「同法附則第二条」「民法附則第三項」のように法令名に続く附則は他の法令の附則なので、自法令の附則へはリンクしない。
+const LAW_NAME_SUFFIX_PATTERN = /[法令則律約]$/;
+const SELF_REFERENCE_PATTERN = /この(?:法律|政令|勅令|府令|省令|規則|命令)$/;
+const ERA_CODES: Record<string, number> = { 明治: 1, 大正: 2, 昭和: 3, 平成: 4, 令和: 5 };
+/** law_id の種別コード。府省令などは発令機関ごとにコードが異なり法令番号から作れないため対象外にする。 */
+const LAW_TYPE_CODES: Record<string, string> = { 法律: 'AC', 政令: 'CO', 勅令: 'IO' };
+
+/**
+ * 本文中で附則を指す参照の解決に使う、附則の一覧とアンカーの集合。
+ */
+export interface SupplProvisionIndex {
+  lawId: string;
+  lawNumKey?: string;
+  /** 附則ブロックIDの接頭辞（制定附則は法令自身の law_id、改正附則は改正法令の law_id）。 */
+  prefixes: Set<string>;
+  anchors: Set<string>;
+}
+
+/**
+ * 本文テキストを附則への参照とそれ以外に分けた断片。`anchor` が無い参照はリンク先を特定できなかったもの。
+ */
+export type SupplRefPart = { type: 'text'; text: string } | { type: 'suppl_ref'; text: string; anchor?: string };
+
+/**
+ * 取得した本文から附則の一覧とリンク先になり得るアンカーを集める。
+ */
+export function buildSupplProvisionIndex(doc: ScrapedLawDocument): SupplProvisionIndex {
+  const prefixes = new Set<string>();
+  const anchors = new Set<string>();
+  for (const block of doc.blocks) {
+    const prefix = block.id.match(SUPPL_BLOCK_PATTERN)?.[1];
+    if (!prefix) {
+      continue;
+    }
+    prefixes.add(prefix);
+    anchors.add(block.id);
+    for (const paragraph of block.paragraphs) {
+      anchors.add(paragraph.anchor);
+    }
+  }
+  return { lawId: doc.lawId, lawNumKey: doc.lawNum ? normalizeLawNum(doc.lawNum)?.key : undefined, prefixes, anchors };
+}
+
+/**
+ * 法令番号（法律・政令・勅令）から law_id を組み立てる。例: 「平成十一年法律第百六十号」→ `411AC0000000160`。
+ */
+export function lawIdFromLawNum(text: string): string | undefined {
+  const lawNum = normalizeLawNum(text);
+  const typeCode = lawNum ? LAW_TYPE_CODES[lawNum.lawType] : undefined;
+  if (!lawNum || !typeCode) {
+    return undefined;
+  }
+  const year = String(lawNum.year).padStart(2, '0');
+  return `${ERA_CODES[lawNum.era]}${year}${typeCode}${String(lawNum.number).padStart(10, '0')}`;
+}
+
+function resolveSupplPrefix(index: SupplProvisionIndex, lawNumText: string | undefined): string | undefined {
+  if (!lawNumText) {
+    // 法令番号の無い「附則第三項」は、附則が1つしか無いときだけ特定できる。改正附則があると、どの附則か分からない。
+    return index.prefixes.size === 1 ? [...index.prefixes][0] : undefined;
+  }
+  if (index.lawNumKey && normalizeLawNum(lawNumText)?.key === index.lawNumKey) {
+    return index.lawId;
+  }
+  const lawId = lawIdFromLawNum(lawNumText);
+  return lawId && index.prefixes.has(lawId) ? lawId : undefined;
+}
+
+/**
+ * e-Govがリンクしていない本文中の「附則第三項」「平成十一年法律第百六十号附則第二条」を探し、附則の項・条のアンカーを対応付ける。
+ * 附則は条ではなく項で構成されることが多く、本則の条番号の規則では引けないため、附則のブロックIDから直接作る。
+ * `precedingText` には直前の断片（リンク文言など）を渡し、他の法令名に続く附則を見分けるのに使う。
+ */
+export function splitSupplProvisionRefs(
+  text: string,
+  index: SupplProvisionIndex,
+  precedingText = '',
+): SupplRefPart[] {
+  const parts: SupplRefPart[] = [];
+  let position = 0;
+  for (const matched of text.matchAll(SUPPL_REF_PATTERN)) {
+    const [whole, lawNumText, numberText, unit] = matched;
+    const start = matched.index ?? 0;
+    const before = `${precedingText}${text.slice(0, start)}`;
+    if (!lawNumText && LAW_NAME_SUFFIX_PATTERN.test(before) && !SELF_REFERENCE_PATTERN.test(before)) {
+      continue;
+    }
+    if (start > position) {
+      parts.push({ type: 'text', text: text.slice(position, start) });
+    }
+    const prefix = resolveSupplPrefix(index, lawNumText);
+    const number = parseJapaneseNumber(numberText);
+    const anchor = prefix && number ? `${prefix}-Sp-${unit === '項' ? 'Pr' : 'At'}_${number}` : undefined;
+    parts.push({ type: 'suppl_ref', text: whole, anchor: anchor && index.anchors.has(anchor) ? anchor : undefined });
+    position = start + whole.length;
+  }
+  if (position < text.length) {
+    parts.push({ type: 'text', text: text.slice(position) });
+  }
+  return parts;
+}
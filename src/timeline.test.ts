@@ -0,0 +1,47 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { defaultCliOptions, promulgationYear, renderTimelineNote } from './index.js';
+
+test('promulgationYear: 公布日を優先し、無ければ law_id から推定する', () => {
+  assert.equal(promulgationYear('334AC0000000121', '1960-01-01'), 1960);
+  assert.equal(promulgationYear('334AC0000000121'), 1959);
+  assert.equal(promulgationYear('xyz'), undefined);
+});
+
+test('renderTimelineNote: 公布年の見出しごとに公布日順でリンクを並べ、公布年不明は末尾に置く', () => {
+  const markdown = renderTimelineNote(
+    [
+      { lawId: '417AC0000000086', title: '会社法', path: '会社法_417AC0000000086.md' },
+      {
+        lawId: '334AC0000000122',
+        title: '実用新案法',
+        path: '実用新案法_334AC0000000122.md',
+        promulgationDate: '1959-04-13',
+      },
+      { lawId: '334AC0000000121', title: '特許法', path: '特許法_334AC0000000121.md', promulgationDate: '1959-04-13' },
+      { lawId: 'unknown', title: '不明な法令', path: 'unknown.md' },
+    ],
+    defaultCliOptions(),
+  );
+  assert.equal(
+    markdown,
+    [
+      '# 法令タイムライン',
+      '',
+      '## 1959年',
+      '',
+      '- [[laws/特許法_334AC0000000121.md|特許法]]（1959-04-13）',
+      '- [[laws/実用新案法_334AC0000000122.md|実用新案法]]（1959-04-13）',
+      '',
+      '## 2005年',
+      '',
+      '- [[laws/会社法_417AC0000000086.md|会社法]]',
+      '',
+      '## 公布年不明',
+      '',
+      '- [[laws/unknown.md|不明な法令]]',
+      '',
+    ].join('\n'),
+  );
+});
@@ -0,0 +1,87 @@
+import path from 'node:path';
+import process from 'node:process';
+import { splitFrontmatter } from './frontmatter.js';
+import { extractYearFromLawId } from './lawnum.js';
+import { listNotes } from './links.js';
+import { displayTitle } from './notes.js';
+import { heading, noteLink } from './render.js';
+import { encodeNoteText, readNoteText, writeFileAtomic } from './storage.js';
+import type { CliOptions, LawDictionary } from './types.js';
+
+/** 出力ディレクトリ直下に置くタイムラインノートのファイル名。法令ノートと並べても先頭に来るよう `_` で始める。 */
+export const TIMELINE_NOTE_NAME = '_timeline.md';
+
+/**
+ * タイムラインに載せる法令1件。`path` は出力ディレクトリからの相対パス。
+ */
+export interface TimelineEntry {
+  lawId: string;
+  title: string;
+  path: string;
+  promulgationDate?: string;
+}
+
+/**
+ * 法令の公布年を西暦で返す。公布日があればその年を使い、無ければ law_id から推定する。
+ */
+export function promulgationYear(lawId: string, promulgationDate?: string): number | undefined {
+  const year = promulgationDate?.match(/^(\d{4})-/)?.[1];
+  return year ? Number(year) : extractYearFromLawId(lawId);
+}
+
+/**
+ * 法令を公布年順に並べたタイムラインノートを生成する。年ごとの見出しの下に法令へのリンクを公布日順に並べ、
+ * 公布年の分からない法令は末尾にまとめる。
+ */
+export function renderTimelineNote(
+  entries: TimelineEntry[],
+  options: Pick<CliOptions, 'linkStyle' | 'outputFormat' | 'standardLinks' | 'headingBaseLevel'>,
+): string {
+  const byYear = new Map<number | undefined, TimelineEntry[]>();
+  for (const entry of entries) {
+    const year = promulgationYear(entry.lawId, entry.promulgationDate);
+    byYear.set(year, [...(byYear.get(year) ?? []), entry]);
+  }
+  const years = [...byYear.keys()].sort((a, b) => (a ?? Infinity) - (b ?? Infinity));
+  const lines = [heading(options.headingBaseLevel - 1, '法令タイムライン')];
+  for (const year of years) {
+    lines.push('', heading(options.headingBaseLevel, year === undefined ? '公布年不明' : `${year}年`), '');
+    const sorted = (byYear.get(year) ?? []).sort(
+      (a, b) => (a.promulgationDate ?? '').localeCompare(b.promulgationDate ?? '') || a.lawId.localeCompare(b.lawId),
+    );
+    for (const entry of sorted) {
+      const date = entry.promulgationDate ? `（${entry.promulgationDate}）` : '';
+      const label = displayTitle(entry.title);
+      lines.push(`- ${noteLink(options, TIMELINE_NOTE_NAME, entry.path, undefined, label)}${date}`);
+    }
+  }
+  return `${lines.join('\n')}\n`;
+}
+
+/**
+ * `--timeline` で、出力ディレクトリの法令ノートすべてからタイムラインノートを作り直す。
+ * 公布日は辞書から引く。スタブノートと `--split-by chapter` のセクションノートは取得した法令そのものではないため載せない。
+ */
+export async function writeTimelineNote(options: CliOptions, dictionary: LawDictionary): Promise<void> {
+  const entries: TimelineEntry[] = [];
+  const seen = new Set<string>();
+  for (const relativePath of (await listNotes(options.outputDir)).sort((a, b) => a.localeCompare(b))) {
+    const markdown = await readNoteText(path.join(options.outputDir, relativePath));
+    const { frontmatter = {} } = splitFrontmatter(markdown);
+    const lawId = frontmatter.law_id;
+    if (typeof lawId !== 'string' || seen.has(lawId) || frontmatter.stub === true) {
+      continue;
+    }
+    seen.add(lawId);
+    const title = frontmatter.title;
+    entries.push({
+      lawId,
+      title: typeof title === 'string' ? title : (dictionary[lawId]?.title ?? lawId),
+      path: relativePath,
+      promulgationDate: dictionary[lawId]?.promulgation_date,
+    });
+  }
+  const timelinePath = path.join(options.outputDir, TIMELINE_NOTE_NAME);
+  await writeFileAtomic(timelinePath, encodeNoteText(renderTimelineNote(entries, options), options));
+  process.stdout.write(`タイムラインを書き出しました: ${timelinePath} (${entries.length}件)\n`);
+}
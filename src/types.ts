@@ -4,41 +4,335 @@ export interface CliOptions {
   buildDictionary: boolean;
   maxDepth: number;
   ifExists: 'overwrite' | 'skip';
+  followExisting: boolean;
+  exportFormat?: ExportFormat;
+  exportOut?: string;
+  exportLinks: ExportLinkMode;
+  archiveDir?: string;
+  fromArchive: boolean;
+  dumpResponsePath?: string;
   retry: number;
   timeoutMs: number;
+  connectTimeoutMs: number;
   dictionaryPath: string;
+  /** 読み込む辞書（先のものほど優先）。`dictionaryPath` は新規登録の書き込み先。 */
+  dictionaryPaths: string[];
   dictionaryAutoupdate: boolean;
   unresolvedPath: string;
   outputDir: string;
   apiBaseUrl: string;
+  webBaseUrl?: string;
+  requestIntervalMs: number;
+  fullTextFormat: LawFullTextFormat;
+  importDictionaryPath?: string;
+  onConflict: DictionaryConflictPolicy;
+  dryRun: boolean;
+  backlinksSection: boolean;
+  useSearchCache: boolean;
+  searchCachePath: string;
+  searchCacheTtlHours: number;
+  /** 辞書の照合キーの重なりを追記するログ（JSON Lines）。 */
+  dictConflictLogPath?: string;
+  /** `--build-dictionary` で一覧APIを1回に取得する件数（`limit`）。 */
+  dictPageSize: number;
+  /** 候補の選択履歴の保存先。指定しなければ選択履歴は同じ実行の中だけで使う。 */
+  choicesPath?: string;
+  linkStyle: LinkStyle;
+  selfLinks: SelfLinkStyle;
+  headingBaseLevel: number;
+  filenameScheme: FilenameScheme;
+  apiVersion: string;
+  organizeByType: boolean;
+  dictCompact: boolean;
+  /** 辞書の保存形式。未指定なら辞書ファイルの拡張子（`.msgpack`）で判別する。読み込みは中身から判別する。 */
+  dictFormat?: DictFormat;
+  verbose: boolean;
+  stats: boolean;
+  /** 条・項・号・別表の数を、`law_data` を法令ごとに追加で取得して数える。既定はページ本文からの概算。 */
+  apiStats: boolean;
+  summaryCallout: boolean;
+  failedPath: string;
+  /** 法令を1件書き込むたびに、未解決参照をログへ追記し、辞書を `flushDictionaryEvery` 件ごとに保存する。 */
+  flushEach: boolean;
+  flushDictionaryEvery: number;
+  /** 実行結果のサマリ（件数・失敗法令・終了コード）をJSONで書き出す先。 */
+  summaryJsonPath?: string;
+  /** 取得した法令のメタ情報と参照関係を書き足すSQLiteデータベースのパス。 */
+  indexDbPath?: string;
+  retryFailed: boolean;
+  paragraphBlockIds: boolean;
+  diff: boolean;
+  fetchAllCandidates: boolean;
+  /** 法令名検索の候補から、廃止・失効した法令を除く。 */
+  excludeRepealed: boolean;
+  titleFilter?: string;
+  maxLaws?: number;
+  nonInteractive: boolean;
+  articles?: string;
+  chapters?: string;
+  resolveRef?: string;
+  /** 未解決参照の pending の記録を、現在の辞書と選択履歴で解決し直す。 */
+  resolveUnresolved: boolean;
+  /** `--resolve-unresolved` で解決できた法令のうち、ノートの無いものを取得する。 */
+  fetchResolved: boolean;
+  refSource?: string;
+  refStatus: Exclude<UnresolvedRefStatus, 'pending'>;
+  refLawId?: string;
+  refTitle?: string;
+  titlesFromStdin: boolean;
+  repl: boolean;
+  splitBy: SplitMode;
+  lockTimeoutMs: number;
+  refPolicies: Record<string, RefPolicy>;
+  refScope: RefScope;
+  linkifyRules: LinkifyKind[];
+  /** `a[href]` を持たない本文中の参照文言（附則の参照など）を、ノート内のアンカーを特定できる場合に限りリンクにする。 */
+  linkTextRefs: boolean;
+  /** 同じ法令への参照が読点・接続詞だけで続くとき、2つ目以降のリンクの表示から法令名を除く。 */
+  shortRefLabels: boolean;
+  retryFailedPasses: number;
+  dumpTags: boolean;
+  anchorStyle: AnchorStyle;
+  itemStyle: ItemStyle;
+  verifyDictionary: boolean;
+  fix: boolean;
+  yes: boolean;
+  traversal: Traversal;
+  outputFormat: OutputFormat;
+  noteFormat: NoteFormat;
+  /** 書き出すノートの改行コード。内部ではLFで扱い、書き込む直前に変換する。 */
+  lineEnding: LineEnding;
+  /** 本文が参照した法令とそのリビジョンIDを書き残す場所。 */
+  citationStyle: CitationStyle;
+  /** 書き出すノートの先頭にUTF-8のBOMを付ける。 */
+  bom: boolean;
+  /** 条文中の表（`TableStruct`）をMarkdownの表に整形する。 */
+  inlineTables: boolean;
+  standardLinks: boolean;
+  missingLinksPath: string;
+  stubMissing: boolean;
+  /** 全取得後に、行き先のノートが無いリンクを文言だけに戻す。 */
+  linkOnlyExisting: boolean;
+  /** 出力ディレクトリの法令を公布年順に並べたタイムラインノートを作る。 */
+  timeline: boolean;
+  /** 法令ノートを書き直す前に既存ノートと条単位で比べ、変わった条を変更履歴ノートに記録する。 */
+  changelog: boolean;
+  /** 実行中に書き出した法令の一覧ノート（MOC）のファイル名。未指定なら作らない。 */
+  mocName?: string;
+  slowestLaws: number;
+  followSuccession: boolean;
+  minConfidence: number;
+  /** 対話なしで法令名を確定するとき、同名の候補が複数ある場合の扱い。 */
+  candidatePolicy: CandidatePolicy;
+  /** 法令名検索が0件のとき、短くした法令名で検索し直す回数の上限。0なら検索し直さない。 */
+  titleFallbackSteps: number;
+  /** 完全一致の候補が無いとき、部分一致が1件だけならこの割合（検索語の長さ / 法令名の長さ）以上で確定する。 */
+  partialMatchRatio?: number;
 }
 
+export type FilenameScheme = 'title' | 'law-id' | 'law-num';
+
+/** `--dict-format` で選べる辞書ファイルの保存形式。JSON / MessagePack（バイナリ）。 */
+export type DictFormat = 'json' | 'messagepack';
+
+export type LinkStyle = 'shortest' | 'relative' | 'absolute';
+
+export type SelfLinkStyle = 'path' | 'internal';
+
+export type SplitMode = 'none' | 'chapter';
+
+/** 出力するMarkdownの形式。Obsidian向け（Wikiリンク）/ Obsidian以外向けのプレーンなMarkdown。 */
+export type OutputFormat = 'obsidian' | 'plain';
+
+/** `--format` で書き出すファイルの形式。Markdownのノート / リンクを除いた素の条文 / 最小限のHTML。 */
+export type NoteFormat = 'md' | 'txt' | 'html';
+
+/** `--citation-style` の選択肢。書かない / ノート末尾の一覧 / frontmatterの `cited_revisions`。 */
+export type CitationStyle = 'none' | 'section' | 'frontmatter';
+
+/** `--line-ending` で選ぶノートの改行コード。 */
+export type LineEnding = 'lf' | 'crlf';
+
+/** 参照先法令の取得順。幅優先（キュー）/ 深さ優先（スタック）。 */
+export type Traversal = 'bfs' | 'dfs';
+
+/** 条へのリンク先。要素IDのアンカー / 漢数字の条見出し / アラビア数字の条見出し。 */
+export type AnchorStyle = 'id' | 'kanji' | 'arabic';
+
+/** 号・細分の体裁。字下げしない / 階層に応じて半角空白で字下げする / 入れ子のリストにする。 */
+export type ItemStyle = 'plain' | 'indent' | 'list';
+
+/** 参照先の種別ごとの扱い。取得対象にする / 外部参照として取得しない / 無視する。 */
+export type RefPolicy = 'search' | 'external' | 'ignore';
+
+/** 参照先を取得対象にする本文の範囲。本則だけ / 附則・別表を含む全体。 */
+export type RefScope = 'main' | 'all';
+
+export type ExportFormat = 'zip' | 'single-md';
+
+/**
+ * `--export single-md` でのWikiリンクの扱い。`keep` はそのまま残し、`anchor` は連結したファイル内のアンカーへ書き換える。
+ */
+export type ExportLinkMode = 'keep' | 'anchor';
+
+/**
+ * 本文のリンク化の種別。`article` / `paragraph` / `item` は同一法令内の条・項・号、`external` は他の法令への参照。
+ */
+export type LinkifyKind = 'article' | 'external' | 'paragraph' | 'item';
+
+/**
+ * 同名の候補が複数あるときの確定方法。`strict` は確定しない。`prefer-current` は廃止されていない法令を優先し、
+ * それでも複数なら公布日の最も新しい法令にする。
+ */
+export type CandidatePolicy = 'strict' | 'prefer-current';
+
+export type DictionaryConflictPolicy = 'keep' | 'overwrite' | 'prefer-with-id';
+
 export interface LawCandidate {
   law_id?: string;
   law_num?: string;
   law_title: string;
   promulgation_date?: string;
+  law_type?: string;
+  /** 略称（`revision_info.abbrev`）。複数ある場合はカンマ区切りのまま持つ。 */
+  abbrev?: string;
+  /** 廃止等の状態（`revision_info.repeal_status`）。現行の法令は `None`。 */
+  repeal_status?: string;
+}
+
+export interface SearchCacheEntry {
+  cached_at: string;
+  candidates: LawCandidate[];
+}
+
+/**
+ * `law_title` 検索クエリをキーに、確定前の検索候補一式を保持するキャッシュ。
+ */
+export type SearchCache = Record<string, SearchCacheEntry>;
+
+/**
+ * 曖昧な法令名に対してユーザーが選んだ法令（選択履歴）の1件。
+ */
+export interface CandidateChoice {
+  query: string;
+  law_id: string;
+  law_title: string;
+  chosen_at: string;
 }
 
+/**
+ * 照合用に正規化した法令名をキーにした選択履歴。`--choices-path` に保存する。
+ */
+export type CandidateChoices = Record<string, CandidateChoice>;
+
 export interface LawDictionaryEntry {
   title: string;
   safe_title: string;
   file_name: string;
   updated_at: string;
+  law_num?: string;
+  law_type?: string;
+  promulgation_date?: string;
+  abbrev?: string;
+  /** 候補から確定したときの元の入力（うろ覚えの法令名など）。法令名・略称と同じく法令名での辞書引きに使う。 */
+  aliases?: string[];
+  /** 表記ゆれを吸収した法令番号の比較キー（`normalizeLawNum` の `key`）。 */
+  law_num_key?: string;
+  /** 登録経路。旧形式の辞書には無いため、欠損時は `dictionaryEntryConfidence` で既定値を補う。 */
+  source?: DictEntrySource;
+  /** 法令名と law_id の対応の確からしさ（0〜1）。 */
+  confidence?: number;
+}
+
+/**
+ * 辞書エントリの登録経路。
+ * `api_scan` は `--build-dictionary` の全件走査、`api_lookup` は law_id でのAPI照会、`fetched` は本文取得時の確定、
+ * `candidate` は法令名検索の候補からの確定、`fuzzy` は参照文言の法令名断片からの検索、`import` は `--import-dictionary`、
+ * `manual` は `--resolve-ref` での手動対応付け、`fallback` は法令名不明の仮登録。
+ */
+/**
+ * 辞書の照合キー（正規化した法令名・略称）が複数の法令で重なったことの記録。`--dict-conflict-log` に1行1件で追記する。
+ */
+export interface DictKeyConflictRecord {
+  timestamp: string;
+  key: string;
+  law_ids: string[];
+  titles: string[];
+  /** 重なりを見つけたときに登録していたエントリの登録経路。 */
+  source: DictEntrySource;
 }
 
+export type DictEntrySource =
+  | 'api_scan'
+  | 'api_lookup'
+  | 'fetched'
+  | 'candidate'
+  | 'fuzzy'
+  | 'import'
+  | 'manual'
+  | 'fallback';
+
 export type LawDictionary = Record<string, LawDictionaryEntry>;
 
+/**
+ * 辞書ファイルの保存形式。`schema_version` が無い law_id をキーとするオブジェクトだけのファイルは旧形式として読む。
+ */
+export interface LawDictionaryFile {
+  schema_version: number;
+  laws: LawDictionary;
+}
+
+export type DictionaryIssueKind =
+  | 'missing'
+  | 'title_mismatch'
+  | 'law_num_mismatch'
+  | 'ambiguous_title'
+  | 'duplicate_title'
+  | 'lookup_failed';
+
+/**
+ * `--verify-dictionary` で見つかった辞書エントリの問題。
+ * `current` は辞書上の値、`expected` はAPI上の現行の値（重複なら重複相手の law_id、照会失敗ならエラー内容）。
+ */
+export interface DictionaryIssue {
+  lawId: string;
+  kind: DictionaryIssueKind;
+  current: string;
+  expected?: string;
+}
+
+export interface DictionaryMergeResult {
+  merged: LawDictionary;
+  added: string[];
+  overwritten: string[];
+  skipped: string[];
+}
+
 export interface UnresolvedRefRecord {
   timestamp: string;
   root_law_id: string;
   root_law_title: string;
+  /** 参照を含むノートの法令（再帰取得では起点法令と異なる）。未設定の記録（旧形式）は起点法令のノートとみなす。 */
+  source_law_id?: string;
   from_anchor: string;
   raw_text: string;
   href: string;
-  reason: 'target_not_built' | 'unknown_format' | 'depth_limit';
+  reason:
+    | 'target_not_built'
+    | 'unknown_format'
+    | 'depth_limit'
+    | 'outside_ref_scope'
+    | 'outside_partial_range'
+    | 'ambiguous_suppl_provision';
+  /** 未設定の記録（旧形式）は pending とみなす。 */
+  status?: UnresolvedRefStatus;
+  /** `--ref-status resolved` で対応付けた法令の law_id。 */
+  resolved_law_id?: string;
 }
 
+/** `invalid` は辞書にもAPIにも見つからなかった（または法令名になり得ない）法令名の参照で、再検索しない。 */
+export type UnresolvedRefStatus = 'pending' | 'resolved' | 'ignored' | 'invalid';
+
 export interface SegmentText {
   type: 'text';
   text: string;
@@ -50,24 +344,195 @@ export interface SegmentLink {
   href: string;
 }
 
-export type ParagraphSegment = SegmentText | SegmentLink;
+/** 本文中の図（`Fig`）。画像の実体は取得せず、参照先のURLだけを保持する。 */
+export interface SegmentFigure {
+  type: 'figure';
+  /** 画像の代替テキスト。統計の文字数計算で他の区分と同じく `text` を読むために持つ。 */
+  text: string;
+  src: string;
+}
+
+/**
+ * 条文中の表（`TableStruct`）の区切り。`--inline-tables` で表に整形する段落だけが持ち、
+ * セルの終わりに `cell`、行の終わりに `row`（見出し行なら `header_row`）を置く。
+ */
+export interface SegmentTableBreak {
+  type: 'table_break';
+  text: '';
+  boundary: 'cell' | 'row' | 'header_row';
+}
+
+export type ParagraphSegment = SegmentText | SegmentLink | SegmentFigure | SegmentTableBreak;
 
 export interface ArticleParagraph {
   anchor: string;
+  /** 項番号（「２」）・号番号（「一」）など。第1項や番号の無い文では未設定。 */
+  number?: string;
+  /** 号・細分の入れ子の深さ（号=1、細分=2以降）。項の文と表では未設定。 */
+  level?: number;
   segments: ParagraphSegment[];
 }
 
 export interface ArticleBlock {
   id: string;
   heading: string;
+  /** 条見出しの括弧書き（「（定義）」）。`--anchor-style` で見出しを条名だけにするときに本文側へ出す。 */
+  caption?: string;
   paragraphs: ArticleParagraph[];
 }
 
 export interface ScrapedLawDocument {
   lawId: string;
   title: string;
+  lawNum?: string;
+  /** 表示中の改正版の施行日（「令和7年6月1日 施行」）。 */
+  revisionTitle?: string;
+  /** 表示中の改正版を制定した改正法令名（法令番号付き）。 */
+  amendingLawTitle?: string;
+  /** 廃止された法令の場合の、廃止した法令（`law_data` の改正法令）。 */
+  supersededBy?: LawSuccessor;
+  /** 廃止・失効した法令か（`law_data` の `revision_info.repeal_status` による）。 */
+  repealed?: boolean;
   sourceUrl: string;
   blocks: ArticleBlock[];
+  /** `--articles` / `--chapters` で一部の条だけに絞り込んだ場合の取得範囲。 */
+  partialRange?: string;
+  /** 本文から出力できなかった要素（要素名・class）ごとの出現数。 */
+  unsupportedElements?: Record<string, number>;
+}
+
+/**
+ * `--articles` / `--chapters` で指定された取得範囲。
+ * 条番号は枝番を含めて `[27, 2]`（第二十七条の二）の形、編・章は要素IDのセグメント列（`['Pa_2', 'Ch_1']`）で持つ。
+ */
+export interface PartialRange {
+  label: string;
+  articles?: { from: number[]; to: number[] };
+  chapters?: string[][];
+}
+
+/**
+ * Dataview集計用にfrontmatterへ出す法令の規模指標。
+ */
+export interface LawStats {
+  article_count: number;
+  paragraph_count: number;
+  item_count: number;
+  char_count: number;
+  external_ref_count: number;
+  appendix_count: number;
+}
+
+/**
+ * `law_full_text` のタグ木から数えられる構造要素の数。
+ */
+export type LawStructureCounts = Pick<LawStats, 'article_count' | 'paragraph_count' | 'item_count' | 'appendix_count'>;
+
+/**
+ * frontmatterに出力できる値。マップ値は `referenced_from` のような「キー→文字列リスト」に限る。
+ */
+export type FrontmatterValue = string | number | boolean | string[] | Record<string, string[]>;
+
+/**
+ * 法令ノートのfrontmatter。キーの定義順がそのまま出力順になり、undefined のキーは出力しない。
+ */
+export interface NoteFrontmatter extends Partial<LawStats> {
+  law_id: string;
+  title: string;
+  law_num?: string;
+  aliases?: string[];
+  source_url: string;
+  fetched_at: string;
+  depth: number;
+  partial?: boolean;
+  partial_range?: string;
+  /** 取得せずに外部参照として扱った参照先（条例など）の参照文言。 */
+  external_refs?: string[];
+  /** この法令を廃止した法令のノートへのリンク。 */
+  superseded_by?: string;
+  /** 廃止・失効した法令。 */
+  repealed?: boolean;
+}
+
+/** エンドポイント（`laws` / `law_data` など）ごとのAPI呼び出し回数。 */
+export interface ApiEndpointStats {
+  /** 再試行を含むリクエスト回数。 */
+  requests: number;
+  retries: number;
+  /** 受信したレスポンス本文の合計バイト数。 */
+  bytes: number;
+}
+
+/**
+ * 法令1件分の、取得開始からMarkdown変換までの計測結果。
+ */
+export interface LawFetchStats {
+  lawId: string;
+  title: string;
+  elapsedMs: number;
+  /** その法令の `law_data` で受信したバイト数。 */
+  bytes: number;
+}
+
+/**
+ * 1回の実行でのe-Gov APIの利用状況。キャッシュは種類（`search` など）ごとにヒット/ミスを数える。
+ */
+export interface ApiStats {
+  endpoints: Record<string, ApiEndpointStats>;
+  cache: Record<string, { hits: number; misses: number }>;
+  /** `law_data` の受信バイト数の law_id ごとの合計。 */
+  lawDataBytes: Record<string, number>;
+  laws: LawFetchStats[];
+}
+
+/** 実行サマリに載せる、取得に失敗した法令。起点法令を法令名で指定して確定前に失敗した場合は law_id が無い。 */
+export interface RunFailedLaw {
+  law_id?: string;
+  title: string;
+  error: string;
+}
+
+/**
+ * 1回の実行での処理件数。`--summary-json` のために、処理の各所で加算する。
+ */
+export interface RunStats {
+  /** 取得して変換した法令の数（`--dry-run` / `--diff` で書き込まなかったものも含む）。 */
+  fetched: number;
+  /** 既存ノートがあるため、または法令名を1件に確定できないため取得しなかった法令の数。 */
+  skipped: number;
+  /** 今回新たに記録した未解決参照の数。 */
+  unresolved: number;
+  /** 辞書に新しく登録した法令の数。 */
+  dictionaryAdded: number;
+  failures: RunFailedLaw[];
+}
+
+/** `--summary-json` に書き出す実行サマリ。 */
+export interface RunSummary {
+  status: 'success' | 'partial' | 'failed' | 'cancelled';
+  exit_code: number;
+  started_at: string;
+  finished_at: string;
+  elapsed_ms: number;
+  /** `--request-interval-ms` の間隔制御で待機した時間の合計。 */
+  throttle_wait_ms: number;
+  fetched: number;
+  skipped: number;
+  unresolved: number;
+  dictionary_added: number;
+  failed_laws: RunFailedLaw[];
+  api: RunApiSummary;
+  /** 処理を継続できずに終わったときのエラー。 */
+  error?: string;
+}
+
+/** 実行サマリに載せる、e-Gov APIの呼び出し回数とキャッシュのヒット/ミス。`requests` などは全エンドポイントの合計。 */
+export interface RunApiSummary {
+  requests: number;
+  retries: number;
+  bytes: number;
+  endpoints: Record<string, ApiEndpointStats>;
+  cache: Record<string, { hits: number; misses: number }>;
 }
 
 export interface QueueItem {
@@ -76,20 +541,146 @@ export interface QueueItem {
   depth: number;
 }
 
+export interface FailedLawRecord {
+  timestamp: string;
+  law_id: string;
+  title: string;
+  depth: number;
+  error: string;
+}
+
+/** 生成済みノート中の、行き先のノートが存在しないWikiリンク。 */
+export interface MissingLinkRecord {
+  /** リンク元ノートの出力ディレクトリからの相対パス。 */
+  note: string;
+  line: number;
+  target: string;
+}
+
+/** `--index-db` の `laws` テーブルの1行。`note_path` は出力ディレクトリからの相対パス。 */
+export interface LawIndexRecord {
+  law_id: string;
+  title: string;
+  law_num?: string;
+  promulgation_date?: string;
+  law_type?: string;
+  depth: number;
+  note_path: string;
+  updated_at: string;
+}
+
+export interface LawReferenceEdge {
+  source_law_id: string;
+  target_law_id: string;
+  anchor?: string;
+  text: string;
+}
+
 export interface ProcessContext {
   rootLawId: string;
   rootLawTitle: string;
   unresolved: UnresolvedRefRecord[];
   unresolvedSeen: Set<string>;
+  failures: FailedLawRecord[];
+  references: LawReferenceEdge[];
+  /** 起点法令を一部の条だけノート化した場合の、法令IDごとのノートに含まれる条の番号キー。 */
+  partialScopes?: Map<string, Set<string>>;
+  /** `ignored` にした未解決参照のhref。同じ参照は再び記録しない。 */
+  ignoredRefs?: Set<string>;
+  /** 法令名で検索しても見つからなかった法令名。`invalid` として記録し、再検索しない。 */
+  invalidRefNames?: Set<string>;
+  /** `--split-by chapter` で分割出力した法令ごとの、アンカー→セクションノートの対応表。 */
+  sectionNotes?: Map<string, SectionNoteIndex>;
+  /** hrefから law_id を読めないリンクについて、リンク文言の法令名で検索して解決した law_id。 */
+  refTitleResolutions?: Map<string, string>;
+}
+
+/**
+ * `--repl` のように1プロセスで起点法令を次々に処理するとき、辞書以外の保存を最後にまとめるために記録を溜める先。
+ */
+export interface DeferredSaves {
+  unresolved: UnresolvedRefRecord[];
+  failures: FailedLawRecord[];
+}
+
+/**
+ * 1回のグラフ探索で最初に取得キューへ積む法令。`fileStem` を渡すと、この法令のファイル名だけ法令名の代わりに
+ * その文字列から作る。`depth` は取得経路上の深さで、省略時は0（起点法令）。`--retry-failed` では失敗時の深さから取り直す。
+ */
+export interface LawGraphRoot {
+  lawId: string;
+  title: string;
+  fileStem?: string;
+  depth?: number;
+}
+
+/**
+ * グラフ探索1回分の処理結果の件数。`failedLawIds` は最終的に取得に失敗した法令（参照先を含む）。
+ */
+export interface LawGraphResult {
+  written: number;
+  skipped: number;
+  failed: number;
+  failedLawIds: string[];
+}
+
+/**
+ * 分割出力した法令で、リンク先のアンカーからセクションノートのパスを引く対応表。
+ * `byDivision` は最上位の階層セグメント（`Pa_1`）、`byArticle` は条の番号キー（`27_2`）をキーにする。
+ */
+export interface SectionNoteIndex {
+  byDivision: Map<string, string>;
+  byArticle: Map<string, string>;
 }
 
 export interface ExistingReferenceScanResult {
   referencedLawIds: string[];
 }
 
+export type LawFullTextFormat = 'json' | 'xml';
+
+/**
+ * 廃止・制定の前後関係で、旧法から見た新法（廃止した法令）。
+ */
+export interface LawSuccessor {
+  lawId: string;
+  title: string;
+}
+
 export interface LawDataResponse {
   law_info?: Record<string, unknown>;
   revision_info?: Record<string, unknown>;
+  law_full_text?: unknown;
+}
+
+/**
+ * `--archive-dir` に保存する `law_data` の生レスポンスと、取得日時・リビジョンIDなどのメタデータ。
+ */
+export interface LawDataArchive {
+  law_id: string;
+  fetched_at: string;
+  revision_id?: string;
+  full_text_format: LawFullTextFormat;
+  response: LawDataResponse;
+}
+
+/**
+ * `--archive-dir` に保存する、法令ページから抽出した本文（取得範囲で絞り込む前のもの）。
+ */
+export interface LawPageArchive {
+  law_id: string;
+  fetched_at: string;
+  document: ScrapedLawDocument;
+}
+
+/**
+ * `law_data` の取得結果と、本文をどのフォーマットで要求したかの組。
+ * XMLの場合 `law_full_text` はXML文字列、JSONの場合はタグ木になる。
+ */
+export interface LawContents {
+  lawId: string;
+  fullTextFormat: LawFullTextFormat;
+  data: LawDataResponse;
 }
 
 export type ExistingNoteIndex = Map<string, string[]>;
@@ -0,0 +1,269 @@
+import path from 'node:path';
+import process from 'node:process';
+import { fetchLawTitleById } from './api.js';
+import { findCandidateChoice } from './choices.js';
+import {
+  buildExistingNoteIndex,
+  getFileName,
+  resolveExistingNotePath,
+  resolveNoteRelativePath,
+  toSafeTitle,
+} from './notes.js';
+import { processLawGraphRoots } from './process.js';
+import { refLawName } from './refpolicy.js';
+import { findDictionaryLawId } from './refsearch.js';
+import { noteLink, parseLawIdFromHref } from './render.js';
+import {
+  dictionaryProvenance,
+  encodeNoteText,
+  isFallbackDictionaryEntry,
+  loadDictionary,
+  loadExistingUnresolved,
+  readNoteText,
+  saveDictionary,
+  unresolvedKey,
+  updateUnresolved,
+  writeFileAtomic,
+} from './storage.js';
+import type {
+  CliOptions,
+  LawDictionary,
+  LawGraphRoot,
+  UnresolvedRefRecord,
+  UnresolvedRefStatus,
+} from './types.js';
+
+const ANCHOR_LINE_PATTERN = /^<a id="([^"]+)"><\/a>$/;
+// 既にリンクになっている部分（Wikiリンク・標準Markdownリンク）は書き換えない。
+const LINK_PATTERN = /\[\[[^\]]*\]\]|\[[^\]]*\]\([^)]*\)/g;
+
+function matchesRef(record: UnresolvedRefRecord, alias: string, sourceLawId?: string): boolean {
+  return (record.raw_text === alias || record.href === alias) && (!sourceLawId || record.root_law_id === sourceLawId);
+}
+
+/**
+ * 別名（リンク文言またはhref）と参照元法令に一致する未解決参照のステータスを更新する。
+ */
+export function updateUnresolvedStatus(
+  records: UnresolvedRefRecord[],
+  alias: string,
+  status: Exclude<UnresolvedRefStatus, 'pending'>,
+  sourceLawId?: string,
+  resolvedLawId?: string,
+): { records: UnresolvedRefRecord[]; matched: UnresolvedRefRecord[] } {
+  const matched: UnresolvedRefRecord[] = [];
+  const updated = records.map((record) => {
+    if (!matchesRef(record, alias, sourceLawId)) {
+      return record;
+    }
+    const next: UnresolvedRefRecord = {
+      ...record,
+      status,
+      resolved_law_id: status === 'resolved' ? resolvedLawId : undefined,
+    };
+    matched.push(next);
+    return next;
+  });
+  return { records: updated, matched };
+}
+
+/**
+ * `--resolve-ref` で指定された未解決参照を resolved / ignored に更新する。
+ * resolved の場合は対応する法令を辞書へ登録し、次回の再帰取得から辞書で解決されるようにする。
+ */
+export async function resolveUnresolvedRef(options: CliOptions): Promise<void> {
+  const alias = options.resolveRef;
+  if (!alias) {
+    throw new Error('--resolve-ref に別名（リンク文言またはhref）を指定してください');
+  }
+  const records = await loadExistingUnresolved(options.unresolvedPath);
+  const candidates = records.filter((record) => matchesRef(record, alias, options.refSource));
+  if (candidates.length === 0) {
+    throw new Error(`一致する未解決参照がありません: ${alias}`);
+  }
+
+  let lawId: string | undefined;
+  if (options.refStatus === 'resolved') {
+    // リンク先が法令ページなら、hrefから law_id が分かるので指定を省略できる。
+    lawId = options.refLawId ?? candidates.map((record) => parseLawIdFromHref(record.href)?.lawId).find(Boolean);
+    if (!lawId) {
+      throw new Error('--ref-status resolved では --ref-law-id に対応する law_id を指定してください');
+    }
+  }
+  const { matched } = updateUnresolvedStatus(records, alias, options.refStatus, options.refSource, lawId);
+
+  if (lawId) {
+    const dictionary = await loadDictionary(options.dictionaryPath, options);
+    const current = dictionary[lawId];
+    const title =
+      options.refTitle ??
+      (current && !isFallbackDictionaryEntry(lawId, current) ? current.title : await fetchLawTitleById(options, lawId));
+    if (!title) {
+      throw new Error(`法令名を取得できませんでした。--ref-title で指定してください: ${lawId}`);
+    }
+    dictionary[lawId] = {
+      ...current,
+      title,
+      safe_title: toSafeTitle(title),
+      file_name: getFileName(lawId, title),
+      updated_at: new Date().toISOString(),
+      ...dictionaryProvenance('manual'),
+    };
+    if (!options.dryRun) {
+      await saveDictionary(options, dictionary);
+    }
+    process.stdout.write(`辞書に登録: ${lawId} ${title}\n`);
+  }
+
+  process.stdout.write(`未解決参照を ${options.refStatus} に更新: ${matched.length}件 (${alias})\n`);
+  if (options.dryRun) {
+    process.stdout.write('--dry-run のため保存しません\n');
+    return;
+  }
+  // 読み込み後に並行実行が追記した記録を消さないよう、書き戻す直前の最新の記録に同じ更新を適用する。
+  await updateUnresolved(
+    options.unresolvedPath,
+    options.lockTimeoutMs,
+    matched,
+    (latest) => updateUnresolvedStatus(latest, alias, options.refStatus, options.refSource, lawId).records,
+  );
+}
+
+/**
+ * pending の未解決参照のうち、法令名での解決に失敗した記録（`unknown_format`）を `lawIdsByName`（法令名 → law_id）で
+ * 解決し直し、解決できたものを resolved にする。解決できなかった記録はそのまま残す。
+ */
+export function retryPendingRefs(
+  records: UnresolvedRefRecord[],
+  lawIdsByName: Map<string, string>,
+): { records: UnresolvedRefRecord[]; resolved: UnresolvedRefRecord[] } {
+  const resolved: UnresolvedRefRecord[] = [];
+  const updated = records.map((record) => {
+    const lawId = isRetryableRef(record) ? lawIdsByName.get(refLawName(record.raw_text)) : undefined;
+    if (!lawId) {
+      return record;
+    }
+    const next: UnresolvedRefRecord = { ...record, status: 'resolved', resolved_law_id: lawId };
+    resolved.push(next);
+    return next;
+  });
+  return { records: updated, resolved };
+}
+
+function isRetryableRef(record: UnresolvedRefRecord): boolean {
+  return (record.status ?? 'pending') === 'pending' && record.reason === 'unknown_format';
+}
+
+/**
+ * ノートのうちアンカー `anchor` の段落（次のアンカーまで）で、最初に現れるリンクになっていない `text` を `link` に置き換える。
+ * 見つからなければ元のノートを返す。
+ */
+export function relinkRefText(markdown: string, anchor: string, text: string, link: string): string {
+  const lines = markdown.split('\n');
+  const start = lines.indexOf(`<a id="${anchor}"></a>`);
+  if (start < 0) {
+    return markdown;
+  }
+  for (let index = start + 1; index < lines.length && !ANCHOR_LINE_PATTERN.test(lines[index]); index += 1) {
+    const line = lines[index];
+    let position = 0;
+    // リンクとリンクの間の部分ごとに探し、最後はリンクの後から行末までを探す。
+    for (const matched of [...line.matchAll(LINK_PATTERN), undefined]) {
+      const end = matched?.index ?? line.length;
+      const found = line.indexOf(text, position);
+      if (found >= 0 && found + text.length <= end) {
+        lines[index] = `${line.slice(0, found)}${link}${line.slice(found + text.length)}`;
+        return lines.join('\n');
+      }
+      position = end + (matched?.[0].length ?? 0);
+    }
+  }
+  return markdown;
+}
+
+/**
+ * `--resolve-unresolved` で、未解決参照の pending の記録を現在の辞書（と選択履歴）で解決し直す。
+ * 辞書の更新で解決できるようになった参照を resolved にし、参照元のノートではその参照の文言を参照先ノートへのリンクにする。
+ * `--fetch-resolved` では、解決できた法令のうちノートの無いものを取得する。解決できなかった記録は pending のまま残す。
+ */
+export async function resolvePendingUnresolvedRefs(options: CliOptions, dictionary: LawDictionary): Promise<void> {
+  const records = await loadExistingUnresolved(options.unresolvedPath);
+  const lawIdsByName = new Map<string, string>();
+  for (const name of new Set(records.filter(isRetryableRef).map((record) => refLawName(record.raw_text)))) {
+    // 選択履歴の法令でも、辞書に無ければノート名を決められないため使わない。
+    const chosen = (await findCandidateChoice(options, name))?.law_id;
+    const lawId = chosen && dictionary[chosen] ? chosen : findDictionaryLawId(options, dictionary, name);
+    if (lawId) {
+      lawIdsByName.set(name, lawId);
+    }
+  }
+  const { resolved } = retryPendingRefs(records, lawIdsByName);
+  const pendingCount = records.filter(isRetryableRef).length - resolved.length;
+  process.stdout.write(`未解決参照を解決し直しました: 解決 ${resolved.length}件 / 未解決のまま ${pendingCount}件\n`);
+  if (resolved.length === 0) {
+    return;
+  }
+  if (options.dryRun) {
+    for (const record of resolved) {
+      process.stdout.write(`  - ${record.raw_text} -> ${record.resolved_law_id} (${record.root_law_title})\n`);
+    }
+    process.stdout.write('--dry-run のため保存しません\n');
+    return;
+  }
+  // 読み込み後に並行実行が追記した記録を消さないよう、書き戻す直前の最新の記録に同じ解決を適用する。
+  await updateUnresolved(
+    options.unresolvedPath,
+    options.lockTimeoutMs,
+    resolved,
+    (latest) => retryPendingRefs(latest, lawIdsByName).records,
+  );
+
+  const existingIndex = await buildExistingNoteIndex(options.outputDir);
+  const findNote = async (lawId: string) => {
+    const entry = dictionary[lawId];
+    const relativePath = entry ? resolveNoteRelativePath(lawId, entry, options) : `law_${lawId}.md`;
+    return resolveExistingNotePath(options.outputDir, lawId, relativePath, existingIndex);
+  };
+  let relinkedCount = 0;
+  const bySource = new Map<string, UnresolvedRefRecord[]>();
+  for (const record of resolved) {
+    // 再帰取得で記録した参照は起点法令ではなく、参照を含む法令のノートにある。
+    const sourceLawId = record.source_law_id ?? record.root_law_id;
+    bySource.set(sourceLawId, [...(bySource.get(sourceLawId) ?? []), record]);
+  }
+  for (const [sourceLawId, sourceRecords] of bySource) {
+    const sourcePath = await findNote(sourceLawId);
+    if (!sourcePath) {
+      continue;
+    }
+    const fromNotePath = path.relative(options.outputDir, sourcePath).split(path.sep).join('/');
+    const markdown = await readNoteText(sourcePath);
+    let updated = markdown;
+    for (const record of new Map(sourceRecords.map((item) => [unresolvedKey(item), item])).values()) {
+      const lawId = record.resolved_law_id as string;
+      const entry = dictionary[lawId];
+      const text = record.raw_text.replace(/\s+/g, ' ').trim();
+      const link = noteLink(options, fromNotePath, resolveNoteRelativePath(lawId, entry, options), undefined, text);
+      const relinked = link === text ? updated : relinkRefText(updated, record.from_anchor, text, link);
+      relinkedCount += relinked === updated ? 0 : 1;
+      updated = relinked;
+    }
+    if (updated !== markdown) {
+      await writeFileAtomic(sourcePath, encodeNoteText(updated, options));
+    }
+  }
+  process.stdout.write(`参照元ノートの文言をリンクにしました: ${relinkedCount}件\n`);
+
+  if (options.fetchResolved) {
+    const roots: LawGraphRoot[] = [];
+    for (const lawId of new Set(resolved.map((record) => record.resolved_law_id as string))) {
+      if (!(await findNote(lawId))) {
+        roots.push({ lawId, title: dictionary[lawId].title });
+      }
+    }
+    if (roots.length > 0) {
+      // 取得する法令どうしが参照し合っていても取り直さず、被参照も失わないよう1回のグラフ探索でまとめて取得する。
+      await processLawGraphRoots(options, roots, dictionary);
+    }
+  }
+}
@@ -0,0 +1,18 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+
+import { defaultCliOptions, getLawPageUrl } from './index.js';
+
+test('getLawPageUrl: --web-base-url 未指定時はAPIベースURLのサイトを使う', () => {
+  const options = defaultCliOptions();
+  assert.equal(getLawPageUrl(options, '334AC0000000121'), 'https://laws.e-gov.go.jp/law/334AC0000000121');
+  assert.equal(
+    getLawPageUrl({ ...options, apiBaseUrl: 'https://mirror.example.com/api/2' }, '334AC0000000121'),
+    'https://mirror.example.com/law/334AC0000000121',
+  );
+});
+
+test('getLawPageUrl: --web-base-url 指定時はそちらを優先する', () => {
+  const options = { ...defaultCliOptions(), webBaseUrl: 'https://laws.example.jp/' };
+  assert.equal(getLawPageUrl(options, '334AC0000000121'), 'https://laws.example.jp/law/334AC0000000121');
+});
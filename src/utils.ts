@@ -1,3 +1,7 @@
+import process from 'node:process';
+import readline from 'node:readline/promises';
+import type { CliOptions } from './types.js';
+
 /**
  * 指定ミリ秒だけ待機する。
  */
@@ -5,9 +9,132 @@ export function wait(ms: number): Promise<void> {
   return new Promise((resolve) => setTimeout(resolve, ms));
 }
 
+/**
+ * 端末で y/N の確認を取る。`y` / `yes` 以外の回答は拒否として扱う。
+ */
+export async function confirm(question: string): Promise<boolean> {
+  const rl = readline.createInterface({ input: process.stdin, output: process.stdout });
+  try {
+    const answer = await rl.question(question);
+    return /^y(?:es)?$/i.test(answer.trim());
+  } finally {
+    rl.close();
+  }
+}
+
+let cancelRequested = false;
+const shutdownCleanups = new Set<() => Promise<void>>();
+const SIGNAL_EXIT_CODES = new Map<NodeJS.Signals, number>([
+  ['SIGINT', 130],
+  ['SIGTERM', 143],
+  ['SIGHUP', 129],
+]);
+
+/**
+ * 待たずに終了するときに、先に済ませる後始末（起動中のブラウザを閉じるなど）を登録する。戻り値は登録を外す関数。
+ */
+export function onShutdown(cleanup: () => Promise<void>): () => void {
+  shutdownCleanups.add(cleanup);
+  return () => {
+    shutdownCleanups.delete(cleanup);
+  };
+}
+
+async function exitAfterCleanup(code: number): Promise<void> {
+  await Promise.allSettled([...shutdownCleanups].map((cleanup) => cleanup()));
+  process.exit(code);
+}
+
+/**
+ * Ctrl+C（SIGINT）を受けたら中断要求を立て、処理中の法令を終えたところで辞書・未解決参照を保存して終われるようにする。
+ * 書き込みの途中で終わらないよう、1回目は即座に終了しない。2回目のCtrl+Cと、SIGTERM・SIGHUPでは待たずに終了する。
+ * ブラウザはシグナルで閉じないよう起動しているため、待たずに終了するときは `onShutdown` の後始末で閉じてから終える。
+ * 戻り値はハンドラを外す関数。
+ */
+export function installCancelHandler(): () => void {
+  const handler = (signal: NodeJS.Signals): void => {
+    if (cancelRequested || signal !== 'SIGINT') {
+      process.stderr.write('\n中断します（保存せずに終了）\n');
+      void exitAfterCleanup(SIGNAL_EXIT_CODES.get(signal) ?? 1);
+      return;
+    }
+    cancelRequested = true;
+    process.stderr.write(
+      '\n中断を受け付けました。処理中の法令を終えてから保存して終了します（もう一度 Ctrl+C で即座に終了）\n',
+    );
+  };
+  for (const signal of SIGNAL_EXIT_CODES.keys()) {
+    process.on(signal, handler);
+  }
+  return () => {
+    for (const signal of SIGNAL_EXIT_CODES.keys()) {
+      process.off(signal, handler);
+    }
+  };
+}
+
+/**
+ * Ctrl+C による中断要求があったかを返す。取得ループは法令1件ごとの境目でこれを確かめる。
+ */
+export function isCancelRequested(): boolean {
+  return cancelRequested;
+}
+
+/**
+ * 中断要求を立てる（`cancel` が false なら取り消す）。シグナルを送らずに中断時の動きを確かめるために使う。
+ */
+export function setCancelRequested(cancel = true): void {
+  cancelRequested = cancel;
+}
+
+let lastRequestAt = 0;
+let throttleChain: Promise<void> = Promise.resolve();
+let throttleWaitTotalMs = 0;
+
+/**
+ * e-Govへの連続アクセスで負荷をかけないよう、前回リクエストから指定間隔が経過するまで待機する。
+ * リトライ時のバックオフとは別管理で、API呼び出しとページ取得の双方で共有する。
+ * 待機した時間（ミリ秒）を返す。待機しなかったときは `0`。
+ */
+export function throttleRequest(intervalMs: number): Promise<number> {
+  if (intervalMs <= 0) {
+    return Promise.resolve(0);
+  }
+  // 並行に呼ばれても間隔が守られるよう、待機判定をPromiseチェーンで直列化する。
+  const next = throttleChain.then(async () => {
+    const elapsed = Date.now() - lastRequestAt;
+    let waitMs = 0;
+    if (lastRequestAt > 0 && elapsed < intervalMs) {
+      waitMs = intervalMs - elapsed;
+      throttleWaitTotalMs += waitMs;
+      await wait(waitMs);
+    }
+    lastRequestAt = Date.now();
+    return waitMs;
+  });
+  throttleChain = next.then(() => undefined);
+  return next;
+}
+
+/**
+ * リクエスト間隔制御で発生した累計待機時間（ミリ秒）を返す。
+ */
+export function getThrottleWaitTotalMs(): number {
+  return throttleWaitTotalMs;
+}
+
 /**
  * APIベースURLから法令ページのベースURLを復元する。
  */
 export function getLawSiteBaseUrl(apiBaseUrl: string): string {
-  return apiBaseUrl.replace(/\/api\/2\/?$/, '').replace(/\/$/, '');
+  return apiBaseUrl.replace(/\/api\/\d+\/?$/, '').replace(/\/$/, '');
+}
+
+/**
+ * ノートに載せる、人が開くための法令ページURLを返す。
+ * `--web-base-url` 未指定時は、APIベースURLから復元した取得元サイトを使う。
+ */
+export function getLawPageUrl(options: Pick<CliOptions, 'apiBaseUrl' | 'webBaseUrl'>, lawId: string): string {
+  const baseUrl = options.webBaseUrl ? options.webBaseUrl.replace(/\/$/, '') : getLawSiteBaseUrl(options.apiBaseUrl);
+  return `${baseUrl}/law/${encodeURIComponent(lawId)}`;
 }